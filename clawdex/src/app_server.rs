@@ -1,8 +1,9 @@
 use std::collections::{HashMap, VecDeque};
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::time::Duration;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use codex_app_server_protocol::{
@@ -112,6 +113,11 @@ impl UserInputHandler for AutoUserInputHandler {
     }
 }
 
+/// How often an interruptible turn re-runs its interrupt check while the app-server is silent.
+const INTERRUPT_POLL: Duration = Duration::from_millis(250);
+/// How long an interrupted turn may take to complete before the app-server is stopped.
+const INTERRUPT_GRACE: Duration = Duration::from_secs(10);
+
 #[derive(Debug)]
 pub struct TurnOutcome {
     pub message: String,
@@ -122,12 +128,14 @@ pub struct TurnOutcome {
 pub struct CodexClient {
     child: Child,
     stdin: Option<ChildStdin>,
-    stdout: BufReader<ChildStdout>,
+    /// Lines read from the app-server's stdout by a background thread, so reads can time out.
+    stdout: Receiver<std::io::Result<String>>,
     pending_notifications: VecDeque<JSONRPCNotification>,
     approval_handler: Option<Box<dyn ApprovalHandler>>,
     user_input_handler: Option<Box<dyn UserInputHandler>>,
     event_sink: Option<Box<dyn EventSink>>,
     warnings: Vec<String>,
+    interrupt_grace: Duration,
 }
 
 impl CodexClient {
@@ -152,19 +160,47 @@ impl CodexClient {
 
         let stdin = child.stdin.take().context("codex stdin unavailable")?;
         let stdout = child.stdout.take().context("codex stdout unavailable")?;
+        let (line_tx, line_rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("codex-app-server-stdout".to_string())
+            .spawn(move || {
+                let mut reader = BufReader::new(stdout);
+                loop {
+                    let mut line = String::new();
+                    match reader.read_line(&mut line) {
+                        Ok(0) => return,
+                        Ok(_) => {
+                            if line_tx.send(Ok(line)).is_err() {
+                                return;
+                            }
+                        }
+                        Err(err) => {
+                            let _ = line_tx.send(Err(err));
+                            return;
+                        }
+                    }
+                }
+            })
+            .context("spawn codex stdout reader")?;
 
         Ok(Self {
             child,
             stdin: Some(stdin),
-            stdout: BufReader::new(stdout),
+            stdout: line_rx,
             pending_notifications: VecDeque::new(),
             approval_handler: Some(Box::new(AutoApprovalHandler::new(approval_mode))),
             user_input_handler: Some(Box::new(AutoUserInputHandler)),
             event_sink: None,
             warnings: Vec::new(),
+            interrupt_grace: INTERRUPT_GRACE,
         })
     }
 
+    /// False once the app-server process has exited, e.g. after it ignored an interrupt.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
     pub fn set_event_sink(&mut self, sink: Option<Box<dyn EventSink>>) {
         self.event_sink = sink;
     }
@@ -256,6 +292,17 @@ impl CodexClient {
         )
     }
 
+    fn send_turn_interrupt(&mut self, thread_id: &str, turn_id: &str) -> Result<()> {
+        let request = ClientRequest::TurnInterrupt {
+            request_id: self.request_id(),
+            params: TurnInterruptParams {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.to_string(),
+            },
+        };
+        self.write_request(&request)
+    }
+
     pub fn turn_interrupt(&mut self, thread_id: &str, turn_id: &str) -> Result<()> {
         let request_id = self.request_id();
         let request = ClientRequest::TurnInterrupt {
@@ -341,17 +388,37 @@ impl CodexClient {
     ) -> Result<TurnOutcome> {
         let mut delta = String::new();
         let mut last_agent_message: Option<String> = None;
-        let mut interrupt_sent = false;
+        // Set once turn/interrupt is sent: the turn must complete by then.
+        let mut interrupt_deadline: Option<Instant> = None;
         let turn_status = loop {
-            if !interrupt_sent {
+            if interrupt_deadline.is_none() {
                 if let Some(check) = should_interrupt.as_mut() {
                     if check(thread_id, turn_id) {
-                        self.turn_interrupt(thread_id, turn_id)?;
-                        interrupt_sent = true;
+                        // Don't wait for the response: a stalled app-server would never send it.
+                        self.send_turn_interrupt(thread_id, turn_id)?;
+                        interrupt_deadline = Some(Instant::now() + self.interrupt_grace);
                     }
                 }
             }
-            let notification = self.next_notification()?;
+            // Interruptible turns wake up regularly so the check runs even while nothing arrives.
+            let deadline = match (interrupt_deadline, should_interrupt.is_some()) {
+                (Some(deadline), _) => Some(deadline),
+                (None, true) => Some(Instant::now() + INTERRUPT_POLL),
+                (None, false) => None,
+            };
+            let Some(notification) = self.next_notification_until(deadline)? else {
+                if interrupt_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    self.warnings.push(format!(
+                        "codex app-server did not finish the interrupted turn within {}s; stopped it",
+                        self.interrupt_grace.as_secs()
+                    ));
+                    let _ = self.stdin.take();
+                    let _ = self.child.kill();
+                    let _ = self.child.wait();
+                    break TurnStatus::Interrupted;
+                }
+                continue;
+            };
             let Ok(server_notification) = ServerNotification::try_from(notification) else {
                 continue;
             };
@@ -463,15 +530,21 @@ impl CodexClient {
         }
     }
 
-    fn next_notification(&mut self) -> Result<JSONRPCNotification> {
+    /// The next notification, or `None` if nothing arrived before `deadline`.
+    fn next_notification_until(
+        &mut self,
+        deadline: Option<Instant>,
+    ) -> Result<Option<JSONRPCNotification>> {
         if let Some(notification) = self.pending_notifications.pop_front() {
-            return Ok(notification);
+            return Ok(Some(notification));
         }
 
         loop {
-            let message = self.read_jsonrpc_message()?;
+            let Some(message) = self.read_jsonrpc_message_until(deadline)? else {
+                return Ok(None);
+            };
             match message {
-                JSONRPCMessage::Notification(notification) => return Ok(notification),
+                JSONRPCMessage::Notification(notification) => return Ok(Some(notification)),
                 JSONRPCMessage::Response(_) | JSONRPCMessage::Error(_) => {
                     continue;
                 }
@@ -483,15 +556,32 @@ impl CodexClient {
     }
 
     fn read_jsonrpc_message(&mut self) -> Result<JSONRPCMessage> {
+        self.read_jsonrpc_message_until(None)?
+            .context("codex app-server closed stdout")
+    }
+
+    /// Reads the next message, or returns `None` once `deadline` passes without one.
+    fn read_jsonrpc_message_until(
+        &mut self,
+        deadline: Option<Instant>,
+    ) -> Result<Option<JSONRPCMessage>> {
         loop {
-            let mut response_line = String::new();
-            let bytes = self
-                .stdout
-                .read_line(&mut response_line)
-                .context("read codex app-server")?;
-            if bytes == 0 {
-                anyhow::bail!("codex app-server closed stdout");
-            }
+            let received = match deadline {
+                Some(deadline) => self
+                    .stdout
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => self
+                    .stdout
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            let response_line = match received {
+                Ok(line) => line.context("read codex app-server")?,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => {
+                    anyhow::bail!("codex app-server closed stdout")
+                }
+            };
             let trimmed = response_line.trim();
             if trimmed.is_empty() {
                 continue;
@@ -500,7 +590,7 @@ impl CodexClient {
                 serde_json::from_str(trimmed).context("invalid JSON-RPC from codex app-server")?;
             let message: JSONRPCMessage =
                 serde_json::from_value(parsed).context("invalid JSON-RPC message")?;
-            return Ok(message);
+            return Ok(Some(message));
         }
     }
 
@@ -704,6 +794,40 @@ mod tests {
             "fuzzy_file_search_session_completed"
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn interruptible_turn_gives_up_on_a_stalled_app_server() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("clawdex-app-server-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        // Accepts requests but never answers or emits a notification.
+        let script = dir.join("codex");
+        std::fs::write(&script, "#!/bin/sh\nexec sleep 30\n").expect("write script");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod script");
+
+        let mut client =
+            CodexClient::spawn(&script, &[], &[], ApprovalMode::AutoDeny).expect("spawn");
+        client.interrupt_grace = Duration::from_millis(300);
+        let started = Instant::now();
+        let mut checks = 0;
+        let mut check = |_: &str, _: &str| {
+            checks += 1;
+            checks >= 3
+        };
+        let outcome = client
+            .stream_turn("thread-1", "turn-1", Some(&mut check))
+            .expect("stream turn");
+
+        assert_eq!(outcome.status, TurnStatus::Interrupted);
+        assert_eq!(checks, 3);
+        assert!(outcome.warnings[0].contains("did not finish the interrupted turn"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!client.is_running());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
 
 impl Drop for CodexClient {
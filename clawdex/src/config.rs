@@ -48,6 +48,8 @@ pub struct CronConfig {
     pub webhook: Option<String>,
     #[serde(alias = "webhookToken")]
     pub webhook_token: Option<String>,
+    /// Codex runners reserved for cron jobs; inbound messages and heartbeats keep their own.
+    pub workers: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -99,9 +101,77 @@ pub struct MemoryConfig {
     #[serde(alias = "chunkOverlap")]
     #[serde(alias = "chunk_overlap")]
     pub chunk_overlap: Option<usize>,
+    #[serde(alias = "chunkStrategy")]
+    #[serde(alias = "chunk_strategy")]
+    pub chunk_strategy: Option<String>,
     #[serde(alias = "sessionMemory")]
     #[serde(alias = "session_memory")]
     pub session_memory: Option<bool>,
+    pub hybrid: Option<MemoryHybridConfig>,
+    #[serde(alias = "vectorIndex")]
+    #[serde(alias = "vector_index")]
+    pub vector_index: Option<MemoryVectorIndexConfig>,
+    #[serde(alias = "sessionSummaries")]
+    #[serde(alias = "session_summaries")]
+    pub session_summaries: Option<SessionSummariesConfig>,
+    pub namespaces: Option<MemoryNamespacesConfig>,
+}
+
+/// Per-agent memory namespaces. When enabled, the agent id of an `agent:<id>:...` session key
+/// picks the default write target. Searches from an agent session never see other agents'
+/// private notes unless `cross_agent_reads` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemoryNamespacesConfig {
+    pub enabled: Option<bool>,
+    /// Agent namespaces every agent can read.
+    pub shared: Option<Vec<String>>,
+    /// Lets agents search other agents' namespaces with `agentId` or `allAgents`.
+    #[serde(alias = "crossAgentReads")]
+    #[serde(alias = "cross_agent_reads")]
+    pub cross_agent_reads: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SessionSummariesConfig {
+    pub enabled: Option<bool>,
+    #[serde(alias = "idleMinutes")]
+    #[serde(alias = "idle_minutes")]
+    pub idle_minutes: Option<u64>,
+    #[serde(alias = "intervalMinutes")]
+    #[serde(alias = "interval_minutes")]
+    pub interval_minutes: Option<u64>,
+    #[serde(alias = "minMessages")]
+    #[serde(alias = "min_messages")]
+    pub min_messages: Option<usize>,
+    #[serde(alias = "maxInputChars")]
+    #[serde(alias = "max_input_chars")]
+    pub max_input_chars: Option<usize>,
+    #[serde(alias = "indexTranscripts")]
+    #[serde(alias = "index_transcripts")]
+    pub index_transcripts: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemoryHybridConfig {
+    pub enabled: Option<bool>,
+    #[serde(alias = "vectorWeight")]
+    #[serde(alias = "vector_weight")]
+    pub vector_weight: Option<f64>,
+    #[serde(alias = "textWeight")]
+    #[serde(alias = "text_weight")]
+    pub text_weight: Option<f64>,
+    #[serde(alias = "candidateMultiplier")]
+    #[serde(alias = "candidate_multiplier")]
+    pub candidate_multiplier: Option<usize>,
+    #[serde(alias = "rrfK")]
+    #[serde(alias = "rrf_k")]
+    pub rrf_k: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemoryVectorIndexConfig {
+    pub enabled: Option<bool>,
+    pub probes: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -116,6 +186,10 @@ pub struct MemorySyncConfig {
     #[serde(alias = "intervalMinutes")]
     #[serde(alias = "interval_minutes")]
     pub interval_minutes: Option<u64>,
+    pub watch: Option<bool>,
+    #[serde(alias = "debounceMs")]
+    #[serde(alias = "debounce_ms")]
+    pub debounce_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -126,6 +200,10 @@ pub struct EmbeddingsConfig {
     pub api_base: Option<String>,
     pub api_key_env: Option<String>,
     pub batch_size: Option<usize>,
+    #[serde(alias = "modelPath")]
+    pub model_path: Option<String>,
+    #[serde(alias = "localFallback")]
+    pub local_fallback: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -167,6 +245,39 @@ pub struct GatewayConfig {
     pub password: Option<String>,
     #[serde(alias = "tlsFingerprint")]
     pub tls_fingerprint: Option<String>,
+    pub channels: Option<std::collections::HashMap<String, GatewayChannelConfig>>,
+    #[serde(alias = "outboxPollMs")]
+    pub outbox_poll_ms: Option<u64>,
+    #[serde(alias = "outboxMaxAttempts")]
+    pub outbox_max_attempts: Option<u32>,
+    #[serde(alias = "outboxRetryBaseMs")]
+    pub outbox_retry_base_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GatewayChannelConfig {
+    pub kind: Option<String>,
+    pub enabled: Option<bool>,
+    pub url: Option<String>,
+    pub token: Option<String>,
+    #[serde(alias = "tokenEnv")]
+    pub token_env: Option<String>,
+    #[serde(alias = "apiBase")]
+    pub api_base: Option<String>,
+    pub headers: Option<std::collections::HashMap<String, String>>,
+    #[serde(alias = "timeoutMs")]
+    pub timeout_ms: Option<u64>,
+    #[serde(alias = "accountId")]
+    pub account_id: Option<String>,
+    #[serde(alias = "agentId")]
+    pub agent_id: Option<String>,
+    pub inbound: Option<bool>,
+    #[serde(alias = "signingSecret")]
+    pub signing_secret: Option<String>,
+    #[serde(alias = "signingSecretEnv")]
+    pub signing_secret_env: Option<String>,
+    #[serde(alias = "userId")]
+    pub user_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -427,6 +538,14 @@ pub fn resolve_cron_enabled(cfg: &ClawdConfig) -> bool {
     cfg.cron.as_ref().and_then(|c| c.enabled).unwrap_or(true)
 }
 
+pub fn resolve_cron_workers(cfg: &ClawdConfig) -> usize {
+    cfg.cron
+        .as_ref()
+        .and_then(|c| c.workers)
+        .unwrap_or(2)
+        .clamp(1, 16)
+}
+
 pub fn resolve_heartbeat_enabled(cfg: &ClawdConfig) -> bool {
     cfg.heartbeat
         .as_ref()
@@ -772,3 +891,21 @@ fn config_path_from_env() -> Option<PathBuf> {
     }
     None
 }
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::path::PathBuf;
+
+    use super::{load_config, ClawdPaths};
+
+    /// Fresh state and workspace dirs under `clawdex-<label>-<uuid>` in the temp dir; returns
+    /// the base dir so the test can remove it.
+    pub fn test_paths(label: &str) -> (PathBuf, ClawdPaths) {
+        let base = std::env::temp_dir().join(format!("clawdex-{label}-{}", uuid::Uuid::new_v4()));
+        let workspace_dir = base.join("workspace");
+        std::fs::create_dir_all(&workspace_dir).expect("workspace");
+        let (_cfg, paths) =
+            load_config(Some(base.join("state")), Some(workspace_dir)).expect("config");
+        (base, paths)
+    }
+}
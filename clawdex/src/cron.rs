@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
@@ -10,11 +10,9 @@ use serde_json::{json, Map, Value};
 use uuid::Uuid;
 
 use crate::config::ClawdPaths;
-use crate::util::{append_json_line, now_ms, read_json_lines, read_json_value, write_json_value};
-
-const JOBS_FILE: &str = "jobs.json";
-const RUNS_DIR: &str = "runs";
-const PENDING_FILE: &str = "pending.json";
+use crate::cron_calendar::{ScheduleCalendar, CALENDAR_KEYS};
+use crate::cron_store::{self, CronStore, CronTx};
+use crate::util::now_ms;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CronJob {
@@ -38,6 +36,38 @@ pub struct CronJob {
     pub delivery: Option<CronDelivery>,
     #[serde(default)]
     pub delete_after_run: bool,
+    /// How many runs of this job may execute at once (`maxConcurrentRuns`, default 1).
+    #[serde(default)]
+    pub max_concurrent_runs: Option<usize>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Result of the upstream run that triggered this one (`state.upstream`), for the
+    /// `{{upstream.*}}` payload variables.
+    #[serde(default)]
+    pub upstream: Option<Value>,
+    /// Results of every upstream that triggered this run, keyed by job id (`state.upstreams`),
+    /// for the `{{upstreams.<jobId>.*}}` payload variables.
+    #[serde(default)]
+    pub upstreams: Option<Value>,
+}
+
+impl CronJob {
+    pub fn concurrency_limit(&self) -> usize {
+        self.max_concurrent_runs.unwrap_or(1).max(1)
+    }
+
+    /// `timeoutMs`, falling back to the OpenClaw-style `payload.timeoutSeconds`.
+    pub fn effective_timeout_ms(&self) -> Option<u64> {
+        self.timeout_ms
+            .or_else(|| {
+                self.payload
+                    .get("timeoutSeconds")
+                    .and_then(|v| v.as_f64())
+                    .filter(|secs| *secs > 0.0)
+                    .map(|secs| (secs * 1000.0) as u64)
+            })
+            .filter(|ms| *ms > 0)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +86,7 @@ struct ScheduleSpec {
     anchor_ms: Option<i64>,
     cron: Option<String>,
     tz: Option<String>,
+    calendar: Option<ScheduleCalendar>,
 }
 
 impl ScheduleSpec {
@@ -89,6 +120,8 @@ impl ScheduleSpec {
             .or_else(|| obj.get("timeZone"))
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
+        // Invalid calendars are rejected when the job is saved.
+        let calendar = ScheduleCalendar::parse(obj).ok().flatten();
         Some(Self {
             kind,
             at_ms,
@@ -96,10 +129,22 @@ impl ScheduleSpec {
             anchor_ms,
             cron,
             tz,
+            calendar,
         })
     }
 
+    /// Next run after `now`, skipping slots the schedule's calendar blocks.
     fn next_run_after(
+        &self,
+        last_run_at_ms: Option<i64>,
+        created_at_ms: Option<i64>,
+        now: i64,
+    ) -> Option<i64> {
+        let next = self.next_grid_run_after(last_run_at_ms, created_at_ms, now)?;
+        self.first_allowed(next).map(CalendarSlot::at)
+    }
+
+    fn next_grid_run_after(
         &self,
         _last_run_at_ms: Option<i64>,
         _created_at_ms: Option<i64>,
@@ -188,6 +233,468 @@ impl ScheduleSpec {
     }
 }
 
+const DEFAULT_CATCH_UP_MAX_RUNS: usize = 10;
+/// Upper bound on the missed slots enumerated for one job after downtime.
+const MAX_MISSED_SLOTS: usize = 10_000;
+/// How far ahead a schedule calendar is searched for an allowed slot. A job with nothing allowed
+/// in that span stays scheduled at the horizon and searches again from there.
+const CALENDAR_HORIZON_MS: i64 = 366 * 24 * 3_600_000;
+const CALENDAR_HORIZON_WARNING: &str =
+    "the calendar allows no run within a year; the job stays scheduled and checks again then";
+/// Upper bound on the blocked days and windows skipped in one calendar search.
+const MAX_CALENDAR_SKIPS: usize = 10_000;
+
+/// Result of searching the grid for a slot the schedule calendar allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CalendarSlot {
+    Allowed(i64),
+    /// Nothing allowed before the horizon; the search resumes from this grid slot.
+    Horizon(i64),
+}
+
+impl CalendarSlot {
+    fn at(self) -> i64 {
+        match self {
+            CalendarSlot::Allowed(slot) | CalendarSlot::Horizon(slot) => slot,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CatchUpMode {
+    /// Run only on time; a slot past the misfire grace, or any slot after downtime, is missed.
+    Skip,
+    /// Run the latest missed slot once.
+    Once,
+    /// Replay up to `max_runs` of the latest missed slots, oldest first.
+    All,
+}
+
+impl CatchUpMode {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "skip" => Some(CatchUpMode::Skip),
+            "once" => Some(CatchUpMode::Once),
+            "all" => Some(CatchUpMode::All),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            CatchUpMode::Skip => "skip",
+            CatchUpMode::Once => "once",
+            CatchUpMode::All => "all",
+        }
+    }
+}
+
+/// Per-job `catchUp` / `maxLatenessMs` settings.
+#[derive(Debug, Clone, Copy)]
+struct CatchUpPolicy {
+    mode: CatchUpMode,
+    max_runs: usize,
+    max_lateness_ms: Option<i64>,
+}
+
+impl CatchUpPolicy {
+    /// `catchUp` is `"skip" | "once" | "all"` or `{ "mode": ..., "maxRuns": n }`; defaults to
+    /// `once`, which matches the scheduler's behavior before the policy existed.
+    fn parse(catch_up: Option<&Value>, max_lateness: Option<&Value>) -> Result<Self> {
+        let mut policy = CatchUpPolicy {
+            mode: CatchUpMode::Once,
+            max_runs: DEFAULT_CATCH_UP_MAX_RUNS,
+            max_lateness_ms: None,
+        };
+        match catch_up {
+            None | Some(Value::Null) => {}
+            Some(Value::String(raw)) => {
+                policy.mode = CatchUpMode::parse(raw)
+                    .with_context(|| format!("invalid catchUp mode: {raw}"))?;
+            }
+            Some(Value::Object(obj)) => {
+                if let Some(raw) = obj.get("mode") {
+                    let raw = raw.as_str().context("catchUp.mode must be a string")?;
+                    policy.mode = CatchUpMode::parse(raw)
+                        .with_context(|| format!("invalid catchUp mode: {raw}"))?;
+                }
+                if let Some(raw) = obj.get("maxRuns").or_else(|| obj.get("max_runs")) {
+                    let max_runs = raw
+                        .as_u64()
+                        .filter(|value| *value > 0)
+                        .context("catchUp.maxRuns must be a positive integer")?;
+                    policy.max_runs = max_runs as usize;
+                }
+            }
+            Some(_) => anyhow::bail!("catchUp must be a string or an object"),
+        }
+        match max_lateness {
+            None | Some(Value::Null) => {}
+            Some(raw) => {
+                let lateness = raw
+                    .as_i64()
+                    .filter(|value| *value >= 0)
+                    .context("maxLatenessMs must be a non-negative integer")?;
+                policy.max_lateness_ms = Some(lateness);
+            }
+        }
+        Ok(policy)
+    }
+
+    fn from_job(job: &Map<String, Value>) -> Self {
+        CatchUpPolicy::parse(job.get("catchUp"), job.get("maxLatenessMs")).unwrap_or(CatchUpPolicy {
+            mode: CatchUpMode::Once,
+            max_runs: DEFAULT_CATCH_UP_MAX_RUNS,
+            max_lateness_ms: None,
+        })
+    }
+}
+
+/// What to do with the slots that came due since `nextRunAtMs`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CatchUpPlan {
+    /// Slots to run, oldest first.
+    run: Vec<i64>,
+    /// Slots recorded as missed, oldest first.
+    missed: Vec<i64>,
+    /// Missed slots older than `maxLatenessMs`.
+    stale: usize,
+    /// First slot after `now`, used when nothing runs.
+    resume_at: Option<i64>,
+}
+
+/// How late a slot may start under `catchUp: "skip"` before it counts as missed; `maxLatenessMs`
+/// overrides it.
+const SKIP_MISFIRE_GRACE_MS: i64 = 60_000;
+
+const DEFAULT_RETRY_BACKOFF_MS: i64 = 30_000;
+const DEFAULT_RETRY_BACKOFF_FACTOR: f64 = 2.0;
+const DEFAULT_RETRY_MAX_BACKOFF_MS: i64 = 3_600_000;
+/// Run statuses a retry policy can act on; also the default `retryOn` list.
+const RETRYABLE_STATUSES: [&str; 2] = ["error", "timeout"];
+
+/// Per-job `retry` settings for failed runs.
+#[derive(Debug, Clone, PartialEq)]
+struct RetryPolicy {
+    max_retries: u32,
+    backoff_ms: i64,
+    backoff_factor: f64,
+    max_backoff_ms: i64,
+    retry_on: Vec<String>,
+}
+
+impl RetryPolicy {
+    /// `retry` is `{ "maxRetries": n, "backoffMs"?, "backoffFactor"?, "maxBackoffMs"?,
+    /// "retryOn"? }`. Without one a failed run simply waits for the next slot.
+    fn parse(raw: Option<&Value>) -> Result<Option<Self>> {
+        let obj = match raw {
+            None | Some(Value::Null) => return Ok(None),
+            Some(Value::Object(obj)) => obj,
+            Some(_) => anyhow::bail!("retry must be an object"),
+        };
+        let field = |camel: &str, snake: &str| obj.get(camel).or_else(|| obj.get(snake));
+        let max_retries = field("maxRetries", "max_retries")
+            .context("retry.maxRetries is required")?
+            .as_u64()
+            .context("retry.maxRetries must be a non-negative integer")?;
+        let mut policy = RetryPolicy {
+            max_retries: max_retries.min(u32::MAX as u64) as u32,
+            backoff_ms: DEFAULT_RETRY_BACKOFF_MS,
+            backoff_factor: DEFAULT_RETRY_BACKOFF_FACTOR,
+            max_backoff_ms: DEFAULT_RETRY_MAX_BACKOFF_MS,
+            retry_on: RETRYABLE_STATUSES.iter().map(|s| s.to_string()).collect(),
+        };
+        if let Some(raw) = field("backoffMs", "backoff_ms") {
+            policy.backoff_ms = raw
+                .as_i64()
+                .filter(|value| *value >= 0)
+                .context("retry.backoffMs must be a non-negative integer")?;
+        }
+        if let Some(raw) = field("backoffFactor", "backoff_factor") {
+            policy.backoff_factor = raw
+                .as_f64()
+                .filter(|value| *value >= 1.0)
+                .context("retry.backoffFactor must be a number >= 1")?;
+        }
+        if let Some(raw) = field("maxBackoffMs", "max_backoff_ms") {
+            policy.max_backoff_ms = raw
+                .as_i64()
+                .filter(|value| *value >= 0)
+                .context("retry.maxBackoffMs must be a non-negative integer")?;
+        }
+        if let Some(raw) = field("retryOn", "retry_on") {
+            let statuses = match raw {
+                Value::String(status) => vec![status.clone()],
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+                    .context("retry.retryOn must list run statuses")?,
+                _ => anyhow::bail!("retry.retryOn must be a status or a list of statuses"),
+            };
+            for status in &statuses {
+                if !RETRYABLE_STATUSES.contains(&status.as_str()) {
+                    anyhow::bail!(
+                        "invalid retry.retryOn status: {status} (expected {})",
+                        RETRYABLE_STATUSES.join(" or ")
+                    );
+                }
+            }
+            policy.retry_on = statuses;
+        }
+        Ok(Some(policy))
+    }
+
+    fn from_job(job: &Map<String, Value>) -> Option<Self> {
+        RetryPolicy::parse(job.get("retry")).ok().flatten()
+    }
+
+    /// Delay before retry number `retry` (1-based): `backoffMs * backoffFactor^(retry - 1)`,
+    /// capped at `maxBackoffMs`.
+    fn delay_ms(&self, retry: u32) -> i64 {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.backoff_ms as f64 * self.backoff_factor.powi(exponent);
+        delay.min(self.max_backoff_ms as f64).max(0.0) as i64
+    }
+
+    /// When to run the next attempt after a run with `status`, given the retries already used.
+    fn retry_at(&self, status: &str, retries_used: u32, ended_at: i64) -> Option<i64> {
+        if retries_used >= self.max_retries || !self.retry_on.iter().any(|s| s == status) {
+            return None;
+        }
+        Some(ended_at.saturating_add(self.delay_ms(retries_used + 1)))
+    }
+}
+
+/// Upstream outcome a `dependsOn` entry reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TriggerOn {
+    Success,
+    Failure,
+    Always,
+}
+
+impl TriggerOn {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "success" | "ok" => Some(TriggerOn::Success),
+            "failure" | "error" => Some(TriggerOn::Failure),
+            "always" | "completion" => Some(TriggerOn::Always),
+            _ => None,
+        }
+    }
+
+    fn matches(self, success: bool) -> bool {
+        match self {
+            TriggerOn::Success => success,
+            TriggerOn::Failure => !success,
+            TriggerOn::Always => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dependency {
+    job_id: String,
+    on: TriggerOn,
+}
+
+/// `dependsOn` is a job id, `{ "jobId": id, "on": "success" | "failure" | "always" }`, or a list
+/// of either. Any listed upstream can trigger the job; `on` defaults to `success`.
+fn parse_dependencies(raw: Option<&Value>) -> Result<Vec<Dependency>> {
+    let items = match raw {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(Value::Array(items)) => items.iter().collect::<Vec<_>>(),
+        Some(other) => vec![other],
+    };
+    let mut deps = Vec::new();
+    for item in items {
+        let dep = match item {
+            Value::String(job_id) => Dependency {
+                job_id: job_id.trim().to_string(),
+                on: TriggerOn::Success,
+            },
+            Value::Object(obj) => {
+                let job_id = obj
+                    .get("jobId")
+                    .or_else(|| obj.get("id"))
+                    .and_then(|v| v.as_str())
+                    .context("dependsOn entries need a jobId")?;
+                let on = match obj.get("on").filter(|v| !v.is_null()) {
+                    None => TriggerOn::Success,
+                    Some(raw) => raw
+                        .as_str()
+                        .and_then(TriggerOn::parse)
+                        .with_context(|| format!("invalid dependsOn.on: {raw}"))?,
+                };
+                Dependency {
+                    job_id: job_id.trim().to_string(),
+                    on,
+                }
+            }
+            _ => anyhow::bail!("dependsOn must be a job id, an object, or a list of them"),
+        };
+        if dep.job_id.is_empty() {
+            anyhow::bail!("dependsOn job ids must not be empty");
+        }
+        deps.push(dep);
+    }
+    Ok(deps)
+}
+
+/// `onSuccess` / `onFailure`: a job id or a list of job ids.
+fn parse_job_ids(raw: Option<&Value>, key: &str) -> Result<Vec<String>> {
+    let ids = match raw {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(Value::String(id)) => vec![id.trim().to_string()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| item.as_str().map(|id| id.trim().to_string()))
+            .collect::<Option<Vec<_>>>()
+            .with_context(|| format!("{key} must list job ids"))?,
+        Some(_) => anyhow::bail!("{key} must be a job id or a list of job ids"),
+    };
+    if ids.iter().any(|id| id.is_empty()) {
+        anyhow::bail!("{key} job ids must not be empty");
+    }
+    Ok(ids)
+}
+
+impl ScheduleSpec {
+    /// Slot following `slot` on this schedule's grid that the calendar allows.
+    fn next_slot(&self, slot: i64) -> Option<i64> {
+        match self.first_allowed(self.next_grid_slot(slot)?)? {
+            CalendarSlot::Allowed(next) => Some(next),
+            CalendarSlot::Horizon(_) => None,
+        }
+    }
+
+    fn next_grid_slot(&self, slot: i64) -> Option<i64> {
+        match self.kind.as_str() {
+            "at" => None,
+            "every" => self.every_ms.filter(|every| *every > 0).map(|every| slot + every),
+            _ => self
+                .next_grid_run_after(None, None, slot)
+                .filter(|next| *next > slot),
+        }
+    }
+
+    /// First grid slot after `slot` that is not before `target`.
+    fn grid_slot_from(&self, slot: i64, target: i64) -> Option<i64> {
+        match self.kind.as_str() {
+            "at" => None,
+            "every" => {
+                let every = self.every_ms.filter(|every| *every > 0)?;
+                let steps = (target.saturating_sub(slot) + every - 1) / every;
+                Some(slot + steps.max(1) * every)
+            }
+            _ => self
+                .next_grid_run_after(None, None, target.saturating_sub(1).max(slot))
+                .filter(|next| *next > slot),
+        }
+    }
+
+    /// `slot`, or the first later grid slot the calendar allows. Blocked days and blackout
+    /// windows are skipped whole rather than slot by slot. One-shot schedules ignore the
+    /// calendar.
+    fn first_allowed(&self, slot: i64) -> Option<CalendarSlot> {
+        let Some(calendar) = self.calendar.as_ref().filter(|_| self.kind != "at") else {
+            return Some(CalendarSlot::Allowed(slot));
+        };
+        let tz = self.time_zone();
+        let check = calendar.check();
+        let horizon = slot.saturating_add(CALENDAR_HORIZON_MS);
+        let mut slot = slot;
+        for _ in 0..MAX_CALENDAR_SKIPS {
+            let Some(utc) = Utc.timestamp_millis_opt(slot).single() else {
+                return Some(CalendarSlot::Allowed(slot));
+            };
+            let Some(until) = check.blocked_until(utc.with_timezone(&tz).naive_local()) else {
+                return Some(CalendarSlot::Allowed(slot));
+            };
+            // A block ending inside a DST gap ends when the gap does.
+            let until_ms = tz
+                .from_local_datetime(&until)
+                .earliest()
+                .or_else(|| {
+                    tz.from_local_datetime(&(until + chrono::Duration::hours(1)))
+                        .earliest()
+                })
+                .map_or(slot + 1, |end| end.timestamp_millis());
+            slot = self.grid_slot_from(slot, until_ms)?;
+            if slot >= horizon {
+                break;
+            }
+        }
+        Some(CalendarSlot::Horizon(slot))
+    }
+
+    fn calendar_allows(&self, slot: i64) -> bool {
+        let Some(calendar) = &self.calendar else {
+            return true;
+        };
+        let tz = self.time_zone();
+        Utc.timestamp_millis_opt(slot)
+            .single()
+            .map(|utc| calendar.allows(utc.with_timezone(&tz).naive_local()))
+            .unwrap_or(true)
+    }
+
+    fn time_zone(&self) -> Tz {
+        self.tz
+            .as_deref()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(chrono_tz::UTC)
+    }
+
+    /// Every slot from `first` through `now`, oldest first, plus the first slot after `now`.
+    fn slots_through(&self, first: i64, now: i64) -> (Vec<i64>, Option<i64>) {
+        let mut slots = vec![first];
+        let mut cursor = first;
+        loop {
+            let Some(next) = self.next_slot(cursor) else {
+                return (slots, None);
+            };
+            if next > now {
+                return (slots, Some(next));
+            }
+            if slots.len() >= MAX_MISSED_SLOTS {
+                // Give up enumerating and resume from now; the count is a lower bound.
+                return (slots, self.next_run_after(None, None, now));
+            }
+            slots.push(next);
+            cursor = next;
+        }
+    }
+}
+
+fn plan_catch_up(schedule: &ScheduleSpec, policy: CatchUpPolicy, first: i64, now: i64) -> CatchUpPlan {
+    let (slots, resume_at) = schedule.slots_through(first, now);
+    let fresh_from = policy
+        .max_lateness_ms
+        .map(|lateness| now.saturating_sub(lateness))
+        .unwrap_or(i64::MIN);
+    let stale = slots.iter().take_while(|slot| **slot < fresh_from).count();
+    let fresh = &slots[stale..];
+    let keep = match policy.mode {
+        CatchUpMode::Skip => {
+            // Only a lone slot that came due within the misfire grace counts as on time.
+            let grace = policy.max_lateness_ms.unwrap_or(SKIP_MISFIRE_GRACE_MS);
+            let on_time = slots.len() == 1 && slots[0] >= now.saturating_sub(grace);
+            usize::from(on_time)
+        }
+        CatchUpMode::Once => fresh.len().min(1),
+        CatchUpMode::All => fresh.len().min(policy.max_runs),
+    };
+    let split = slots.len() - keep;
+    CatchUpPlan {
+        run: slots[split..].to_vec(),
+        missed: slots[..split].to_vec(),
+        stale,
+        resume_at,
+    }
+}
+
 fn parse_at_ms(value: &Value) -> Option<i64> {
     match value {
         Value::Number(num) => num.as_i64(),
@@ -592,6 +1099,22 @@ fn normalize_job_input(raw: &Value, apply_defaults: bool) -> Result<Map<String,
         );
     }
 
+    for (alias, key) in [
+        ("catch_up", "catchUp"),
+        ("max_lateness_ms", "maxLatenessMs"),
+        ("max_concurrent_runs", "maxConcurrentRuns"),
+        ("timeout_ms", "timeoutMs"),
+        ("depends_on", "dependsOn"),
+        ("on_success", "onSuccess"),
+        ("on_failure", "onFailure"),
+    ] {
+        if !map.contains_key(key) {
+            if let Some(value) = map.remove(alias) {
+                map.insert(key.to_string(), value);
+            }
+        }
+    }
+
     if let Some(Value::Object(payload)) = map.get("payload").cloned() {
         map.insert(
             "payload".to_string(),
@@ -729,6 +1252,29 @@ fn validate_job_spec(map: &Map<String, Value>) -> Result<()> {
     let delivery = map.get("delivery");
     let has_delivery = delivery.is_some();
 
+    CatchUpPolicy::parse(map.get("catchUp"), map.get("maxLatenessMs"))?;
+    RetryPolicy::parse(map.get("retry"))?;
+    if let Some(schedule) = map.get("schedule").and_then(|v| v.as_object()) {
+        validate_schedule_calendar(schedule)?;
+    }
+    let self_id = map.get("id").and_then(|v| v.as_str());
+    let mut chained = parse_dependencies(map.get("dependsOn"))?
+        .into_iter()
+        .map(|dep| dep.job_id)
+        .collect::<Vec<_>>();
+    chained.extend(parse_job_ids(map.get("onSuccess"), "onSuccess")?);
+    chained.extend(parse_job_ids(map.get("onFailure"), "onFailure")?);
+    if self_id.is_some_and(|id| chained.iter().any(|other| other == id)) {
+        anyhow::bail!("a cron job cannot be chained to itself");
+    }
+    for key in ["maxConcurrentRuns", "timeoutMs"] {
+        if let Some(value) = map.get(key).filter(|v| !v.is_null()) {
+            if value.as_u64().filter(|value| *value > 0).is_none() {
+                anyhow::bail!("{key} must be a positive integer");
+            }
+        }
+    }
+
     if session_target == "main" && payload_kind != "systemEvent" {
         anyhow::bail!("main cron jobs require payload.kind=\"systemEvent\"");
     }
@@ -756,37 +1302,30 @@ fn validate_job_spec(map: &Map<String, Value>) -> Result<()> {
     Ok(())
 }
 
-fn jobs_path(paths: &ClawdPaths) -> PathBuf {
-    paths.cron_dir.join(JOBS_FILE)
-}
-
-fn runs_path(paths: &ClawdPaths, job_id: &str) -> PathBuf {
-    paths
-        .cron_dir
-        .join(RUNS_DIR)
-        .join(format!("{job_id}.jsonl"))
-}
-
-fn pending_path(paths: &ClawdPaths) -> PathBuf {
-    paths.cron_dir.join(PENDING_FILE)
-}
-
-fn load_jobs(paths: &ClawdPaths) -> Result<Vec<Value>> {
-    let path = jobs_path(paths);
-    let Some(value) = read_json_value(&path)? else {
-        return Ok(Vec::new());
-    };
-    let mut jobs = match value {
-        Value::Array(items) => items,
-        Value::Object(mut obj) => match obj.remove("jobs") {
-            Some(Value::Array(items)) => items,
-            _ => anyhow::bail!("cron jobs file missing jobs array"),
-        },
-        _ => anyhow::bail!("cron jobs file is not an array or object"),
+fn validate_schedule_calendar(schedule: &Map<String, Value>) -> Result<()> {
+    let Some(calendar) = ScheduleCalendar::parse(schedule)? else {
+        return Ok(());
     };
+    if schedule.get("kind").and_then(|v| v.as_str()) == Some("at") {
+        anyhow::bail!(
+            "{} only apply to cron and every schedules",
+            CALENDAR_KEYS.join(", ")
+        );
+    }
+    let tz = schedule
+        .get("timezone")
+        .or_else(|| schedule.get("tz"))
+        .and_then(|v| v.as_str());
+    if let Some(tz) = tz.filter(|tz| tz.parse::<Tz>().is_err()) {
+        anyhow::bail!("unknown time zone {tz:?} for the schedule calendar");
+    }
+    calendar.validate()
+}
 
-    let mut mutated = false;
-    for job in &mut jobs {
+/// Upgrades job documents written by older versions (legacy `session_key`, delivery hints
+/// inside the payload, main-session deliveries). Applied once when `jobs.json` is imported.
+pub(crate) fn normalize_legacy_jobs(jobs: &mut [Value]) {
+    for job in jobs.iter_mut() {
         let Some(map) = job.as_object_mut() else {
             continue;
         };
@@ -794,7 +1333,6 @@ fn load_jobs(paths: &ClawdPaths) -> Result<Vec<Value>> {
         if !map.contains_key("sessionKey") {
             if let Some(session_key) = map.remove("session_key") {
                 map.insert("sessionKey".to_string(), session_key);
-                mutated = true;
             }
         }
         if let Some(session_key) = map.get("sessionKey").cloned() {
@@ -803,33 +1341,22 @@ fn load_jobs(paths: &ClawdPaths) -> Result<Vec<Value>> {
                     let trimmed = value.trim();
                     if trimmed.is_empty() {
                         map.remove("sessionKey");
-                        mutated = true;
                     } else if trimmed != value {
                         map.insert("sessionKey".to_string(), Value::String(trimmed.to_string()));
-                        mutated = true;
                     }
                 }
                 _ => {
                     map.remove("sessionKey");
-                    mutated = true;
                 }
             }
         }
 
         if let Some(Value::Object(payload)) = map.get_mut("payload") {
-            let normalized = normalize_payload(payload.clone());
-            if *payload != normalized {
-                *payload = normalized;
-                mutated = true;
-            }
+            *payload = normalize_payload(payload.clone());
         }
 
         if let Some(Value::Object(delivery)) = map.get_mut("delivery") {
-            let normalized = normalize_delivery(delivery.clone());
-            if *delivery != normalized {
-                *delivery = normalized;
-                mutated = true;
-            }
+            *delivery = normalize_delivery(delivery.clone());
         }
 
         let payload_kind = map
@@ -863,7 +1390,6 @@ fn load_jobs(paths: &ClawdPaths) -> Result<Vec<Value>> {
                         strip_legacy_delivery_fields(payload_mut);
                     }
                     strip_legacy_job_delivery_fields(map);
-                    mutated = true;
                 } else {
                     map.insert(
                         "delivery".to_string(),
@@ -872,7 +1398,6 @@ fn load_jobs(paths: &ClawdPaths) -> Result<Vec<Value>> {
                             Value::String("announce".to_string()),
                         )])),
                     );
-                    mutated = true;
                 }
             } else if let Some(delivery) = legacy_patch {
                 let merged = merge_delivery(map.get("delivery"), &delivery);
@@ -881,49 +1406,24 @@ fn load_jobs(paths: &ClawdPaths) -> Result<Vec<Value>> {
                     strip_legacy_delivery_fields(payload_mut);
                 }
                 strip_legacy_job_delivery_fields(map);
-                mutated = true;
             }
         }
 
         if session_target == "main" {
             let keep_webhook = delivery_mode(map.get("delivery")).as_deref() == Some("webhook");
-            if !keep_webhook && map.remove("delivery").is_some() {
-                mutated = true;
+            if !keep_webhook {
+                map.remove("delivery");
             }
         }
     }
-
-    if mutated {
-        save_jobs(paths, &jobs)?;
-    }
-
-    Ok(jobs)
 }
 
-fn save_jobs(paths: &ClawdPaths, jobs: &[Value]) -> Result<()> {
-    let path = jobs_path(paths);
-    write_json_value(
-        &path,
-        &json!({
-            "version": 1,
-            "jobs": jobs
-        }),
-    )
+fn load_jobs(paths: &ClawdPaths) -> Result<Vec<Value>> {
+    CronStore::open(paths)?.read(|tx| tx.list_jobs())
 }
 
 pub(crate) fn load_job_value(paths: &ClawdPaths, job_id: &str) -> Result<Option<Value>> {
-    let jobs = load_jobs(paths)?;
-    for job in jobs {
-        if job
-            .get("id")
-            .and_then(|v| v.as_str())
-            .map(|v| v == job_id)
-            .unwrap_or(false)
-        {
-            return Ok(Some(job));
-        }
-    }
-    Ok(None)
+    CronStore::open(paths)?.read(|tx| tx.get_job(job_id))
 }
 
 fn ensure_job_id(map: &mut Map<String, Value>) -> String {
@@ -952,6 +1452,13 @@ fn job_enabled(job: &Value) -> bool {
     job.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true)
 }
 
+/// `maxConcurrentRuns`, at least 1.
+fn job_concurrency_limit(job: &Value) -> usize {
+    job.get("maxConcurrentRuns")
+        .and_then(|v| v.as_u64())
+        .map_or(1, |limit| limit.max(1) as usize)
+}
+
 fn job_running(job: &Value) -> bool {
     job.get("state")
         .and_then(|v| v.get("runningAtMs"))
@@ -980,22 +1487,6 @@ fn job_id_from_args(args: &Value) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-fn find_job_mut<'a>(jobs: &'a mut [Value], job_id: &str) -> Option<&'a mut Map<String, Value>> {
-    for job in jobs {
-        if let Value::Object(map) = job {
-            if map
-                .get("id")
-                .and_then(|v| v.as_str())
-                .map(|v| v == job_id)
-                .unwrap_or(false)
-            {
-                return Some(map);
-            }
-        }
-    }
-    None
-}
-
 fn job_state_mut(job: &mut Map<String, Value>) -> &mut Map<String, Value> {
     let needs_init = match job.get("state") {
         Some(Value::Object(_)) => false,
@@ -1146,6 +1637,21 @@ pub(crate) fn build_cron_job(job: &Value) -> Option<CronJob> {
         .get("deleteAfterRun")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let max_concurrent_runs = job
+        .get("maxConcurrentRuns")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize);
+    let timeout_ms = job.get("timeoutMs").and_then(|v| v.as_u64());
+    let upstream = job
+        .get("state")
+        .and_then(|v| v.get("upstream"))
+        .filter(|v| v.is_object())
+        .cloned();
+    let upstreams = job
+        .get("state")
+        .and_then(|v| v.get("upstreams"))
+        .filter(|v| v.is_object())
+        .cloned();
 
     Some(CronJob {
         id,
@@ -1161,6 +1667,10 @@ pub(crate) fn build_cron_job(job: &Value) -> Option<CronJob> {
         best_effort,
         delivery,
         delete_after_run,
+        max_concurrent_runs,
+        timeout_ms,
+        upstream,
+        upstreams,
     })
 }
 
@@ -1170,7 +1680,11 @@ pub fn job_prompt(job: &CronJob, now: i64) -> Option<String> {
         .get("kind")
         .and_then(|v| v.as_str())
         .unwrap_or("agentTurn");
-    let message = payload_message(&job.payload)?;
+    let message = render_upstream_vars(
+        &payload_message(&job.payload)?,
+        job.upstream.as_ref(),
+        job.upstreams.as_ref(),
+    );
     let stamp = Utc
         .timestamp_millis_opt(now)
         .single()
@@ -1187,6 +1701,48 @@ pub fn job_prompt(job: &CronJob, now: i64) -> Option<String> {
     Some(format!("{label} {prefix} @ {stamp}\n\n{message}"))
 }
 
+/// Fills `{{upstream.message}}`, `{{upstream.status}}`, `{{upstream.jobId}}`,
+/// `{{upstream.name}}` and `{{upstream.error}}` from the latest run that triggered the job, and
+/// `{{upstreams.<jobId>.<field>}}` from the named upstream when several fed this run. They render
+/// empty when the job was not triggered; other `{{...}}` text is left alone.
+fn render_upstream_vars(
+    message: &str,
+    upstream: Option<&Value>,
+    upstreams: Option<&Value>,
+) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + len].trim();
+        out.push_str(&rest[..start]);
+        let source = match name.strip_prefix("upstream.") {
+            Some(field) => Some((upstream, field)),
+            None => name
+                .strip_prefix("upstreams.")
+                .and_then(|key| key.rsplit_once('.'))
+                .map(|(job_id, field)| (upstreams.and_then(|u| u.get(job_id)), field)),
+        };
+        match source {
+            Some((source, field))
+                if matches!(field, "message" | "status" | "jobId" | "name" | "error") =>
+            {
+                let value = source
+                    .and_then(|u| u.get(field))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                out.push_str(value);
+            }
+            _ => out.push_str(&rest[start..start + len + 2]),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
 pub fn job_session_key(job: &CronJob) -> String {
     if let Some(session_key) = job
         .session_key
@@ -1203,28 +1759,52 @@ pub fn job_session_key(job: &CronJob) -> String {
 }
 
 pub fn drain_pending_jobs(paths: &ClawdPaths) -> Result<Vec<CronJob>> {
-    let path = pending_path(paths);
-    if !path.exists() {
-        return Ok(Vec::new());
-    }
-    let data = read_json_value(&path)?.unwrap_or(Value::Array(Vec::new()));
-    let jobs: Vec<CronJob> = serde_json::from_value(data).unwrap_or_default();
-    write_json_value(&path, &Value::Array(Vec::new()))?;
-    Ok(jobs)
+    let pending = CronStore::open(paths)?.write(|tx| tx.drain_pending())?;
+    Ok(pending
+        .into_iter()
+        .filter_map(|job| serde_json::from_value(job).ok())
+        .collect())
 }
 
-fn enqueue_pending_job(paths: &ClawdPaths, job: &CronJob) -> Result<()> {
-    let path = pending_path(paths);
-    let mut pending: Vec<CronJob> = if let Some(value) = read_json_value(&path)? {
-        serde_json::from_value(value).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-    pending.push(job.clone());
-    write_json_value(&path, &serde_json::to_value(pending)?)?;
+/// A queued next-heartbeat run. It stays in the queue until `ack_pending_job` removes it, so a
+/// run the daemon could not start yet survives a restart.
+pub struct PendingRun {
+    pub seq: i64,
+    pub job: CronJob,
+}
+
+/// Queued runs, oldest first, without removing them.
+pub fn peek_pending_jobs(paths: &ClawdPaths) -> Result<Vec<PendingRun>> {
+    let pending = CronStore::open(paths)?.write(|tx| {
+        let mut runs = Vec::new();
+        for (seq, job) in tx.list_pending()? {
+            match serde_json::from_value(job) {
+                Ok(job) => runs.push(PendingRun { seq, job }),
+                Err(_) => {
+                    tx.remove_pending_seq(seq)?;
+                }
+            }
+        }
+        Ok(runs)
+    })?;
+    Ok(pending)
+}
+
+/// Removes a queued run once it has been handed to a runner.
+pub fn ack_pending_job(paths: &ClawdPaths, seq: i64) -> Result<()> {
+    CronStore::open(paths)?.write(|tx| tx.remove_pending_seq(seq))?;
     Ok(())
 }
 
+/// Queues a run the daemon could not start yet so it is retried, also after a restart.
+pub fn requeue_pending_job(paths: &ClawdPaths, job: &CronJob) -> Result<()> {
+    CronStore::open(paths)?.write(|tx| enqueue_pending_job(tx, job))
+}
+
+fn enqueue_pending_job(tx: &CronTx<'_>, job: &CronJob) -> Result<()> {
+    tx.push_pending(&job.id, &serde_json::to_value(job)?)
+}
+
 pub fn list_jobs(paths: &ClawdPaths, include_disabled: bool) -> Result<Value> {
     let jobs = load_jobs(paths)?;
     let now = now_ms();
@@ -1234,6 +1814,7 @@ pub fn list_jobs(paths: &ClawdPaths, include_disabled: bool) -> Result<Value> {
         jobs.into_iter().filter(job_enabled).collect()
     };
 
+    let mut warnings = Vec::new();
     for job in &mut filtered {
         let enabled = job_enabled(job);
         let next_run = if enabled {
@@ -1241,6 +1822,12 @@ pub fn list_jobs(paths: &ClawdPaths, include_disabled: bool) -> Result<Value> {
         } else {
             None
         };
+        if let (Some(next), Some(schedule)) = (next_run, job_schedule(job)) {
+            if !schedule.calendar_allows(next) {
+                let id = job.get("id").and_then(Value::as_str).unwrap_or_default();
+                warnings.push(format!("job {id}: {CALENDAR_HORIZON_WARNING}"));
+            }
+        }
         if let Value::Object(map) = job {
             if enabled {
                 if let Some(next) = next_run {
@@ -1267,7 +1854,168 @@ pub fn list_jobs(paths: &ClawdPaths, include_disabled: bool) -> Result<Value> {
         a_next.cmp(&b_next)
     });
 
-    Ok(json!({ "jobs": filtered }))
+    Ok(json!({ "jobs": filtered, "warnings": warnings }))
+}
+
+/// Up to `count` upcoming fire times for `job`, starting from its next run after `now`.
+pub fn upcoming_runs(job: &Value, now: i64, count: usize) -> Vec<i64> {
+    let Some(schedule) = job_schedule(job) else {
+        return Vec::new();
+    };
+    let mut runs = Vec::new();
+    let mut next = compute_next_run(job, now);
+    while let Some(slot) = next.filter(|slot| schedule.calendar_allows(*slot)) {
+        if runs.len() >= count {
+            break;
+        }
+        runs.push(slot);
+        next = schedule.next_slot(slot);
+    }
+    runs
+}
+
+/// Parses a cron expression the way scheduled jobs read it: seconds first, six or seven fields.
+/// Returns the number of fields.
+pub fn check_cron_expr(expr: &str) -> Result<usize> {
+    let expr = expr.trim();
+    let fields = expr.split_whitespace().count();
+    match fields {
+        0 => anyhow::bail!("cron expression is empty"),
+        5 => anyhow::bail!(
+            "cron expression {expr:?} has 5 fields, but cron jobs use seconds-first syntax \
+             (sec min hour day-of-month month day-of-week [year]); did you mean \"0 {expr}\"?"
+        ),
+        6 | 7 => {}
+        n => anyhow::bail!(
+            "cron expression {expr:?} has {n} fields; expected 6 or 7 \
+             (sec min hour day-of-month month day-of-week [year])"
+        ),
+    }
+    cron::Schedule::from_str(expr)
+        .map_err(|err| anyhow::anyhow!("invalid cron expression {expr:?}: {err}"))?;
+    Ok(fields)
+}
+
+const DEFAULT_PREVIEW_COUNT: usize = 5;
+const MAX_PREVIEW_COUNT: usize = 100;
+
+/// `cron.preview`: validates `args.schedule` and lists its next `count` fire times after
+/// `fromMs` (default now) in UTC and in the schedule's time zone.
+pub fn preview_schedule(args: &Value) -> Result<Value> {
+    let schedule = args
+        .get("schedule")
+        .and_then(Value::as_object)
+        .cloned()
+        .context("cron.preview requires a schedule object")?;
+    let schedule = Value::Object(normalize_schedule(schedule));
+    let spec = ScheduleSpec::from_value(&schedule).context("invalid schedule")?;
+    let count = args
+        .get("count")
+        .and_then(Value::as_u64)
+        .map(|count| count as usize)
+        .unwrap_or(DEFAULT_PREVIEW_COUNT)
+        .clamp(1, MAX_PREVIEW_COUNT);
+    let from = args
+        .get("fromMs")
+        .and_then(Value::as_i64)
+        .unwrap_or_else(now_ms);
+    let tz_name = spec
+        .tz
+        .as_deref()
+        .map(str::trim)
+        .filter(|tz| !tz.is_empty());
+    let tz: Tz = match tz_name {
+        Some(raw) => raw.parse().map_err(|_| {
+            anyhow::anyhow!("unknown time zone {raw:?}; use an IANA name such as Europe/Oslo")
+        })?,
+        None => chrono_tz::UTC,
+    };
+
+    let mut out = Map::new();
+    let mut warnings = Vec::new();
+    out.insert("kind".to_string(), json!(spec.kind));
+    match spec.kind.as_str() {
+        "cron" => {
+            let expr = spec
+                .cron
+                .as_deref()
+                .context("cron schedules require expr")?;
+            let fields = check_cron_expr(expr)?;
+            out.insert("expr".to_string(), json!(expr.trim()));
+            out.insert("fields".to_string(), json!(fields));
+            if tz_name.is_none() {
+                warnings.push("no tz set; the expression is evaluated in UTC".to_string());
+            }
+        }
+        "every" => {
+            if spec.every_ms.filter(|every| *every > 0).is_none() {
+                anyhow::bail!("every schedules require a positive everyMs");
+            }
+        }
+        "at" => {
+            let at_ms = spec.at_ms.context("at schedules require atMs")?;
+            if at_ms <= from {
+                warnings.push("atMs is in the past; the job will never fire".to_string());
+            }
+        }
+        other => anyhow::bail!("unknown schedule kind {other:?}; expected at, every or cron"),
+    }
+    out.insert("timezone".to_string(), json!(tz.name()));
+
+    let mut runs = Vec::new();
+    let mut previous_offset = None;
+    let mut next = spec
+        .next_run_after(None, None, from)
+        .filter(|slot| *slot > from);
+    while let Some(slot) = next {
+        if runs.len() >= count {
+            break;
+        }
+        if !spec.calendar_allows(slot) {
+            warnings.push(CALENDAR_HORIZON_WARNING.to_string());
+            break;
+        }
+        let Some(utc) = Utc.timestamp_millis_opt(slot).single() else {
+            break;
+        };
+        let local = utc.with_timezone(&tz);
+        let offset = local.format("%:z").to_string();
+        let ambiguous = matches!(
+            tz.from_local_datetime(&local.naive_local()),
+            chrono::LocalResult::Ambiguous(_, _)
+        );
+        if ambiguous {
+            warnings.push(format!(
+                "{} occurs twice in {} (DST fall-back); this run fires at {}",
+                local.format("%Y-%m-%d %H:%M:%S"),
+                tz.name(),
+                utc.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            ));
+        }
+        if let Some(previous) = previous_offset.replace(offset.clone()) {
+            if previous != offset {
+                warnings.push(format!(
+                    "UTC offset changes from {previous} to {offset} before {} (DST); wall-clock \
+                     times in a skipped or repeated hour may shift",
+                    local.format("%Y-%m-%d %H:%M")
+                ));
+            }
+        }
+        runs.push(json!({
+            "atMs": slot,
+            "utc": utc.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            "local": local.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            "dstAmbiguous": ambiguous,
+        }));
+        next = spec.next_slot(slot);
+    }
+    if runs.is_empty() && spec.kind != "at" {
+        warnings.push("schedule has no upcoming runs".to_string());
+    }
+    out.insert("ok".to_string(), json!(true));
+    out.insert("runs".to_string(), Value::Array(runs));
+    out.insert("warnings".to_string(), json!(warnings));
+    Ok(Value::Object(out))
 }
 
 pub fn status(paths: &ClawdPaths, enabled: bool) -> Result<Value> {
@@ -1295,14 +2043,13 @@ pub fn status(paths: &ClawdPaths, enabled: bool) -> Result<Value> {
     }
     Ok(json!({
         "enabled": enabled,
-        "storePath": jobs_path(paths).to_string_lossy(),
+        "storePath": cron_store::db_path(paths).to_string_lossy(),
         "jobs": total_jobs,
         "nextWakeAtMs": next_wake,
     }))
 }
 
 pub fn add_job(paths: &ClawdPaths, args: &Value) -> Result<Value> {
-    let mut jobs = load_jobs(paths)?;
     let mut map = normalize_job_input(args, true)?;
 
     let _id = ensure_job_id(&mut map);
@@ -1322,22 +2069,32 @@ pub fn add_job(paths: &ClawdPaths, args: &Value) -> Result<Value> {
             }
         }
     }
-    jobs.push(value.clone());
-    save_jobs(paths, &jobs)?;
+    CronStore::open(paths)?.write(|tx| {
+        let id = value.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+        if tx.get_job(id)?.is_some() {
+            anyhow::bail!("cron job {id} already exists");
+        }
+        tx.put_job(&value)
+    })?;
     Ok(value)
 }
 
 pub fn update_job(paths: &ClawdPaths, args: &Value) -> Result<Value> {
-    let mut jobs = load_jobs(paths)?;
     let job_id = job_id_from_args(args).context("cron.update requires jobId or id")?;
     let patch = args
         .get("patch")
         .cloned()
         .context("cron.update requires patch")?;
     let patch_map = normalize_job_input(&patch, false)?;
-    let now = now_ms();
+    CronStore::open(paths)?.write(|tx| {
+        let mut value = tx.get_job(&job_id)?.context("job not found")?;
+        apply_job_patch(cron_store::job_map(&mut value)?, patch_map, now_ms())?;
+        tx.put_job(&value)?;
+        Ok(value)
+    })
+}
 
-    let job = find_job_mut(&mut jobs, &job_id).context("job not found")?;
+fn apply_job_patch(job: &mut Map<String, Value>, patch_map: Map<String, Value>, now: i64) -> Result<()> {
     let mut saw_delivery_patch = false;
     let mut payload_patch: Option<Map<String, Value>> = None;
     for (key, value) in patch_map {
@@ -1427,6 +2184,11 @@ pub fn update_job(paths: &ClawdPaths, args: &Value) -> Result<Value> {
 
     job.insert("updatedAtMs".to_string(), Value::Number(now.into()));
     validate_job_spec(job)?;
+    // Replays and retries were planned under the old schedule/policy.
+    let state = job_state_mut(job);
+    state.remove("catchUpQueue");
+    state.remove("retryAttempt");
+    state.remove("retryAtMs");
     let enabled = job_enabled(&Value::Object(job.clone()));
     if enabled {
         let value = Value::Object(job.clone());
@@ -1438,23 +2200,12 @@ pub fn update_job(paths: &ClawdPaths, args: &Value) -> Result<Value> {
         state.remove("nextRunAtMs");
         state.remove("runningAtMs");
     }
-    let updated = Value::Object(job.clone());
-    save_jobs(paths, &jobs)?;
-    Ok(updated)
+    Ok(())
 }
 
 pub fn remove_job(paths: &ClawdPaths, args: &Value) -> Result<Value> {
-    let mut jobs = load_jobs(paths)?;
     let job_id = job_id_from_args(args).context("cron.remove requires jobId or id")?;
-    let before = jobs.len();
-    jobs.retain(|job| {
-        job.get("id")
-            .and_then(|v| v.as_str())
-            .map(|v| v != job_id)
-            .unwrap_or(true)
-    });
-    let removed = before.saturating_sub(jobs.len()) > 0;
-    save_jobs(paths, &jobs)?;
+    let removed = CronStore::open(paths)?.write(|tx| tx.delete_job(&job_id))?;
     Ok(json!({ "ok": true, "removed": removed }))
 }
 
@@ -1464,7 +2215,7 @@ pub fn runs(paths: &ClawdPaths, args: &Value) -> Result<Value> {
         .get("limit")
         .and_then(|v| v.as_u64())
         .map(|v| v as usize);
-    let entries = read_json_lines(&runs_path(paths, &job_id), None)?;
+    let entries = CronStore::open(paths)?.read(|tx| tx.list_runs(&job_id))?;
     let mut finished = Vec::new();
     for entry in entries {
         let action = entry.get("action").and_then(|v| v.as_str());
@@ -1497,37 +2248,124 @@ pub fn runs(paths: &ClawdPaths, args: &Value) -> Result<Value> {
 }
 
 pub fn mark_job_running(paths: &ClawdPaths, job_id: &str, started_at: i64) -> Result<()> {
-    let mut jobs = load_jobs(paths)?;
-    let Some(job) = find_job_mut(&mut jobs, job_id) else {
-        return Ok(());
-    };
-    let state = job_state_mut(job);
-    state.insert("runningAtMs".to_string(), Value::Number(started_at.into()));
-    state.remove("lastError");
-    job.insert("updatedAtMs".to_string(), Value::Number(started_at.into()));
-    save_jobs(paths, &jobs)?;
-    Ok(())
+    CronStore::open(paths)?.write(|tx| {
+        let Some(mut value) = tx.get_job(job_id)? else {
+            return Ok(());
+        };
+        let job = cron_store::job_map(&mut value)?;
+        let state = job_state_mut(job);
+        state.insert("runningAtMs".to_string(), Value::Number(started_at.into()));
+        state.remove("lastError");
+        state.remove("cancelRequestedAtMs");
+        job.insert("updatedAtMs".to_string(), Value::Number(started_at.into()));
+        tx.put_job(&value)
+    })
+}
+
+/// Stops a job: drops its queued next-heartbeat runs and, if it is running, flags it so the
+/// daemon interrupts the turn within about a second.
+pub fn cancel_job(paths: &ClawdPaths, args: &Value) -> Result<Value> {
+    let job_id = job_id_from_args(args).context("cron.cancel requires jobId or id")?;
+    let now = now_ms();
+    CronStore::open(paths)?.write(|tx| {
+        let Some(mut value) = tx.get_job(&job_id)? else {
+            return Ok(json!({ "ok": false, "cancelled": false, "reason": "not-found" }));
+        };
+        let dequeued = tx.remove_pending(&job_id)?;
+        if dequeued > 0 {
+            record_run_in(
+                tx,
+                &job_id,
+                "cancelled",
+                "cancelled before start",
+                Some(json!({ "applyState": false })),
+            )?;
+        }
+        let running = job_running(&value);
+        if running {
+            let job = cron_store::job_map(&mut value)?;
+            set_state_field(job, "cancelRequestedAtMs", Value::Number(now.into()));
+            tx.put_job(&value)?;
+        }
+        let mut result = json!({
+            "ok": true,
+            "cancelled": running || dequeued > 0,
+            "running": running,
+            "dequeued": dequeued,
+        });
+        if !running && dequeued == 0 {
+            result["reason"] = Value::String("not-running".to_string());
+        }
+        Ok(result)
+    })
+}
+
+/// Whether `cron.cancel` was called for the job's current run.
+pub fn cancel_requested(paths: &ClawdPaths, job_id: &str) -> Result<bool> {
+    Ok(load_job_value(paths, job_id)?
+        .and_then(|job| job.get("state")?.get("cancelRequestedAtMs")?.as_i64())
+        .is_some())
+}
+
+/// What recording a finished run did to its job.
+#[derive(Debug, Default)]
+struct RunStateUpdate {
+    next_run: Option<i64>,
+    /// 1-based attempt number of the run; only tracked for jobs with a retry policy.
+    attempt: Option<u32>,
+    /// Set when the run failed and a retry is scheduled.
+    retry_at: Option<i64>,
 }
 
 fn update_job_state_from_run(
-    paths: &ClawdPaths,
+    tx: &CronTx<'_>,
     job_id: &str,
     status: &str,
     run_at_ms: i64,
     duration_ms: i64,
     error: Option<String>,
-) -> Result<Option<i64>> {
-    let mut jobs = load_jobs(paths)?;
-    let Some(job) = find_job_mut(&mut jobs, job_id) else {
-        return Ok(None);
+    retryable: bool,
+) -> Result<RunStateUpdate> {
+    let Some(mut value) = tx.get_job(job_id)? else {
+        return Ok(RunStateUpdate::default());
     };
+    let job = cron_store::job_map(&mut value)?;
     let schedule = job.get("schedule").and_then(ScheduleSpec::from_value);
     let created_at = job.get("createdAtMs").and_then(|v| v.as_i64());
+    let retry = RetryPolicy::from_job(job);
     let duration_ms = duration_ms.max(0);
     let ended_at = run_at_ms.saturating_add(duration_ms);
 
     let state = job_state_mut(job);
+    let retries_used = state
+        .remove("retryAttempt")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+        .min(u32::MAX as u64) as u32;
+    state.remove("retryAtMs");
     state.remove("runningAtMs");
+    // A trigger that arrived while this run was going still has to run.
+    let pending_trigger = state
+        .get("triggeredAtMs")
+        .and_then(|v| v.as_i64())
+        .filter(|at| *at > run_at_ms);
+    if let Some(pending) = pending_trigger {
+        // Keep only the upstreams that fired after this run started.
+        if let Some(Value::Object(upstreams)) = state.get_mut("upstreams") {
+            upstreams.retain(|_, entry| {
+                entry
+                    .get("triggeredAtMs")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(pending)
+                    > run_at_ms
+            });
+        }
+    } else {
+        state.remove("triggeredAtMs");
+        state.remove("upstream");
+        state.remove("upstreams");
+    }
+    state.remove("cancelRequestedAtMs");
     state.insert("lastRunAtMs".to_string(), Value::Number(run_at_ms.into()));
     state.insert("lastStatus".to_string(), Value::String(status.to_string()));
     state.insert(
@@ -1565,16 +2403,41 @@ fn update_job_state_from_run(
             next_run = schedule.next_run_after(Some(run_at_ms), created_at, ended_at);
         }
     }
+    if let Some(slot) = job
+        .get("state")
+        .and_then(|v| v.get("catchUpQueue"))
+        .and_then(|v| v.get(0))
+        .and_then(|v| v.as_i64())
+        .filter(|_| enabled)
+    {
+        // Catch-up replays pending: the next one is already due.
+        next_run = Some(slot);
+    }
+    if let Some(at) = pending_trigger.filter(|_| enabled) {
+        next_run = Some(next_run.map_or(at, |next| next.min(at)));
+    }
+    let retry_at = retry
+        .as_ref()
+        .filter(|_| enabled && !delete_job && retryable)
+        .and_then(|policy| policy.retry_at(status, retries_used, ended_at));
+    if let Some(at) = retry_at {
+        let state = job_state_mut(job);
+        state.insert("retryAttempt".to_string(), Value::Number((retries_used + 1).into()));
+        state.insert("retryAtMs".to_string(), Value::Number(at.into()));
+        next_run = Some(at);
+    }
+    let update = RunStateUpdate {
+        next_run,
+        attempt: retry.as_ref().map(|_| retries_used + 1),
+        retry_at,
+    };
 
     if delete_job {
-        jobs.retain(|job| {
-            job.get("id")
-                .and_then(|v| v.as_str())
-                .map(|v| v != job_id)
-                .unwrap_or(true)
+        tx.delete_job(job_id)?;
+        return Ok(RunStateUpdate {
+            next_run: None,
+            ..update
         });
-        save_jobs(paths, &jobs)?;
-        return Ok(None);
     }
 
     if let Some(next) = next_run {
@@ -1590,22 +2453,37 @@ fn update_job_state_from_run(
         state.remove("runningAtMs");
     }
 
-    save_jobs(paths, &jobs)?;
-    Ok(next_run)
+    tx.put_job(&value)?;
+    Ok(update)
 }
 
+/// Appends a run log entry and, for finished runs, updates the job state in the same
+/// transaction.
 pub fn record_run(
     paths: &ClawdPaths,
     job_id: &str,
     status: &str,
     reason: &str,
     details: Option<Value>,
+) -> Result<Value> {
+    CronStore::open(paths)?.write(|tx| record_run_in(tx, job_id, status, reason, details))
+}
+
+fn record_run_in(
+    tx: &CronTx<'_>,
+    job_id: &str,
+    status: &str,
+    reason: &str,
+    details: Option<Value>,
 ) -> Result<Value> {
     let now = now_ms();
     let (action, run_status) = match status {
         "completed" => ("finished", Some("ok")),
-        "delivery_failed" => ("finished", Some("error")),
+        "delivery_failed" | "failed" => ("finished", Some("error")),
         "skipped" => ("finished", Some("skipped")),
+        "missed" => ("finished", Some("missed")),
+        "timeout" => ("finished", Some("timeout")),
+        "cancelled" => ("finished", Some("cancelled")),
         _ => ("queued", None),
     };
     let summary = details
@@ -1626,7 +2504,7 @@ pub fn record_run(
                 Some(reason.to_string())
             }
         });
-    let mut error = if run_status == Some("error") {
+    let mut error = if matches!(run_status, Some("error") | Some("timeout")) {
         details
             .as_ref()
             .and_then(|d| d.get("error"))
@@ -1635,7 +2513,10 @@ pub fn record_run(
     } else {
         None
     };
-    if run_status == Some("error") && error.is_none() && !reason.trim().is_empty() {
+    if matches!(run_status, Some("error") | Some("timeout"))
+        && error.is_none()
+        && !reason.trim().is_empty()
+    {
         error = Some(reason.to_string());
     }
 
@@ -1667,15 +2548,40 @@ pub fn record_run(
         .and_then(|d| d.get("nextRunAtMs"))
         .and_then(|v| v.as_i64());
 
+    // A failed delivery is logged as an error but not retried: the turn already ran, and running
+    // it again would send a second message.
+    let retryable = status != "delivery_failed";
+    let mut update = RunStateUpdate::default();
+    let mut triggered = Vec::new();
     if let (Some(status), true) = (run_status, apply_state) {
+        // Read before the state update, which may delete a finished one-shot job.
+        let upstream = match status {
+            "ok" | "skipped" | "error" | "timeout" => tx.get_job(job_id)?,
+            _ => None,
+        };
         let run_at = run_at_ms.unwrap_or(now);
         let duration = duration_ms.unwrap_or(0).max(0);
-        if let Some(next) =
-            update_job_state_from_run(paths, job_id, status, run_at, duration, error.clone())?
-        {
-            if next_run_at_ms.is_none() {
-                next_run_at_ms = Some(next);
-            }
+        update = update_job_state_from_run(
+            tx,
+            job_id,
+            status,
+            run_at,
+            duration,
+            error.clone(),
+            retryable,
+        )?;
+        if next_run_at_ms.is_none() {
+            next_run_at_ms = update.next_run;
+        }
+        if let (Some(mut upstream), None) = (upstream, update.retry_at) {
+            let result = json!({
+                "jobId": job_id,
+                "status": status,
+                "message": summary,
+                "error": error,
+                "finishedAtMs": now,
+            });
+            triggered = trigger_dependents(tx, cron_store::job_map(&mut upstream)?, result, now)?;
         }
     }
 
@@ -1706,12 +2612,128 @@ pub fn record_run(
     if let Some(next) = next_run_at_ms {
         entry.insert("nextRunAtMs".to_string(), Value::Number(next.into()));
     }
+    for key in ["scheduledAtMs", "lastScheduledAtMs", "missedCount", "attempt"] {
+        if let Some(value) = details.as_ref().and_then(|d| d.get(key)).filter(|v| v.is_i64()) {
+            entry.insert(key.to_string(), value.clone());
+        }
+    }
+    if let Some(attempt) = update.attempt {
+        entry.insert("attempt".to_string(), Value::Number(attempt.into()));
+    }
+    if let Some(retry_at) = update.retry_at {
+        entry.insert("retryAtMs".to_string(), Value::Number(retry_at.into()));
+    }
+    if !triggered.is_empty() {
+        entry.insert("triggered".to_string(), json!(triggered));
+    }
+    if let Some(upstream) = details
+        .as_ref()
+        .and_then(|d| d.get("triggeredBy"))
+        .filter(|v| v.is_string())
+    {
+        entry.insert("triggeredBy".to_string(), upstream.clone());
+    }
 
     let entry = Value::Object(entry);
-    append_json_line(&runs_path(paths, job_id), &entry)?;
+    tx.append_run(job_id, &entry)?;
     Ok(entry)
 }
 
+/// Makes the jobs chained to a finished run due now: the upstream's `onSuccess` / `onFailure`
+/// lists plus every job whose `dependsOn` matches. `result` (job id, status, message, error) is
+/// stored as the downstream `state.upstream`. Returns the triggered job ids.
+fn trigger_dependents(
+    tx: &CronTx<'_>,
+    upstream: &Map<String, Value>,
+    result: Value,
+    now: i64,
+) -> Result<Vec<String>> {
+    let job_id = result["jobId"].as_str().unwrap_or_default().to_string();
+    let success = matches!(result["status"].as_str(), Some("ok" | "skipped"));
+    let key = if success { "onSuccess" } else { "onFailure" };
+    let mut targets = parse_job_ids(upstream.get(key), key).unwrap_or_default();
+    let jobs = tx.list_jobs()?;
+    for job in &jobs {
+        let depends = parse_dependencies(job.get("dependsOn")).unwrap_or_default();
+        if depends
+            .iter()
+            .any(|dep| dep.job_id == job_id && dep.on.matches(success))
+        {
+            if let Some(id) = job.get("id").and_then(|v| v.as_str()) {
+                if !targets.iter().any(|target| target == id) {
+                    targets.push(id.to_string());
+                }
+            }
+        }
+    }
+    if targets.is_empty() {
+        return Ok(targets);
+    }
+
+    // Ids of every job earlier in this pipeline run, to stop cycles. A run fed by several
+    // upstreams carries all of their chains.
+    let upstream_job_state = upstream.get("state");
+    let mut chain = Vec::new();
+    let feeds = upstream_job_state
+        .and_then(|v| v.get("upstreams"))
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flat_map(|upstreams| upstreams.values())
+        .chain(upstream_job_state.and_then(|v| v.get("upstream")));
+    for feed in feeds {
+        let ids = feed.get("chain").and_then(|v| v.as_array());
+        for id in ids.into_iter().flatten().filter_map(|id| id.as_str()) {
+            if !chain.iter().any(|seen| seen == id) {
+                chain.push(id.to_string());
+            }
+        }
+    }
+    chain.push(job_id.clone());
+    let mut upstream_state = result;
+    if let Some(obj) = upstream_state.as_object_mut() {
+        obj.retain(|_, value| !value.is_null());
+        if let Some(name) = upstream.get("name").filter(|v| v.is_string()) {
+            obj.insert("name".to_string(), name.clone());
+        }
+        obj.insert("chain".to_string(), json!(chain));
+        obj.insert("triggeredAtMs".to_string(), Value::Number(now.into()));
+    }
+
+    let mut triggered = Vec::new();
+    for target in targets {
+        if chain.contains(&target) {
+            eprintln!("[clawdex][cron] not triggering {target} from {job_id}: dependency cycle");
+            continue;
+        }
+        let Some(mut value) = jobs
+            .iter()
+            .find(|job| job.get("id").and_then(|v| v.as_str()) == Some(target.as_str()))
+            .cloned()
+        else {
+            continue;
+        };
+        if !job_enabled(&value) {
+            continue;
+        }
+        let job = cron_store::job_map(&mut value)?;
+        let state = job_state_mut(job);
+        state.insert("upstream".to_string(), upstream_state.clone());
+        // Upstreams that fire before the job runs are all kept, keyed by job id.
+        let mut upstreams = state
+            .get("upstreams")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+        upstreams.insert(job_id.clone(), upstream_state.clone());
+        state.insert("upstreams".to_string(), Value::Object(upstreams));
+        state.insert("triggeredAtMs".to_string(), Value::Number(now.into()));
+        state.insert("nextRunAtMs".to_string(), Value::Number(now.into()));
+        tx.put_job(&value)?;
+        triggered.push(target);
+    }
+    Ok(triggered)
+}
+
 pub fn run_jobs(paths: &ClawdPaths, args: &Value) -> Result<Value> {
     let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("due");
     let job_id = job_id_from_args(args).context("cron.run requires jobId or id")?;
@@ -1738,16 +2760,45 @@ pub fn collect_due_jobs(
     mode: &str,
     job_filter: Option<String>,
 ) -> Result<(Vec<CronJob>, Vec<Value>)> {
-    let mut jobs = load_jobs(paths)?;
+    CronStore::open(paths)?
+        .write(|tx| collect_due_jobs_in(tx, now, mode, job_filter, &HashMap::new()))
+}
+
+/// Like `collect_due_jobs` in `due` mode, but counts the runs already handed to a worker
+/// (`in_flight`, queued or running, by job id) against each job's `maxConcurrentRuns`.
+pub fn collect_due_jobs_in_flight(
+    paths: &ClawdPaths,
+    now: i64,
+    in_flight: &HashMap<String, usize>,
+) -> Result<(Vec<CronJob>, Vec<Value>)> {
+    CronStore::open(paths)?.write(|tx| collect_due_jobs_in(tx, now, "due", None, in_flight))
+}
+
+fn collect_due_jobs_in(
+    tx: &CronTx<'_>,
+    now: i64,
+    mode: &str,
+    job_filter: Option<String>,
+    in_flight: &HashMap<String, usize>,
+) -> Result<(Vec<CronJob>, Vec<Value>)> {
+    let mut jobs = tx.list_jobs()?;
     let mut queued = Vec::new();
     let mut entries = Vec::new();
-    let mut dirty = false;
 
     for job in &mut jobs {
         if !job_enabled(job) {
             continue;
         }
-        if job_running(job) {
+        // A run marked running by another process counts once; its cron lock enforces the
+        // exact limit when the run starts.
+        let limit = job_concurrency_limit(job);
+        let running = job
+            .get("id")
+            .and_then(|v| v.as_str())
+            .and_then(|id| in_flight.get(id).copied())
+            .unwrap_or(0)
+            .max(usize::from(job_running(job)));
+        if running >= limit {
             continue;
         }
         let Some(map) = job.as_object_mut() else {
@@ -1770,7 +2821,7 @@ pub fn collect_due_jobs(
                     state_next = schedule.next_run_after(last_run, created_at, now);
                     if let Some(next) = state_next {
                         set_state_field(map, "nextRunAtMs", Value::Number(next.into()));
-                        dirty = true;
+                        tx.put_job(&Value::Object(map.clone()))?;
                     }
                 }
             }
@@ -1797,21 +2848,86 @@ pub fn collect_due_jobs(
             continue;
         }
 
+        let mut details = None;
+        let retry = map
+            .get("state")
+            .filter(|state| state.get("retryAtMs").and_then(|v| v.as_i64()) == state_next)
+            .and_then(|state| state.get("retryAttempt"))
+            .and_then(|v| v.as_u64());
+        let triggered_by = map
+            .get("state")
+            .filter(|state| state.get("triggeredAtMs").and_then(|v| v.as_i64()) == state_next)
+            .and_then(|state| state.get("upstream"))
+            .and_then(|v| v.get("jobId"))
+            .cloned();
+        if let Some(retry) = retry.filter(|_| mode == "due") {
+            // Retries and triggers are not schedule slots, so the catch-up policy does not
+            // apply to them.
+            details = Some(json!({ "scheduledAtMs": state_next, "attempt": retry + 1 }));
+        } else if let Some(upstream) = triggered_by.filter(|_| mode == "due") {
+            details = Some(json!({ "scheduledAtMs": state_next, "triggeredBy": upstream }));
+        } else if mode == "due" {
+            if let (Some(schedule), Some(planned)) = (schedule.as_ref(), state_next) {
+                // The calendar may have changed since the slot was planned (an edited holidays
+                // file, say), so a blocked slot moves on to the next allowed one.
+                let first = match schedule.first_allowed(planned) {
+                    Some(CalendarSlot::Allowed(first)) if first <= now => first,
+                    // Nothing allowed within the horizon: stay scheduled and search again
+                    // from there rather than dropping the job's next run.
+                    Some(CalendarSlot::Allowed(next) | CalendarSlot::Horizon(next)) => {
+                        set_state_field(map, "nextRunAtMs", json!(next));
+                        tx.put_job(&Value::Object(map.clone()))?;
+                        continue;
+                    }
+                    None => {
+                        job_state_mut(map).remove("nextRunAtMs");
+                        tx.put_job(&Value::Object(map.clone()))?;
+                        continue;
+                    }
+                };
+                match take_catch_up_slot(tx, map, &job_id, schedule, first, now, &mut entries)? {
+                    Some(slot) => details = Some(json!({ "scheduledAtMs": slot })),
+                    None => continue,
+                }
+            }
+        }
+
+        if mode == "due" && limit > 1 {
+            // Other runs may start while this one is going, so move past the dispatched slot
+            // now rather than when the run finishes, or the next tick would dispatch it again.
+            let next = match map
+                .get("state")
+                .and_then(|v| v.get("catchUpQueue"))
+                .and_then(|v| v.get(0))
+                .and_then(|v| v.as_i64())
+            {
+                Some(replay) => Some(replay),
+                None => schedule
+                    .as_ref()
+                    .filter(|schedule| schedule.kind != "at")
+                    .and_then(|schedule| schedule.next_run_after(None, None, now)),
+            };
+            if let Some(next) = next {
+                set_state_field(map, "nextRunAtMs", json!(next));
+                tx.put_job(&Value::Object(map.clone()))?;
+            }
+        }
+
         let job_value = Value::Object(map.clone());
         if let Some(cron_job) = build_cron_job(&job_value) {
             let wake_mode = cron_job.wake_mode.clone();
             if wake_mode == "next-heartbeat" {
-                enqueue_pending_job(paths, &cron_job)?;
-                let entry = record_run(paths, &job_id, "queued", "next-heartbeat", None)?;
+                enqueue_pending_job(tx, &cron_job)?;
+                let entry = record_run_in(tx, &job_id, "queued", "next-heartbeat", details)?;
                 entries.push(entry);
             } else {
-                let entry = record_run(paths, &job_id, "queued", "scheduled", None)?;
+                let entry = record_run_in(tx, &job_id, "queued", "scheduled", details)?;
                 entries.push(entry);
                 queued.push(cron_job.clone());
             }
         } else {
-            let entry = record_run(
-                paths,
+            let entry = record_run_in(
+                tx,
                 &job_id,
                 "skipped",
                 "invalid job",
@@ -1821,12 +2937,101 @@ pub fn collect_due_jobs(
         }
     }
 
-    if dirty {
-        save_jobs(paths, &jobs)?;
-    }
     Ok((queued, entries))
 }
 
+/// Applies the job's catch-up policy to the slots that came due since `first` (its
+/// `nextRunAtMs`). Slots that will not run are recorded as one `missed` run log entry. Returns
+/// the slot to run now, or `None` when nothing runs this tick.
+fn take_catch_up_slot(
+    tx: &CronTx<'_>,
+    job: &mut Map<String, Value>,
+    job_id: &str,
+    schedule: &ScheduleSpec,
+    first: i64,
+    now: i64,
+    entries: &mut Vec<Value>,
+) -> Result<Option<i64>> {
+    let policy = CatchUpPolicy::from_job(job);
+    let replay = job
+        .get("state")
+        .and_then(|v| v.get("catchUpQueue"))
+        .and_then(|v| v.as_array())
+        .map(|queue| queue.iter().filter_map(|v| v.as_i64()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let plan = if replay.first() == Some(&first) {
+        // Replays planned on an earlier tick; only lateness can still drop them.
+        let fresh_from = policy
+            .max_lateness_ms
+            .map(|lateness| now.saturating_sub(lateness))
+            .unwrap_or(i64::MIN);
+        let stale = replay.iter().take_while(|slot| **slot < fresh_from).count();
+        CatchUpPlan {
+            run: replay[stale..].to_vec(),
+            missed: replay[..stale].to_vec(),
+            stale,
+            resume_at: schedule.next_run_after(None, None, now),
+        }
+    } else {
+        plan_catch_up(schedule, policy, first, now)
+    };
+
+    if !plan.missed.is_empty() {
+        let count = plan.missed.len();
+        let mut reason = format!(
+            "missed {count} scheduled run{} (catchUp={})",
+            if count == 1 { "" } else { "s" },
+            policy.mode.as_str()
+        );
+        if plan.stale > 0 {
+            reason.push_str(&format!(", {} past maxLatenessMs", plan.stale));
+        }
+        let entry = record_run_in(
+            tx,
+            job_id,
+            "missed",
+            &reason,
+            Some(json!({
+                "applyState": false,
+                "runAtMs": plan.missed[0],
+                "scheduledAtMs": plan.missed[0],
+                "lastScheduledAtMs": plan.missed[count - 1],
+                "missedCount": count,
+            })),
+        )?;
+        entries.push(entry);
+        set_state_field(job, "lastMissedAtMs", Value::Number(plan.missed[count - 1].into()));
+    }
+
+    let Some((&slot, rest)) = plan.run.split_first() else {
+        let state = job_state_mut(job);
+        state.remove("catchUpQueue");
+        match plan.resume_at.filter(|_| schedule.kind != "at") {
+            Some(next) => {
+                state.insert("nextRunAtMs".to_string(), Value::Number(next.into()));
+            }
+            None => {
+                state.remove("nextRunAtMs");
+                job.insert("enabled".to_string(), Value::Bool(false));
+            }
+        }
+        job.insert("updatedAtMs".to_string(), Value::Number(now.into()));
+        tx.put_job(&Value::Object(job.clone()))?;
+        return Ok(None);
+    };
+
+    let state = job_state_mut(job);
+    if rest.is_empty() {
+        state.remove("catchUpQueue");
+    } else {
+        state.insert("catchUpQueue".to_string(), json!(rest));
+    }
+    state.insert("nextRunAtMs".to_string(), Value::Number(slot.into()));
+    tx.put_job(&Value::Object(job.clone()))?;
+    Ok(Some(slot))
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -1916,6 +3121,172 @@ mod tests {
         );
     }
 
+    #[test]
+    fn preview_validates_field_counts_and_zones() {
+        assert_eq!(super::check_cron_expr("0 0 9 * * Mon-Fri").unwrap(), 6);
+        assert_eq!(super::check_cron_expr("0 0 9 * * * 2027").unwrap(), 7);
+        let five = super::check_cron_expr("0 9 * * 1").unwrap_err().to_string();
+        assert!(five.contains("5 fields") && five.contains("\"0 0 9 * * 1\""), "{five}");
+        assert!(super::check_cron_expr("0 9 *").is_err());
+        assert!(super::check_cron_expr("0 0 25 * * *").is_err());
+        assert!(super::preview_schedule(&json!({
+            "schedule": { "kind": "cron", "expr": "0 0 9 * * *", "tz": "Mars/Olympus" }
+        }))
+        .is_err());
+
+        let preview = super::preview_schedule(&json!({
+            "schedule": { "kind": "cron", "expr": "0 0 9 * * Mon-Fri", "tz": "Europe/Oslo" },
+            "count": 2,
+            "fromMs": ms(2026, 10, 16, 12, 0, 0)
+        }))
+        .expect("preview");
+        assert_eq!(preview["fields"], json!(6));
+        assert_eq!(preview["timezone"], json!("Europe/Oslo"));
+        assert_eq!(preview["runs"][0]["utc"], json!("2026-10-19T07:00:00Z"));
+        assert_eq!(
+            preview["runs"][0]["local"],
+            json!("2026-10-19T09:00:00+02:00")
+        );
+        assert_eq!(preview["runs"][1]["atMs"], json!(ms(2026, 10, 20, 7, 0, 0)));
+    }
+
+    #[test]
+    fn calendar_skips_weekends_holidays_and_blackouts() {
+        let spec = ScheduleSpec::from_value(&json!({
+            "kind": "cron",
+            "expr": "0 0 9 * * *",
+            "tz": "Europe/Oslo",
+            "businessDaysOnly": true,
+            "holidays": ["2026-10-19"],
+            "blackoutWindows": [{ "from": "2026-10-21", "to": "2026-10-22" }]
+        }))
+        .expect("schedule spec");
+        // Friday 2026-10-16 after the run: the weekend, the Monday holiday and the
+        // Wednesday-Thursday blackout are skipped.
+        let friday = ms(2026, 10, 16, 12, 0, 0);
+        assert_eq!(
+            spec.next_run_after(None, None, friday),
+            Some(ms(2026, 10, 20, 7, 0, 0))
+        );
+        assert_eq!(
+            spec.next_slot(ms(2026, 10, 20, 7, 0, 0)),
+            Some(ms(2026, 10, 23, 7, 0, 0))
+        );
+
+        let every = ScheduleSpec::from_value(&json!({
+            "kind": "every",
+            "everyMs": 3_600_000,
+            "anchorMs": ms(2026, 10, 16, 0, 0, 0),
+            "blackoutWindows": [{ "start": "12:00", "end": "14:00" }]
+        }))
+        .expect("schedule spec");
+        assert_eq!(
+            every.next_run_after(None, None, ms(2026, 10, 16, 11, 30, 0)),
+            Some(ms(2026, 10, 16, 14, 0, 0))
+        );
+
+        let paths = temp_paths();
+        let job = |schedule: Value| {
+            json!({
+                "name": "standup",
+                "schedule": schedule,
+                "sessionTarget": "main",
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "standup" }
+            })
+        };
+        assert!(super::add_job(
+            &paths,
+            &job(json!({ "kind": "at", "atMs": friday, "businessDaysOnly": true }))
+        )
+        .is_err());
+        assert!(super::add_job(
+            &paths,
+            &job(json!({ "kind": "cron", "expr": "0 0 9 * * *", "holidays": ["tomorrow"] }))
+        )
+        .is_err());
+        assert!(super::add_job(
+            &paths,
+            &job(json!({ "kind": "cron", "expr": "0 0 9 * * *", "businessDaysOnly": true }))
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn calendar_that_blocks_every_slot_keeps_the_job_scheduled() {
+        let start = ms(2026, 10, 16, 12, 0, 0);
+        let schedule = json!({
+            "kind": "every",
+            "everyMs": 60_000,
+            "anchorMs": start,
+            "blackoutWindows": [{ "start": "00:00", "end": "24:00" }]
+        });
+        let spec = ScheduleSpec::from_value(&schedule).expect("schedule spec");
+        let horizon = spec.next_run_after(None, None, start).expect("horizon");
+        assert!(horizon >= start + super::CALENDAR_HORIZON_MS);
+        assert_eq!(spec.next_slot(start), None);
+
+        let paths = temp_paths();
+        let mut job = super::add_job(
+            &paths,
+            &json!({
+                "id": "never",
+                "name": "never",
+                "schedule": schedule,
+                "sessionTarget": "main",
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .expect("add job");
+        job["state"]["nextRunAtMs"] = json!(start);
+        super::CronStore::open(&paths)
+            .and_then(|mut store| store.write(|tx| tx.put_job(&job)))
+            .expect("put job");
+        let (queued, _) =
+            super::collect_due_jobs(&paths, start + 1_000, "due", None).expect("collect");
+        assert!(queued.is_empty());
+        let job = super::load_jobs(&paths).expect("jobs").remove(0);
+        let next = job["state"]["nextRunAtMs"]
+            .as_i64()
+            .expect("still scheduled");
+        assert!(next >= start + super::CALENDAR_HORIZON_MS);
+
+        let listed = super::list_jobs(&paths, true).expect("list");
+        let warnings = listed["warnings"].as_array().expect("warnings");
+        assert!(warnings[0].as_str().unwrap_or("").starts_with("job never:"));
+    }
+
+    #[test]
+    fn preview_flags_dst_ambiguous_runs() {
+        // Oslo falls back from 03:00 CEST to 02:00 CET at 01:00 UTC, so 02:xx happens twice.
+        let anchor = ms(2026, 10, 25, 0, 0, 0);
+        let preview = super::preview_schedule(&json!({
+            "schedule": {
+                "kind": "every",
+                "everyMs": 3_600_000,
+                "anchorMs": anchor,
+                "tz": "Europe/Oslo"
+            },
+            "count": 3,
+            "fromMs": anchor - 1
+        }))
+        .expect("preview");
+        let runs = preview["runs"].as_array().expect("runs");
+        assert_eq!(runs[0]["local"], json!("2026-10-25T02:00:00+02:00"));
+        assert_eq!(runs[1]["local"], json!("2026-10-25T02:00:00+01:00"));
+        assert_eq!(
+            runs.iter()
+                .map(|run| run["dstAmbiguous"].clone())
+                .collect::<Vec<_>>(),
+            vec![json!(true), json!(true), json!(false)]
+        );
+        let warnings = preview["warnings"].as_array().expect("warnings");
+        assert!(warnings
+            .iter()
+            .any(|warning| warning.as_str().unwrap_or("").contains("+02:00 to +01:00")));
+    }
+
     #[test]
     fn normalize_job_defaults_from_payload() {
         let input = json!({
@@ -2216,6 +3587,641 @@ mod tests {
         assert!(drop.get("delivery").is_none());
     }
 
+    #[test]
+    fn legacy_cron_files_are_imported_once() {
+        let paths = temp_paths();
+        let job = json!({
+            "id": "legacy",
+            "name": "legacy",
+            "enabled": true,
+            "schedule": { "kind": "every", "everyMs": 60000 },
+            "sessionTarget": "main",
+            "wakeMode": "now",
+            "payload": { "kind": "systemEvent", "text": "ping" },
+            "state": {}
+        });
+        fs::write(
+            paths.cron_dir.join("jobs.json"),
+            serde_json::to_string(&json!({ "version": 1, "jobs": [job] })).unwrap(),
+        )
+        .expect("write jobs");
+        fs::create_dir_all(paths.cron_dir.join("runs")).expect("runs dir");
+        fs::write(
+            paths.cron_dir.join("runs").join("legacy.jsonl"),
+            "{\"ts\":1,\"jobId\":\"legacy\",\"action\":\"finished\",\"status\":\"ok\"}\n",
+        )
+        .expect("write runs");
+
+        let listed = super::list_jobs(&paths, true).expect("list");
+        assert_eq!(listed["jobs"].as_array().map(Vec::len), Some(1));
+        let runs = super::runs(&paths, &json!({ "jobId": "legacy" })).expect("runs");
+        assert_eq!(runs["entries"].as_array().map(Vec::len), Some(1));
+        assert!(!paths.cron_dir.join("jobs.json").exists());
+        assert!(paths.cron_dir.join("jobs.json.migrated").exists());
+        assert!(paths.cron_dir.join("runs.migrated").exists());
+
+        // A stray jobs.json written later is not imported again.
+        fs::write(
+            paths.cron_dir.join("jobs.json"),
+            serde_json::to_string(&json!({ "version": 1, "jobs": [] })).unwrap(),
+        )
+        .expect("write jobs");
+        let listed = super::list_jobs(&paths, true).expect("list");
+        assert_eq!(listed["jobs"].as_array().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn concurrent_cron_writes_are_not_lost() {
+        let paths = temp_paths();
+        let handles = (0..8)
+            .map(|idx| {
+                let paths = paths.clone();
+                std::thread::spawn(move || {
+                    super::add_job(
+                        &paths,
+                        &json!({
+                            "name": format!("job-{idx}"),
+                            "schedule": { "kind": "every", "everyMs": 60000 },
+                            "sessionTarget": "main",
+                            "wakeMode": "now",
+                            "payload": { "kind": "systemEvent", "text": "ping" }
+                        }),
+                    )
+                    .expect("add job")
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().expect("join");
+        }
+        let listed = super::list_jobs(&paths, true).expect("list");
+        assert_eq!(listed["jobs"].as_array().map(Vec::len), Some(8));
+    }
+
+    #[test]
+    fn catch_up_plan_follows_policy_and_lateness() {
+        let start = ms(2026, 2, 4, 12, 0, 0);
+        let now = start + 5 * 60_000 + 1_000;
+        let spec = ScheduleSpec::from_value(&json!({
+            "kind": "every",
+            "everyMs": 60_000,
+            "anchorMs": start
+        }))
+        .expect("schedule spec");
+        let plan = |catch_up: Value, lateness: Value| {
+            let policy = super::CatchUpPolicy::parse(Some(&catch_up), Some(&lateness))
+                .expect("policy");
+            super::plan_catch_up(&spec, policy, start, now)
+        };
+        let slot = |idx: i64| start + idx * 60_000;
+
+        let skip = plan(json!("skip"), Value::Null);
+        assert!(skip.run.is_empty());
+        assert_eq!(skip.missed.len(), 6);
+        assert_eq!(skip.resume_at, Some(slot(6)));
+
+        let once = plan(json!("once"), Value::Null);
+        assert_eq!(once.run, vec![slot(5)]);
+        assert_eq!(once.missed.len(), 5);
+
+        let all = plan(json!({ "mode": "all", "maxRuns": 3 }), Value::Null);
+        assert_eq!(all.run, vec![slot(3), slot(4), slot(5)]);
+        assert_eq!(all.missed, vec![slot(0), slot(1), slot(2)]);
+
+        let late = plan(json!("all"), json!(90_000));
+        assert_eq!(late.run, vec![slot(4), slot(5)]);
+        assert_eq!(late.stale, 4);
+
+        // On time: skip still runs the single due slot.
+        let on_time = super::plan_catch_up(
+            &spec,
+            super::CatchUpPolicy::parse(Some(&json!("skip")), None).expect("policy"),
+            slot(5),
+            now,
+        );
+        assert_eq!(on_time.run, vec![slot(5)]);
+        assert!(on_time.missed.is_empty());
+
+        // A single slot past the misfire grace is missed, not run late.
+        let late_single = super::plan_catch_up(
+            &spec,
+            super::CatchUpPolicy::parse(Some(&json!("skip")), None).expect("policy"),
+            slot(5),
+            slot(5) + super::SKIP_MISFIRE_GRACE_MS + 1,
+        );
+        assert!(late_single.run.is_empty());
+        assert_eq!(late_single.missed, vec![slot(5)]);
+        let strict = super::plan_catch_up(
+            &spec,
+            super::CatchUpPolicy::parse(Some(&json!("skip")), Some(&json!(500))).expect("policy"),
+            slot(5),
+            now,
+        );
+        assert_eq!(strict.missed, vec![slot(5)]);
+
+        assert!(super::CatchUpPolicy::parse(Some(&json!("later")), None).is_err());
+        assert!(super::CatchUpPolicy::parse(Some(&json!({ "maxRuns": 0 })), None).is_err());
+        assert!(super::CatchUpPolicy::parse(None, Some(&json!(-1))).is_err());
+    }
+
+    #[test]
+    fn collect_due_jobs_replays_and_records_missed_runs() {
+        let paths = temp_paths();
+        let start = ms(2026, 2, 4, 12, 0, 0);
+        let now = start + 5 * 60_000 + 1_000;
+        let mut job = super::add_job(
+            &paths,
+            &json!({
+                "id": "catch-up",
+                "name": "catch-up",
+                "schedule": { "kind": "every", "everyMs": 60_000, "anchorMs": start },
+                "catchUp": { "mode": "all", "maxRuns": 2 },
+                "sessionTarget": "main",
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .expect("add job");
+        // Pretend the daemon was down since the first slot.
+        job["state"]["nextRunAtMs"] = json!(start);
+        super::CronStore::open(&paths)
+            .and_then(|mut store| store.write(|tx| tx.put_job(&job)))
+            .expect("put job");
+
+        let (queued, _) = super::collect_due_jobs(&paths, now, "due", None).expect("collect");
+        assert_eq!(queued.len(), 1);
+        let runs = super::runs(&paths, &json!({ "jobId": "catch-up" })).expect("runs");
+        let entries = runs["entries"].as_array().cloned().unwrap_or_default();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["status"], json!("missed"));
+        assert_eq!(entries[0]["missedCount"], json!(4));
+        assert_eq!(entries[0]["scheduledAtMs"], json!(start));
+        assert_eq!(entries[0]["lastScheduledAtMs"], json!(start + 3 * 60_000));
+
+        let job = super::load_job_value(&paths, "catch-up").expect("load").expect("job");
+        assert_eq!(job["state"]["catchUpQueue"], json!([start + 5 * 60_000]));
+
+        // Finishing the first replay schedules the second right away.
+        super::record_run(
+            &paths,
+            "catch-up",
+            "completed",
+            "ok",
+            Some(json!({ "runAtMs": now, "durationMs": 10 })),
+        )
+        .expect("record run");
+        let job = super::load_job_value(&paths, "catch-up").expect("load").expect("job");
+        assert_eq!(job["state"]["nextRunAtMs"], json!(start + 5 * 60_000));
+        let (queued, entries) =
+            super::collect_due_jobs(&paths, now + 20, "due", None).expect("collect");
+        assert_eq!(queued.len(), 1);
+        assert_eq!(entries[0]["scheduledAtMs"], json!(start + 5 * 60_000));
+        let job = super::load_job_value(&paths, "catch-up").expect("load").expect("job");
+        assert!(job["state"].get("catchUpQueue").is_none());
+    }
+
+    #[test]
+    fn collect_due_jobs_counts_runs_held_by_workers() {
+        let paths = temp_paths();
+        let start = ms(2026, 2, 4, 12, 0, 0);
+        let mut job = super::add_job(
+            &paths,
+            &json!({
+                "id": "busy",
+                "name": "busy",
+                "schedule": { "kind": "every", "everyMs": 60_000, "anchorMs": start },
+                "maxConcurrentRuns": 2,
+                "sessionTarget": "main",
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .expect("add job");
+        job["state"]["nextRunAtMs"] = json!(start);
+        super::CronStore::open(&paths)
+            .and_then(|mut store| store.write(|tx| tx.put_job(&job)))
+            .expect("put job");
+        let in_flight = |count: usize| [("busy".to_string(), count)].into_iter().collect();
+
+        // One run is going; the slot still starts a second one.
+        let (queued, _) = super::collect_due_jobs_in_flight(&paths, start + 1_000, &in_flight(1))
+            .expect("collect");
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].concurrency_limit(), 2);
+        let job = super::load_job_value(&paths, "busy")
+            .expect("load")
+            .expect("job");
+        assert_eq!(job["state"]["nextRunAtMs"], json!(start + 60_000));
+        // The dispatched slot is not dispatched again.
+        let (queued, _) = super::collect_due_jobs_in_flight(&paths, start + 2_000, &in_flight(1))
+            .expect("collect");
+        assert!(queued.is_empty());
+
+        // Both slots are taken at the next slot, so it waits.
+        let (queued, _) = super::collect_due_jobs_in_flight(&paths, start + 61_000, &in_flight(2))
+            .expect("collect");
+        assert!(queued.is_empty());
+        let (queued, _) = super::collect_due_jobs_in_flight(&paths, start + 61_000, &in_flight(1))
+            .expect("collect");
+        assert_eq!(queued.len(), 1);
+
+        assert!(super::add_job(
+            &paths,
+            &json!({
+                "name": "bad",
+                "schedule": { "kind": "every", "everyMs": 60_000 },
+                "maxConcurrentRuns": 0,
+                "sessionTarget": "main",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn stale_one_shot_job_is_missed_and_disabled() {
+        let paths = temp_paths();
+        let at = ms(2026, 2, 4, 12, 0, 0);
+        super::add_job(
+            &paths,
+            &json!({
+                "id": "reminder",
+                "name": "reminder",
+                "schedule": { "kind": "at", "atMs": at },
+                "maxLatenessMs": 600_000,
+                "sessionTarget": "main",
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .expect("add job");
+
+        let (queued, entries) =
+            super::collect_due_jobs(&paths, at + 3_600_000, "due", None).expect("collect");
+        assert!(queued.is_empty());
+        assert_eq!(entries[0]["status"], json!("missed"));
+        let job = super::load_job_value(&paths, "reminder").expect("load").expect("job");
+        assert_eq!(job["enabled"], json!(false));
+        assert_eq!(job["state"]["lastMissedAtMs"], json!(at));
+    }
+
+    #[test]
+    fn cancel_job_flags_running_runs_and_dequeues_pending_ones() {
+        let paths = temp_paths();
+        let job = super::add_job(
+            &paths,
+            &json!({
+                "id": "slow",
+                "name": "slow",
+                "schedule": { "kind": "every", "everyMs": 60_000 },
+                "timeoutMs": 30_000,
+                "sessionTarget": "main",
+                "wakeMode": "next-heartbeat",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .expect("add job");
+        let parsed = super::build_cron_job(&job).expect("job");
+        assert_eq!(parsed.effective_timeout_ms(), Some(30_000));
+
+        let idle = super::cancel_job(&paths, &json!({ "id": "slow" })).expect("cancel");
+        assert_eq!(idle["cancelled"], json!(false));
+        assert_eq!(idle["reason"], json!("not-running"));
+        let missing = super::cancel_job(&paths, &json!({ "id": "nope" })).expect("cancel");
+        assert_eq!(missing["reason"], json!("not-found"));
+
+        super::CronStore::open(&paths)
+            .and_then(|mut store| store.write(|tx| tx.push_pending("slow", &job)))
+            .expect("push pending");
+        super::mark_job_running(&paths, "slow", super::now_ms()).expect("mark running");
+        assert!(!super::cancel_requested(&paths, "slow").expect("requested"));
+
+        let res = super::cancel_job(&paths, &json!({ "jobId": "slow" })).expect("cancel");
+        assert_eq!(res["cancelled"], json!(true));
+        assert_eq!(res["running"], json!(true));
+        assert_eq!(res["dequeued"], json!(1));
+        assert!(super::cancel_requested(&paths, "slow").expect("requested"));
+        assert!(super::drain_pending_jobs(&paths).expect("drain").is_empty());
+
+        super::record_run(
+            &paths,
+            "slow",
+            "cancelled",
+            "cancelled",
+            Some(json!({ "runAtMs": super::now_ms(), "durationMs": 5 })),
+        )
+        .expect("record");
+        assert!(!super::cancel_requested(&paths, "slow").expect("requested"));
+        let runs = super::runs(&paths, &json!({ "id": "slow" })).expect("runs");
+        let statuses = runs["entries"]
+            .as_array()
+            .expect("entries")
+            .iter()
+            .map(|e| e["status"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(statuses, vec![json!("cancelled"), json!("cancelled")]);
+        let job = super::load_job_value(&paths, "slow").expect("load").expect("job");
+        assert_eq!(job["state"]["lastStatus"], json!("cancelled"));
+        assert!(job["state"].get("runningAtMs").is_none());
+    }
+
+    #[test]
+    fn job_timeout_falls_back_to_payload_timeout() {
+        let job = super::build_cron_job(&json!({
+            "id": "job-1",
+            "name": "job",
+            "sessionTarget": "isolated",
+            "payload": { "kind": "agentTurn", "message": "hi", "timeoutSeconds": 90 }
+        }))
+        .expect("job");
+        assert_eq!(job.effective_timeout_ms(), Some(90_000));
+        let input = json!({
+            "name": "bad",
+            "schedule": { "kind": "every", "everyMs": 60_000 },
+            "timeoutMs": -5,
+            "sessionTarget": "main",
+            "payload": { "kind": "systemEvent", "text": "ping" }
+        });
+        let normalized = super::normalize_job_input(&input, true).expect("normalize");
+        assert!(super::validate_job_spec(&normalized).is_err());
+    }
+
+    #[test]
+    fn retry_policy_backs_off_exponentially() {
+        let policy = super::RetryPolicy::parse(Some(&json!({
+            "maxRetries": 3,
+            "backoffMs": 1_000,
+            "maxBackoffMs": 5_000
+        })))
+        .expect("parse")
+        .expect("policy");
+        let delays = (1..=4).map(|retry| policy.delay_ms(retry)).collect::<Vec<_>>();
+        assert_eq!(delays, vec![1_000, 2_000, 4_000, 5_000]);
+        assert_eq!(policy.retry_at("error", 0, 10_000), Some(11_000));
+        assert_eq!(policy.retry_at("timeout", 2, 10_000), Some(14_000));
+        assert_eq!(policy.retry_at("error", 3, 10_000), None);
+        assert_eq!(policy.retry_at("cancelled", 0, 10_000), None);
+
+        let only_timeouts =
+            super::RetryPolicy::parse(Some(&json!({ "maxRetries": 1, "retryOn": "timeout" })))
+                .expect("parse")
+                .expect("policy");
+        assert_eq!(only_timeouts.retry_at("error", 0, 0), None);
+        assert!(super::RetryPolicy::parse(Some(&json!({ "maxRetries": 1, "retryOn": ["ok"] })))
+            .is_err());
+        assert!(super::RetryPolicy::parse(Some(&json!({ "backoffMs": 10 }))).is_err());
+    }
+
+    #[test]
+    fn failed_runs_are_retried_until_retries_run_out() {
+        let paths = temp_paths();
+        let start = ms(2026, 2, 4, 12, 0, 0);
+        super::add_job(
+            &paths,
+            &json!({
+                "id": "flaky",
+                "name": "flaky",
+                "schedule": { "kind": "every", "everyMs": 3_600_000, "anchorMs": start },
+                "retry": { "maxRetries": 2, "backoffMs": 1_000 },
+                "sessionTarget": "main",
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .expect("add job");
+        let fail = |status: &str, run_at: i64| {
+            super::record_run(
+                &paths,
+                "flaky",
+                status,
+                "execution failed",
+                Some(json!({ "error": "boom", "runAtMs": run_at, "durationMs": 0 })),
+            )
+            .expect("record")
+        };
+
+        let first = fail("failed", start);
+        assert_eq!(first["status"], json!("error"));
+        assert_eq!(first["attempt"], json!(1));
+        assert_eq!(first["retryAtMs"], json!(start + 1_000));
+        let job = super::load_job_value(&paths, "flaky").expect("load").expect("job");
+        assert_eq!(job["state"]["nextRunAtMs"], json!(start + 1_000));
+        assert_eq!(job["state"]["retryAttempt"], json!(1));
+
+        let (queued, entries) =
+            super::collect_due_jobs(&paths, start + 1_500, "due", None).expect("collect");
+        assert_eq!(queued.len(), 1);
+        assert_eq!(entries[0]["attempt"], json!(2));
+        assert_eq!(entries[0]["scheduledAtMs"], json!(start + 1_000));
+
+        let second = fail("timeout", start + 1_500);
+        assert_eq!(second["attempt"], json!(2));
+        assert_eq!(second["retryAtMs"], json!(start + 3_500));
+
+        let last = fail("failed", start + 3_500);
+        assert_eq!(last["attempt"], json!(3));
+        assert!(last.get("retryAtMs").is_none());
+        let job = super::load_job_value(&paths, "flaky").expect("load").expect("job");
+        assert_eq!(job["state"]["nextRunAtMs"], json!(start + 3_600_000));
+        assert!(job["state"].get("retryAttempt").is_none());
+
+        let ok = super::record_run(
+            &paths,
+            "flaky",
+            "completed",
+            "executed",
+            Some(json!({ "runAtMs": start + 3_600_000, "durationMs": 0 })),
+        )
+        .expect("record");
+        assert_eq!(ok["attempt"], json!(1));
+
+        // The turn already ran: retrying would deliver its output twice.
+        let undelivered = fail("delivery_failed", start + 7_200_000);
+        assert_eq!(undelivered["status"], json!("error"));
+        assert!(undelivered.get("retryAtMs").is_none());
+        let job = super::load_job_value(&paths, "flaky").expect("load").expect("job");
+        assert_eq!(job["state"]["nextRunAtMs"], json!(start + 10_800_000));
+    }
+
+    #[test]
+    fn pending_runs_stay_queued_until_acked() {
+        let paths = temp_paths();
+        let job = super::add_job(
+            &paths,
+            &json!({
+                "id": "later",
+                "name": "later",
+                "schedule": { "kind": "every", "everyMs": 60_000 },
+                "sessionTarget": "isolated",
+                "wakeMode": "next-heartbeat",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        )
+        .expect("add job");
+        let parsed = super::build_cron_job(&job).expect("job");
+        super::requeue_pending_job(&paths, &parsed).expect("requeue");
+        super::requeue_pending_job(&paths, &parsed).expect("requeue");
+
+        // Peeking (as a daemon that cannot start the run yet does) leaves both queued.
+        let first = super::peek_pending_jobs(&paths).expect("peek");
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].job.id, "later");
+        let again = super::peek_pending_jobs(&paths).expect("peek");
+        assert_eq!(again.len(), 2);
+
+        super::ack_pending_job(&paths, first[0].seq).expect("ack");
+        let rest = super::peek_pending_jobs(&paths).expect("peek");
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].seq, first[1].seq);
+    }
+
+    #[test]
+    fn finished_runs_trigger_chained_jobs_with_upstream_message() {
+        let paths = temp_paths();
+        let add = |spec: Value| super::add_job(&paths, &spec).expect("add job");
+        add(json!({
+            "id": "collect",
+            "name": "collect",
+            "schedule": { "kind": "every", "everyMs": 3_600_000 },
+            "onSuccess": "summarize",
+            "sessionTarget": "main",
+            "wakeMode": "now",
+            "payload": { "kind": "systemEvent", "text": "collect" }
+        }));
+        add(json!({
+            "id": "summarize",
+            "name": "summarize",
+            "dependsOn": { "jobId": "collect", "on": "success" },
+            "sessionTarget": "main",
+            "wakeMode": "now",
+            "payload": {
+                "kind": "systemEvent",
+                "text": "Summarize ({{ upstream.status }}): {{upstream.message}} {{other}}"
+            }
+        }));
+        add(json!({
+            "id": "deliver",
+            "name": "deliver",
+            "dependsOn": "summarize",
+            "onSuccess": ["collect"],
+            "sessionTarget": "main",
+            "wakeMode": "next-heartbeat",
+            "payload": { "kind": "systemEvent", "text": "Send: {{upstream.message}}" }
+        }));
+        add(json!({
+            "id": "alert",
+            "name": "alert",
+            "dependsOn": { "jobId": "collect", "on": "failure" },
+            "sessionTarget": "main",
+            "wakeMode": "now",
+            "payload": { "kind": "systemEvent", "text": "collect failed: {{upstream.error}}" }
+        }));
+        let finish = |job_id: &str, status: &str, summary: &str| {
+            super::record_run(
+                &paths,
+                job_id,
+                status,
+                "executed",
+                Some(json!({ "summary": summary, "error": "boom" })),
+            )
+            .expect("record")
+        };
+
+        let entry = finish("collect", "completed", "42 new items");
+        assert_eq!(entry["triggered"], json!(["summarize"]));
+        let now = super::now_ms() + 1_000;
+        let (queued, entries) = super::collect_due_jobs(&paths, now, "due", None).expect("collect");
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].id, "summarize");
+        assert_eq!(entries[0]["triggeredBy"], json!("collect"));
+        let prompt = super::job_prompt(&queued[0], now).expect("prompt");
+        assert!(prompt.ends_with("Summarize (ok): 42 new items {{other}}"));
+
+        // Only the failure branch fires on failure; the summarize -> deliver -> collect loop
+        // stops before collect runs twice in one pipeline.
+        let entry = finish("summarize", "completed", "digest");
+        assert_eq!(entry["triggered"], json!(["deliver"]));
+        let job = super::load_job_value(&paths, "summarize").expect("load").expect("job");
+        assert!(job["state"].get("upstream").is_none());
+        let pending = super::drain_pending_jobs(&paths).expect("drain");
+        assert!(pending.is_empty());
+        let (_, entries) = super::collect_due_jobs(&paths, now + 1_000, "due", None).expect("collect");
+        assert_eq!(entries[0]["jobId"], json!("deliver"));
+        let pending = super::drain_pending_jobs(&paths).expect("drain");
+        assert_eq!(
+            super::job_prompt(&pending[0], now).expect("prompt").lines().last(),
+            Some("Send: digest")
+        );
+        let entry = finish("deliver", "completed", "sent");
+        assert!(entry.get("triggered").is_none());
+
+        let entry = finish("collect", "failed", "");
+        assert_eq!(entry["triggered"], json!(["alert"]));
+
+        let looped = super::add_job(
+            &paths,
+            &json!({
+                "id": "loop",
+                "name": "loop",
+                "dependsOn": "loop",
+                "sessionTarget": "main",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }),
+        );
+        assert!(looped.is_err());
+    }
+
+    #[test]
+    fn jobs_fed_by_several_upstreams_see_each_result() {
+        let paths = temp_paths();
+        let add = |spec: Value| super::add_job(&paths, &spec).expect("add job");
+        for id in ["fetch-eu", "fetch-us"] {
+            add(json!({
+                "id": id,
+                "name": id,
+                "schedule": { "kind": "every", "everyMs": 3_600_000 },
+                "sessionTarget": "main",
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "fetch" }
+            }));
+        }
+        add(json!({
+            "id": "merge",
+            "name": "merge",
+            "dependsOn": ["fetch-eu", "fetch-us"],
+            "sessionTarget": "main",
+            "wakeMode": "now",
+            "payload": {
+                "kind": "systemEvent",
+                "text": "EU: {{upstreams.fetch-eu.message}} US: {{upstreams.fetch-us.message}}"
+            }
+        }));
+        let finish = |job_id: &str, summary: &str| {
+            super::record_run(
+                &paths,
+                job_id,
+                "completed",
+                "executed",
+                Some(json!({ "summary": summary })),
+            )
+            .expect("record")
+        };
+
+        finish("fetch-eu", "3 rows");
+        finish("fetch-us", "5 rows");
+        let now = super::now_ms() + 1_000;
+        let (queued, _) = super::collect_due_jobs(&paths, now, "due", None).expect("collect");
+        assert_eq!(queued.len(), 1);
+        let prompt = super::job_prompt(&queued[0], now).expect("prompt");
+        assert!(prompt.ends_with("EU: 3 rows US: 5 rows"));
+
+        finish("merge", "merged");
+        let job = super::load_job_value(&paths, "merge")
+            .expect("load")
+            .expect("job");
+        assert!(job["state"].get("upstreams").is_none());
+    }
+
     #[test]
     fn job_session_key_prefers_explicit_session_key() {
         let job = super::build_cron_job(&json!({
@@ -0,0 +1,471 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use serde_json::{Map, Value};
+
+use crate::util::home_dir;
+
+/// Schedule keys that make up a calendar; they only apply to `cron` and `every` schedules.
+pub const CALENDAR_KEYS: &[&str] = &[
+    "businessDaysOnly",
+    "holidays",
+    "holidaysFile",
+    "blackoutWindows",
+];
+
+/// Days and hours on which a recurring schedule must not fire, evaluated in the job time zone.
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleCalendar {
+    business_days_only: bool,
+    holidays: BTreeSet<NaiveDate>,
+    holidays_file: Option<PathBuf>,
+    blackouts: Vec<Blackout>,
+}
+
+#[derive(Debug, Clone)]
+enum Blackout {
+    /// Minutes after local midnight, end exclusive; wraps past midnight when `end <= start`.
+    Daily {
+        start: u32,
+        end: u32,
+        days: Option<Vec<Weekday>>,
+    },
+    /// Local date-times, end exclusive.
+    Range {
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    },
+}
+
+impl ScheduleCalendar {
+    /// Reads `businessDaysOnly`, `holidays`, `holidaysFile` and `blackoutWindows` from a schedule
+    /// object. Returns `None` when none of them is set.
+    pub fn parse(schedule: &Map<String, Value>) -> Result<Option<Self>> {
+        let mut calendar = ScheduleCalendar::default();
+        let mut any = false;
+        if let Some(value) = non_null(schedule, "businessDaysOnly") {
+            calendar.business_days_only = value
+                .as_bool()
+                .context("businessDaysOnly must be a boolean")?;
+            any = true;
+        }
+        if let Some(value) = non_null(schedule, "holidays") {
+            let items = value
+                .as_array()
+                .context("holidays must be a list of YYYY-MM-DD dates")?;
+            for item in items {
+                let raw = item.as_str().unwrap_or_default();
+                let date = NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+                    .with_context(|| format!("invalid holiday {item}: expected YYYY-MM-DD"))?;
+                calendar.holidays.insert(date);
+            }
+            any = true;
+        }
+        if let Some(value) = non_null(schedule, "holidaysFile") {
+            let raw = value
+                .as_str()
+                .map(str::trim)
+                .filter(|raw| !raw.is_empty())
+                .context("holidaysFile must be a path to an .ics file")?;
+            calendar.holidays_file = Some(resolve_path(raw)?);
+            any = true;
+        }
+        if let Some(value) = non_null(schedule, "blackoutWindows") {
+            let items = value.as_array().context("blackoutWindows must be a list")?;
+            for item in items {
+                calendar.blackouts.push(Blackout::parse(item)?);
+            }
+            any = true;
+        }
+        Ok(any.then_some(calendar))
+    }
+
+    /// Fails when the holidays file cannot be read; at run time an unreadable file blocks nothing.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(path) = &self.holidays_file {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("read holidaysFile {}", path.display()))?;
+            if !raw.contains("BEGIN:VCALENDAR") {
+                anyhow::bail!("holidaysFile {} is not an iCalendar file", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a run may start at this wall-clock time.
+    pub fn allows(&self, local: NaiveDateTime) -> bool {
+        self.check().blocked_until(local).is_none()
+    }
+
+    /// Reads the holidays file once so that many times can be checked against it.
+    pub fn check(&self) -> CalendarCheck<'_> {
+        CalendarCheck {
+            calendar: self,
+            file_holidays: self.holidays_file.as_deref().map(holidays_from_file),
+        }
+    }
+}
+
+/// A calendar with its holidays file loaded.
+pub struct CalendarCheck<'a> {
+    calendar: &'a ScheduleCalendar,
+    file_holidays: Option<Arc<BTreeSet<NaiveDate>>>,
+}
+
+impl CalendarCheck<'_> {
+    /// `None` when a run may start at this wall-clock time; otherwise the earliest time the
+    /// blocks covering it end, before which no run is allowed.
+    pub fn blocked_until(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let calendar = self.calendar;
+        let date = local.date();
+        let day_blocked = (calendar.business_days_only
+            && matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+            || calendar.holidays.contains(&date)
+            || self
+                .file_holidays
+                .as_ref()
+                .is_some_and(|dates| dates.contains(&date));
+        let mut until = day_blocked.then(|| {
+            date.succ_opt()
+                .and_then(|next| next.and_hms_opt(0, 0, 0))
+                .unwrap_or(NaiveDateTime::MAX)
+        });
+        for blackout in &calendar.blackouts {
+            if let Some(end) = blackout.covered_until(local) {
+                until = until.max(Some(end));
+            }
+        }
+        until
+    }
+}
+
+impl Blackout {
+    /// `{ start: "HH:MM", end: "HH:MM", days?: ["Sat", ...] }` for a daily window, or
+    /// `{ from, to }` with `YYYY-MM-DD[ HH:MM]` bounds for a one-off range (a date-only `to`
+    /// includes that whole day).
+    fn parse(value: &Value) -> Result<Self> {
+        let map = value
+            .as_object()
+            .context("blackoutWindows entries must be objects")?;
+        let text = |key: &str| map.get(key).and_then(Value::as_str).map(str::trim);
+        if let (Some(from), Some(to)) = (text("from"), text("to")) {
+            let from_at = parse_local(from, false).with_context(|| {
+                format!("invalid blackout from {from:?}: expected YYYY-MM-DD[ HH:MM]")
+            })?;
+            let to_at = parse_local(to, true).with_context(|| {
+                format!("invalid blackout to {to:?}: expected YYYY-MM-DD[ HH:MM]")
+            })?;
+            if to_at <= from_at {
+                anyhow::bail!("blackout window {from:?}..{to:?} ends before it starts");
+            }
+            return Ok(Blackout::Range {
+                from: from_at,
+                to: to_at,
+            });
+        }
+        let (Some(start), Some(end)) = (text("start"), text("end")) else {
+            anyhow::bail!("blackout windows need start/end (HH:MM) or from/to (YYYY-MM-DD)");
+        };
+        let start_min = parse_minutes(start, false)
+            .with_context(|| format!("invalid blackout start {start:?}: expected HH:MM"))?;
+        let end_min = parse_minutes(end, true)
+            .with_context(|| format!("invalid blackout end {end:?}: expected HH:MM"))?;
+        if start_min == end_min {
+            anyhow::bail!("blackout window {start}-{end} is empty");
+        }
+        let days = match map.get("days").filter(|days| !days.is_null()) {
+            Some(days) => {
+                let items = days
+                    .as_array()
+                    .context("blackout days must be a list of weekdays")?;
+                let parsed = items
+                    .iter()
+                    .map(|day| {
+                        day.as_str()
+                            .and_then(|day| day.trim().parse::<Weekday>().ok())
+                            .with_context(|| format!("invalid weekday {day}: expected e.g. Mon"))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Some(parsed)
+            }
+            None => None,
+        };
+        Ok(Blackout::Daily {
+            start: start_min,
+            end: end_min,
+            days,
+        })
+    }
+
+    /// End of this window when it covers `local`.
+    fn covered_until(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Blackout::Range { from, to } => (local >= *from && local < *to).then_some(*to),
+            Blackout::Daily { start, end, days } => {
+                let minutes = local.hour() * 60 + local.minute();
+                // For windows past midnight the early-morning part belongs to the previous day.
+                let (inside, day, end_minutes) = if end > start {
+                    (
+                        minutes >= *start && minutes < *end,
+                        Some(local.date()),
+                        *end,
+                    )
+                } else if minutes >= *start {
+                    (true, Some(local.date()), *end + 24 * 60)
+                } else {
+                    (minutes < *end, local.date().pred_opt(), *end + 24 * 60)
+                };
+                let day = day?;
+                let on_day = days
+                    .as_ref()
+                    .map_or(true, |days| days.contains(&day.weekday()));
+                if !(inside && on_day) {
+                    return None;
+                }
+                let ends_at = day.and_hms_opt(0, 0, 0)? + Duration::minutes(end_minutes.into());
+                Some(ends_at)
+            }
+        }
+    }
+}
+
+fn non_null<'a>(map: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    map.get(key).filter(|value| !value.is_null())
+}
+
+fn resolve_path(raw: &str) -> Result<PathBuf> {
+    if let Some(rest) = raw.strip_prefix("~/") {
+        return Ok(home_dir()?.join(rest));
+    }
+    let path = PathBuf::from(raw);
+    if !path.is_absolute() {
+        anyhow::bail!("holidaysFile must be an absolute path or start with ~/");
+    }
+    Ok(path)
+}
+
+fn parse_minutes(raw: &str, allow_24: bool) -> Option<u32> {
+    let (hour, minute) = raw.split_once(':')?;
+    let hour: u32 = hour.parse().ok()?;
+    let minute: u32 = minute.parse().ok()?;
+    if minute > 59 {
+        return None;
+    }
+    match hour {
+        24 if allow_24 && minute == 0 => Some(24 * 60),
+        0..=23 => Some(hour * 60 + minute),
+        _ => None,
+    }
+}
+
+fn parse_local(raw: &str, end_of_day: bool) -> Option<NaiveDateTime> {
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(parsed) = NaiveDateTime::parse_from_str(raw, format) {
+            return Some(parsed);
+        }
+    }
+    let date = NaiveDate::parse_from_str(raw, "%Y-%m-%d").ok()?;
+    let date = if end_of_day { date.succ_opt()? } else { date };
+    date.and_hms_opt(0, 0, 0)
+}
+
+type HolidayCache = HashMap<PathBuf, (Option<SystemTime>, Arc<BTreeSet<NaiveDate>>)>;
+
+/// Dates of the events in an `.ics` file, re-read when its mtime changes.
+fn holidays_from_file(path: &Path) -> Arc<BTreeSet<NaiveDate>> {
+    static CACHE: OnceLock<Mutex<HolidayCache>> = OnceLock::new();
+    let mtime = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let mut cache = CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    if let Some((cached_mtime, dates)) = cache.get(path) {
+        if *cached_mtime == mtime {
+            return dates.clone();
+        }
+    }
+    let dates = match fs::read_to_string(path) {
+        Ok(raw) => parse_ics_dates(&raw),
+        Err(err) => {
+            eprintln!(
+                "[clawdex][cron] holidaysFile {} unreadable: {err}",
+                path.display()
+            );
+            BTreeSet::new()
+        }
+    };
+    let dates = Arc::new(dates);
+    cache.insert(path.to_path_buf(), (mtime, dates.clone()));
+    dates
+}
+
+/// Every date covered by a `VEVENT` in an iCalendar document. All-day events cover
+/// `DTSTART` up to the exclusive `DTEND`; timed events cover their start date. Recurrence rules
+/// are not expanded.
+pub fn parse_ics_dates(raw: &str) -> BTreeSet<NaiveDate> {
+    // Unfold continuation lines (RFC 5545 §3.1) first.
+    let mut lines: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+    let mut dates = BTreeSet::new();
+    let mut event: Option<(Option<(NaiveDate, bool)>, Option<NaiveDate>)> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let prop = name.split_once(';').map_or(name, |(prop, _)| prop);
+        match (prop.to_ascii_uppercase().as_str(), value.trim()) {
+            ("BEGIN", "VEVENT") => event = Some((None, None)),
+            ("END", "VEVENT") => {
+                if let Some((Some((start, all_day)), end)) = event.take() {
+                    let end = end.filter(|end| all_day && *end > start);
+                    let mut day = start;
+                    loop {
+                        dates.insert(day);
+                        match (end, day.succ_opt()) {
+                            (Some(end), Some(next)) if next < end => day = next,
+                            _ => break,
+                        }
+                    }
+                }
+            }
+            ("DTSTART", value) => {
+                if let (Some(current), Some(date)) = (event.as_mut(), ics_date(value)) {
+                    // A bare date (VALUE=DATE) marks an all-day event.
+                    current.0 = Some((date, value.len() == 8));
+                }
+            }
+            ("DTEND", value) => {
+                if let Some(current) = event.as_mut() {
+                    current.1 = ics_date(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    dates
+}
+
+fn ics_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    use super::{parse_ics_dates, ScheduleCalendar};
+
+    fn at(raw: &str) -> chrono::NaiveDateTime {
+        chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn calendar(value: serde_json::Value) -> ScheduleCalendar {
+        ScheduleCalendar::parse(value.as_object().unwrap())
+            .unwrap()
+            .expect("calendar")
+    }
+
+    #[test]
+    fn business_days_holidays_and_blackouts() {
+        let cal = calendar(json!({
+            "businessDaysOnly": true,
+            "holidays": ["2026-12-24"],
+            "blackoutWindows": [
+                { "start": "22:00", "end": "06:00", "days": ["Fri"] },
+                { "from": "2026-12-28", "to": "2026-12-30" }
+            ]
+        }));
+        assert!(cal.allows(at("2026-12-23 09:00")));
+        assert!(!cal.allows(at("2026-12-24 09:00")));
+        assert!(!cal.allows(at("2026-12-26 09:00")));
+        // The Friday window runs past midnight; Thursday night is not covered.
+        assert!(!cal.allows(at("2026-12-18 23:00")));
+        assert!(cal.allows(at("2026-12-17 23:00")));
+        // A date-only `to` covers the whole day.
+        assert!(!cal.allows(at("2026-12-30 18:00")));
+        assert!(cal.allows(at("2026-12-31 09:00")));
+
+        assert!(ScheduleCalendar::parse(json!({}).as_object().unwrap())
+            .unwrap()
+            .is_none());
+        for bad in [
+            json!({ "holidays": ["24.12.2026"] }),
+            json!({ "blackoutWindows": [{ "start": "25:00", "end": "06:00" }] }),
+            json!({ "blackoutWindows": [{ "from": "2026-12-30", "to": "2026-12-28" }] }),
+            json!({ "blackoutWindows": [{ "start": "12:00", "end": "13:00", "days": ["Funday"] }] }),
+            json!({ "holidaysFile": "holidays.ics" }),
+        ] {
+            assert!(
+                ScheduleCalendar::parse(bad.as_object().unwrap()).is_err(),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn blocks_report_when_they_end() {
+        let cal = calendar(json!({
+            "businessDaysOnly": true,
+            "blackoutWindows": [
+                { "start": "22:00", "end": "06:00" },
+                { "from": "2026-12-21 09:00", "to": "2026-12-21 17:30" }
+            ]
+        }));
+        let check = cal.check();
+        assert_eq!(check.blocked_until(at("2026-12-21 08:00")), None);
+        assert_eq!(
+            check.blocked_until(at("2026-12-21 10:00")),
+            Some(at("2026-12-21 17:30"))
+        );
+        assert_eq!(
+            check.blocked_until(at("2026-12-21 23:00")),
+            Some(at("2026-12-22 06:00"))
+        );
+        assert_eq!(
+            check.blocked_until(at("2026-12-22 01:00")),
+            Some(at("2026-12-22 06:00"))
+        );
+        // Saturday: the whole day is blocked, and the night window runs past it.
+        assert_eq!(
+            check.blocked_until(at("2026-12-26 12:00")),
+            Some(at("2026-12-27 00:00"))
+        );
+        assert_eq!(
+            check.blocked_until(at("2026-12-26 23:00")),
+            Some(at("2026-12-27 06:00"))
+        );
+    }
+
+    #[test]
+    fn ics_events_expand_to_dates() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Christmas\r\nDTSTART;VALUE=DATE:20261225\r\n\
+            DTEND;VALUE=DATE:20261227\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Offsite\r\nDTSTART;TZID=Europe/Oslo:20260611T090000\r\n\
+            DTEND;TZID=Europe/Oslo:20260612T170000\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:\r\n 20270101\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let dates = parse_ics_dates(ics);
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            dates.into_iter().collect::<Vec<_>>(),
+            vec![
+                date(2026, 6, 11),
+                date(2026, 12, 25),
+                date(2026, 12, 26),
+                date(2027, 1, 1)
+            ]
+        );
+    }
+}
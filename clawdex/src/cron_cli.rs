@@ -0,0 +1,731 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde_json::{json, Map, Value};
+
+use crate::config::ClawdPaths;
+use crate::util::now_ms;
+use crate::{cron, daemon_client};
+
+const DEFAULT_NEXT_COUNT: usize = 5;
+const MAX_CELL_CHARS: usize = 60;
+
+/// Job fields set from `clawdex cron add|update` flags. Unset flags leave the field alone.
+#[derive(Debug, Default, Clone)]
+pub struct JobFlags {
+    pub name: Option<String>,
+    pub cron: Option<String>,
+    pub tz: Option<String>,
+    pub every: Option<String>,
+    pub at: Option<String>,
+    pub message: Option<String>,
+    pub system_event: Option<String>,
+    pub session: Option<String>,
+    pub wake: Option<String>,
+    pub depends_on: Vec<String>,
+    pub enabled: Option<bool>,
+}
+
+impl JobFlags {
+    /// The fields in the shape `cron.add` takes and `cron.update` takes as `patch`.
+    pub fn to_job_fields(&self, now: i64) -> Result<Map<String, Value>> {
+        let mut fields = Map::new();
+        if let Some(name) = &self.name {
+            fields.insert("name".to_string(), json!(name));
+        }
+        if let Some(schedule) = self.schedule(now)? {
+            fields.insert("schedule".to_string(), schedule);
+        }
+        match (&self.message, &self.system_event) {
+            (Some(message), _) => {
+                fields.insert(
+                    "payload".to_string(),
+                    json!({ "kind": "agentTurn", "message": message }),
+                );
+            }
+            (None, Some(text)) => {
+                fields.insert(
+                    "payload".to_string(),
+                    json!({ "kind": "systemEvent", "text": text }),
+                );
+            }
+            (None, None) => {}
+        }
+        if let Some(session) = &self.session {
+            fields.insert("sessionTarget".to_string(), json!(session));
+        }
+        if let Some(wake) = &self.wake {
+            fields.insert("wakeMode".to_string(), json!(wake));
+        }
+        if !self.depends_on.is_empty() {
+            fields.insert("dependsOn".to_string(), json!(self.depends_on));
+        }
+        if let Some(enabled) = self.enabled {
+            fields.insert("enabled".to_string(), json!(enabled));
+        }
+        Ok(fields)
+    }
+
+    fn schedule(&self, now: i64) -> Result<Option<Value>> {
+        if self.tz.is_some() && self.cron.is_none() {
+            anyhow::bail!("--tz only applies to --cron; put the zone inside --at instead");
+        }
+        if let Some(expr) = &self.cron {
+            cron::check_cron_expr(expr)?;
+            let mut schedule = json!({ "kind": "cron", "expr": expr.trim() });
+            if let Some(tz) = &self.tz {
+                tz.parse::<Tz>()
+                    .map_err(|_| anyhow::anyhow!("unknown time zone {tz:?}"))?;
+                schedule["tz"] = json!(tz);
+            }
+            return Ok(Some(schedule));
+        }
+        if let Some(every) = &self.every {
+            let every_ms = parse_duration_ms(every)?;
+            return Ok(Some(json!({ "kind": "every", "everyMs": every_ms })));
+        }
+        if let Some(at) = &self.at {
+            let at_ms = parse_at(at, now)?;
+            if at_ms <= now {
+                anyhow::bail!("--at {at:?} is in the past");
+            }
+            return Ok(Some(json!({ "kind": "at", "atMs": at_ms })));
+        }
+        Ok(None)
+    }
+}
+
+/// Parses durations like `90s`, `15m`, `1h30m` or `2d` (units `ms`, `s`, `m`, `h`, `d`).
+pub fn parse_duration_ms(raw: &str) -> Result<i64> {
+    let text = raw.trim().to_ascii_lowercase();
+    let invalid = || anyhow::anyhow!("invalid duration {raw:?}: expected e.g. 90s, 15m or 1h30m");
+    if text.is_empty() {
+        return Err(invalid());
+    }
+    let mut total: i64 = 0;
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(invalid());
+        }
+        let amount: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let factor = match rest[..unit_len].trim() {
+            "ms" => 1,
+            "s" | "sec" => 1_000,
+            "m" | "min" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            _ => return Err(invalid()),
+        };
+        total = amount
+            .checked_mul(factor)
+            .and_then(|ms| total.checked_add(ms))
+            .ok_or_else(invalid)?;
+        rest = &rest[unit_len..];
+    }
+    if total <= 0 {
+        anyhow::bail!("duration {raw:?} must be positive");
+    }
+    Ok(total)
+}
+
+/// Formats milliseconds the way `parse_duration_ms` reads them (`1h30m`, `45s`, `250ms`).
+pub fn format_duration_ms(ms: i64) -> String {
+    if ms <= 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    let mut rest = ms;
+    for (unit, size) in [("d", 86_400_000), ("h", 3_600_000), ("m", 60_000), ("s", 1_000)] {
+        if rest >= size {
+            out.push_str(&format!("{}{unit}", rest / size));
+            rest %= size;
+        }
+    }
+    if rest > 0 {
+        out.push_str(&format!("{rest}ms"));
+    }
+    out
+}
+
+/// Parses `--at`: `+<duration>` from now, RFC 3339, or `YYYY-MM-DD[ HH:MM[:SS]]` followed by an
+/// optional IANA zone (`Europe/Oslo`, `UTC`). Without a zone the local time zone is used.
+pub fn parse_at(raw: &str, now: i64) -> Result<i64> {
+    let text = raw.trim();
+    if let Some(offset) = text.strip_prefix('+') {
+        return Ok(now + parse_duration_ms(offset)?);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Ok(dt.timestamp_millis());
+    }
+    let (stamp, zone) = match text.rsplit_once(char::is_whitespace) {
+        Some((head, tail)) if parse_zone(tail).is_some() => (head.trim(), parse_zone(tail)),
+        _ => (text, None),
+    };
+    let naive = parse_naive(stamp).with_context(|| {
+        format!("invalid time {raw:?}: expected \"YYYY-MM-DD HH:MM [Area/City]\", RFC 3339 or +15m")
+    })?;
+    let resolved = match zone {
+        Some(tz) => tz.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp_millis()),
+        None => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.timestamp_millis()),
+    };
+    resolved.with_context(|| format!("{raw:?} does not exist in that time zone (DST gap)"))
+}
+
+fn parse_zone(raw: &str) -> Option<Tz> {
+    if raw.eq_ignore_ascii_case("z") || raw.eq_ignore_ascii_case("utc") {
+        return Some(chrono_tz::UTC);
+    }
+    raw.parse().ok()
+}
+
+fn parse_naive(raw: &str) -> Option<NaiveDateTime> {
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(parsed) = NaiveDateTime::parse_from_str(raw, format) {
+            return Some(parsed);
+        }
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+pub fn list_command(paths: &ClawdPaths, include_disabled: bool, as_json: bool) -> Result<String> {
+    let value = cron::list_jobs(paths, include_disabled)?;
+    if as_json {
+        return Ok(serde_json::to_string_pretty(&value)?);
+    }
+    let jobs = value
+        .get("jobs")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if jobs.is_empty() {
+        return Ok("no cron jobs".to_string());
+    }
+    let rows = jobs
+        .iter()
+        .map(|job| {
+            let state = job.get("state");
+            vec![
+                str_field(Some(job), "id"),
+                str_field(Some(job), "name"),
+                describe_schedule(job),
+                if job.get("enabled").and_then(Value::as_bool).unwrap_or(true) {
+                    "yes".to_string()
+                } else {
+                    "no".to_string()
+                },
+                time_field(state, "nextRunAtMs"),
+                time_field(state, "lastRunAtMs"),
+                str_field(state, "lastStatus"),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let mut out = vec![render_table(
+        &["ID", "NAME", "SCHEDULE", "ENABLED", "NEXT RUN", "LAST RUN", "STATUS"],
+        &rows,
+    )];
+    for warning in value
+        .get("warnings")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        out.push(format!("warning: {warning}"));
+    }
+    Ok(out.join("\n"))
+}
+
+pub fn add_command(
+    paths: &ClawdPaths,
+    id: Option<&str>,
+    flags: &JobFlags,
+    as_json: bool,
+) -> Result<String> {
+    let mut fields = flags.to_job_fields(now_ms())?;
+    if !fields.contains_key("schedule") && flags.depends_on.is_empty() {
+        anyhow::bail!("pass a schedule (--cron, --every or --at) or --depends-on");
+    }
+    if !fields.contains_key("payload") {
+        anyhow::bail!("pass --message or --system-event");
+    }
+    if let Some(id) = id {
+        fields.insert("id".to_string(), json!(id));
+    }
+    let job = cron::add_job(paths, &Value::Object(fields))?;
+    if as_json {
+        return Ok(serde_json::to_string_pretty(&job)?);
+    }
+    Ok(format!("added {}", describe_saved_job(&job)))
+}
+
+pub fn update_command(
+    paths: &ClawdPaths,
+    job_id: &str,
+    flags: &JobFlags,
+    as_json: bool,
+) -> Result<String> {
+    let patch = flags.to_job_fields(now_ms())?;
+    if patch.is_empty() {
+        anyhow::bail!("nothing to update");
+    }
+    let job = cron::update_job(paths, &json!({ "jobId": job_id, "patch": patch }))?;
+    if as_json {
+        return Ok(serde_json::to_string_pretty(&job)?);
+    }
+    Ok(format!("updated {}", describe_saved_job(&job)))
+}
+
+pub fn remove_command(paths: &ClawdPaths, job_id: &str, as_json: bool) -> Result<String> {
+    let value = cron::remove_job(paths, &json!({ "jobId": job_id }))?;
+    if as_json {
+        return Ok(serde_json::to_string_pretty(&value)?);
+    }
+    if value.get("removed").and_then(Value::as_bool).unwrap_or(false) {
+        Ok(format!("removed {job_id}"))
+    } else {
+        Ok(format!("no cron job {job_id}"))
+    }
+}
+
+/// Asks a running daemon to run the job and falls back to queueing it in the store, like
+/// `cron.run`.
+pub fn run_command(paths: &ClawdPaths, job_id: &str, force: bool, as_json: bool) -> Result<String> {
+    let mode = if force { "force" } else { "due" };
+    let value = match daemon_client::cron_run(job_id, mode) {
+        Some(value) => value,
+        None => cron::run_jobs(paths, &json!({ "jobId": job_id, "mode": mode }))?,
+    };
+    if as_json {
+        return Ok(serde_json::to_string_pretty(&value)?);
+    }
+    if value.get("ran").and_then(Value::as_bool).unwrap_or(false) {
+        return Ok(format!("started {job_id}"));
+    }
+    let reason = value
+        .get("reason")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+    Ok(match reason {
+        "not-due" => format!("{job_id} is not due; pass --force to run it now"),
+        "not-found" => format!("no cron job {job_id}"),
+        _ => format!("{job_id} did not run: {reason}"),
+    })
+}
+
+pub fn runs_command(
+    paths: &ClawdPaths,
+    job_id: &str,
+    limit: Option<usize>,
+    as_json: bool,
+) -> Result<String> {
+    let mut args = json!({ "jobId": job_id });
+    if let Some(limit) = limit {
+        args["limit"] = json!(limit);
+    }
+    let value = cron::runs(paths, &args)?;
+    if as_json {
+        return Ok(serde_json::to_string_pretty(&value)?);
+    }
+    let entries = value
+        .get("entries")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if entries.is_empty() {
+        return Ok(format!("no runs recorded for {job_id}"));
+    }
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            let started = entry.get("runAtMs").or_else(|| entry.get("ts"));
+            let detail = entry
+                .get("error")
+                .or_else(|| entry.get("summary"))
+                .and_then(Value::as_str)
+                .unwrap_or("");
+            vec![
+                started
+                    .and_then(Value::as_i64)
+                    .map(format_time)
+                    .unwrap_or_else(|| "-".to_string()),
+                str_field(Some(entry), "status"),
+                entry
+                    .get("durationMs")
+                    .and_then(Value::as_i64)
+                    .map(format_duration_ms)
+                    .unwrap_or_else(|| "-".to_string()),
+                truncate(detail, MAX_CELL_CHARS),
+            ]
+        })
+        .collect::<Vec<_>>();
+    Ok(render_table(&["STARTED", "STATUS", "DURATION", "DETAIL"], &rows))
+}
+
+/// Upcoming fire times for one job, or merged across every enabled job.
+pub fn next_command(
+    paths: &ClawdPaths,
+    job_id: Option<&str>,
+    count: Option<usize>,
+    as_json: bool,
+) -> Result<String> {
+    let count = count.unwrap_or(DEFAULT_NEXT_COUNT).max(1);
+    let now = now_ms();
+    let jobs = match job_id {
+        Some(job_id) => vec![cron::load_job_value(paths, job_id)?
+            .with_context(|| format!("no cron job {job_id}"))?],
+        None => cron::list_jobs(paths, false)?
+            .get("jobs")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default(),
+    };
+    let mut upcoming = Vec::new();
+    for job in &jobs {
+        for at_ms in cron::upcoming_runs(job, now, count) {
+            upcoming.push((at_ms, job));
+        }
+    }
+    upcoming.sort_by_key(|(at_ms, _)| *at_ms);
+    upcoming.truncate(count);
+
+    if as_json {
+        let runs = upcoming
+            .iter()
+            .map(|(at_ms, job)| {
+                json!({
+                    "jobId": job.get("id"),
+                    "name": job.get("name"),
+                    "atMs": at_ms,
+                })
+            })
+            .collect::<Vec<_>>();
+        return Ok(serde_json::to_string_pretty(&json!({ "runs": runs }))?);
+    }
+    if upcoming.is_empty() {
+        return Ok("no upcoming cron runs".to_string());
+    }
+    let rows = upcoming
+        .iter()
+        .map(|(at_ms, job)| {
+            vec![
+                format_time(*at_ms),
+                format!("in {}", format_duration_ms(round_to_minute(at_ms - now))),
+                str_field(Some(job), "id"),
+                str_field(Some(job), "name"),
+            ]
+        })
+        .collect::<Vec<_>>();
+    Ok(render_table(&["TIME", "IN", "ID", "NAME"], &rows))
+}
+
+/// Fire times of a schedule given by flags, or of an existing job's schedule, like
+/// `cron.preview`.
+pub fn preview_command(
+    paths: &ClawdPaths,
+    job_id: Option<&str>,
+    flags: &JobFlags,
+    count: Option<usize>,
+    as_json: bool,
+) -> Result<String> {
+    let schedule = match job_id {
+        Some(job_id) => cron::load_job_value(paths, job_id)?
+            .with_context(|| format!("no cron job {job_id}"))?
+            .get("schedule")
+            .filter(|schedule| schedule.is_object())
+            .cloned()
+            .with_context(|| format!("cron job {job_id} has no schedule"))?,
+        None => flags
+            .schedule(now_ms())?
+            .context("pass a schedule (--cron, --every or --at) or --job-id")?,
+    };
+    let mut args = json!({ "schedule": schedule });
+    if let Some(count) = count {
+        args["count"] = json!(count);
+    }
+    let value = cron::preview_schedule(&args)?;
+    if as_json {
+        return Ok(serde_json::to_string_pretty(&value)?);
+    }
+    let timezone = value
+        .get("timezone")
+        .and_then(Value::as_str)
+        .unwrap_or("UTC");
+    let mut out = vec![describe_schedule(&json!({ "schedule": schedule }))];
+    let runs = value
+        .get("runs")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    if runs.is_empty() {
+        out.push("no upcoming runs".to_string());
+    } else {
+        let rows = runs
+            .iter()
+            .map(|run| {
+                let ambiguous = run
+                    .get("dstAmbiguous")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                vec![
+                    str_field(Some(run), "utc"),
+                    str_field(Some(run), "local"),
+                    if ambiguous { "DST ambiguous" } else { "" }.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let local_header = format!("LOCAL ({timezone})");
+        out.push(render_table(&["UTC", local_header.as_str(), "NOTE"], &rows));
+    }
+    for warning in value
+        .get("warnings")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        out.push(format!("warning: {warning}"));
+    }
+    Ok(out.join("\n"))
+}
+
+fn describe_saved_job(job: &Value) -> String {
+    let id = str_field(Some(job), "id");
+    let name = str_field(Some(job), "name");
+    let next = job
+        .get("state")
+        .and_then(|state| state.get("nextRunAtMs"))
+        .and_then(Value::as_i64);
+    match next {
+        Some(next) => format!("{id} ({name}), next run {}", format_time(next)),
+        None => format!("{id} ({name}), {}", describe_schedule(job)),
+    }
+}
+
+fn describe_schedule(job: &Value) -> String {
+    let Some(schedule) = job.get("schedule").filter(|s| s.is_object()) else {
+        let upstream = job
+            .get("dependsOn")
+            .map(|deps| match deps {
+                Value::Array(items) => items.iter().filter_map(dependency_id).collect(),
+                other => dependency_id(other).into_iter().collect::<Vec<_>>(),
+            })
+            .unwrap_or_default();
+        if upstream.is_empty() {
+            return "-".to_string();
+        }
+        return format!("after {}", upstream.join(", "));
+    };
+    match schedule.get("kind").and_then(Value::as_str).unwrap_or("cron") {
+        "at" => schedule
+            .get("atMs")
+            .and_then(Value::as_i64)
+            .map(|at_ms| format!("at {}", format_time(at_ms)))
+            .unwrap_or_else(|| "at ?".to_string()),
+        "every" => schedule
+            .get("everyMs")
+            .and_then(Value::as_i64)
+            .map(|every_ms| format!("every {}", format_duration_ms(every_ms)))
+            .unwrap_or_else(|| "every ?".to_string()),
+        _ => {
+            let expr = schedule
+                .get("cron")
+                .or_else(|| schedule.get("expr"))
+                .and_then(Value::as_str)
+                .unwrap_or("?");
+            match schedule
+                .get("timezone")
+                .or_else(|| schedule.get("tz"))
+                .and_then(Value::as_str)
+            {
+                Some(tz) => format!("cron {expr} ({tz})"),
+                None => format!("cron {expr}"),
+            }
+        }
+    }
+}
+
+fn dependency_id(dep: &Value) -> Option<String> {
+    match dep {
+        Value::String(id) => Some(id.clone()),
+        Value::Object(map) => map
+            .get("jobId")
+            .or_else(|| map.get("job_id"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+fn str_field(value: Option<&Value>, key: &str) -> String {
+    value
+        .and_then(|v| v.get(key))
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(|s| truncate(s, MAX_CELL_CHARS))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn time_field(value: Option<&Value>, key: &str) -> String {
+    value
+        .and_then(|v| v.get(key))
+        .and_then(Value::as_i64)
+        .map(format_time)
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_time(ms: i64) -> String {
+    Local
+        .timestamp_millis_opt(ms)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M %Z").to_string())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+fn round_to_minute(ms: i64) -> i64 {
+    if ms < 60_000 {
+        return ms.max(0) / 1_000 * 1_000;
+    }
+    (ms + 30_000) / 60_000 * 60_000
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= max {
+        return flat;
+    }
+    let mut out = flat.chars().take(max.saturating_sub(1)).collect::<String>();
+    out.push('…');
+    out
+}
+
+fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = headers.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+    for row in rows {
+        for (idx, cell) in row.iter().enumerate() {
+            widths[idx] = widths[idx].max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[&str]| {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| format!("{cell:<width$}", width = widths[idx]))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut out = vec![line(headers)];
+    for row in rows {
+        out.push(line(&row.iter().map(String::as_str).collect::<Vec<_>>()));
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono_tz::Europe::Oslo;
+    use serde_json::json;
+
+    use super::{format_duration_ms, parse_at, parse_duration_ms, render_table, JobFlags};
+
+    #[test]
+    fn durations_round_trip() {
+        assert_eq!(parse_duration_ms("15m").unwrap(), 900_000);
+        assert_eq!(parse_duration_ms("1h30m").unwrap(), 5_400_000);
+        assert_eq!(parse_duration_ms(" 2d ").unwrap(), 172_800_000);
+        assert_eq!(parse_duration_ms("250ms").unwrap(), 250);
+        assert!(parse_duration_ms("15").is_err());
+        assert!(parse_duration_ms("m").is_err());
+        assert!(parse_duration_ms("0s").is_err());
+        assert!(parse_duration_ms("3w").is_err());
+        assert_eq!(format_duration_ms(5_400_000), "1h30m");
+        assert_eq!(format_duration_ms(61_500), "1m1s500ms");
+    }
+
+    #[test]
+    fn at_accepts_zoned_local_and_relative_times() {
+        let oslo = Oslo
+            .with_ymd_and_hms(2026, 10, 20, 9, 0, 0)
+            .unwrap()
+            .timestamp_millis();
+        assert_eq!(parse_at("2026-10-20 09:00 Europe/Oslo", 0).unwrap(), oslo);
+        assert_eq!(parse_at("2026-10-20T07:00:00Z", 0).unwrap(), oslo);
+        assert_eq!(parse_at("2026-10-20 07:00 UTC", 0).unwrap(), oslo);
+        assert_eq!(parse_at("+20m", 1_000).unwrap(), 1_201_000);
+        // 02:30 does not exist in Oslo on the spring-forward night.
+        assert!(parse_at("2026-03-29 02:30 Europe/Oslo", 0).is_err());
+        assert!(parse_at("next tuesday", 0).is_err());
+    }
+
+    #[test]
+    fn flags_build_job_fields() {
+        let flags = JobFlags {
+            name: Some("digest".to_string()),
+            cron: Some("0 0 9 * * Mon-Fri".to_string()),
+            tz: Some("Europe/Oslo".to_string()),
+            message: Some("Summarize my inbox".to_string()),
+            enabled: Some(false),
+            ..JobFlags::default()
+        };
+        let fields = flags.to_job_fields(0).unwrap();
+        assert_eq!(
+            fields["schedule"],
+            json!({ "kind": "cron", "expr": "0 0 9 * * Mon-Fri", "tz": "Europe/Oslo" })
+        );
+        assert_eq!(fields["payload"]["kind"], "agentTurn");
+        assert_eq!(fields["enabled"], false);
+
+        let every = JobFlags {
+            every: Some("15m".to_string()),
+            ..JobFlags::default()
+        };
+        assert_eq!(every.to_job_fields(0).unwrap()["schedule"]["everyMs"], 900_000);
+
+        let past = JobFlags {
+            at: Some("2020-01-01 09:00 UTC".to_string()),
+            ..JobFlags::default()
+        };
+        assert!(past.to_job_fields(super::now_ms()).is_err());
+        let stray_tz = JobFlags {
+            every: Some("1h".to_string()),
+            tz: Some("UTC".to_string()),
+            ..JobFlags::default()
+        };
+        assert!(stray_tz.to_job_fields(0).is_err());
+        let five_fields = JobFlags {
+            cron: Some("0 9 * * 1".to_string()),
+            ..JobFlags::default()
+        };
+        assert!(five_fields.to_job_fields(0).is_err());
+    }
+
+    #[test]
+    fn tables_pad_columns() {
+        let table = render_table(
+            &["ID", "NAME"],
+            &[
+                vec!["a".to_string(), "first".to_string()],
+                vec!["long-id".to_string(), "x".to_string()],
+            ],
+        );
+        assert_eq!(table, "ID       NAME\na        first\nlong-id  x");
+    }
+}
@@ -0,0 +1,697 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::ClawdPaths;
+use crate::cron::{self, build_cron_job, load_job_value};
+use crate::cron_store::CronStore;
+use crate::util::now_ms;
+
+/// How often a running job refreshes its lock's heartbeat.
+pub const LOCK_HEARTBEAT_INTERVAL_MS: i64 = 30_000;
+/// Silence after which the lock of a job without a timeout counts as stale.
+const DEFAULT_STALE_LOCK_MS: i64 = 3_600_000;
+
+/// Who holds a job lock; stored as JSON in `cron/locks/<jobId>[.<n>].lock`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockOwner {
+    pub pid: u32,
+    pub hostname: String,
+    pub acquired_at_ms: i64,
+    pub heartbeat_at_ms: i64,
+}
+
+impl LockOwner {
+    fn current(now: i64) -> Self {
+        LockOwner {
+            pid: std::process::id(),
+            hostname: hostname().to_string(),
+            acquired_at_ms: now,
+            heartbeat_at_ms: now,
+        }
+    }
+
+    /// Older daemons wrote only the acquisition timestamp; those locks have no known owner.
+    fn read(path: &Path) -> Option<Self> {
+        let raw = fs::read_to_string(path).ok()?;
+        if let Ok(owner) = serde_json::from_str::<LockOwner>(&raw) {
+            return Some(owner);
+        }
+        let stamp = raw.trim().parse::<i64>().ok()?;
+        Some(LockOwner {
+            pid: 0,
+            hostname: String::new(),
+            acquired_at_ms: stamp,
+            heartbeat_at_ms: stamp,
+        })
+    }
+
+    /// Why the lock may be taken over, or `None` while its owner still looks alive. Locks held by
+    /// this process are never stale: they are released when the run ends, even on panic.
+    pub fn stale_reason(&self, stale_after_ms: i64, now: i64) -> Option<String> {
+        if self.pid != 0 && self.hostname == hostname() {
+            if self.pid == std::process::id() {
+                return None;
+            }
+            if !process_alive(self.pid) {
+                return Some(format!("owner pid {} is gone", self.pid));
+            }
+        }
+        let silent_ms = now.saturating_sub(self.heartbeat_at_ms);
+        if silent_ms > stale_after_ms {
+            return Some(format!("no heartbeat for {silent_ms}ms"));
+        }
+        None
+    }
+
+    /// Whether both describe the same acquisition; heartbeats do not change the holder.
+    fn same_holder(&self, other: &LockOwner) -> bool {
+        self.pid == other.pid
+            && self.hostname == other.hostname
+            && self.acquired_at_ms == other.acquired_at_ms
+    }
+}
+
+/// A held job lock; dropping it removes the lock file unless another daemon has taken it over.
+pub struct JobLock {
+    path: PathBuf,
+    owner: LockOwner,
+}
+
+impl JobLock {
+    /// Refreshes the heartbeat so other daemons do not reclaim the lock. Returns false, without
+    /// touching the file, once the lock was released or reclaimed by someone else.
+    pub fn heartbeat(&mut self, now: i64) -> Result<bool> {
+        if !self.still_held() {
+            return Ok(false);
+        }
+        self.owner.heartbeat_at_ms = now;
+        write_owner(&self.path, &self.owner)?;
+        Ok(true)
+    }
+
+    /// Whether the lock file still names this acquisition.
+    fn still_held(&self) -> bool {
+        LockOwner::read(&self.path).is_some_and(|owner| owner.same_holder(&self.owner))
+    }
+
+    /// Runs `work` while a background thread refreshes the heartbeat on its own timer, so the
+    /// lock stays fresh however long `work` blocks.
+    pub fn heartbeat_while<T>(&mut self, work: impl FnOnce() -> T) -> T {
+        self.heartbeat_every(
+            Duration::from_millis(LOCK_HEARTBEAT_INTERVAL_MS as u64),
+            work,
+        )
+    }
+
+    fn heartbeat_every<T>(&mut self, interval: Duration, work: impl FnOnce() -> T) -> T {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                    match self.heartbeat(now_ms()) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            eprintln!(
+                                "[clawdex][cron] lost lock {}; stopping its heartbeat",
+                                self.path.display()
+                            );
+                            break;
+                        }
+                        Err(err) => eprintln!(
+                            "[clawdex][cron] lock heartbeat failed for {}: {err}",
+                            self.path.display()
+                        ),
+                    }
+                }
+            });
+            let result = work();
+            drop(stop_tx);
+            result
+        })
+    }
+}
+
+impl Drop for JobLock {
+    fn drop(&mut self) {
+        if self.still_held() {
+            let _ = fs::remove_file(&self.path);
+        } else if self.path.exists() {
+            eprintln!(
+                "[clawdex][cron] leaving lock {}: it now belongs to another owner",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// How long a lock may go without a heartbeat: the job's timeout, or an hour without one.
+pub fn stale_after_ms(timeout_ms: Option<u64>) -> i64 {
+    timeout_ms
+        .map(|ms| ms.min(i64::MAX as u64) as i64)
+        .unwrap_or(DEFAULT_STALE_LOCK_MS)
+        .max(2 * LOCK_HEARTBEAT_INTERVAL_MS)
+}
+
+/// Takes one of the job's `slots` lock files (`<jobId>.lock`, then `<jobId>.<n>.lock`),
+/// reclaiming any whose owner is dead or silent for longer than `stale_after_ms`.
+pub fn acquire(
+    paths: &ClawdPaths,
+    job_id: &str,
+    slots: usize,
+    stale_after_ms: i64,
+) -> Result<Option<JobLock>> {
+    let locks_dir = locks_dir(paths);
+    fs::create_dir_all(&locks_dir)?;
+    for slot in 0..slots.max(1) {
+        let path = if slot == 0 {
+            locks_dir.join(format!("{job_id}.lock"))
+        } else {
+            locks_dir.join(format!("{job_id}.{slot}.lock"))
+        };
+        if let Some(lock) = try_create(&path)? {
+            return Ok(Some(lock));
+        }
+        let now = now_ms();
+        let Some(stale) = LockOwner::read(&path)
+            .filter(|owner| owner.stale_reason(stale_after_ms, now).is_some())
+        else {
+            continue;
+        };
+        if !retire_stale(&path, &stale, stale_after_ms)? {
+            continue;
+        }
+        if let Some(lock) = try_create(&path)? {
+            return Ok(Some(lock));
+        }
+    }
+    Ok(None)
+}
+
+/// Moves a stale lock out of the way without clobbering a newer one. Removing the file directly
+/// would race: another daemon may reclaim it and write a fresh lock between our staleness check
+/// and the removal. Renaming to a unique tombstone is atomic, so exactly one daemon gets the
+/// file; it then confirms the tombstone still holds the owner it judged stale, and puts the
+/// lock back if it picked up a fresh one instead.
+fn retire_stale(path: &Path, stale: &LockOwner, stale_after_ms: i64) -> Result<bool> {
+    let tombstone = path.with_extension(format!("lock.stale-{}", uuid::Uuid::new_v4()));
+    match fs::rename(path, &tombstone) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    }
+    let taken = LockOwner::read(&tombstone);
+    let still_stale = taken.as_ref().is_some_and(|owner| {
+        owner == stale && owner.stale_reason(stale_after_ms, now_ms()).is_some()
+    });
+    if !still_stale {
+        // Someone else's live lock: hand it back unless yet another lock took its place.
+        if fs::hard_link(&tombstone, path).is_err() {
+            eprintln!(
+                "[clawdex][cron] could not restore lock {}; it was replaced meanwhile",
+                path.display()
+            );
+        }
+        let _ = fs::remove_file(&tombstone);
+        return Ok(false);
+    }
+    let reason = stale
+        .stale_reason(stale_after_ms, now_ms())
+        .unwrap_or_default();
+    eprintln!("[clawdex][cron] reclaiming lock {}: {reason}", path.display());
+    let _ = fs::remove_file(&tombstone);
+    Ok(true)
+}
+
+fn try_create(path: &Path) -> Result<Option<JobLock>> {
+    match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(mut file) => {
+            let owner = LockOwner::current(now_ms());
+            file.write_all(serde_json::to_string(&owner)?.as_bytes())
+                .with_context(|| format!("write {}", path.display()))?;
+            Ok(Some(JobLock {
+                path: path.to_path_buf(),
+                owner,
+            }))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn write_owner(path: &Path, owner: &LockOwner) -> Result<()> {
+    // Rename over the old file so readers never see a half-written owner.
+    let tmp = path.with_extension("lock.tmp");
+    fs::write(&tmp, serde_json::to_string(owner)?)
+        .with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("replace {}", path.display()))
+}
+
+/// A lock file found on disk.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockInfo {
+    pub job_id: String,
+    pub file: String,
+    pub owner: Option<LockOwner>,
+    /// Why the lock counts as stale, if it does.
+    pub stale: Option<String>,
+}
+
+/// Every job lock, with the staleness verdict the scheduler would apply to it.
+pub fn list_locks(paths: &ClawdPaths) -> Result<Vec<LockInfo>> {
+    let Ok(entries) = fs::read_dir(locks_dir(paths)) else {
+        return Ok(Vec::new());
+    };
+    let known = CronStore::open(paths)?
+        .read(|tx| tx.list_jobs())?
+        .iter()
+        .filter_map(|job| job.get("id").and_then(|v| v.as_str()).map(str::to_string))
+        .collect::<HashSet<_>>();
+    let now = now_ms();
+    let mut locks = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let file = entry.file_name().to_string_lossy().to_string();
+        let Some(job_id) = lock_job_id(&file, |id| known.contains(id)) else {
+            continue;
+        };
+        let owner = LockOwner::read(&entry.path());
+        let stale_after = stale_after_ms(job_timeout_ms(paths, &job_id));
+        let stale = owner.as_ref().and_then(|o| o.stale_reason(stale_after, now));
+        locks.push(LockInfo {
+            job_id,
+            file,
+            owner,
+            stale,
+        });
+    }
+    locks.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(locks)
+}
+
+/// Removes stale locks and ends runs that lost their lock: a job still marked as running
+/// (`state.runningAtMs`) with no lock left is recorded as a failed run. Jobs in `busy` are being
+/// run by this process and are left alone. Returns the affected job ids.
+pub fn recover_stale_runs(paths: &ClawdPaths, busy: &HashSet<String>) -> Result<Vec<String>> {
+    release_locks(paths, None, false, busy)
+}
+
+/// `clawdex cron unlock`: releases the locks of `job_id` (or of every job) that are stale, or all
+/// of them with `force`, and closes the runs they belonged to.
+pub fn unlock(paths: &ClawdPaths, job_id: Option<&str>, force: bool) -> Result<Value> {
+    let kept = list_locks(paths)?
+        .into_iter()
+        .filter(|lock| job_id.is_none_or(|id| lock.job_id == id))
+        .filter(|lock| !force && lock.stale.is_none())
+        .collect::<Vec<_>>();
+    let released = release_locks(paths, job_id, force, &HashSet::new())?;
+    Ok(json!({ "ok": true, "released": released, "kept": kept }))
+}
+
+fn release_locks(
+    paths: &ClawdPaths,
+    job_id: Option<&str>,
+    force: bool,
+    busy: &HashSet<String>,
+) -> Result<Vec<String>> {
+    let mut released = BTreeSet::new();
+    let mut reasons = Vec::new();
+    let mut held = HashSet::new();
+    for lock in list_locks(paths)? {
+        if job_id.is_some_and(|id| lock.job_id != id) || busy.contains(&lock.job_id) {
+            continue;
+        }
+        let reason = match (lock.stale, force) {
+            (Some(reason), _) => reason,
+            (None, true) => "unlocked by operator".to_string(),
+            (None, false) => {
+                held.insert(lock.job_id);
+                continue;
+            }
+        };
+        match fs::remove_file(locks_dir(paths).join(&lock.file)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        eprintln!("[clawdex][cron] released lock {}: {reason}", lock.file);
+        released.insert(lock.job_id.clone());
+        reasons.push((lock.job_id, reason));
+    }
+
+    let jobs = CronStore::open(paths)?.read(|tx| tx.list_jobs())?;
+    for job in &jobs {
+        let Some(id) = job.get("id").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let Some(running_at) = job
+            .get("state")
+            .and_then(|v| v.get("runningAtMs"))
+            .and_then(|v| v.as_i64())
+        else {
+            continue;
+        };
+        if job_id.is_some_and(|only| only != id) || busy.contains(id) || held.contains(id) {
+            continue;
+        }
+        let reason = reasons
+            .iter()
+            .find(|(job, _)| job == id)
+            .map(|(_, reason)| reason.clone())
+            .unwrap_or_else(|| "no lock held".to_string());
+        let now = now_ms();
+        cron::record_run(
+            paths,
+            id,
+            "failed",
+            "run abandoned",
+            Some(json!({
+                "error": format!("run abandoned: {reason}"),
+                "runAtMs": running_at,
+                "durationMs": now.saturating_sub(running_at),
+            })),
+        )?;
+        released.insert(id.to_string());
+    }
+    Ok(released.into_iter().collect())
+}
+
+fn locks_dir(paths: &ClawdPaths) -> PathBuf {
+    paths.cron_dir.join("locks")
+}
+
+/// `<jobId>.lock` or `<jobId>.<n>.lock` -> `<jobId>`. Job ids may end in `.<n>` themselves, so
+/// the suffix only counts as a slot when it leaves a known job id and the whole stem is not one.
+fn lock_job_id(file: &str, is_job: impl Fn(&str) -> bool) -> Option<String> {
+    let stem = file.strip_suffix(".lock")?;
+    let job_id = match stem.rsplit_once('.') {
+        Some((job_id, slot))
+            if !slot.is_empty()
+                && slot.bytes().all(|b| b.is_ascii_digit())
+                && is_job(job_id)
+                && !is_job(stem) =>
+        {
+            job_id
+        }
+        _ => stem,
+    };
+    Some(job_id.to_string()).filter(|id| !id.is_empty())
+}
+
+fn job_timeout_ms(paths: &ClawdPaths, job_id: &str) -> Option<u64> {
+    load_job_value(paths, job_id)
+        .ok()
+        .flatten()
+        .and_then(|job| build_cron_job(&job))
+        .and_then(|job| job.effective_timeout_ms())
+}
+
+fn hostname() -> &'static str {
+    static HOSTNAME: OnceLock<String> = OnceLock::new();
+    HOSTNAME.get_or_init(|| {
+        fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .or_else(|| {
+                std::process::Command::new("hostname")
+                    .output()
+                    .ok()
+                    .and_then(|out| String::from_utf8(out.stdout).ok())
+            })
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "localhost".to_string())
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn process_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::fs;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::{acquire, list_locks, recover_stale_runs, unlock, LockOwner};
+    use crate::config::{load_config, ClawdPaths};
+    use crate::util::now_ms;
+
+    fn temp_paths() -> ClawdPaths {
+        let base = std::env::temp_dir().join(format!("clawdex-cron-lock-{}", Uuid::new_v4()));
+        let workspace_dir = base.join("workspace");
+        fs::create_dir_all(&workspace_dir).expect("create workspace");
+        let (_cfg, paths) =
+            load_config(Some(base.join("state")), Some(workspace_dir)).expect("paths");
+        paths
+    }
+
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().expect("spawn");
+        let pid = child.id();
+        child.wait().expect("wait");
+        pid
+    }
+
+    fn write_lock(paths: &ClawdPaths, file: &str, owner: &LockOwner) {
+        let dir = paths.cron_dir.join("locks");
+        fs::create_dir_all(&dir).expect("locks dir");
+        fs::write(dir.join(file), serde_json::to_string(owner).expect("json")).expect("write");
+    }
+
+    #[test]
+    fn stale_locks_are_reclaimed_and_live_ones_kept() {
+        let paths = temp_paths();
+        let now = now_ms();
+        let ours = acquire(&paths, "job", 1, 60_000).expect("acquire").expect("lock");
+        assert!(acquire(&paths, "job", 1, 60_000).expect("acquire").is_none());
+        drop(ours);
+
+        let dead = LockOwner {
+            pid: dead_pid(),
+            hostname: super::hostname().to_string(),
+            acquired_at_ms: now,
+            heartbeat_at_ms: now,
+        };
+        write_lock(&paths, "job.lock", &dead);
+        let reason = dead.stale_reason(60_000, now).expect("stale");
+        assert!(reason.contains("is gone"));
+        let lock = acquire(&paths, "job", 1, 60_000).expect("acquire");
+        assert!(lock.is_some());
+        drop(lock);
+
+        // Another host cannot be checked, so only a missed heartbeat makes its lock stale.
+        let remote = LockOwner {
+            pid: 42,
+            hostname: "elsewhere.invalid".to_string(),
+            acquired_at_ms: now - 120_000,
+            heartbeat_at_ms: now - 90_000,
+        };
+        assert!(remote.stale_reason(120_000, now).is_none());
+        assert!(remote.stale_reason(60_000, now).is_some());
+
+        // Locks from older versions hold only a timestamp.
+        let dir = paths.cron_dir.join("locks");
+        fs::write(dir.join("legacy.lock"), format!("{}\n", now - 7_200_000)).expect("write");
+        let locks = list_locks(&paths).expect("list");
+        assert_eq!(locks.len(), 1);
+        assert_eq!(locks[0].job_id, "legacy");
+        assert!(locks[0].stale.is_some());
+    }
+
+    #[test]
+    fn abandoned_runs_are_closed_and_unlock_respects_live_owners() {
+        let paths = temp_paths();
+        for id in ["crashed", "busy"] {
+            crate::cron::add_job(
+                &paths,
+                &json!({
+                    "id": id,
+                    "name": id,
+                    "schedule": { "kind": "every", "everyMs": 60_000 },
+                    "sessionTarget": "main",
+                    "wakeMode": "now",
+                    "payload": { "kind": "systemEvent", "text": "ping" }
+                }),
+            )
+            .expect("add job");
+        }
+        let now = now_ms();
+        crate::cron::mark_job_running(&paths, "crashed", now - 5_000).expect("mark");
+        write_lock(
+            &paths,
+            "crashed.lock",
+            &LockOwner {
+                pid: dead_pid(),
+                hostname: super::hostname().to_string(),
+                acquired_at_ms: now - 5_000,
+                heartbeat_at_ms: now - 5_000,
+            },
+        );
+        crate::cron::mark_job_running(&paths, "busy", now).expect("mark");
+        let held = acquire(&paths, "busy", 1, 60_000).expect("acquire").expect("lock");
+
+        let busy = HashSet::new();
+        let recovered = recover_stale_runs(&paths, &busy).expect("recover");
+        assert_eq!(recovered, vec!["crashed".to_string()]);
+        let job = crate::cron::load_job_value(&paths, "crashed").expect("load").expect("job");
+        assert!(job["state"].get("runningAtMs").is_none());
+        assert_eq!(job["state"]["lastStatus"], json!("error"));
+        assert!(job["state"]["lastError"]
+            .as_str()
+            .is_some_and(|err| err.contains("is gone")));
+
+        let res = unlock(&paths, Some("busy"), false).expect("unlock");
+        assert_eq!(res["released"], json!([]));
+        assert_eq!(res["kept"][0]["jobId"], json!("busy"));
+        let res = unlock(&paths, Some("busy"), true).expect("unlock");
+        assert_eq!(res["released"], json!(["busy"]));
+        assert!(list_locks(&paths).expect("list").is_empty());
+        drop(held);
+    }
+
+    #[test]
+    fn racing_acquirers_reclaim_a_stale_lock_once() {
+        let paths = temp_paths();
+        let dead = LockOwner {
+            pid: dead_pid(),
+            hostname: super::hostname().to_string(),
+            acquired_at_ms: now_ms(),
+            heartbeat_at_ms: now_ms(),
+        };
+        for _ in 0..20 {
+            write_lock(&paths, "job.lock", &dead);
+            let barrier = std::sync::Barrier::new(8);
+            let winners = std::thread::scope(|scope| {
+                let handles = (0..8)
+                    .map(|_| {
+                        scope.spawn(|| {
+                            barrier.wait();
+                            acquire(&paths, "job", 1, 60_000).expect("acquire")
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .filter_map(|handle| handle.join().expect("join"))
+                    .collect::<Vec<_>>()
+            });
+            assert_eq!(winners.len(), 1);
+            let owner = LockOwner::read(&winners[0].path).expect("owner");
+            assert_eq!(owner.pid, std::process::id());
+            let leftovers = fs::read_dir(paths.cron_dir.join("locks"))
+                .expect("locks dir")
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_name().to_string_lossy().contains(".stale-"))
+                .count();
+            assert_eq!(leftovers, 0);
+        }
+    }
+
+    #[test]
+    fn heartbeat_runs_while_work_blocks() {
+        let paths = temp_paths();
+        let mut lock = acquire(&paths, "slow", 1, 60_000).expect("acquire").expect("lock");
+        let before = lock.owner.heartbeat_at_ms;
+        let path = lock.path.clone();
+        lock.heartbeat_every(std::time::Duration::from_millis(20), || {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        });
+        let owner = LockOwner::read(&path).expect("owner");
+        assert!(owner.heartbeat_at_ms > before);
+    }
+
+    #[test]
+    fn slot_suffixes_are_told_apart_from_job_ids_ending_in_digits() {
+        let paths = temp_paths();
+        for id in ["report", "backup.2"] {
+            crate::cron::add_job(
+                &paths,
+                &json!({
+                    "id": id,
+                    "name": id,
+                    "schedule": { "kind": "every", "everyMs": 60_000 },
+                    "sessionTarget": "main",
+                    "wakeMode": "now",
+                    "payload": { "kind": "systemEvent", "text": "ping" }
+                }),
+            )
+            .expect("add job");
+        }
+        let _report = acquire(&paths, "report", 2, 60_000)
+            .expect("acquire")
+            .expect("lock");
+        let _slot = acquire(&paths, "report", 2, 60_000)
+            .expect("acquire")
+            .expect("lock");
+        let _backup = acquire(&paths, "backup.2", 1, 60_000)
+            .expect("acquire")
+            .expect("lock");
+
+        let locks = list_locks(&paths).expect("list");
+        let ids = locks
+            .iter()
+            .map(|lock| (lock.file.as_str(), lock.job_id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                ("backup.2.lock", "backup.2"),
+                ("report.1.lock", "report"),
+                ("report.lock", "report")
+            ]
+        );
+    }
+
+    #[test]
+    fn a_lock_taken_over_is_neither_refreshed_nor_removed() {
+        let paths = temp_paths();
+        let mut lock = acquire(&paths, "taken", 1, 60_000)
+            .expect("acquire")
+            .expect("lock");
+        let path = lock.path.clone();
+        let other = LockOwner {
+            pid: 42,
+            hostname: "elsewhere.invalid".to_string(),
+            acquired_at_ms: lock.owner.acquired_at_ms + 1,
+            heartbeat_at_ms: lock.owner.heartbeat_at_ms + 1,
+        };
+        write_lock(&paths, "taken.lock", &other);
+
+        assert!(!lock.heartbeat(now_ms()).expect("heartbeat"));
+        lock.heartbeat_every(std::time::Duration::from_millis(20), || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        });
+        assert_eq!(LockOwner::read(&path), Some(other.clone()));
+        drop(lock);
+        assert_eq!(LockOwner::read(&path), Some(other));
+    }
+}
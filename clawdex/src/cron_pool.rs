@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use anyhow::{Context, Result};
+
+/// A unit of cron work. It receives the worker's runner (or the error that kept it from
+/// starting); returning an error discards the runner so the next task starts a fresh one.
+pub type CronTask<R> = Box<dyn FnOnce(Result<&mut R>) -> Result<()> + Send>;
+
+type RunnerFactory<R> = Arc<dyn Fn() -> Result<R> + Send + Sync>;
+type InFlight = Arc<Mutex<HashMap<String, usize>>>;
+
+struct CronWork<R> {
+    key: String,
+    task: CronTask<R>,
+}
+
+/// Fixed set of worker threads that run cron jobs off the daemon's main loop, each with its own
+/// lazily started runner. Work is keyed by job id so the daemon can cap concurrent runs per job,
+/// counting in-flight runs in the next due scan.
+pub struct CronPool<R> {
+    sender: Option<mpsc::Sender<CronWork<R>>>,
+    workers: Vec<JoinHandle<()>>,
+    in_flight: InFlight,
+}
+
+impl<R: 'static> CronPool<R> {
+    pub fn start(
+        size: usize,
+        factory: impl Fn() -> Result<R> + Send + Sync + 'static,
+    ) -> Result<Self> {
+        let (sender, receiver) = mpsc::channel::<CronWork<R>>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let factory: RunnerFactory<R> = Arc::new(factory);
+        let in_flight: InFlight = Arc::new(Mutex::new(HashMap::new()));
+        let mut workers = Vec::new();
+        for idx in 0..size.max(1) {
+            let receiver = receiver.clone();
+            let factory = factory.clone();
+            let in_flight = in_flight.clone();
+            let handle = thread::Builder::new()
+                .name(format!("clawdex-cron-{idx}"))
+                .spawn(move || worker_loop(idx, &receiver, &factory, &in_flight))
+                .context("spawn cron worker")?;
+            workers.push(handle);
+        }
+        Ok(Self {
+            sender: Some(sender),
+            workers,
+            in_flight,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Tasks for `key` that are queued or running.
+    pub fn in_flight(&self, key: &str) -> usize {
+        lock_in_flight(&self.in_flight).get(key).copied().unwrap_or(0)
+    }
+
+    /// Keys with at least one task queued or running.
+    pub fn busy(&self) -> HashSet<String> {
+        lock_in_flight(&self.in_flight).keys().cloned().collect()
+    }
+
+    /// Queued or running tasks per key.
+    pub fn in_flight_counts(&self) -> HashMap<String, usize> {
+        lock_in_flight(&self.in_flight).clone()
+    }
+
+    /// Queues `task` for the next free worker.
+    pub fn submit(&self, key: &str, task: CronTask<R>) -> Result<()> {
+        *lock_in_flight(&self.in_flight)
+            .entry(key.to_string())
+            .or_insert(0) += 1;
+        let work = CronWork {
+            key: key.to_string(),
+            task,
+        };
+        let sent = self
+            .sender
+            .as_ref()
+            .map(|sender| sender.send(work).is_ok())
+            .unwrap_or(false);
+        if !sent {
+            finish(&self.in_flight, key);
+            anyhow::bail!("cron workers stopped");
+        }
+        Ok(())
+    }
+}
+
+impl<R> Drop for CronPool<R> {
+    /// Stops accepting work and waits for running tasks to finish.
+    fn drop(&mut self) {
+        self.sender.take();
+        for handle in self.workers.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn worker_loop<R>(
+    idx: usize,
+    receiver: &Mutex<mpsc::Receiver<CronWork<R>>>,
+    factory: &RunnerFactory<R>,
+    in_flight: &InFlight,
+) {
+    let mut runner: Option<R> = None;
+    loop {
+        let next = match receiver.lock() {
+            Ok(guard) => guard.recv(),
+            Err(_) => return,
+        };
+        let Ok(work) = next else {
+            return;
+        };
+        if runner.is_none() {
+            match factory() {
+                Ok(started) => runner = Some(started),
+                Err(err) => {
+                    eprintln!("[clawdex][cron] worker {idx} could not start a runner: {err}");
+                    let _ = catch_unwind(AssertUnwindSafe(|| (work.task)(Err(err))));
+                    finish(in_flight, &work.key);
+                    continue;
+                }
+            }
+        }
+        let Some(active) = runner.as_mut() else {
+            continue;
+        };
+        let healthy = match catch_unwind(AssertUnwindSafe(|| (work.task)(Ok(active)))) {
+            Ok(Ok(())) => true,
+            Ok(Err(err)) => {
+                eprintln!("[clawdex][cron] worker {idx} job {} failed: {err}", work.key);
+                false
+            }
+            Err(_) => {
+                eprintln!("[clawdex][cron] worker {idx} job {} panicked", work.key);
+                false
+            }
+        };
+        if !healthy {
+            runner = None;
+        }
+        finish(in_flight, &work.key);
+    }
+}
+
+fn lock_in_flight(in_flight: &InFlight) -> std::sync::MutexGuard<'_, HashMap<String, usize>> {
+    in_flight.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn finish(in_flight: &InFlight, key: &str) {
+    let mut map = lock_in_flight(in_flight);
+    if let Some(count) = map.get_mut(key) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            map.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc, Barrier};
+    use std::time::Duration;
+
+    use super::{CronPool, CronTask};
+
+    #[test]
+    fn jobs_run_in_parallel_and_track_in_flight_keys() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let counter = started.clone();
+        let pool = CronPool::start(2, move || Ok(counter.fetch_add(1, Ordering::SeqCst)))
+            .expect("pool");
+        assert_eq!(pool.size(), 2);
+
+        // Both tasks must be running at the same time to get past the barrier.
+        let barrier = Arc::new(Barrier::new(3));
+        let (done_tx, done_rx) = mpsc::channel();
+        for key in ["a", "b"] {
+            let barrier = barrier.clone();
+            let done_tx = done_tx.clone();
+            let task: CronTask<usize> = Box::new(move |runner| {
+                runner?;
+                barrier.wait();
+                done_tx.send(key).ok();
+                Ok(())
+            });
+            pool.submit(key, task).expect("submit");
+        }
+        assert_eq!(pool.in_flight("a"), 1);
+        assert_eq!(pool.busy().len(), 2);
+        barrier.wait();
+        let mut done = vec![
+            done_rx.recv_timeout(Duration::from_secs(5)).expect("first"),
+            done_rx.recv_timeout(Duration::from_secs(5)).expect("second"),
+        ];
+        done.sort();
+        assert_eq!(done, vec!["a", "b"]);
+
+        drop(pool);
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn failed_task_restarts_the_runner() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let counter = started.clone();
+        let pool = CronPool::start(1, move || Ok(counter.fetch_add(1, Ordering::SeqCst)))
+            .expect("pool");
+        let (seen_tx, seen_rx) = mpsc::channel();
+        for fail in [true, false] {
+            let seen_tx = seen_tx.clone();
+            let task: CronTask<usize> = Box::new(move |runner| {
+                seen_tx.send(*runner?).ok();
+                if fail {
+                    anyhow::bail!("turn failed");
+                }
+                Ok(())
+            });
+            pool.submit("job", task).expect("submit");
+        }
+        assert_eq!(seen_rx.recv_timeout(Duration::from_secs(5)).expect("first"), 0);
+        assert_eq!(seen_rx.recv_timeout(Duration::from_secs(5)).expect("second"), 1);
+        drop(pool);
+    }
+}
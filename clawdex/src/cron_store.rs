@@ -0,0 +1,476 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use serde_json::{Map, Value};
+
+use crate::config::ClawdPaths;
+use crate::util::{now_ms, read_json_lines, read_json_value};
+
+const DB_FILE: &str = "cron.sqlite";
+const SCHEMA_VERSION: i64 = 1;
+const LEGACY_JOBS_FILE: &str = "jobs.json";
+const LEGACY_RUNS_DIR: &str = "runs";
+const LEGACY_PENDING_FILE: &str = "pending.json";
+const LEGACY_SUFFIX: &str = "migrated";
+const META_LEGACY_IMPORTED: &str = "legacy_imported_at_ms";
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn db_path(paths: &ClawdPaths) -> PathBuf {
+    paths.cron_dir.join(DB_FILE)
+}
+
+/// Cron jobs, run log and the next-heartbeat queue. Jobs are stored as their JSON document (the
+/// same shape `cron.list` returns) plus a few indexed columns; every read-modify-write happens
+/// inside one `BEGIN IMMEDIATE` transaction so the MCP server, the daemon and HTTP handlers
+/// serialize on the database lock instead of overwriting each other.
+pub struct CronStore {
+    conn: Connection,
+}
+
+impl CronStore {
+    pub fn open(paths: &ClawdPaths) -> Result<Self> {
+        std::fs::create_dir_all(&paths.cron_dir)
+            .with_context(|| format!("create cron dir {}", paths.cron_dir.display()))?;
+        let conn = Connection::open(db_path(paths)).context("open cron database")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL").ok();
+        conn.pragma_update(None, "synchronous", "NORMAL").ok();
+        let mut store = CronStore { conn };
+        store.migrate()?;
+        store.import_legacy_files(&paths.cron_dir)?;
+        Ok(store)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS cron_jobs (
+                id TEXT PRIMARY KEY,
+                position INTEGER NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                next_run_at_ms INTEGER,
+                running_at_ms INTEGER,
+                updated_at_ms INTEGER NOT NULL,
+                job_json TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS cron_runs (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                job_id TEXT NOT NULL,
+                ts_ms INTEGER NOT NULL,
+                entry_json TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS cron_runs_job ON cron_runs(job_id, seq);
+
+            CREATE TABLE IF NOT EXISTS cron_pending (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                job_id TEXT NOT NULL,
+                job_json TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS cron_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            "#,
+        )?;
+        self.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    /// One-time import of `jobs.json`, `runs/<jobId>.jsonl` and `pending.json`. The files are
+    /// renamed with a `.migrated` suffix afterwards so they are kept for reference but never
+    /// read again.
+    fn import_legacy_files(&mut self, cron_dir: &Path) -> Result<()> {
+        let jobs_path = cron_dir.join(LEGACY_JOBS_FILE);
+        let runs_dir = cron_dir.join(LEGACY_RUNS_DIR);
+        let pending_path = cron_dir.join(LEGACY_PENDING_FILE);
+        if !jobs_path.exists() && !runs_dir.exists() && !pending_path.exists() {
+            return Ok(());
+        }
+        let imported = self.write(|tx| {
+            if tx.meta(META_LEGACY_IMPORTED)?.is_some() {
+                return Ok(false);
+            }
+            let mut jobs = match read_json_value(&jobs_path)? {
+                None => Vec::new(),
+                Some(Value::Array(items)) => items,
+                Some(Value::Object(mut obj)) => match obj.remove("jobs") {
+                    Some(Value::Array(items)) => items,
+                    _ => anyhow::bail!("cron jobs file missing jobs array"),
+                },
+                Some(_) => anyhow::bail!("cron jobs file is not an array or object"),
+            };
+            crate::cron::normalize_legacy_jobs(&mut jobs);
+            for job in &jobs {
+                if job.get("id").and_then(|v| v.as_str()).is_some() {
+                    tx.put_job(job)?;
+                }
+            }
+
+            if let Ok(entries) = std::fs::read_dir(&runs_dir) {
+                let mut files = entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("jsonl"))
+                    .collect::<Vec<_>>();
+                files.sort();
+                for file in files {
+                    let Some(job_id) = file.file_stem().and_then(|s| s.to_str()) else {
+                        continue;
+                    };
+                    for entry in read_json_lines(&file, None)? {
+                        tx.append_run(job_id, &entry)?;
+                    }
+                }
+            }
+
+            if let Some(Value::Array(pending)) = read_json_value(&pending_path)? {
+                for job in pending {
+                    let job_id = job.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+                    tx.conn.execute(
+                        "INSERT INTO cron_pending (job_id, job_json) VALUES (?, ?)",
+                        params![job_id, serde_json::to_string(&job)?],
+                    )?;
+                }
+            }
+            tx.set_meta(META_LEGACY_IMPORTED, &now_ms().to_string())?;
+            Ok(true)
+        })?;
+        if imported {
+            for path in [jobs_path, runs_dir, pending_path] {
+                if !path.exists() {
+                    continue;
+                }
+                let mut target = path.clone().into_os_string();
+                target.push(format!(".{LEGACY_SUFFIX}"));
+                if let Err(err) = std::fs::rename(&path, &target) {
+                    eprintln!(
+                        "[clawdex][cron] imported {} but could not rename it: {err}",
+                        path.display()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `f` in an immediate (write-locked) transaction and commits if it succeeds.
+    pub fn write<R>(&mut self, f: impl FnOnce(&CronTx<'_>) -> Result<R>) -> Result<R> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("begin cron transaction")?;
+        let result = f(&CronTx { conn: &tx })?;
+        tx.commit().context("commit cron transaction")?;
+        Ok(result)
+    }
+
+    /// Runs `f` against a consistent snapshot.
+    pub fn read<R>(&mut self, f: impl FnOnce(&CronTx<'_>) -> Result<R>) -> Result<R> {
+        let tx = self.conn.transaction().context("begin cron transaction")?;
+        f(&CronTx { conn: &tx })
+    }
+}
+
+pub struct CronTx<'a> {
+    conn: &'a Connection,
+}
+
+impl CronTx<'_> {
+    /// All jobs in creation order.
+    pub fn list_jobs(&self) -> Result<Vec<Value>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT job_json FROM cron_jobs ORDER BY position")?;
+        let mut rows = stmt.query([])?;
+        let mut jobs = Vec::new();
+        while let Some(row) = rows.next()? {
+            let raw: String = row.get(0)?;
+            jobs.push(serde_json::from_str(&raw).context("parse stored cron job")?);
+        }
+        Ok(jobs)
+    }
+
+    pub fn get_job(&self, job_id: &str) -> Result<Option<Value>> {
+        let raw: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT job_json FROM cron_jobs WHERE id = ?",
+                params![job_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        raw.map(|raw| serde_json::from_str(&raw).context("parse stored cron job"))
+            .transpose()
+    }
+
+    /// Inserts or replaces a job, keeping its original position.
+    pub fn put_job(&self, job: &Value) -> Result<()> {
+        let id = job
+            .get("id")
+            .and_then(|v| v.as_str())
+            .context("cron job is missing id")?;
+        let state = job.get("state");
+        let state_i64 = |key: &str| state.and_then(|s| s.get(key)).and_then(|v| v.as_i64());
+        self.conn.execute(
+            "INSERT INTO cron_jobs (id, position, enabled, next_run_at_ms, running_at_ms, updated_at_ms, job_json)
+             VALUES (?1, (SELECT COALESCE(MAX(position), 0) + 1 FROM cron_jobs), ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                enabled = excluded.enabled,
+                next_run_at_ms = excluded.next_run_at_ms,
+                running_at_ms = excluded.running_at_ms,
+                updated_at_ms = excluded.updated_at_ms,
+                job_json = excluded.job_json",
+            params![
+                id,
+                job.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true),
+                state_i64("nextRunAtMs"),
+                state_i64("runningAtMs"),
+                job.get("updatedAtMs").and_then(|v| v.as_i64()).unwrap_or_else(now_ms),
+                serde_json::to_string(job)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_job(&self, job_id: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM cron_jobs WHERE id = ?", params![job_id])?
+            > 0)
+    }
+
+    pub fn append_run(&self, job_id: &str, entry: &Value) -> Result<()> {
+        let ts = entry.get("ts").and_then(|v| v.as_i64()).unwrap_or_else(now_ms);
+        self.conn.execute(
+            "INSERT INTO cron_runs (job_id, ts_ms, entry_json) VALUES (?, ?, ?)",
+            params![job_id, ts, serde_json::to_string(entry)?],
+        )?;
+        Ok(())
+    }
+
+    /// Run log entries for a job, oldest first.
+    pub fn list_runs(&self, job_id: &str) -> Result<Vec<Value>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT entry_json FROM cron_runs WHERE job_id = ? ORDER BY seq")?;
+        let mut rows = stmt.query(params![job_id])?;
+        let mut entries = Vec::new();
+        while let Some(row) = rows.next()? {
+            let raw: String = row.get(0)?;
+            if let Ok(entry) = serde_json::from_str(&raw) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn push_pending(&self, job_id: &str, job: &Value) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO cron_pending (job_id, job_json) VALUES (?, ?)",
+            params![job_id, serde_json::to_string(job)?],
+        )?;
+        Ok(())
+    }
+
+    /// Drops the queued next-heartbeat runs of one job.
+    pub fn remove_pending(&self, job_id: &str) -> Result<usize> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM cron_pending WHERE job_id = ?", params![job_id])?)
+    }
+
+    /// Queued next-heartbeat jobs with their queue position, oldest first. Entries stay queued
+    /// until `remove_pending_seq` drops them; unreadable ones are dropped right away.
+    pub fn list_pending(&self) -> Result<Vec<(i64, Value)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT seq, job_json FROM cron_pending ORDER BY seq")?;
+        let mut rows = stmt.query([])?;
+        let mut jobs = Vec::new();
+        let mut unreadable = Vec::new();
+        while let Some(row) = rows.next()? {
+            let seq: i64 = row.get(0)?;
+            let raw: String = row.get(1)?;
+            match serde_json::from_str(&raw) {
+                Ok(job) => jobs.push((seq, job)),
+                Err(_) => unreadable.push(seq),
+            }
+        }
+        for seq in unreadable {
+            self.remove_pending_seq(seq)?;
+        }
+        Ok(jobs)
+    }
+
+    pub fn remove_pending_seq(&self, seq: i64) -> Result<bool> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM cron_pending WHERE seq = ?", params![seq])?
+            > 0)
+    }
+
+    /// Removes and returns every queued next-heartbeat job, oldest first.
+    pub fn drain_pending(&self) -> Result<Vec<Value>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT job_json FROM cron_pending ORDER BY seq")?;
+        let mut rows = stmt.query([])?;
+        let mut jobs = Vec::new();
+        while let Some(row) = rows.next()? {
+            let raw: String = row.get(0)?;
+            if let Ok(job) = serde_json::from_str(&raw) {
+                jobs.push(job);
+            }
+        }
+        self.conn.execute("DELETE FROM cron_pending", [])?;
+        Ok(jobs)
+    }
+
+    fn meta(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT value FROM cron_meta WHERE key = ?",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO cron_meta (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+}
+
+/// Mutable view of a job document for transactional updates.
+pub fn job_map(job: &mut Value) -> Result<&mut Map<String, Value>> {
+    job.as_object_mut().context("stored cron job is not an object")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::CronStore;
+    use crate::config::{load_config, ClawdPaths};
+
+    fn temp_paths() -> ClawdPaths {
+        let base = std::env::temp_dir().join(format!("clawdex-cron-store-{}", Uuid::new_v4()));
+        let workspace_dir = base.join("workspace");
+        fs::create_dir_all(&workspace_dir).expect("create workspace");
+        let (_cfg, paths) =
+            load_config(Some(base.join("state")), Some(workspace_dir)).expect("create temp paths");
+        paths
+    }
+
+    #[test]
+    fn open_creates_the_schema_once() {
+        let paths = temp_paths();
+        let store = CronStore::open(&paths).expect("open");
+        let version: i64 = store
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .expect("user_version");
+        assert_eq!(version, super::SCHEMA_VERSION);
+        let sql = "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'cron_%' \
+                   ORDER BY name";
+        let tables = store
+            .conn
+            .prepare(sql)
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .expect("tables");
+        assert_eq!(
+            tables,
+            vec!["cron_jobs", "cron_meta", "cron_pending", "cron_runs"]
+        );
+
+        // An up-to-date database is not migrated again.
+        store
+            .conn
+            .execute_batch("DROP TABLE cron_meta")
+            .expect("drop table");
+        drop(store);
+        let mut store = CronStore::open(&paths).expect("reopen");
+        assert!(store
+            .read(|tx| tx.meta(super::META_LEGACY_IMPORTED))
+            .is_err());
+    }
+
+    #[test]
+    fn open_imports_the_legacy_json_files() {
+        let paths = temp_paths();
+        let cron_dir = &paths.cron_dir;
+        fs::create_dir_all(cron_dir.join("runs")).expect("create runs dir");
+        fs::write(
+            cron_dir.join("jobs.json"),
+            json!({
+                "jobs": [{
+                    "id": "job-1",
+                    "name": "nightly",
+                    "session_key": " agent:main:main ",
+                    "schedule": { "kind": "every", "everyMs": 60_000 },
+                    "payload": { "kind": "systemEvent", "text": "ping" }
+                }]
+            })
+            .to_string(),
+        )
+        .expect("write jobs");
+        fs::write(
+            cron_dir.join("runs").join("job-1.jsonl"),
+            "{\"ts\":1,\"status\":\"ok\"}\n{\"ts\":2,\"status\":\"error\"}\n",
+        )
+        .expect("write runs");
+        fs::write(
+            cron_dir.join("pending.json"),
+            json!([{ "id": "job-1", "name": "nightly" }]).to_string(),
+        )
+        .expect("write pending");
+
+        let mut store = CronStore::open(&paths).expect("open");
+        let jobs = store.read(|tx| tx.list_jobs()).expect("jobs");
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0]["sessionKey"], json!("agent:main:main"));
+        assert!(jobs[0].get("session_key").is_none());
+        let runs = store.read(|tx| tx.list_runs("job-1")).expect("runs");
+        let statuses = runs
+            .iter()
+            .map(|run| run["status"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(statuses, vec![json!("ok"), json!("error")]);
+        let pending = store.write(|tx| tx.drain_pending()).expect("pending");
+        assert_eq!(pending.len(), 1);
+        for name in ["jobs.json", "runs", "pending.json"] {
+            assert!(!cron_dir.join(name).exists());
+            assert!(cron_dir.join(format!("{name}.migrated")).exists());
+        }
+
+        // Reopening neither re-imports nor duplicates anything.
+        drop(store);
+        let mut store = CronStore::open(&paths).expect("reopen");
+        assert_eq!(
+            store.read(|tx| tx.list_runs("job-1")).expect("runs").len(),
+            2
+        );
+    }
+}
@@ -1,12 +1,10 @@
 use std::collections::HashMap;
-use std::fs::{self, OpenOptions};
-use std::io::Write;
 use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use codex_app_server_protocol::AskForApproval;
+use codex_app_server_protocol::{AskForApproval, TurnStatus};
 use reqwest::blocking::Client;
 use serde_json::{json, Value};
 
@@ -14,21 +12,31 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc};
 
 use crate::config::{
-    resolve_context_max_input_chars, resolve_heartbeat_enabled, resolve_heartbeat_interval_ms,
-    ClawdConfig, ClawdPaths,
+    resolve_context_max_input_chars, resolve_cron_workers, resolve_heartbeat_enabled,
+    resolve_heartbeat_interval_ms, ClawdConfig, ClawdPaths,
 };
 use crate::cron::{
-    build_cron_job, collect_due_jobs, drain_pending_jobs, is_job_due_value, job_prompt,
-    load_job_value, mark_job_running, normalize_http_webhook_url, record_run, CronJob,
+    ack_pending_job, build_cron_job, cancel_requested, collect_due_jobs_in_flight,
+    is_job_due_value, job_prompt, load_job_value, mark_job_running, normalize_http_webhook_url,
+    peek_pending_jobs, record_run, requeue_pending_job, CronJob,
 };
+use crate::cron_lock;
+use crate::cron_pool::CronPool;
 use crate::gateway;
 use crate::heartbeat;
 use crate::memory;
+use crate::memory_watch;
 use crate::runner::{CodexRunner, CodexRunnerConfig};
+use crate::session_digest;
 use crate::sessions;
 use crate::task_db::TaskStore;
 use crate::util::{apply_text_budget, now_ms};
 
+/// How often a running cron turn checks the store for `cron.cancel`.
+const CANCEL_POLL_INTERVAL_MS: i64 = 1_000;
+/// How often the daemon looks for cron locks and runs left behind by a crashed daemon.
+const STALE_LOCK_SWEEP_INTERVAL_MS: i64 = 60_000;
+
 #[derive(Debug, Clone)]
 enum DeliveryMode {
     None,
@@ -100,6 +108,27 @@ pub enum DaemonCommand {
     },
 }
 
+/// Everything a cron worker needs to run a job, shared by all workers.
+struct CronExecContext {
+    cfg: ClawdConfig,
+    paths: ClawdPaths,
+    approval_policy: AskForApproval,
+    workspace_policy: crate::config::WorkspacePolicy,
+    workspace: PathBuf,
+    context_max_input_chars: Option<usize>,
+}
+
+/// Runs one cron job on the runner it is given; shared by the main lane and the cron workers.
+type CronRunFn<R> = Arc<dyn Fn(&mut R, &CronJob) -> Result<()> + Send + Sync>;
+
+/// A cron run waiting for a worker; `respond_to` is set for runs requested over the daemon API and
+/// `pending_seq` for runs still held in the `cron_pending` queue.
+struct CronDispatch {
+    job: CronJob,
+    respond_to: Option<mpsc::Sender<DaemonRunResult>>,
+    pending_seq: Option<i64>,
+}
+
 pub fn run_daemon_loop(
     cfg: ClawdConfig,
     paths: ClawdPaths,
@@ -120,13 +149,41 @@ pub fn run_daemon_loop(
         approval_policy,
         config_overrides: resolve_codex_overrides(&cfg),
     };
-    let mut runner = CodexRunner::start(runner_cfg)?;
+    // The main runner is the lane for inbound messages, heartbeats, digests and main-session cron
+    // jobs, which all share its main thread; isolated cron jobs run on their own pool of runners
+    // so a long job never holds up a chat reply.
+    let worker_cfg = runner_cfg.clone();
+    let mut runner = CodexRunner::start(runner_cfg.clone())?;
+    let cron_pool = CronPool::start(resolve_cron_workers(&cfg), move || {
+        CodexRunner::start(worker_cfg.clone())
+    })?;
+    let cron_ctx = Arc::new(CronExecContext {
+        cfg: cfg.clone(),
+        paths: paths.clone(),
+        approval_policy,
+        workspace_policy: workspace_policy.clone(),
+        workspace: workspace.clone(),
+        context_max_input_chars: resolve_context_max_input_chars(&cfg),
+    });
+    let run_cron: CronRunFn<CodexRunner> = Arc::new(move |runner, job| {
+        execute_job(
+            &cron_ctx.cfg,
+            runner,
+            &cron_ctx.paths,
+            job,
+            cron_ctx.approval_policy,
+            &cron_ctx.workspace_policy,
+            &cron_ctx.workspace,
+            cron_ctx.context_max_input_chars,
+        )
+    });
+    let mut held_back: Vec<CronDispatch> = Vec::new();
+    let mut next_lock_sweep = 0i64;
     let agent_routing = resolve_agent_backend_routing(&cfg);
 
     let heartbeat_enabled = resolve_heartbeat_enabled(&cfg);
     let interval = resolve_heartbeat_interval_ms(&cfg);
     let mut next_heartbeat = now_ms() + interval as i64;
-    let context_max_input_chars = resolve_context_max_input_chars(&cfg);
 
     let memory_sync_minutes = cfg
         .memory
@@ -136,6 +193,15 @@ pub fn run_daemon_loop(
         .unwrap_or(0);
     let memory_sync_interval_ms = memory_sync_minutes.saturating_mul(60_000);
     let mut next_memory_sync = now_ms() + memory_sync_interval_ms as i64;
+    let session_digests = session_digest::resolve_settings(&cfg);
+    let mut next_session_digest = now_ms() + session_digests.interval_ms;
+    let _memory_watcher = match memory_watch::spawn_memory_watcher(&cfg, &paths, shutdown.clone()) {
+        Ok(handle) => handle,
+        Err(err) => {
+            eprintln!("[clawdex][memory] watcher unavailable, relying on periodic sync: {err}");
+            None
+        }
+    };
 
     loop {
         if shutdown.load(Ordering::SeqCst) {
@@ -143,17 +209,69 @@ pub fn run_daemon_loop(
         }
         let now = now_ms();
 
+        if now >= next_lock_sweep {
+            match cron_lock::recover_stale_runs(&paths, &cron_pool.busy()) {
+                Ok(jobs) if !jobs.is_empty() => {
+                    eprintln!("[clawdex][cron] recovered stale runs: {}", jobs.join(", "));
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("[clawdex][cron] stale lock sweep failed: {err}"),
+            }
+            next_lock_sweep = now + STALE_LOCK_SWEEP_INTERVAL_MS;
+        }
+
+        // API runs held back by their job's concurrency limit go first.
+        let mut queue = std::mem::take(&mut held_back);
+
         if let Some(receiver) = commands.as_ref() {
-            drain_daemon_commands(
-                &cfg,
-                &mut runner,
-                &paths,
-                approval_policy,
-                &workspace_policy,
-                &workspace,
-                context_max_input_chars,
-                receiver,
-            );
+            drain_daemon_commands(&paths, receiver, &mut queue);
+        }
+
+        // Pending jobs (wakeMode = next-heartbeat or manual cron.run) stay queued until they run.
+        let pending_jobs = peek_pending_jobs(&paths)?;
+        queue.extend(pending_jobs.into_iter().map(|run| CronDispatch {
+            job: run.job,
+            respond_to: None,
+            pending_seq: Some(run.seq),
+        }));
+
+        // Due jobs, counting the runs workers already have against each job's limit.
+        let (due_jobs, _entries) =
+            collect_due_jobs_in_flight(&paths, now, &cron_pool.in_flight_counts())?;
+        queue.extend(due_jobs.into_iter().map(|job| CronDispatch {
+            job,
+            respond_to: None,
+            pending_seq: None,
+        }));
+
+        let mut accept = |seq: i64| {
+            if let Err(err) = ack_pending_job(&paths, seq) {
+                eprintln!("[clawdex][cron] could not dequeue pending run {seq}: {err}");
+            }
+        };
+        for work in queue {
+            let Some(work) =
+                dispatch_cron_job(&mut runner, &cron_pool, &run_cron, work, &mut accept)
+            else {
+                continue;
+            };
+            // Only API runs, whose caller is waiting, are held in memory; the rest stay in (or go
+            // back to) `cron_pending` so a restart does not lose them.
+            if work.respond_to.is_some() {
+                held_back.push(work);
+            } else if work.pending_seq.is_none() {
+                if let Err(err) = requeue_pending_job(&paths, &work.job) {
+                    eprintln!(
+                        "[clawdex][cron] could not requeue job {}: {err}",
+                        work.job.id
+                    );
+                }
+            }
+        }
+        if !runner.is_running() {
+            // A main-session job's turn ignored its interrupt and the app-server was stopped.
+            eprintln!("[clawdex][cron] restarting the main codex app-server");
+            runner = CodexRunner::start(runner_cfg.clone())?;
         }
 
         // Drain inbound messages from the gateway and run agent turns.
@@ -162,36 +280,6 @@ pub fn run_daemon_loop(
             handle_incoming_message(&mut runner, &agent_routing, &paths, entry)?;
         }
 
-        // Drain pending jobs (wakeMode = next-heartbeat or manual cron.run)
-        let pending_jobs = drain_pending_jobs(&paths)?;
-        for job in pending_jobs {
-            execute_job(
-                &cfg,
-                &mut runner,
-                &paths,
-                &job,
-                approval_policy,
-                &workspace_policy,
-                &workspace,
-                context_max_input_chars,
-            )?;
-        }
-
-        // Execute due jobs
-        let (due_jobs, _entries) = collect_due_jobs(&paths, now, "due", None)?;
-        for job in due_jobs {
-            execute_job(
-                &cfg,
-                &mut runner,
-                &paths,
-                &job,
-                approval_policy,
-                &workspace_policy,
-                &workspace,
-                context_max_input_chars,
-            )?;
-        }
-
         if heartbeat_enabled && now >= next_heartbeat {
             if let Err(err) = execute_heartbeat(&mut runner, &cfg, &paths) {
                 eprintln!("[clawdex][heartbeat] tick failed: {err}");
@@ -206,20 +294,22 @@ pub fn run_daemon_loop(
             next_memory_sync = now + memory_sync_interval_ms as i64;
         }
 
+        if session_digests.enabled && now >= next_session_digest {
+            if let Err(err) = execute_session_digests(&mut runner, &cfg, &paths, &workspace) {
+                eprintln!("[clawdex][memory] session digests failed: {err}");
+            }
+            next_session_digest = now + session_digests.interval_ms;
+        }
+
         thread::sleep(Duration::from_millis(500));
     }
     Ok(())
 }
 
 fn drain_daemon_commands(
-    cfg: &ClawdConfig,
-    runner: &mut CodexRunner,
     paths: &ClawdPaths,
-    base_approval_policy: AskForApproval,
-    base_workspace_policy: &crate::config::WorkspacePolicy,
-    base_workspace: &PathBuf,
-    context_max_input_chars: Option<usize>,
     receiver: &mpsc::Receiver<DaemonCommand>,
+    queue: &mut Vec<CronDispatch>,
 ) {
     while let Ok(cmd) = receiver.try_recv() {
         match cmd {
@@ -227,81 +317,117 @@ fn drain_daemon_commands(
                 job_id,
                 mode,
                 respond_to,
-            } => {
-                let result = run_cron_job_now(
-                    cfg,
-                    runner,
-                    paths,
-                    &job_id,
-                    &mode,
-                    base_approval_policy,
-                    base_workspace_policy,
-                    base_workspace,
-                    context_max_input_chars,
-                );
-                let _ = respond_to.send(result);
-            }
+            } => match resolve_cron_run(paths, &job_id, &mode) {
+                Ok(job) => queue.push(CronDispatch {
+                    job,
+                    respond_to: Some(respond_to),
+                    pending_seq: None,
+                }),
+                Err(result) => {
+                    let _ = respond_to.send(result);
+                }
+            },
         }
     }
 }
 
-fn run_cron_job_now(
-    cfg: &ClawdConfig,
-    runner: &mut CodexRunner,
+/// Loads a job for an API-requested run; the error is the response when it should not run.
+fn resolve_cron_run(
     paths: &ClawdPaths,
     job_id: &str,
     mode: &str,
-    base_approval_policy: AskForApproval,
-    base_workspace_policy: &crate::config::WorkspacePolicy,
-    base_workspace: &PathBuf,
-    context_max_input_chars: Option<usize>,
-) -> DaemonRunResult {
+) -> std::result::Result<CronJob, DaemonRunResult> {
     let now = now_ms();
     let job_value = match load_job_value(paths, job_id) {
         Ok(Some(job)) => job,
         Ok(None) => {
-            return DaemonRunResult {
+            return Err(DaemonRunResult {
                 ok: false,
                 ran: false,
                 reason: Some("not-found".to_string()),
-            }
+            })
         }
         Err(err) => {
-            return DaemonRunResult {
+            return Err(DaemonRunResult {
                 ok: false,
                 ran: false,
                 reason: Some(err.to_string()),
-            }
+            })
         }
     };
 
     let forced = mode.eq_ignore_ascii_case("force");
     if !is_job_due_value(&job_value, now, forced) {
-        return DaemonRunResult {
+        return Err(DaemonRunResult {
             ok: true,
             ran: false,
             reason: Some("not-due".to_string()),
-        };
+        });
     }
 
-    let Some(job) = build_cron_job(&job_value) else {
-        return DaemonRunResult {
-            ok: false,
-            ran: false,
-            reason: Some("invalid-job".to_string()),
-        };
-    };
+    build_cron_job(&job_value).ok_or_else(|| DaemonRunResult {
+        ok: false,
+        ran: false,
+        reason: Some("invalid-job".to_string()),
+    })
+}
 
-    match execute_job(
-        cfg,
-        runner,
-        paths,
-        &job,
-        base_approval_policy,
-        base_workspace_policy,
-        base_workspace,
-        context_max_input_chars,
-    ) {
+/// Runs main-session jobs inline on the daemon's main runner, so they land on the thread that
+/// heartbeats and `agent:main:main` messages use, and hands isolated jobs to the cron workers.
+/// An isolated run is given back when its job is at its concurrency limit. `accept` is called
+/// with the `cron_pending` entry of a queued run once it is handed to a runner.
+fn dispatch_cron_job<R: 'static>(
+    main: &mut R,
+    pool: &CronPool<R>,
+    run: &CronRunFn<R>,
+    work: CronDispatch,
+    accept: &mut dyn FnMut(i64),
+) -> Option<CronDispatch> {
+    if work.job.session_target != "isolated" {
+        if let Some(seq) = work.pending_seq {
+            accept(seq);
+        }
+        let result = run(main, &work.job);
+        if let Err(err) = &result {
+            eprintln!("[clawdex][cron] job {} failed: {err}", work.job.id);
+        }
+        respond_cron_run(work.respond_to, &result);
+        return None;
+    }
+    if pool.in_flight(&work.job.id) >= work.job.concurrency_limit() {
+        return Some(work);
+    }
+    let job_id = work.job.id.clone();
+    let run = run.clone();
+    let CronDispatch {
+        job,
+        respond_to,
+        pending_seq,
+    } = work;
+    let submitted = pool.submit(
+        &job_id,
+        Box::new(move |runner| {
+            let result = runner.and_then(|runner| run(runner, &job));
+            respond_cron_run(respond_to, &result);
+            result
+        }),
+    );
+    match submitted {
+        Ok(()) => {
+            if let Some(seq) = pending_seq {
+                accept(seq);
+            }
+        }
+        Err(err) => eprintln!("[clawdex][cron] could not queue job {job_id}: {err}"),
+    }
+    None
+}
+
+fn respond_cron_run(respond_to: Option<mpsc::Sender<DaemonRunResult>>, result: &Result<()>) {
+    let Some(respond_to) = respond_to else {
+        return;
+    };
+    let _ = respond_to.send(match result {
         Ok(()) => DaemonRunResult {
             ok: true,
             ran: true,
@@ -312,7 +438,7 @@ fn run_cron_job_now(
             ran: false,
             reason: Some(err.to_string()),
         },
-    }
+    });
 }
 
 fn execute_job(
@@ -400,7 +526,8 @@ fn execute_job(
         );
     }
 
-    let _lock = match acquire_job_lock(paths, &job.id)? {
+    let stale_after = cron_lock::stale_after_ms(job.effective_timeout_ms());
+    let mut lock = match cron_lock::acquire(paths, &job.id, job.concurrency_limit(), stale_after)? {
         Some(lock) => lock,
         None => {
             record_run(
@@ -452,29 +579,43 @@ fn execute_job(
             "sessionTarget": job.session_target,
         }),
     );
-    let outcome = if job.session_target == "isolated" {
-        runner.run_isolated_with_policy(
-            &job.id,
-            &prompt_budget.text,
-            approval_policy,
-            &workspace_policy,
-            workspace.clone(),
-        )
-    } else {
-        runner.run_main_with_policy(
+    let timeout_ms = job.effective_timeout_ms();
+    let mut stop_reason: Option<&str> = None;
+    let mut next_cancel_check = 0i64;
+    // Polled while the turn runs, also when the app-server is silent.
+    let mut should_interrupt = || {
+        let now = now_ms();
+        if timeout_ms.is_some_and(|limit| now.saturating_sub(started_at) >= limit as i64) {
+            stop_reason = Some("timeout");
+            return true;
+        }
+        if now >= next_cancel_check {
+            next_cancel_check = now + CANCEL_POLL_INTERVAL_MS;
+            if cancel_requested(paths, &job.id).unwrap_or(false) {
+                stop_reason = Some("cancelled");
+                return true;
+            }
+        }
+        false
+    };
+    let isolated_key = (job.session_target == "isolated").then_some(job.id.as_str());
+    let outcome = lock.heartbeat_while(|| {
+        runner.run_interruptible(
+            isolated_key,
             &prompt_budget.text,
             approval_policy,
             &workspace_policy,
             workspace.clone(),
+            &mut should_interrupt,
         )
-    };
+    });
     let outcome = match outcome {
         Ok(outcome) => outcome,
         Err(err) => {
             let ended_at = now_ms();
             let duration_ms = ended_at.saturating_sub(started_at);
             let error_text = err.to_string();
-            let _ = record_run(
+            if let Ok(entry) = record_run(
                 paths,
                 &job.id,
                 "failed",
@@ -484,7 +625,9 @@ fn execute_job(
                     "runAtMs": started_at,
                     "durationMs": duration_ms,
                 })),
-            );
+            ) {
+                notify_job_failure(cfg, paths, job, &entry);
+            }
             record_cron_task_event(&mut task_run, "turn_failed", json!({ "error": error_text }));
             record_cron_task_event(
                 &mut task_run,
@@ -518,6 +661,55 @@ fn execute_job(
     let ended_at = now_ms();
     let duration_ms = ended_at.saturating_sub(started_at);
     let summary = outcome.message.trim().to_string();
+
+    if outcome.status == TurnStatus::Interrupted {
+        // Timed out or cancelled: keep whatever the turn produced, but deliver nothing.
+        let (status, reason) = match stop_reason {
+            Some("timeout") => (
+                "timeout",
+                format!("timed out after {}ms", timeout_ms.unwrap_or_default()),
+            ),
+            _ => ("cancelled", "cancelled".to_string()),
+        };
+        let entry = record_run(
+            paths,
+            &job.id,
+            status,
+            &reason,
+            Some(json!({
+                "summary": summary,
+                "runAtMs": started_at,
+                "durationMs": duration_ms,
+            })),
+        )?;
+        if status == "timeout" {
+            notify_job_failure(cfg, paths, job, &entry);
+        }
+        record_cron_task_event(
+            &mut task_run,
+            "turn_interrupted",
+            json!({ "jobId": job.id, "status": status, "reason": reason }),
+        );
+        finish_cron_task_run(
+            &mut task_run,
+            if status == "timeout" { "failed" } else { "cancelled" },
+            "cron_job_finished",
+            json!({
+                "jobId": job.id,
+                "status": status,
+                "reason": reason,
+                "runAtMs": started_at,
+                "endedAtMs": ended_at,
+                "durationMs": duration_ms,
+            }),
+        );
+        if !runner.is_running() {
+            // The app-server ignored the interrupt and was stopped; have the worker replace it.
+            anyhow::bail!("codex app-server stopped after job {} was interrupted", job.id);
+        }
+        return Ok(());
+    }
+
     let mut status = "completed";
     let mut reason = "executed";
     let mut error: Option<String> = None;
@@ -525,29 +717,8 @@ fn execute_job(
     let plan = resolve_delivery_plan(job);
     match plan.mode {
         DeliveryMode::None => {}
-        DeliveryMode::Announce => {
-            let mut channel = plan.channel.clone();
-            let mut to = plan.to.clone();
-            let mut account_id: Option<String> = None;
-            let mut session_key = job.session_key.clone();
-
-            if channel.as_deref() == Some("last") || to.is_none() {
-                if let Some(resolved) = resolve_delivery_target(
-                    paths,
-                    channel.clone(),
-                    to.clone(),
-                    job.session_key.clone(),
-                ) {
-                    channel = Some(resolved.channel);
-                    to = Some(resolved.to);
-                    account_id = resolved.account_id;
-                    if session_key.is_none() {
-                        session_key = resolved.session_key;
-                    }
-                }
-            }
-
-            if channel.is_none() || to.is_none() {
+        DeliveryMode::Announce => match resolve_announce_target(paths, job, &plan) {
+            None => {
                 if plan.best_effort {
                     status = "skipped";
                     reason = "no delivery target (best effort)";
@@ -556,12 +727,13 @@ fn execute_job(
                     reason = "no delivery target";
                     error = Some("no delivery target".to_string());
                 }
-            } else {
+            }
+            Some(target) => {
                 let args = json!({
-                    "sessionKey": session_key,
-                    "channel": channel,
-                    "to": to,
-                    "accountId": account_id,
+                    "sessionKey": target.session_key,
+                    "channel": target.channel,
+                    "to": target.to,
+                    "accountId": target.account_id,
                     "text": summary,
                     "bestEffort": plan.best_effort,
                     "idempotencyKey": format!("cron:{}:{}", job.id, started_at),
@@ -578,7 +750,7 @@ fn execute_job(
                     }
                 }
             }
-        }
+        },
         DeliveryMode::Webhook => {
             if !summary.is_empty() {
                 if let Some(target_url) = plan.to.as_ref() {
@@ -806,6 +978,36 @@ fn execute_heartbeat(
     Ok(())
 }
 
+/// Summarizes idle sessions on isolated, read-only threads so digest turns never touch the
+/// workspace or the main conversation.
+fn execute_session_digests(
+    runner: &mut CodexRunner,
+    cfg: &ClawdConfig,
+    paths: &ClawdPaths,
+    workspace: &std::path::Path,
+) -> Result<()> {
+    let mut policy = paths.workspace_policy.clone();
+    policy.read_only = true;
+    let mut summarize = |key: &str, prompt: &str| -> Result<String> {
+        let outcome = runner.run_isolated_with_policy(
+            key,
+            prompt,
+            AskForApproval::Never,
+            &policy,
+            workspace.to_path_buf(),
+        )?;
+        Ok(outcome.message)
+    };
+    let run = session_digest::run_session_digests(cfg, paths, now_ms(), &mut summarize)?;
+    if run.summarized > 0 || run.failed > 0 {
+        eprintln!(
+            "[clawdex][memory] session digests: {} summarized, {} failed",
+            run.summarized, run.failed
+        );
+    }
+    Ok(())
+}
+
 fn resolve_delivery_plan(job: &CronJob) -> DeliveryPlan {
     let payload = job.payload.as_object();
     let payload_channel = payload
@@ -917,6 +1119,101 @@ fn post_cron_webhook(cfg: &ClawdConfig, target_url: &str, payload: &Value) -> Re
     Ok(())
 }
 
+/// Channel route for an announce delivery; `channel: "last"` or a missing `to` fall back to the
+/// session's last route.
+fn resolve_announce_target(
+    paths: &ClawdPaths,
+    job: &CronJob,
+    plan: &DeliveryPlan,
+) -> Option<ResolvedTarget> {
+    let mut channel = plan.channel.clone();
+    let mut to = plan.to.clone();
+    let mut account_id: Option<String> = None;
+    let mut session_key = job.session_key.clone();
+
+    if channel.as_deref() == Some("last") || to.is_none() {
+        if let Some(resolved) =
+            resolve_delivery_target(paths, channel.clone(), to.clone(), job.session_key.clone())
+        {
+            channel = Some(resolved.channel);
+            to = Some(resolved.to);
+            account_id = resolved.account_id;
+            if session_key.is_none() {
+                session_key = resolved.session_key;
+            }
+        }
+    }
+    Some(ResolvedTarget {
+        channel: channel?,
+        to: to?,
+        account_id,
+        session_key,
+    })
+}
+
+/// Tells the job's delivery target about a failed run once no retry is left; runs that will be
+/// retried (`retryAtMs` in the run log entry) stay quiet.
+fn notify_job_failure(cfg: &ClawdConfig, paths: &ClawdPaths, job: &CronJob, entry: &Value) {
+    if entry.get("retryAtMs").is_some() {
+        return;
+    }
+    let plan = resolve_delivery_plan(job);
+    let status = entry.get("status").and_then(|v| v.as_str()).unwrap_or("error");
+    let error = entry
+        .get("error")
+        .or_else(|| entry.get("summary"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(status);
+    let attempts = entry.get("attempt").and_then(|v| v.as_u64()).unwrap_or(1);
+    let run_at = entry.get("runAtMs").and_then(|v| v.as_i64()).unwrap_or_default();
+    let result = match plan.mode {
+        DeliveryMode::None => return,
+        DeliveryMode::Announce => {
+            let Some(target) = resolve_announce_target(paths, job, &plan) else {
+                eprintln!("[clawdex][cron] no target for failure notice of job {}", job.id);
+                return;
+            };
+            let name = job.name.as_deref().unwrap_or(&job.id);
+            let text = if attempts > 1 {
+                format!("Cron job \"{name}\" failed after {attempts} attempts: {error}")
+            } else {
+                format!("Cron job \"{name}\" failed: {error}")
+            };
+            gateway::send_message(
+                paths,
+                &json!({
+                    "sessionKey": target.session_key,
+                    "channel": target.channel,
+                    "to": target.to,
+                    "accountId": target.account_id,
+                    "text": text,
+                    "bestEffort": plan.best_effort,
+                    "idempotencyKey": format!("cron:{}:{}:failed", job.id, run_at),
+                }),
+            )
+            .map(|_| ())
+        }
+        DeliveryMode::Webhook => {
+            let Some(target_url) = plan.to.as_ref() else {
+                return;
+            };
+            let payload = json!({
+                "jobId": job.id,
+                "action": "finished",
+                "status": status,
+                "error": error,
+                "attempt": attempts,
+                "runAtMs": run_at,
+                "durationMs": entry.get("durationMs"),
+            });
+            post_cron_webhook(cfg, target_url, &payload)
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("[clawdex][cron] failure notice for job {} failed: {err}", job.id);
+    }
+}
+
 struct ResolvedTarget {
     channel: String,
     to: String,
@@ -1096,7 +1393,10 @@ fn resolve_inbound_session_key(entry: &serde_json::Value) -> String {
     "agent:main:main".to_string()
 }
 
-fn resolve_codex_path(cfg: &ClawdConfig, override_path: Option<PathBuf>) -> Result<PathBuf> {
+pub(crate) fn resolve_codex_path(
+    cfg: &ClawdConfig,
+    override_path: Option<PathBuf>,
+) -> Result<PathBuf> {
     if let Some(path) = override_path {
         return Ok(path);
     }
@@ -1456,35 +1756,56 @@ fn apply_workspace_overrides(
     Ok((policy, workspace))
 }
 
-struct JobLock {
-    path: PathBuf,
-}
-
-impl Drop for JobLock {
-    fn drop(&mut self) {
-        let _ = fs::remove_file(&self.path);
-    }
-}
-
-fn acquire_job_lock(paths: &ClawdPaths, job_id: &str) -> Result<Option<JobLock>> {
-    let locks_dir = paths.cron_dir.join("locks");
-    fs::create_dir_all(&locks_dir)?;
-    let path = locks_dir.join(format!("{job_id}.lock"));
-    match OpenOptions::new().write(true).create_new(true).open(&path) {
-        Ok(mut file) => {
-            let _ = writeln!(file, "{}", now_ms());
-            Ok(Some(JobLock { path }))
-        }
-        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Ok(None),
-        Err(err) => Err(err.into()),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn main_session_jobs_share_the_main_runner_with_heartbeats() {
+        let pool = CronPool::start(1, || Ok("worker-thread".to_string())).expect("pool");
+        // Heartbeats and `agent:main:main` messages run on this runner's main thread.
+        let mut main = "main-thread".to_string();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let record = seen.clone();
+        let run: CronRunFn<String> = Arc::new(move |runner, job| {
+            record
+                .lock()
+                .expect("seen")
+                .push((job.id.clone(), runner.clone()));
+            Ok(())
+        });
+        let job = |id: &str, target: &str| {
+            build_cron_job(&json!({
+                "id": id,
+                "name": id,
+                "sessionTarget": target,
+                "wakeMode": "now",
+                "payload": { "kind": "systemEvent", "text": "ping" }
+            }))
+            .expect("job")
+        };
+        for (id, target) in [("digest", "main"), ("crawl", "isolated")] {
+            let work = CronDispatch {
+                job: job(id, target),
+                respond_to: None,
+                pending_seq: None,
+            };
+            assert!(dispatch_cron_job(&mut main, &pool, &run, work, &mut |_| {}).is_none());
+        }
+        drop(pool);
+
+        let mut seen = seen.lock().expect("seen").clone();
+        seen.sort();
+        assert_eq!(
+            seen,
+            vec![
+                ("crawl".to_string(), "worker-thread".to_string()),
+                ("digest".to_string(), "main-thread".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn resolve_inbound_session_key_namespaces_with_agent_id() {
         let key = resolve_inbound_session_key(&json!({
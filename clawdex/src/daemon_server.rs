@@ -20,6 +20,7 @@ use crate::config::{
 use crate::cron;
 use crate::daemon::{run_daemon_loop, DaemonCommand, DaemonRunResult};
 use crate::gateway;
+use crate::memory_watch;
 use crate::permissions::{self, PermissionsUpdate};
 use crate::plugins;
 use crate::task_db::TaskStore;
@@ -736,6 +737,19 @@ fn handle_request(
             let job = cron::add_job(paths, &payload)?;
             Ok(json_response(json!({ "job": job }))?)
         }
+        _ if method == Method::Post
+            && url.starts_with("/v1/cron/jobs/")
+            && url.ends_with("/cancel") =>
+        {
+            let id = url
+                .trim_start_matches("/v1/cron/jobs/")
+                .trim_end_matches("/cancel");
+            if id.is_empty() {
+                return Ok(Response::from_data(Vec::new()).with_status_code(StatusCode(404)));
+            }
+            let value = cron::cancel_job(paths, &json!({ "id": id }))?;
+            Ok(json_response(value)?)
+        }
         _ if method == Method::Post
             && url.starts_with("/v1/cron/jobs/")
             && !url.ends_with("/run") =>
@@ -947,6 +961,7 @@ fn admin_overview(cfg: &ClawdConfig, paths: &ClawdPaths, broker: &ApprovalBroker
             "channels": channel_count,
             "pendingApprovals": approvals.len(),
             "pendingUserInputs": inputs.len(),
+            "memoryIndexQueue": memory_watch::queue_depth(),
         },
         "tasks": tasks,
         "runs": runs,
@@ -957,6 +972,7 @@ fn admin_overview(cfg: &ClawdConfig, paths: &ClawdPaths, broker: &ApprovalBroker
         "permissions": permissions_value,
         "cron": cron_value,
         "memory": config_value.get("memory").cloned().unwrap_or_else(|| json!({})),
+        "memoryWatcher": memory_watch::status(),
     }))
 }
 
@@ -17,6 +17,14 @@ use tungstenite::{accept, Message};
 use uuid::Uuid;
 
 use crate::config::{ClawdPaths, GatewayConfig};
+use crate::gateway_channels::ChannelRegistry;
+use crate::gateway_events::{
+    self, EventJournal, EventSubscriptions, EVENT_KINDS, KIND_INBOX, KIND_PRESENCE, KIND_RECEIPT,
+};
+use crate::gateway_outbox::{parse_outbox_query, OutboxStore, RetryPolicy};
+use crate::gateway_plugins::{self, PluginMethodError, PluginMethodSpec};
+use crate::gateway_receivers::{parse_slack_event, verify_slack_signature, SlackEvent};
+use crate::plugins::{apply_plugin_permissions_to_policy, plugin_permissions_for_root};
 use crate::task_db::TaskStore;
 use crate::text_sanitize::strip_reasoning_tags_from_text;
 use crate::util::{append_json_line, now_ms, read_json_lines, read_json_value, write_json_value};
@@ -30,12 +38,16 @@ const ATTACHMENTS_INDEX_FILE: &str = "attachments.jsonl";
 const ROUTES_FILE: &str = "routes.json";
 const IDEMPOTENCY_FILE: &str = "idempotency.json";
 const INBOX_OFFSET_FILE: &str = "inbox_offset.json";
+const OUTBOX_OFFSET_FILE: &str = "outbox_offset.json";
 const AUTH_TOKENS_FILE: &str = "auth_tokens.json";
 const DEVICE_AUTH_FILE: &str = "device_auth.json";
 const DEFAULT_ATTACHMENTS_MAX_BYTES: usize = 5_000_000;
 const WS_MAX_PAYLOAD_BYTES: usize = 8 * 1024 * 1024;
 const WS_MAX_BUFFERED_BYTES: usize = 16 * 1024 * 1024;
+const WS_EVENT_POLL: Duration = Duration::from_millis(250);
 const DEVICE_CODE_TTL_MS: i64 = 10 * 60 * 1000;
+const DEFAULT_OUTBOX_POLL_MS: u64 = 1000;
+const OUTBOX_CLAIM_BATCH: usize = 50;
 const DEFAULT_CHANNEL_ORDER: &[&str] = &[
     "telegram",
     "whatsapp",
@@ -444,6 +456,16 @@ where
     f(&mut guard)
 }
 
+/// Journals the current presence entry for `key` for event subscribers.
+fn publish_presence(paths: &ClawdPaths, key: &str) {
+    let entry =
+        with_presence_state(|state| state.entries.get(key).map(|entry| entry.to_value(now_ms())));
+    if let Some(mut entry) = entry {
+        entry["key"] = Value::String(key.to_string());
+        gateway_events::publish(paths, KIND_PRESENCE, &entry);
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SendMode {
     Direct,
@@ -454,14 +476,22 @@ enum SendMode {
 enum GatewayMethodError {
     InvalidRequest(String),
     NotImplemented(String),
+    UnsupportedVersion(String),
+    Forbidden(String),
+    Timeout(String),
+    Plugin { code: String, message: String },
     Internal(String),
 }
 
 impl GatewayMethodError {
-    fn code(&self) -> &'static str {
+    fn code(&self) -> &str {
         match self {
             Self::InvalidRequest(_) => "invalid_request",
             Self::NotImplemented(_) => "not_implemented",
+            Self::UnsupportedVersion(_) => "unsupported_version",
+            Self::Forbidden(_) => "forbidden",
+            Self::Timeout(_) => "timeout",
+            Self::Plugin { code, .. } => code.as_str(),
             Self::Internal(_) => "internal_error",
         }
     }
@@ -470,16 +500,35 @@ impl GatewayMethodError {
         match self {
             Self::InvalidRequest(message)
             | Self::NotImplemented(message)
+            | Self::UnsupportedVersion(message)
+            | Self::Forbidden(message)
+            | Self::Timeout(message)
+            | Self::Plugin { message, .. }
             | Self::Internal(message) => message,
         }
     }
 }
 
+impl From<PluginMethodError> for GatewayMethodError {
+    fn from(err: PluginMethodError) -> Self {
+        match err {
+            PluginMethodError::NotImplemented(message) => Self::NotImplemented(message),
+            PluginMethodError::Forbidden(message) => Self::Forbidden(message),
+            PluginMethodError::Timeout(message) => Self::Timeout(message),
+            PluginMethodError::Handler { code, message } => Self::Plugin { code, message },
+            PluginMethodError::Internal(message) => Self::Internal(message),
+        }
+    }
+}
+
 type GatewayMethodResult = std::result::Result<Value, GatewayMethodError>;
-type GatewayMethodHandler = Box<dyn Fn(&ClawdPaths, &Value) -> GatewayMethodResult + Send + Sync>;
+/// Handlers receive the negotiated protocol version as their last argument.
+type GatewayMethodHandler =
+    Box<dyn Fn(&ClawdPaths, &Value, u32) -> GatewayMethodResult + Send + Sync>;
 
 struct GatewayMethodDefinition {
     version: u32,
+    min_version: u32,
     handler: GatewayMethodHandler,
 }
 
@@ -490,28 +539,64 @@ struct GatewayMethodRegistry {
 
 impl GatewayMethodRegistry {
     fn register(&mut self, name: &str, version: u32, handler: GatewayMethodHandler) {
+        self.register_range(name, version, version, handler);
+    }
+
+    fn register_range(
+        &mut self,
+        name: &str,
+        min_version: u32,
+        version: u32,
+        handler: GatewayMethodHandler,
+    ) {
         let key = name.trim().to_string();
         if key.is_empty() {
             return;
         }
-        self.methods
-            .insert(key, GatewayMethodDefinition { version, handler });
+        self.methods.insert(
+            key,
+            GatewayMethodDefinition {
+                version,
+                min_version: min_version.min(version),
+                handler,
+            },
+        );
     }
 
-    fn handle(&self, name: &str, paths: &ClawdPaths, params: &Value) -> GatewayMethodResult {
+    /// Dispatches `name`, negotiating the caller's requested version against
+    /// the registered `min_version..=version` range (newest when omitted).
+    fn handle(
+        &self,
+        name: &str,
+        requested_version: Option<u32>,
+        paths: &ClawdPaths,
+        params: &Value,
+    ) -> GatewayMethodResult {
         let Some(def) = self.methods.get(name) else {
             return Err(GatewayMethodError::NotImplemented(format!(
                 "unsupported method: {name}"
             )));
         };
-        (def.handler)(paths, params)
+        let version = match requested_version {
+            None => def.version,
+            Some(v) if (def.min_version..=def.version).contains(&v) => v,
+            Some(v) => {
+                return Err(GatewayMethodError::UnsupportedVersion(format!(
+                    "{name} supports versions {}..={}, not {v}",
+                    def.min_version, def.version
+                )))
+            }
+        };
+        (def.handler)(paths, params, version)
     }
 
     fn list_versions(&self) -> Vec<Value> {
         let mut entries = self
             .methods
             .iter()
-            .map(|(name, def)| json!({ "name": name, "version": def.version }))
+            .map(|(name, def)| {
+                json!({ "name": name, "version": def.version, "minVersion": def.min_version })
+            })
             .collect::<Vec<_>>();
         entries.sort_by(|a, b| {
             let a_name = a.get("name").and_then(|v| v.as_str()).unwrap_or("");
@@ -775,7 +860,7 @@ impl DeviceAuthStore {
     }
 }
 
-fn gateway_dir(paths: &ClawdPaths) -> PathBuf {
+pub(crate) fn gateway_dir(paths: &ClawdPaths) -> PathBuf {
     paths.state_dir.join(GATEWAY_DIR)
 }
 
@@ -816,6 +901,50 @@ fn save_inbox_offset(paths: &ClawdPaths, offset: usize) -> Result<()> {
     write_json_value(&inbox_offset_path(paths), &json!({ "offset": offset }))
 }
 
+fn outbox_offset_path(paths: &ClawdPaths) -> PathBuf {
+    gateway_dir(paths).join(OUTBOX_OFFSET_FILE)
+}
+
+fn load_outbox_offset(paths: &ClawdPaths) -> Result<usize> {
+    if let Some(value) = read_json_value(&outbox_offset_path(paths))? {
+        if let Some(offset) = value.get("offset").and_then(|v| v.as_u64()) {
+            return Ok(offset as usize);
+        }
+    }
+    Ok(0)
+}
+
+/// Moves entries the earlier offset-based dispatcher had not reached into the
+/// durable outbox store, then drops the offset file.
+fn import_outbox_backlog(paths: &ClawdPaths, store: &OutboxStore) -> Result<usize> {
+    let offset_path = outbox_offset_path(paths);
+    if !offset_path.exists() {
+        return Ok(0);
+    }
+    let offset = load_outbox_offset(paths)?;
+    let mut imported = 0;
+    for entry in read_json_lines(&outbox_path(paths), None)?
+        .iter()
+        .skip(offset)
+    {
+        if store.enqueue(entry)? {
+            imported += 1;
+        }
+    }
+    std::fs::remove_file(&offset_path)
+        .with_context(|| format!("remove {}", offset_path.display()))?;
+    Ok(imported)
+}
+
+/// Queued messages go to the durable outbox store only when the gateway has
+/// outbound adapters to deliver them; otherwise `outbox.jsonl` is left for an
+/// external bridge.
+fn outbox_store_enabled(cfg: &GatewayConfig) -> bool {
+    cfg.channels
+        .as_ref()
+        .is_some_and(|channels| channels.values().any(|entry| entry.enabled != Some(false)))
+}
+
 fn normalize_channel_id(raw: &str) -> String {
     let trimmed = raw.trim().to_lowercase();
     if trimmed.is_empty() {
@@ -884,26 +1013,7 @@ fn load_manifest_value(path: &Path) -> Option<Value> {
     serde_json::from_str::<Value>(&raw).ok()
 }
 
-fn collect_gateway_methods_from_value(value: &Value) -> Vec<String> {
-    let mut out = Vec::new();
-    let methods = value
-        .get("gatewayMethods")
-        .or_else(|| value.get("gateway_methods"))
-        .and_then(|v| v.as_array());
-    if let Some(methods) = methods {
-        for entry in methods {
-            if let Some(name) = entry.as_str() {
-                let trimmed = name.trim();
-                if !trimmed.is_empty() {
-                    out.push(trimmed.to_string());
-                }
-            }
-        }
-    }
-    out
-}
-
-fn plugin_gateway_methods_for_root(root: &Path) -> Vec<String> {
+fn plugin_gateway_methods_for_root(plugin_id: &str, root: &Path) -> Vec<PluginMethodSpec> {
     let mut seen = HashSet::new();
     let mut out = Vec::new();
     let candidates = [
@@ -915,16 +1025,16 @@ fn plugin_gateway_methods_for_root(root: &Path) -> Vec<String> {
         let Some(value) = load_manifest_value(&path) else {
             continue;
         };
-        for method in collect_gateway_methods_from_value(&value) {
-            if seen.insert(method.clone()) {
-                out.push(method);
+        for spec in gateway_plugins::method_specs_from_manifest(plugin_id, root, &value) {
+            if seen.insert(spec.name.clone()) {
+                out.push(spec);
             }
         }
     }
     out
 }
 
-fn load_plugin_gateway_methods(paths: &ClawdPaths) -> Vec<String> {
+fn load_plugin_gateway_methods(paths: &ClawdPaths) -> Vec<PluginMethodSpec> {
     let mut out = Vec::new();
     let mut seen = HashSet::new();
     let Ok(store) = TaskStore::open(paths) else {
@@ -933,9 +1043,9 @@ fn load_plugin_gateway_methods(paths: &ClawdPaths) -> Vec<String> {
     let plugins = store.list_plugins(false).unwrap_or_default();
     for plugin in plugins {
         let root = Path::new(&plugin.path);
-        for method in plugin_gateway_methods_for_root(root) {
-            if seen.insert(method.clone()) {
-                out.push(method);
+        for spec in plugin_gateway_methods_for_root(&plugin.id, root) {
+            if seen.insert(spec.name.clone()) {
+                out.push(spec);
             }
         }
     }
@@ -947,7 +1057,7 @@ fn build_gateway_registry(paths: &ClawdPaths) -> GatewayMethodRegistry {
     registry.register(
         "send",
         1,
-        Box::new(|paths, params| {
+        Box::new(|paths, params, _version| {
             send_message_with_mode(paths, params, SendMode::Queue)
                 .map_err(|err| GatewayMethodError::InvalidRequest(err.to_string()))
         }),
@@ -955,18 +1065,33 @@ fn build_gateway_registry(paths: &ClawdPaths) -> GatewayMethodRegistry {
     registry.register(
         "health",
         1,
-        Box::new(|_paths, _params| Ok(json!({ "ok": true }))),
+        Box::new(|_paths, _params, _version| Ok(json!({ "ok": true }))),
     );
 
-    for method in load_plugin_gateway_methods(paths) {
-        let name = method.clone();
-        registry.register(
-            &method,
-            1,
-            Box::new(move |_paths, _params| {
-                Err(GatewayMethodError::NotImplemented(format!(
-                    "method not implemented: {name}"
-                )))
+    for spec in load_plugin_gateway_methods(paths) {
+        let name = spec.name.clone();
+        // Built-in methods keep their handlers; a plugin cannot take over `send` or `health`.
+        if registry.methods.contains_key(name.trim()) {
+            eprintln!(
+                "[clawdex][gateway] ignoring plugin {} method {name}: already registered",
+                spec.plugin_id
+            );
+            continue;
+        }
+        registry.register_range(
+            &name,
+            spec.min_version,
+            spec.version,
+            Box::new(move |paths, params, version| {
+                // Re-read permissions per call so manifest edits apply without a reload.
+                let permissions = plugin_permissions_for_root(&spec.root);
+                let policy = apply_plugin_permissions_to_policy(
+                    &paths.workspace_policy,
+                    permissions.as_ref(),
+                    &paths.workspace_dir,
+                );
+                gateway_plugins::invoke(&spec, paths, params, version, &policy)
+                    .map_err(GatewayMethodError::from)
             }),
         );
     }
@@ -1013,7 +1138,12 @@ fn list_gateway_method_versions(paths: &ClawdPaths) -> Vec<Value> {
                 .map(|s| s.to_string())
         })
         .collect();
-    for extra in ["methods.list", "gateway.reload"] {
+    for extra in [
+        "methods.list",
+        "gateway.reload",
+        "events.subscribe",
+        "events.unsubscribe",
+    ] {
         if names.insert(extra.to_string()) {
             entries.push(json!({ "name": extra, "version": 1 }));
         }
@@ -1385,7 +1515,9 @@ fn rotate_auth_token(
 }
 
 fn append_receipt(paths: &ClawdPaths, receipt: &Value) -> Result<()> {
-    append_json_line(&receipts_path(paths), receipt)
+    append_json_line(&receipts_path(paths), receipt)?;
+    gateway_events::publish(paths, KIND_RECEIPT, receipt);
+    Ok(())
 }
 
 fn record_receipt(paths: &ClawdPaths, receipt: &Value) {
@@ -2035,6 +2167,9 @@ fn send_message_with_mode(paths: &ClawdPaths, args: &Value, mode: SendMode) -> R
 
     if mode == SendMode::Queue {
         append_json_line(&outbox_path(paths), &entry)?;
+        if outbox_store_enabled(&cfg) {
+            OutboxStore::open(paths)?.enqueue(&entry)?;
+        }
         route_store.update_route(
             &session_key,
             RouteEntry {
@@ -2395,6 +2530,7 @@ pub fn record_incoming(paths: &ClawdPaths, payload: &Value) -> Result<Value> {
     }
 
     append_json_line(&inbox_path(paths), &entry)?;
+    gateway_events::publish(paths, KIND_INBOX, &entry);
     let message_id = payload
         .get("messageId")
         .or_else(|| payload.get("id"))
@@ -2448,10 +2584,135 @@ pub fn drain_inbox(paths: &ClawdPaths) -> Result<Vec<Value>> {
     Ok(new_entries)
 }
 
+fn outbox_receipt(status: &str, entry: &Value) -> Value {
+    let field = |key: &str| entry.get(key).and_then(|v| v.as_str());
+    build_receipt(
+        status,
+        "outgoing",
+        field("id"),
+        field("sessionKey"),
+        field("channel"),
+        field("to"),
+        None,
+        field("accountId"),
+        field("idempotencyKey"),
+        now_ms(),
+    )
+}
+
+fn deliver_outbox_entry(registry: &ChannelRegistry, entry: &Value) -> Result<Value> {
+    let channel = entry
+        .get("channel")
+        .and_then(|v| v.as_str())
+        .map(normalize_channel_id)
+        .filter(|s| !s.is_empty())
+        .context("outbox entry missing channel")?;
+    let account_id = entry.get("accountId").and_then(|v| v.as_str());
+    let adapter = registry
+        .resolve(&channel, account_id)
+        .with_context(|| format!("no channel adapter configured for {channel}"))?;
+    adapter.send(entry)
+}
+
+/// Deliver due outbox messages through the configured channel adapters, one
+/// message at a time per session, recording a receipt for every attempt.
+/// Failed sends are retried with backoff and dead-lettered after
+/// `policy.max_attempts`.
+pub fn dispatch_outbox(
+    paths: &ClawdPaths,
+    registry: &ChannelRegistry,
+    policy: &RetryPolicy,
+) -> Result<Value> {
+    let mut store = OutboxStore::open(paths)?;
+    let (mut sent, mut failed, mut dead) = (0usize, 0usize, 0usize);
+    loop {
+        let batch = store.claim_due(now_ms(), OUTBOX_CLAIM_BATCH)?;
+        if batch.is_empty() {
+            break;
+        }
+        let mut delivered = 0;
+        for message in batch {
+            match deliver_outbox_entry(registry, &message.entry) {
+                Ok(result) => {
+                    store.mark_sent(&message.id, now_ms())?;
+                    let mut receipt = outbox_receipt("sent", &message.entry);
+                    receipt["attempt"] = json!(message.attempts + 1);
+                    receipt["result"] = result;
+                    record_receipt(paths, &receipt);
+                    sent += 1;
+                    delivered += 1;
+                }
+                Err(err) => {
+                    let err = err.to_string();
+                    let outcome = store.mark_failed(&message.id, &err, now_ms(), policy)?;
+                    let status = if outcome.dead { "dead" } else { "failed" };
+                    let mut receipt = outbox_receipt(status, &message.entry);
+                    receipt["attempt"] = json!(outcome.attempts);
+                    receipt["error"] = Value::String(err);
+                    if let Some(retry_at) = outcome.next_attempt_at_ms {
+                        receipt["retryAtMs"] = json!(retry_at);
+                    }
+                    record_receipt(paths, &receipt);
+                    if outcome.dead {
+                        dead += 1;
+                    } else {
+                        failed += 1;
+                    }
+                }
+            }
+        }
+        // Another round only helps when a send freed up the next message of a
+        // session; otherwise everything left is waiting on a retry.
+        if delivered == 0 {
+            break;
+        }
+    }
+    Ok(json!({ "sent": sent, "failed": failed, "dead": dead }))
+}
+
+fn run_outbox_dispatcher(
+    paths: ClawdPaths,
+    registry: ChannelRegistry,
+    policy: RetryPolicy,
+    poll: Duration,
+) {
+    loop {
+        if let Err(err) = dispatch_outbox(&paths, &registry, &policy) {
+            eprintln!("[clawdex][gateway] outbox dispatch failed: {err}");
+        }
+        std::thread::sleep(poll);
+    }
+}
+
+fn start_outbox_dispatcher(paths: &ClawdPaths) -> Result<()> {
+    let cfg = load_gateway_config(paths)?;
+    let registry = ChannelRegistry::from_config(&cfg)?;
+    if registry.is_empty() {
+        return Ok(());
+    }
+    let poll = Duration::from_millis(
+        cfg.outbox_poll_ms
+            .filter(|ms| *ms > 0)
+            .unwrap_or(DEFAULT_OUTBOX_POLL_MS),
+    );
+    let store = OutboxStore::open(paths)?;
+    store.release_claims()?;
+    let imported = import_outbox_backlog(paths, &store)?;
+    if imported > 0 {
+        eprintln!("[clawdex][gateway] imported {imported} undelivered outbox entries");
+    }
+    let policy = RetryPolicy::from_config(&cfg);
+    let paths = paths.clone();
+    std::thread::spawn(move || run_outbox_dispatcher(paths, registry, policy, poll));
+    Ok(())
+}
+
 pub fn run_gateway(bind: &str, paths: &ClawdPaths) -> Result<()> {
     std::fs::create_dir_all(gateway_dir(paths))
         .with_context(|| format!("create gateway dir {}", gateway_dir(paths).display()))?;
     let server = Server::http(bind).map_err(|err| anyhow::anyhow!("bind gateway {bind}: {err}"))?;
+    start_outbox_dispatcher(paths)?;
+    crate::gateway_receivers::start_receivers(paths, &load_gateway_config(paths)?)?;
 
     for mut request in server.incoming_requests() {
         let response = match handle_request(paths, &mut request) {
@@ -2466,6 +2727,7 @@ pub fn run_gateway(bind: &str, paths: &ClawdPaths) -> Result<()> {
 pub fn run_gateway_ws(bind: &str, paths: &ClawdPaths) -> Result<()> {
     let listener =
         TcpListener::bind(bind).map_err(|err| anyhow::anyhow!("bind gateway ws {bind}: {err}"))?;
+    gateway_events::start_task_event_pump(paths);
     for stream in listener.incoming() {
         let stream = match stream {
             Ok(stream) => stream,
@@ -2490,40 +2752,82 @@ pub fn run_gateway_ws(bind: &str, paths: &ClawdPaths) -> Result<()> {
                     GatewayAuth::none()
                 }
             };
+            // Reads time out so subscribed events are pushed between requests.
+            if let Err(err) = websocket.get_mut().set_read_timeout(Some(WS_EVENT_POLL)) {
+                eprintln!("[clawdex][gateway-ws] set read timeout failed: {err}");
+            }
             let mut authorized = !auth.required();
             let mut presence_key: Option<String> = None;
+            let mut subscriptions = EventSubscriptions::default();
+            let mut journal: Option<EventJournal> = None;
             let conn_id = Uuid::new_v4().to_string();
             loop {
-                let msg = match websocket.read() {
-                    Ok(msg) => msg,
+                match websocket.read() {
+                    Ok(msg) if msg.is_close() => break,
+                    Ok(msg) => {
+                        let text = match msg {
+                            Message::Text(text) => text,
+                            Message::Binary(bin) => String::from_utf8(bin).unwrap_or_default(),
+                            _ => String::new(),
+                        };
+                        let response =
+                            serde_json::from_str::<Value>(&text).ok().and_then(|frame| {
+                                handle_ws_frame(
+                                    &frame,
+                                    &paths,
+                                    &conn_id,
+                                    &auth,
+                                    &mut authorized,
+                                    &mut presence_key,
+                                    &mut subscriptions,
+                                )
+                            });
+                        if let Some(response) = response {
+                            if websocket.send(Message::Text(response.to_string())).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(tungstenite::Error::Io(err))
+                        if matches!(
+                            err.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) => {}
                     Err(_) => break,
-                };
-                if msg.is_close() {
-                    break;
                 }
-                let text = match msg {
-                    Message::Text(text) => text,
-                    Message::Binary(bin) => String::from_utf8(bin).unwrap_or_default(),
-                    _ => continue,
-                };
-                let frame: Value = match serde_json::from_str(&text) {
-                    Ok(frame) => frame,
-                    Err(_) => continue,
+                if subscriptions.is_empty() {
+                    continue;
+                }
+                if journal.is_none() {
+                    match EventJournal::open(&paths) {
+                        Ok(opened) => journal = Some(opened),
+                        Err(err) => {
+                            eprintln!("[clawdex][gateway-ws] open event journal failed: {err}");
+                            continue;
+                        }
+                    }
+                }
+                let Some(events) = journal.as_ref() else {
+                    continue;
                 };
-                let response = handle_ws_frame(
-                    &frame,
-                    &paths,
-                    &conn_id,
-                    &auth,
-                    &mut authorized,
-                    &mut presence_key,
-                );
-                if let Some(response) = response {
-                    let _ = websocket.send(Message::Text(response.to_string()));
+                match subscriptions.poll(events) {
+                    Ok(frames) => {
+                        if frames
+                            .into_iter()
+                            .any(|frame| websocket.send(Message::Text(frame.to_string())).is_err())
+                        {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("[clawdex][gateway-ws] read events failed: {err}");
+                        journal = None;
+                    }
                 }
             }
             if let Some(key) = presence_key.as_deref() {
                 with_presence_state(|state| state.mark_disconnect(key));
+                publish_presence(&paths, key);
             }
         });
     }
@@ -2537,6 +2841,7 @@ fn handle_ws_frame(
     auth: &GatewayAuth,
     authorized: &mut bool,
     presence_key: &mut Option<String>,
+    subscriptions: &mut EventSubscriptions,
 ) -> Option<Value> {
     if frame.get("type").and_then(|v| v.as_str()) != Some("req") {
         return None;
@@ -2571,7 +2876,8 @@ fn handle_ws_frame(
             }
             if let Some((key, entry)) = presence_from_params(&params, conn_id) {
                 *presence_key = Some(key.clone());
-                with_presence_state(|state| state.upsert(key, entry));
+                with_presence_state(|state| state.upsert(key.clone(), entry));
+                publish_presence(paths, &key);
             }
             Some(ws_response_ok(&id, hello_ok_payload(paths, conn_id)))
         }
@@ -2579,6 +2885,14 @@ fn handle_ws_frame(
             let methods = list_gateway_method_versions(paths);
             Some(ws_response_ok(&id, json!({ "methods": methods })))
         }
+        "events.subscribe" => match subscriptions.subscribe(paths, &params) {
+            Ok(payload) => Some(ws_response_ok(&id, payload)),
+            Err(err) => Some(ws_response_err(&id, "invalid_request", &err.to_string())),
+        },
+        "events.unsubscribe" => match subscriptions.unsubscribe(&params) {
+            Ok(payload) => Some(ws_response_ok(&id, payload)),
+            Err(err) => Some(ws_response_err(&id, "invalid_request", &err.to_string())),
+        },
         "gateway.reload" => {
             reload_gateway_registry(paths);
             let methods = list_gateway_method_versions(paths);
@@ -2590,7 +2904,11 @@ fn handle_ws_frame(
         _ => {
             let handle = gateway_registry_handle(paths);
             let registry = handle.read().unwrap_or_else(|err| err.into_inner());
-            match registry.handle(method, paths, &params) {
+            let version = frame
+                .get("version")
+                .and_then(|v| v.as_u64())
+                .map(|v| v.min(u32::MAX as u64) as u32);
+            match registry.handle(method, version, paths, &params) {
                 Ok(payload) => Some(ws_response_ok(&id, payload)),
                 Err(err) => Some(ws_response_err(&id, err.code(), err.message())),
             }
@@ -2612,7 +2930,7 @@ fn hello_ok_payload(paths: &ClawdPaths, conn_id: &str) -> Value {
         },
         "features": {
             "methods": methods,
-            "events": [],
+            "events": EVENT_KINDS,
         },
         "snapshot": snapshot,
         "policy": {
@@ -2654,12 +2972,18 @@ fn handle_request(
         Some((path, query)) => (path, Some(query)),
         None => (url.as_str(), None),
     };
-    let requires_auth = !matches!(
-        (&method, path),
-        (&Method::Get, "/v1/health")
-            | (&Method::Post, "/v1/auth/device/start")
-            | (&Method::Post, "/v1/auth/device/poll")
-    );
+    let channel_event_key = path
+        .strip_prefix("/v1/channels/")
+        .and_then(|rest| rest.strip_suffix("/events"))
+        .filter(|key| !key.is_empty() && !key.contains('/'));
+    // Channel event callbacks authenticate with the channel's own signature.
+    let requires_auth = channel_event_key.is_none()
+        && !matches!(
+            (&method, path),
+            (&Method::Get, "/v1/health")
+                | (&Method::Post, "/v1/auth/device/start")
+                | (&Method::Post, "/v1/auth/device/poll")
+        );
     if requires_auth {
         let cfg = load_gateway_config(paths)?;
         let auth = resolve_gateway_auth(&cfg);
@@ -2671,6 +2995,12 @@ fn handle_request(
             }
         }
     }
+    if let Some(key) = channel_event_key {
+        if method != Method::Post {
+            return Ok(Response::from_data(Vec::new()).with_status_code(StatusCode(405)));
+        }
+        return handle_channel_event(paths, request, key);
+    }
     if let Some(rest) = path.strip_prefix("/v1/attachments/") {
         let rest = rest.trim_matches('/');
         if rest.is_empty() {
@@ -2800,6 +3130,65 @@ fn handle_request(
             let result = rotate_auth_token(&mut token_store, current.as_deref(), &payload)?;
             Ok(json_response(json!({ "ok": true, "token": result }))?)
         }
+        (&Method::Get, "/v1/channels/adapters") => {
+            let cfg = load_gateway_config(paths)?;
+            let adapters = ChannelRegistry::from_config(&cfg)?.describe();
+            let count = adapters.len();
+            Ok(json_response(
+                json!({ "ok": true, "adapters": adapters, "count": count }),
+            )?)
+        }
+        (&Method::Get, "/v1/outbox") => {
+            let query = match parse_outbox_query(query) {
+                Ok(query) => query,
+                Err(err) => return Ok(json_error_response(&err.to_string(), StatusCode(400))),
+            };
+            let store = OutboxStore::open(paths)?;
+            let messages = store
+                .list(&query)?
+                .iter()
+                .map(|message| message.to_json())
+                .collect::<Vec<_>>();
+            let count = messages.len();
+            Ok(json_response(json!({
+                "ok": true,
+                "messages": messages,
+                "count": count,
+                "counts": store.counts()?,
+            }))?)
+        }
+        (&Method::Post, "/v1/outbox/replay") => {
+            let body = read_body(request)?;
+            let payload: Value = if body.is_empty() {
+                json!({})
+            } else {
+                serde_json::from_slice(&body).context("invalid json")?
+            };
+            let mut ids = payload
+                .get("ids")
+                .and_then(|v| v.as_array())
+                .map(|list| {
+                    list.iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            if let Some(id) = payload.get("id").and_then(|v| v.as_str()) {
+                ids.push(id.to_string());
+            }
+            let status = payload.get("status").and_then(|v| v.as_str());
+            if ids.is_empty() && !matches!(status, None | Some("dead") | Some("sent")) {
+                return Ok(json_error_response(
+                    "replay status must be dead or sent",
+                    StatusCode(400),
+                ));
+            }
+            let replayed = OutboxStore::open(paths)?.replay(&ids, status)?;
+            let count = replayed.len();
+            Ok(json_response(
+                json!({ "ok": true, "replayed": replayed, "count": count }),
+            )?)
+        }
         (&Method::Get, "/v1/receipts") => {
             let query = parse_receipt_query(query);
             let receipts = list_receipts(paths, query)?;
@@ -2818,6 +3207,66 @@ fn handle_request(
     }
 }
 
+fn request_header(request: &tiny_http::Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str().to_string())
+}
+
+/// Slack Events API callback for `gateway.channels.<key>`: verifies the
+/// signing secret, answers `url_verification`, and records user messages
+/// (deduped on `event_id`, since Slack retries slow acknowledgements).
+fn handle_channel_event(
+    paths: &ClawdPaths,
+    request: &mut tiny_http::Request,
+    key: &str,
+) -> Result<Response<std::io::Cursor<Vec<u8>>>> {
+    let cfg = load_gateway_config(paths)?;
+    let Some(inbound) = crate::gateway_receivers::inbound_channel(&cfg, key)
+        .filter(|inbound| inbound.kind == "slack")
+    else {
+        return Ok(json_error_response(
+            "unknown channel events endpoint",
+            StatusCode(404),
+        ));
+    };
+    let Some(secret) = inbound.slack_signing_secret() else {
+        return Ok(json_error_response(
+            "slack signingSecret not configured",
+            StatusCode(403),
+        ));
+    };
+    let timestamp = request_header(request, "x-slack-request-timestamp");
+    let signature = request_header(request, "x-slack-signature");
+    let body = read_body(request)?;
+    if let Err(err) = verify_slack_signature(
+        &secret,
+        timestamp.as_deref(),
+        signature.as_deref(),
+        &body,
+        now_ms() / 1000,
+    ) {
+        return Ok(unauthorized_response(&err.to_string()));
+    }
+    let payload: Value = serde_json::from_slice(&body).context("invalid json")?;
+    match parse_slack_event(&payload, &inbound) {
+        SlackEvent::Challenge(challenge) => Ok(json_response(json!({ "challenge": challenge }))?),
+        SlackEvent::Incoming { event_id, payload } => {
+            let dedupe_key = format!("{}:event:{event_id}", inbound.key);
+            let mut idempotency = IdempotencyStore::load(paths)?;
+            if idempotency.seen(&dedupe_key) {
+                return Ok(json_response(json!({ "ok": true, "deduped": true }))?);
+            }
+            record_incoming(paths, &payload)?;
+            idempotency.insert(&dedupe_key, now_ms())?;
+            Ok(json_response(json!({ "ok": true }))?)
+        }
+        SlackEvent::Ignored => Ok(json_response(json!({ "ok": true, "ignored": true }))?),
+    }
+}
+
 fn read_body(request: &mut tiny_http::Request) -> Result<Vec<u8>> {
     let mut body = Vec::new();
     request
@@ -3374,6 +3823,105 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn dispatch_outbox_retries_in_session_order_and_dead_letters() -> Result<()> {
+        let mock = crate::gateway_channels::test_support::MockHttpServer::start(vec![
+            (503, json!({ "error": "busy" })),
+            (200, json!({ "ok": true, "id": "hook-1" })),
+            (200, json!({ "ok": true, "id": "hook-2" })),
+        ]);
+        let base = std::env::temp_dir().join(format!("clawdex-outbox-{}", Uuid::new_v4()));
+        let state_dir = base.join("state");
+        let workspace_dir = base.join("workspace");
+        std::fs::create_dir_all(&workspace_dir)?;
+        std::fs::create_dir_all(&state_dir)?;
+        std::fs::write(
+            state_dir.join("config.json"),
+            serde_json::to_vec(&json!({
+                "gateway": {
+                    "channels": {
+                        "ops": { "kind": "webhook", "url": format!("{}/hook", mock.base_url) }
+                    }
+                }
+            }))?,
+        )?;
+
+        let (cfg, paths) = crate::config::load_config(Some(state_dir), Some(workspace_dir))?;
+        let registry = ChannelRegistry::from_config(&cfg.gateway.unwrap_or_default())?;
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            base_ms: 0,
+        };
+
+        for (channel, to, text) in [
+            ("ops", "room-1", "build green"),
+            ("ops", "room-1", "deploy done"),
+            ("signal", "+100", "no adapter"),
+        ] {
+            send_message_with_mode(
+                &paths,
+                &json!({ "channel": channel, "to": to, "text": text }),
+                SendMode::Queue,
+            )?;
+        }
+
+        // The first ops message fails, holding back the second one.
+        let summary = dispatch_outbox(&paths, &registry, &policy)?;
+        assert_eq!(summary, json!({ "sent": 0, "failed": 2, "dead": 0 }));
+        let summary = dispatch_outbox(&paths, &registry, &policy)?;
+        assert_eq!(summary, json!({ "sent": 2, "failed": 0, "dead": 1 }));
+        let summary = dispatch_outbox(&paths, &registry, &policy)?;
+        assert_eq!(summary, json!({ "sent": 0, "failed": 0, "dead": 0 }));
+
+        let texts = mock
+            .requests()
+            .iter()
+            .map(|request| request.body["text"].as_str().unwrap_or("").to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["build green", "build green", "deploy done"]);
+
+        let receipts = list_receipts(&paths, ReceiptQuery::default())?;
+        let statuses = receipts
+            .iter()
+            .map(|r| {
+                (
+                    r.get("status").and_then(|v| v.as_str()).unwrap_or(""),
+                    r.get("channel").and_then(|v| v.as_str()).unwrap_or(""),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                ("queued", "ops"),
+                ("queued", "ops"),
+                ("queued", "signal"),
+                ("failed", "ops"),
+                ("failed", "signal"),
+                ("sent", "ops"),
+                ("dead", "signal"),
+                ("sent", "ops"),
+            ]
+        );
+        assert_eq!(receipts[3]["attempt"], 1);
+        assert!(receipts[3]["error"]
+            .as_str()
+            .unwrap_or("")
+            .contains("HTTP 503"));
+        assert_eq!(receipts[5]["result"]["id"], "hook-1");
+        assert!(receipts[6]["error"]
+            .as_str()
+            .unwrap_or("")
+            .contains("no channel adapter configured for signal"));
+
+        let dead = OutboxStore::open(&paths)?.list(&parse_outbox_query(Some("status=dead"))?)?;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].session_key, "signal:+100");
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
     #[test]
     fn gateway_methods_registry_discovers_plugin_methods() -> Result<()> {
         let base = std::env::temp_dir().join(format!("clawdex-methods-{}", Uuid::new_v4()));
@@ -3412,6 +3960,24 @@ mod tests {
         assert!(registry.methods.contains_key("plugin.foo"));
         assert!(registry.methods.contains_key("plugin.bar"));
 
+        // Declaring a built-in name does not replace the built-in handler.
+        let manifest = json!({
+            "id": "plugin-a",
+            "gatewayMethods": ["health", "send", "plugin.foo"],
+            "gatewayHandler": { "command": "sh", "args": ["handler.sh"] },
+            "configSchema": {}
+        });
+        std::fs::write(
+            plugin_dir.join("openclaw.plugin.json"),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        let registry = build_gateway_registry(&paths);
+        let health = registry
+            .handle("health", None, &paths, &json!({}))
+            .expect("built-in health");
+        assert_eq!(health, json!({ "ok": true }));
+        assert!(registry.methods.contains_key("plugin.foo"));
+
         let _ = std::fs::remove_dir_all(base);
         Ok(())
     }
@@ -3472,4 +4038,162 @@ mod tests {
         let _ = std::fs::remove_dir_all(base);
         Ok(())
     }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn gateway_plugin_methods_execute_with_versions_and_permissions() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let base = std::env::temp_dir().join(format!("clawdex-methods-exec-{}", Uuid::new_v4()));
+        let state_dir = base.join("state");
+        let workspace_dir = base.join("workspace");
+        let plugin_dir = base.join("plugin-c");
+        std::fs::create_dir_all(&workspace_dir)?;
+        std::fs::create_dir_all(&plugin_dir)?;
+        std::fs::create_dir_all(&state_dir)?;
+        // Stand-in for `codex sandbox`: drop the sandbox arguments and run the handler.
+        let fake_codex = base.join("codex");
+        std::fs::write(
+            &fake_codex,
+            "#!/bin/sh\nwhile [ \"$1\" != \"--\" ]; do shift; done\nshift\nexec \"$@\"\n",
+        )?;
+        std::fs::set_permissions(&fake_codex, std::fs::Permissions::from_mode(0o755))?;
+        std::fs::write(
+            state_dir.join("config.json"),
+            serde_json::to_vec(&json!({ "codex": { "path": fake_codex } }))?,
+        )?;
+        std::fs::write(
+            plugin_dir.join("handler.sh"),
+            "#!/bin/sh\nread -r line\nprintf '{\"result\":%s}\\n' \"$line\"\n",
+        )?;
+
+        let manifest = json!({
+            "id": "plugin-c",
+            "configSchema": {},
+            "permissions": { "networkAccess": false },
+            "gatewayHandler": { "command": "sh", "args": ["handler.sh"] },
+            "gatewayMethods": [
+                { "name": "plugin.c.echo", "version": 2, "minVersion": 1 },
+                { "name": "plugin.c.fetch", "requires": ["network"] }
+            ]
+        });
+        std::fs::write(
+            plugin_dir.join("openclaw.plugin.json"),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+
+        let (_cfg, paths) = crate::config::load_config(Some(state_dir), Some(workspace_dir))?;
+        let store = TaskStore::open(&paths)?;
+        store.upsert_plugin(&crate::task_db::PluginRecord {
+            id: "plugin-c".to_string(),
+            name: "Plugin C".to_string(),
+            version: None,
+            description: None,
+            source: None,
+            path: plugin_dir.to_string_lossy().to_string(),
+            enabled: true,
+            installed_at_ms: now_ms(),
+            updated_at_ms: now_ms(),
+        })?;
+
+        let registry = build_gateway_registry(&paths);
+        let echoed = registry
+            .handle("plugin.c.echo", Some(1), &paths, &json!({ "q": "hi" }))
+            .map_err(|err| anyhow::anyhow!("{}: {}", err.code(), err.message()))?;
+        assert_eq!(echoed["method"], "plugin.c.echo");
+        assert_eq!(echoed["version"], 1);
+        assert_eq!(echoed["params"]["q"], "hi");
+        assert_eq!(echoed["plugin"]["id"], "plugin-c");
+        assert_eq!(echoed["permissions"]["networkAccess"], false);
+
+        let err = registry
+            .handle("plugin.c.echo", Some(3), &paths, &json!({}))
+            .expect_err("version 3 is unsupported");
+        assert_eq!(err.code(), "unsupported_version");
+        let err = registry
+            .handle("plugin.c.fetch", None, &paths, &json!({}))
+            .expect_err("network is not granted");
+        assert_eq!(err.code(), "forbidden");
+
+        let versions = registry.list_versions();
+        let echo = versions
+            .iter()
+            .find(|entry| entry["name"] == "plugin.c.echo")
+            .expect("echo listed");
+        assert_eq!(echo["version"], 2);
+        assert_eq!(echo["minVersion"], 1);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[test]
+    fn ws_event_subscriptions_push_inbox_and_receipts() -> Result<()> {
+        let base = std::env::temp_dir().join(format!("clawdex-ws-events-{}", Uuid::new_v4()));
+        let state_dir = base.join("state");
+        let workspace_dir = base.join("workspace");
+        std::fs::create_dir_all(&workspace_dir)?;
+        let (_cfg, paths) = crate::config::load_config(Some(state_dir), Some(workspace_dir))?;
+
+        let auth = GatewayAuth::none();
+        let mut authorized = true;
+        let mut presence_key = None;
+        let mut subscriptions = EventSubscriptions::default();
+        let mut call = |frame: Value, subscriptions: &mut EventSubscriptions| {
+            handle_ws_frame(
+                &frame,
+                &paths,
+                "conn-1",
+                &auth,
+                &mut authorized,
+                &mut presence_key,
+                subscriptions,
+            )
+            .unwrap_or(Value::Null)
+        };
+
+        let ack = call(
+            json!({
+                "type": "req",
+                "id": "1",
+                "method": "events.subscribe",
+                "params": { "kinds": ["inbox", "receipt"], "filters": { "channel": "telegram" } }
+            }),
+            &mut subscriptions,
+        );
+        assert_eq!(ack["ok"], true, "{ack}");
+        let rejected = call(
+            json!({
+                "type": "req",
+                "id": "2",
+                "method": "events.subscribe",
+                "params": { "kinds": ["nope"] }
+            }),
+            &mut subscriptions,
+        );
+        assert_eq!(rejected["ok"], false);
+
+        record_incoming(
+            &paths,
+            &json!({ "channel": "telegram", "from": "42", "text": "hi" }),
+        )?;
+        record_incoming(
+            &paths,
+            &json!({ "channel": "slack", "from": "U1", "text": "other" }),
+        )?;
+
+        let journal = EventJournal::open(&paths)?;
+        let frames = subscriptions.poll(&journal)?;
+        let kinds = frames
+            .iter()
+            .map(|frame| frame["event"].as_str().unwrap_or_default().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["inbox".to_string(), "receipt".to_string()]);
+        assert_eq!(frames[0]["payload"]["text"], "hi");
+        assert_eq!(frames[1]["payload"]["status"], "received");
+        assert!(frames[0]["seq"].as_i64() < frames[1]["seq"].as_i64());
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
 }
@@ -0,0 +1,895 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::{GatewayChannelConfig, GatewayConfig};
+use crate::util::now_ms;
+
+pub(crate) const DEFAULT_TELEGRAM_API_BASE: &str = "https://api.telegram.org";
+const DEFAULT_SLACK_API_BASE: &str = "https://slack.com/api";
+const TELEGRAM_MAX_TEXT_CHARS: usize = 4096;
+const DEFAULT_ADAPTER_TIMEOUT_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelCapabilities {
+    pub text: bool,
+    pub attachments: bool,
+    pub threads: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_text_chars: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelHealth {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub checked_at_ms: i64,
+}
+
+impl ChannelHealth {
+    fn ok(detail: Option<String>) -> Self {
+        Self {
+            ok: true,
+            detail,
+            checked_at_ms: now_ms(),
+        }
+    }
+
+    fn failed(detail: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            detail: Some(detail.into()),
+            checked_at_ms: now_ms(),
+        }
+    }
+}
+
+/// A transport that delivers queued outbox entries to one external channel.
+///
+/// `send` receives the outbox entry as written by `message.send` (`to`, `text`,
+/// `threadId`, `attachments`, ...) and returns the channel's result on success.
+pub trait ChannelAdapter: Send + Sync {
+    fn kind(&self) -> &'static str;
+    fn send(&self, message: &Value) -> Result<Value>;
+    fn capabilities(&self) -> ChannelCapabilities;
+    fn health(&self) -> ChannelHealth;
+}
+
+/// Adapters configured under `gateway.channels`, keyed by channel id (or
+/// `<channel>:<accountId>` for account-specific adapters).
+#[derive(Clone, Default)]
+pub struct ChannelRegistry {
+    adapters: HashMap<String, Arc<dyn ChannelAdapter>>,
+    accounts: HashMap<String, String>,
+}
+
+impl ChannelRegistry {
+    pub fn from_config(cfg: &GatewayConfig) -> Result<Self> {
+        let mut registry = Self::default();
+        let Some(channels) = cfg.channels.as_ref() else {
+            return Ok(registry);
+        };
+        for (key, channel_cfg) in channels {
+            let key = key.trim().to_lowercase();
+            if key.is_empty() || channel_cfg.enabled == Some(false) {
+                continue;
+            }
+            let adapter = build_adapter(&key, channel_cfg)
+                .with_context(|| format!("gateway.channels.{key}"))?;
+            if let Some(account_id) = channel_cfg
+                .account_id
+                .as_deref()
+                .map(|value| value.trim().to_lowercase())
+                .filter(|value| !value.is_empty())
+            {
+                let channel = key.split(':').next().unwrap_or(&key);
+                registry
+                    .accounts
+                    .insert(format!("{channel}:{account_id}"), key.clone());
+            }
+            registry.adapters.insert(key, adapter);
+        }
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, key: &str, adapter: Arc<dyn ChannelAdapter>) {
+        self.adapters.insert(key.trim().to_lowercase(), adapter);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adapters.is_empty()
+    }
+
+    pub fn resolve(
+        &self,
+        channel: &str,
+        account_id: Option<&str>,
+    ) -> Option<Arc<dyn ChannelAdapter>> {
+        if let Some(account_id) = account_id.filter(|value| !value.trim().is_empty()) {
+            let key = format!("{channel}:{}", account_id.trim().to_lowercase());
+            let key = self.accounts.get(&key).unwrap_or(&key);
+            if let Some(adapter) = self.adapters.get(key) {
+                return Some(adapter.clone());
+            }
+        }
+        self.adapters.get(channel).cloned()
+    }
+
+    pub fn describe(&self) -> Vec<Value> {
+        let mut keys = self.adapters.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        keys.into_iter()
+            .filter_map(|key| {
+                let adapter = self.adapters.get(&key)?;
+                Some(json!({
+                    "channel": key,
+                    "kind": adapter.kind(),
+                    "capabilities": adapter.capabilities(),
+                    "health": adapter.health(),
+                }))
+            })
+            .collect()
+    }
+}
+
+/// The adapter kind for a `gateway.channels` entry: explicit `kind`, else the
+/// channel part of the key (`telegram:work` -> `telegram`).
+pub(crate) fn channel_kind(key: &str, cfg: &GatewayChannelConfig) -> String {
+    cfg.kind
+        .as_deref()
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| key.split(':').next().unwrap_or(key).to_string())
+}
+
+pub(crate) fn channel_api_base<'a>(cfg: &'a GatewayChannelConfig, default: &'a str) -> &'a str {
+    cfg.api_base
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(default)
+        .trim_end_matches('/')
+}
+
+fn build_adapter(key: &str, cfg: &GatewayChannelConfig) -> Result<Arc<dyn ChannelAdapter>> {
+    let kind = channel_kind(key, cfg);
+    let timeout = Duration::from_millis(cfg.timeout_ms.unwrap_or(DEFAULT_ADAPTER_TIMEOUT_MS));
+    match kind.as_str() {
+        "telegram" => {
+            let token = resolve_secret(cfg.token.as_deref(), cfg.token_env.as_deref())
+                .context("telegram adapter requires token or tokenEnv")?;
+            let api_base = channel_api_base(cfg, DEFAULT_TELEGRAM_API_BASE);
+            Ok(Arc::new(TelegramAdapter::new(api_base, &token, timeout)?))
+        }
+        "slack" => {
+            let token = resolve_secret(cfg.token.as_deref(), cfg.token_env.as_deref())
+                .context("slack adapter requires token or tokenEnv")?;
+            let api_base = channel_api_base(cfg, DEFAULT_SLACK_API_BASE);
+            Ok(Arc::new(SlackAdapter::new(api_base, &token, timeout)?))
+        }
+        "matrix" => {
+            let token = resolve_secret(cfg.token.as_deref(), cfg.token_env.as_deref())
+                .context("matrix adapter requires token or tokenEnv")?;
+            let homeserver = channel_api_base(cfg, "");
+            if homeserver.is_empty() {
+                anyhow::bail!("matrix adapter requires apiBase (homeserver url)");
+            }
+            Ok(Arc::new(MatrixAdapter::new(homeserver, &token, timeout)?))
+        }
+        "webhook" | "http" => {
+            let url = cfg
+                .url
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .context("webhook adapter requires url")?;
+            let mut headers = cfg.headers.clone().unwrap_or_default();
+            if let Some(token) = resolve_secret(cfg.token.as_deref(), cfg.token_env.as_deref()) {
+                headers
+                    .entry("Authorization".to_string())
+                    .or_insert_with(|| format!("Bearer {token}"));
+            }
+            Ok(Arc::new(WebhookAdapter::new(url, headers, timeout)?))
+        }
+        other => anyhow::bail!("unsupported channel adapter kind {other:?}"),
+    }
+}
+
+pub(crate) fn resolve_secret(value: Option<&str>, env: Option<&str>) -> Option<String> {
+    if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+        return Some(value.to_string());
+    }
+    let name = env.map(str::trim).filter(|value| !value.is_empty())?;
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub(crate) fn build_client(timeout: Duration) -> Result<Client> {
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .context("build channel adapter http client")
+}
+
+fn message_text(message: &Value) -> Option<&str> {
+    message
+        .get("text")
+        .or_else(|| message.get("message"))
+        .and_then(|v| v.as_str())
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn message_attachments(message: &Value) -> Vec<Value> {
+    message
+        .get("attachments")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Telegram Bot API adapter: `sendMessage` for text (split at 4096 chars) and
+/// `sendDocument` for attachments that carry a public `url`.
+pub struct TelegramAdapter {
+    client: Client,
+    base_url: String,
+}
+
+impl TelegramAdapter {
+    pub fn new(api_base: &str, token: &str, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: build_client(timeout)?,
+            base_url: format!("{}/bot{}", api_base.trim_end_matches('/'), token),
+        })
+    }
+
+    fn call(&self, method: &str, body: &Value) -> Result<Value> {
+        let url = format!("{}/{method}", self.base_url);
+        let resp =
+            self.client.post(&url).json(body).send().map_err(|err| {
+                anyhow::anyhow!("telegram {method} failed: {}", err.without_url())
+            })?;
+        let status = resp.status();
+        let value = resp.json::<Value>().unwrap_or(Value::Null);
+        if value.get("ok").and_then(|v| v.as_bool()) == Some(true) {
+            return Ok(value.get("result").cloned().unwrap_or(Value::Null));
+        }
+        let description = value
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+        anyhow::bail!("telegram {method} failed: {description}")
+    }
+}
+
+impl ChannelAdapter for TelegramAdapter {
+    fn kind(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn send(&self, message: &Value) -> Result<Value> {
+        let chat_id = message
+            .get("to")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .context("telegram send requires to")?;
+        let thread_id = message
+            .get("threadId")
+            .and_then(|v| v.as_str())
+            .and_then(|value| value.trim().parse::<i64>().ok());
+        let attachments = message_attachments(message);
+        let mut urls = Vec::new();
+        for attachment in &attachments {
+            let url = attachment
+                .get("url")
+                .and_then(|v| v.as_str())
+                .filter(|value| !value.trim().is_empty())
+                .context("telegram adapter only supports attachments with a url")?;
+            urls.push(url.to_string());
+        }
+
+        let mut message_ids = Vec::new();
+        let mut push_result = |result: Value| {
+            if let Some(id) = result.get("message_id").cloned() {
+                message_ids.push(id);
+            }
+        };
+        if let Some(text) = message_text(message) {
+            for chunk in split_text(text, TELEGRAM_MAX_TEXT_CHARS) {
+                let mut body = json!({ "chat_id": chat_id, "text": chunk });
+                if let Some(thread_id) = thread_id {
+                    body["message_thread_id"] = json!(thread_id);
+                }
+                push_result(self.call("sendMessage", &body)?);
+            }
+        }
+        for url in urls {
+            let mut body = json!({ "chat_id": chat_id, "document": url });
+            if let Some(thread_id) = thread_id {
+                body["message_thread_id"] = json!(thread_id);
+            }
+            push_result(self.call("sendDocument", &body)?);
+        }
+        Ok(json!({ "messageIds": message_ids }))
+    }
+
+    fn capabilities(&self) -> ChannelCapabilities {
+        ChannelCapabilities {
+            text: true,
+            attachments: true,
+            threads: true,
+            max_text_chars: Some(TELEGRAM_MAX_TEXT_CHARS),
+        }
+    }
+
+    fn health(&self) -> ChannelHealth {
+        match self.call("getMe", &json!({})) {
+            Ok(me) => ChannelHealth::ok(
+                me.get("username")
+                    .and_then(|v| v.as_str())
+                    .map(|name| format!("@{name}")),
+            ),
+            Err(err) => ChannelHealth::failed(err.to_string()),
+        }
+    }
+}
+
+/// Generic webhook adapter: POSTs the outbox entry as JSON. Any 2xx response
+/// counts as delivered unless the body is JSON with `ok: false`.
+pub struct WebhookAdapter {
+    client: Client,
+    url: String,
+    headers: HashMap<String, String>,
+    last: Mutex<Option<ChannelHealth>>,
+}
+
+impl WebhookAdapter {
+    pub fn new(url: &str, headers: HashMap<String, String>, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: build_client(timeout)?,
+            url: url.to_string(),
+            headers,
+            last: Mutex::new(None),
+        })
+    }
+
+    fn post(&self, message: &Value) -> Result<Value> {
+        let mut request = self.client.post(&self.url).json(message);
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let resp = request
+            .send()
+            .map_err(|err| anyhow::anyhow!("webhook send failed: {}", err.without_url()))?;
+        let status = resp.status();
+        let body = resp.json::<Value>().ok();
+        if !status.is_success() {
+            let detail = body
+                .as_ref()
+                .and_then(|value| value.get("error"))
+                .and_then(|v| v.as_str())
+                .map(|err| format!(": {err}"))
+                .unwrap_or_default();
+            anyhow::bail!("webhook returned HTTP {}{detail}", status.as_u16());
+        }
+        match body {
+            Some(value) if value.get("ok").and_then(|v| v.as_bool()) == Some(false) => {
+                let err = value
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("webhook rejected message");
+                anyhow::bail!("{err}")
+            }
+            Some(value) => Ok(value),
+            None => Ok(json!({ "ok": true, "status": status.as_u16() })),
+        }
+    }
+}
+
+impl ChannelAdapter for WebhookAdapter {
+    fn kind(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, message: &Value) -> Result<Value> {
+        let result = self.post(message);
+        let health = match &result {
+            Ok(_) => ChannelHealth::ok(None),
+            Err(err) => ChannelHealth::failed(err.to_string()),
+        };
+        if let Ok(mut last) = self.last.lock() {
+            *last = Some(health);
+        }
+        result
+    }
+
+    fn capabilities(&self) -> ChannelCapabilities {
+        ChannelCapabilities {
+            text: true,
+            attachments: true,
+            threads: true,
+            max_text_chars: None,
+        }
+    }
+
+    /// Webhooks have no standard probe, so health reflects the last delivery.
+    fn health(&self) -> ChannelHealth {
+        self.last
+            .lock()
+            .ok()
+            .and_then(|last| last.clone())
+            .unwrap_or_else(|| ChannelHealth::ok(Some("no deliveries yet".to_string())))
+    }
+}
+
+/// Text plus one line per URL attachment, for channels whose send call only
+/// takes a message body.
+fn text_with_attachment_urls(message: &Value, channel: &str) -> Result<String> {
+    let mut lines = Vec::new();
+    if let Some(text) = message_text(message) {
+        lines.push(text.to_string());
+    }
+    for attachment in message_attachments(message) {
+        let url = attachment
+            .get("url")
+            .and_then(|v| v.as_str())
+            .filter(|value| !value.trim().is_empty())
+            .with_context(|| format!("{channel} adapter only supports attachments with a url"))?;
+        lines.push(url.to_string());
+    }
+    if lines.is_empty() {
+        anyhow::bail!("{channel} send requires text or attachments");
+    }
+    Ok(lines.join("\n"))
+}
+
+fn message_target<'a>(message: &'a Value, channel: &str) -> Result<&'a str> {
+    message
+        .get("to")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .with_context(|| format!("{channel} send requires to"))
+}
+
+/// Slack Web API adapter: `chat.postMessage` with the bot token, replying in
+/// the thread named by `threadId` (a parent message `ts`).
+pub struct SlackAdapter {
+    client: Client,
+    api_base: String,
+    token: String,
+}
+
+impl SlackAdapter {
+    pub fn new(api_base: &str, token: &str, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: build_client(timeout)?,
+            api_base: api_base.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        })
+    }
+
+    fn call(&self, method: &str, body: &Value) -> Result<Value> {
+        let resp = self
+            .client
+            .post(format!("{}/{method}", self.api_base))
+            .bearer_auth(&self.token)
+            .json(body)
+            .send()
+            .map_err(|err| anyhow::anyhow!("slack {method} failed: {}", err.without_url()))?;
+        let status = resp.status();
+        let value = resp.json::<Value>().unwrap_or(Value::Null);
+        if value.get("ok").and_then(|v| v.as_bool()) == Some(true) {
+            return Ok(value);
+        }
+        let err = value
+            .get("error")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+        anyhow::bail!("slack {method} failed: {err}")
+    }
+}
+
+impl ChannelAdapter for SlackAdapter {
+    fn kind(&self) -> &'static str {
+        "slack"
+    }
+
+    fn send(&self, message: &Value) -> Result<Value> {
+        let channel = message_target(message, "slack")?;
+        let mut body = json!({
+            "channel": channel,
+            "text": text_with_attachment_urls(message, "slack")?,
+        });
+        if let Some(thread_ts) = message.get("threadId").and_then(|v| v.as_str()) {
+            body["thread_ts"] = Value::String(thread_ts.to_string());
+        }
+        let value = self.call("chat.postMessage", &body)?;
+        Ok(json!({ "channel": value.get("channel"), "ts": value.get("ts") }))
+    }
+
+    fn capabilities(&self) -> ChannelCapabilities {
+        ChannelCapabilities {
+            text: true,
+            attachments: false,
+            threads: true,
+            max_text_chars: None,
+        }
+    }
+
+    fn health(&self) -> ChannelHealth {
+        match self.call("auth.test", &json!({})) {
+            Ok(value) => ChannelHealth::ok(
+                value
+                    .get("user")
+                    .and_then(|v| v.as_str())
+                    .map(|user| format!("@{user}")),
+            ),
+            Err(err) => ChannelHealth::failed(err.to_string()),
+        }
+    }
+}
+
+/// Matrix client-server adapter: sends `m.text` room messages, with `to`
+/// being the room id.
+pub struct MatrixAdapter {
+    client: Client,
+    homeserver: String,
+    token: String,
+}
+
+impl MatrixAdapter {
+    pub fn new(homeserver: &str, token: &str, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: build_client(timeout)?,
+            homeserver: homeserver.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        })
+    }
+}
+
+/// `<homeserver>/_matrix/client/v3/<segments...>` with each segment escaped.
+pub(crate) fn matrix_url(homeserver: &str, segments: &[&str]) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(homeserver)
+        .with_context(|| format!("invalid matrix homeserver url {homeserver}"))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("invalid matrix homeserver url {homeserver}"))?
+        .pop_if_empty()
+        .extend(["_matrix", "client", "v3"])
+        .extend(segments);
+    Ok(url)
+}
+
+pub(crate) fn matrix_whoami(client: &Client, homeserver: &str, token: &str) -> Result<String> {
+    let resp = client
+        .get(matrix_url(homeserver, &["account", "whoami"])?)
+        .bearer_auth(token)
+        .send()
+        .map_err(|err| anyhow::anyhow!("matrix whoami failed: {}", err.without_url()))?;
+    let status = resp.status();
+    let value = resp.json::<Value>().unwrap_or(Value::Null);
+    value
+        .get("user_id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .with_context(|| {
+            let err = value
+                .get("error")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+            format!("matrix whoami failed: {err}")
+        })
+}
+
+impl ChannelAdapter for MatrixAdapter {
+    fn kind(&self) -> &'static str {
+        "matrix"
+    }
+
+    fn send(&self, message: &Value) -> Result<Value> {
+        let room_id = message_target(message, "matrix")?;
+        let txn_id = message
+            .get("id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let url = matrix_url(
+            &self.homeserver,
+            &["rooms", room_id, "send", "m.room.message", &txn_id],
+        )?;
+        let body = json!({
+            "msgtype": "m.text",
+            "body": text_with_attachment_urls(message, "matrix")?,
+        });
+        let resp = self
+            .client
+            .put(url)
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .map_err(|err| anyhow::anyhow!("matrix send failed: {}", err.without_url()))?;
+        let status = resp.status();
+        let value = resp.json::<Value>().unwrap_or(Value::Null);
+        if !status.is_success() {
+            let err = value
+                .get("error")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+            anyhow::bail!("matrix send failed: {err}");
+        }
+        Ok(json!({ "eventId": value.get("event_id") }))
+    }
+
+    fn capabilities(&self) -> ChannelCapabilities {
+        ChannelCapabilities {
+            text: true,
+            attachments: false,
+            threads: false,
+            max_text_chars: None,
+        }
+    }
+
+    fn health(&self) -> ChannelHealth {
+        match matrix_whoami(&self.client, &self.homeserver, &self.token) {
+            Ok(user_id) => ChannelHealth::ok(Some(user_id)),
+            Err(err) => ChannelHealth::failed(err.to_string()),
+        }
+    }
+}
+
+fn split_text(text: &str, max_chars: usize) -> Vec<String> {
+    let chars = text.chars().collect::<Vec<_>>();
+    chars
+        .chunks(max_chars.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::io::Read;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use serde_json::Value;
+    use tiny_http::{Header, Response, Server};
+
+    /// A request captured by [`MockHttpServer`].
+    #[derive(Debug)]
+    pub struct CapturedRequest {
+        pub url: String,
+        pub headers: Vec<(String, String)>,
+        pub body: Value,
+    }
+
+    /// Local HTTP server that answers each request with the next canned
+    /// `(status, body)` pair and records what it received.
+    pub struct MockHttpServer {
+        pub base_url: String,
+        requests: mpsc::Receiver<CapturedRequest>,
+    }
+
+    impl MockHttpServer {
+        pub fn start(responses: Vec<(u16, Value)>) -> Self {
+            let server = Server::http("127.0.0.1:0").expect("bind mock server");
+            let port = server
+                .server_addr()
+                .to_ip()
+                .map(|addr| addr.port())
+                .expect("mock server port");
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                for (status, body) in responses {
+                    let Ok(mut request) = server.recv() else {
+                        return;
+                    };
+                    let mut raw = String::new();
+                    let _ = request.as_reader().read_to_string(&mut raw);
+                    let captured = CapturedRequest {
+                        url: request.url().to_string(),
+                        headers: request
+                            .headers()
+                            .iter()
+                            .map(|h| (h.field.to_string(), h.value.to_string()))
+                            .collect(),
+                        body: serde_json::from_str(&raw).unwrap_or(Value::Null),
+                    };
+                    let _ = tx.send(captured);
+                    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                        .expect("content-type header");
+                    let response = Response::from_string(body.to_string())
+                        .with_status_code(status)
+                        .with_header(header);
+                    let _ = request.respond(response);
+                }
+            });
+            Self {
+                base_url: format!("http://127.0.0.1:{port}"),
+                requests: rx,
+            }
+        }
+
+        pub fn requests(&self) -> Vec<CapturedRequest> {
+            self.requests.try_iter().collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::MockHttpServer;
+    use super::*;
+
+    #[test]
+    fn telegram_adapter_sends_text_and_documents() -> Result<()> {
+        let mock = MockHttpServer::start(vec![
+            (200, json!({ "ok": true, "result": { "message_id": 11 } })),
+            (200, json!({ "ok": true, "result": { "message_id": 12 } })),
+            (
+                400,
+                json!({ "ok": false, "error_code": 400, "description": "Bad Request: chat not found" }),
+            ),
+        ]);
+        let adapter = TelegramAdapter::new(&mock.base_url, "123:abc", Duration::from_secs(5))?;
+        assert_eq!(adapter.capabilities().max_text_chars, Some(4096));
+
+        let result = adapter.send(&json!({
+            "to": "42",
+            "text": "hello",
+            "threadId": "7",
+            "attachments": [{ "url": "https://example.com/report.pdf" }]
+        }))?;
+        assert_eq!(result, json!({ "messageIds": [11, 12] }));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, "/bot123:abc/sendMessage");
+        assert_eq!(
+            requests[0].body,
+            json!({ "chat_id": "42", "text": "hello", "message_thread_id": 7 })
+        );
+        assert_eq!(requests[1].url, "/bot123:abc/sendDocument");
+        assert_eq!(
+            requests[1].body["document"],
+            "https://example.com/report.pdf"
+        );
+
+        let err = adapter
+            .send(&json!({ "to": "missing", "text": "hi" }))
+            .unwrap_err();
+        assert!(err.to_string().contains("chat not found"));
+
+        let err = adapter
+            .send(&json!({ "to": "42", "attachments": [{ "id": "local" }] }))
+            .unwrap_err();
+        assert!(err.to_string().contains("url"));
+        Ok(())
+    }
+
+    #[test]
+    fn webhook_adapter_reports_status_and_health() -> Result<()> {
+        let mock = MockHttpServer::start(vec![
+            (200, json!({ "ok": true, "id": "remote-1" })),
+            (503, json!({ "error": "upstream down" })),
+        ]);
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), "Bearer hook".to_string());
+        let adapter = WebhookAdapter::new(
+            &format!("{}/hook", mock.base_url),
+            headers,
+            Duration::from_secs(5),
+        )?;
+        assert!(adapter.health().ok);
+
+        let result = adapter.send(&json!({ "to": "ops", "text": "deploy done" }))?;
+        assert_eq!(result["id"], "remote-1");
+        let err = adapter
+            .send(&json!({ "to": "ops", "text": "again" }))
+            .unwrap_err();
+        assert!(err.to_string().contains("HTTP 503: upstream down"));
+        let health = adapter.health();
+        assert!(!health.ok);
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].url, "/hook");
+        assert_eq!(requests[0].body["text"], "deploy done");
+        assert!(requests[0]
+            .headers
+            .iter()
+            .any(|(name, value)| name.eq_ignore_ascii_case("authorization")
+                && value == "Bearer hook"));
+        Ok(())
+    }
+
+    #[test]
+    fn slack_and_matrix_adapters_post_messages() -> Result<()> {
+        let mock = MockHttpServer::start(vec![
+            (200, json!({ "ok": true, "channel": "C1", "ts": "1700.01" })),
+            (200, json!({ "ok": false, "error": "channel_not_found" })),
+            (200, json!({ "event_id": "$ev1" })),
+        ]);
+        let slack = SlackAdapter::new(&mock.base_url, "xoxb-1", Duration::from_secs(5))?;
+        let result = slack.send(&json!({
+            "to": "C1",
+            "text": "hi",
+            "threadId": "1699.99",
+            "attachments": [{ "url": "https://example.com/a.png" }]
+        }))?;
+        assert_eq!(result, json!({ "channel": "C1", "ts": "1700.01" }));
+        let err = slack
+            .send(&json!({ "to": "C404", "text": "hi" }))
+            .unwrap_err();
+        assert!(err.to_string().contains("channel_not_found"));
+
+        let matrix = MatrixAdapter::new(&mock.base_url, "mx-token", Duration::from_secs(5))?;
+        let result =
+            matrix.send(&json!({ "id": "m-1", "to": "!room:example.org", "text": "hey" }))?;
+        assert_eq!(result, json!({ "eventId": "$ev1" }));
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].url, "/chat.postMessage");
+        assert_eq!(
+            requests[0].body,
+            json!({
+                "channel": "C1",
+                "text": "hi\nhttps://example.com/a.png",
+                "thread_ts": "1699.99"
+            })
+        );
+        assert_eq!(
+            requests[2].url,
+            "/_matrix/client/v3/rooms/!room:example.org/send/m.room.message/m-1"
+        );
+        assert_eq!(
+            requests[2].body,
+            json!({ "msgtype": "m.text", "body": "hey" })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn registry_resolves_account_specific_adapters() -> Result<()> {
+        let cfg: GatewayConfig = serde_json::from_value(json!({
+            "channels": {
+                "telegram": { "token": "t1" },
+                "telegram:work": { "kind": "telegram", "token": "t2" },
+                "tg-home": { "kind": "telegram", "token": "t3", "accountId": "Home" },
+                "ops": { "kind": "webhook", "url": "http://127.0.0.1:9/hook" },
+                "slack": { "kind": "webhook", "url": "http://127.0.0.1:9/s", "enabled": false }
+            }
+        }))?;
+        let registry = ChannelRegistry::from_config(&cfg)?;
+        assert!(registry.resolve("slack", None).is_none());
+        assert_eq!(
+            registry.resolve("ops", None).map(|a| a.kind()),
+            Some("webhook")
+        );
+        assert!(registry.resolve("telegram", Some("work")).is_some());
+        assert!(registry.resolve("telegram", Some("other")).is_some());
+        let home = registry
+            .resolve("telegram", Some("home"))
+            .expect("home adapter");
+        let by_key = registry.resolve("tg-home", None).expect("tg-home adapter");
+        assert!(Arc::ptr_eq(&home, &by_key));
+
+        let bad: GatewayConfig = serde_json::from_value(json!({
+            "channels": { "ops": { "kind": "webhook" } }
+        }))?;
+        assert!(ChannelRegistry::from_config(&bad).is_err());
+        Ok(())
+    }
+}
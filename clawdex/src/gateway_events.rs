@@ -0,0 +1,538 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::config::ClawdPaths;
+use crate::gateway::gateway_dir;
+use crate::task_db::TaskStore;
+use crate::util::now_ms;
+
+const DB_FILE: &str = "events.sqlite";
+const SCHEMA_VERSION: i64 = 1;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of most recent events kept for resuming subscribers.
+const RETAINED_EVENTS: i64 = 10_000;
+const PRUNE_EVERY: i64 = 500;
+const TASK_EVENT_CURSOR: &str = "task_events";
+const TASK_EVENT_BATCH: usize = 500;
+const TASK_EVENT_POLL: Duration = Duration::from_millis(1_000);
+pub const DELIVERY_BATCH: usize = 200;
+
+pub const KIND_INBOX: &str = "inbox";
+pub const KIND_RECEIPT: &str = "receipt";
+pub const KIND_PRESENCE: &str = "presence";
+pub const KIND_TASK_RUN: &str = "task.run";
+pub const EVENT_KINDS: &[&str] = &[KIND_INBOX, KIND_RECEIPT, KIND_PRESENCE, KIND_TASK_RUN];
+
+pub fn db_path(paths: &ClawdPaths) -> PathBuf {
+    gateway_dir(paths).join(DB_FILE)
+}
+
+/// One journaled gateway event. `seq` is assigned by the journal and only
+/// ever increases, so it doubles as the resume cursor for subscribers.
+#[derive(Debug, Clone)]
+pub struct GatewayEvent {
+    pub seq: i64,
+    pub kind: String,
+    pub ts_ms: i64,
+    pub session_key: Option<String>,
+    pub channel: Option<String>,
+    pub run_id: Option<String>,
+    pub payload: Value,
+}
+
+impl GatewayEvent {
+    /// The server-initiated WebSocket frame for this event.
+    pub fn to_frame(&self, subscription_id: &str) -> Value {
+        json!({
+            "type": "event",
+            "event": self.kind,
+            "seq": self.seq,
+            "subscriptionId": subscription_id,
+            "tsMs": self.ts_ms,
+            "payload": self.payload,
+        })
+    }
+}
+
+/// Append-only journal of gateway events in `gateway/events.sqlite`.
+///
+/// The journal is shared by every process writing to the state dir (the
+/// gateway, the daemon recording receipts), so WebSocket subscribers see
+/// events no matter which process produced them. Only the most recent
+/// events are retained; older sequence numbers are reported as a gap.
+pub struct EventJournal {
+    conn: Connection,
+}
+
+impl EventJournal {
+    pub fn open(paths: &ClawdPaths) -> Result<Self> {
+        let dir = gateway_dir(paths);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("create gateway dir {}", dir.display()))?;
+        let conn = Connection::open(db_path(paths)).context("open event journal")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL").ok();
+        conn.pragma_update(None, "synchronous", "NORMAL").ok();
+        let journal = EventJournal { conn };
+        journal.migrate()?;
+        Ok(journal)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS gateway_events (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                ts_ms INTEGER NOT NULL,
+                session_key TEXT,
+                channel TEXT,
+                run_id TEXT,
+                payload_json TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS gateway_event_cursors (
+                name TEXT PRIMARY KEY,
+                value INTEGER NOT NULL
+            );
+            "#,
+        )?;
+        self.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    /// Appends an event and returns its sequence number. `sessionKey`,
+    /// `channel` and `runId` are lifted from the payload for filtering.
+    pub fn append(&self, kind: &str, payload: &Value) -> Result<i64> {
+        let field = |key: &str| payload.get(key).and_then(|v| v.as_str());
+        self.conn.execute(
+            "INSERT INTO gateway_events(kind, ts_ms, session_key, channel, run_id, payload_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                kind,
+                now_ms(),
+                field("sessionKey"),
+                field("channel"),
+                field("runId"),
+                serde_json::to_string(payload)?,
+            ],
+        )?;
+        let seq = self.conn.last_insert_rowid();
+        if seq % PRUNE_EVERY == 0 {
+            self.prune(RETAINED_EVENTS)?;
+        }
+        Ok(seq)
+    }
+
+    /// Events with a sequence number above `after`, oldest first.
+    pub fn after(&self, after: i64, limit: usize) -> Result<Vec<GatewayEvent>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT seq, kind, ts_ms, session_key, channel, run_id, payload_json
+             FROM gateway_events WHERE seq > ?1 ORDER BY seq ASC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![after, limit as i64], |row| {
+            let payload_json: String = row.get(6)?;
+            Ok(GatewayEvent {
+                seq: row.get(0)?,
+                kind: row.get(1)?,
+                ts_ms: row.get(2)?,
+                session_key: row.get(3)?,
+                channel: row.get(4)?,
+                run_id: row.get(5)?,
+                payload: serde_json::from_str(&payload_json).unwrap_or(Value::Null),
+            })
+        })?;
+        let mut events = Vec::new();
+        for row in rows {
+            events.push(row?);
+        }
+        Ok(events)
+    }
+
+    /// Highest sequence number ever assigned (0 when nothing was journaled).
+    pub fn latest_seq(&self) -> Result<i64> {
+        let seq: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT seq FROM sqlite_sequence WHERE name = 'gateway_events'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(seq.unwrap_or(0))
+    }
+
+    /// Lowest sequence number still retained, if any.
+    pub fn oldest_seq(&self) -> Result<Option<i64>> {
+        Ok(self
+            .conn
+            .query_row("SELECT MIN(seq) FROM gateway_events", [], |row| row.get(0))?)
+    }
+
+    pub fn prune(&self, keep: i64) -> Result<usize> {
+        let removed = self.conn.execute(
+            "DELETE FROM gateway_events WHERE seq <= (SELECT MAX(seq) FROM gateway_events) - ?1",
+            params![keep],
+        )?;
+        Ok(removed)
+    }
+
+    /// Copies task-run events recorded since the last call into the journal.
+    /// The first call only records the current position, so task history is
+    /// not replayed.
+    pub fn import_task_events(&self, tasks: &TaskStore) -> Result<usize> {
+        let cursor: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT value FROM gateway_event_cursors WHERE name = ?1",
+                params![TASK_EVENT_CURSOR],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(mut cursor) = cursor else {
+            self.set_cursor(TASK_EVENT_CURSOR, tasks.max_event_rowid()?)?;
+            return Ok(0);
+        };
+        let mut imported = 0;
+        loop {
+            let batch = tasks.list_events_after_rowid(cursor, TASK_EVENT_BATCH)?;
+            let Some((last, _)) = batch.last() else {
+                break;
+            };
+            cursor = *last;
+            for (_, event) in &batch {
+                self.append(
+                    KIND_TASK_RUN,
+                    &json!({
+                        "id": event.id,
+                        "runId": event.task_run_id,
+                        "kind": event.kind,
+                        "tsMs": event.ts_ms,
+                        "payload": event.payload,
+                    }),
+                )?;
+            }
+            imported += batch.len();
+            self.set_cursor(TASK_EVENT_CURSOR, cursor)?;
+            if batch.len() < TASK_EVENT_BATCH {
+                break;
+            }
+        }
+        Ok(imported)
+    }
+
+    fn set_cursor(&self, name: &str, value: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO gateway_event_cursors(name, value) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET value = excluded.value",
+            params![name, value],
+        )?;
+        Ok(())
+    }
+}
+
+/// Journals an event, logging (rather than failing the caller) on error.
+/// The journal connection is opened once per state dir and reused.
+pub fn publish(paths: &ClawdPaths, kind: &str, payload: &Value) {
+    static JOURNALS: OnceLock<Mutex<HashMap<PathBuf, EventJournal>>> = OnceLock::new();
+    let mut journals = JOURNALS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let key = db_path(paths);
+    let result = match journals.entry(key.clone()) {
+        Entry::Occupied(entry) => entry.get().append(kind, payload),
+        Entry::Vacant(entry) => EventJournal::open(paths)
+            .and_then(|journal| entry.insert(journal).append(kind, payload)),
+    };
+    if let Err(err) = result {
+        // Reopen on the next event in case the database was replaced underneath us.
+        journals.remove(&key);
+        eprintln!("[clawdex][gateway] failed to journal {kind} event: {err}");
+    }
+}
+
+/// Tails task-run events into the journal in the background.
+pub fn start_task_event_pump(paths: &ClawdPaths) {
+    let paths = paths.clone();
+    std::thread::spawn(move || {
+        let mut stores: Option<(EventJournal, TaskStore)> = None;
+        loop {
+            if stores.is_none() {
+                match EventJournal::open(&paths)
+                    .and_then(|journal| TaskStore::open(&paths).map(|tasks| (journal, tasks)))
+                {
+                    Ok(opened) => stores = Some(opened),
+                    Err(err) => eprintln!("[clawdex][gateway] open task event pump failed: {err}"),
+                }
+            }
+            if let Some((journal, tasks)) = stores.as_ref() {
+                if let Err(err) = journal.import_task_events(tasks) {
+                    eprintln!("[clawdex][gateway] import task events failed: {err}");
+                    stores = None;
+                }
+            }
+            std::thread::sleep(TASK_EVENT_POLL);
+        }
+    });
+}
+
+/// Which events a subscription receives. Empty `kinds` means every kind;
+/// each set filter field must match the event's own value, so events that
+/// do not carry the field (e.g. presence for a `sessionKey` filter) are
+/// skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    pub kinds: HashSet<String>,
+    pub session_key: Option<String>,
+    pub channel: Option<String>,
+    pub run_id: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &GatewayEvent) -> bool {
+        fn field_matches(filter: &Option<String>, value: &Option<String>) -> bool {
+            filter.is_none() || filter == value
+        }
+        (self.kinds.is_empty() || self.kinds.contains(&event.kind))
+            && field_matches(&self.session_key, &event.session_key)
+            && field_matches(&self.channel, &event.channel)
+            && field_matches(&self.run_id, &event.run_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub id: String,
+    pub filter: EventFilter,
+    /// Sequence number of the last journal event this subscription has seen.
+    pub cursor: i64,
+}
+
+/// Parses `events.subscribe` params: `kinds`, `filters` (`sessionKey`,
+/// `channel`, `runId`) and an optional `since` sequence number to resume
+/// from.
+pub fn parse_subscribe_params(params: &Value) -> Result<(EventFilter, Option<i64>)> {
+    let mut filter = EventFilter::default();
+    if let Some(kinds) = params.get("kinds").filter(|v| !v.is_null()) {
+        let kinds = kinds.as_array().context("kinds must be an array")?;
+        for kind in kinds {
+            let kind = kind.as_str().context("kinds must be strings")?.trim();
+            if !EVENT_KINDS.contains(&kind) {
+                return Err(anyhow::anyhow!(
+                    "unknown event kind {kind:?} (expected one of {})",
+                    EVENT_KINDS.join(", ")
+                ));
+            }
+            filter.kinds.insert(kind.to_string());
+        }
+    }
+    let filters = params.get("filters").unwrap_or(params);
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| filters.get(*key))
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    };
+    filter.session_key = text(&["sessionKey", "session_key"]);
+    filter.channel = text(&["channel"]);
+    filter.run_id = text(&["runId", "run_id"]);
+    let since = match params.get("since").filter(|v| !v.is_null()) {
+        Some(value) => Some(
+            value
+                .as_i64()
+                .filter(|seq| *seq >= 0)
+                .context("since must be a non-negative sequence number")?,
+        ),
+        None => None,
+    };
+    Ok((filter, since))
+}
+
+/// Per-connection subscription state for the WebSocket gateway.
+#[derive(Debug, Default)]
+pub struct EventSubscriptions {
+    subscriptions: Vec<Subscription>,
+}
+
+impl EventSubscriptions {
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+
+    /// Adds a subscription and returns the `events.subscribe` response.
+    /// Without `since` only new events are delivered. With it, retained
+    /// events after `since` are replayed first; `gap` reports that some
+    /// were already pruned.
+    pub fn subscribe(&mut self, paths: &ClawdPaths, params: &Value) -> Result<Value> {
+        let (filter, since) = parse_subscribe_params(params)?;
+        let journal = EventJournal::open(paths)?;
+        let latest = journal.latest_seq()?;
+        let cursor = since.map(|seq| seq.min(latest)).unwrap_or(latest);
+        let gap = match journal.oldest_seq()? {
+            Some(oldest) => since.is_some() && cursor + 1 < oldest,
+            None => since.is_some() && cursor < latest,
+        };
+        let id = Uuid::new_v4().to_string();
+        let mut kinds = filter.kinds.iter().cloned().collect::<Vec<_>>();
+        kinds.sort();
+        self.subscriptions.push(Subscription {
+            id: id.clone(),
+            filter,
+            cursor,
+        });
+        Ok(json!({
+            "subscriptionId": id,
+            "kinds": kinds,
+            "seq": latest,
+            "since": cursor,
+            "gap": gap,
+        }))
+    }
+
+    pub fn unsubscribe(&mut self, params: &Value) -> Result<Value> {
+        let id = params
+            .get("subscriptionId")
+            .or_else(|| params.get("id"))
+            .and_then(|v| v.as_str())
+            .context("events.unsubscribe requires subscriptionId")?;
+        let before = self.subscriptions.len();
+        self.subscriptions.retain(|sub| sub.id != id);
+        Ok(json!({ "removed": self.subscriptions.len() < before }))
+    }
+
+    /// Event frames for every subscription, advancing each cursor past the
+    /// events it has now seen (matching or not).
+    pub fn poll(&mut self, journal: &EventJournal) -> Result<Vec<Value>> {
+        let mut frames = Vec::new();
+        for sub in &mut self.subscriptions {
+            let events = journal.after(sub.cursor, DELIVERY_BATCH)?;
+            for event in events {
+                sub.cursor = event.seq;
+                if sub.filter.matches(&event) {
+                    frames.push(event.to_frame(&sub.id));
+                }
+            }
+        }
+        Ok(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::test_paths;
+
+    #[test]
+    fn subscriptions_filter_and_resume_from_sequence() -> Result<()> {
+        let (base, paths) = test_paths("gateway-events");
+        let journal = EventJournal::open(&paths)?;
+        let first = journal.append(
+            KIND_INBOX,
+            &json!({ "sessionKey": "telegram:1", "channel": "telegram", "text": "hi" }),
+        )?;
+
+        let mut live = EventSubscriptions::default();
+        let ack = live.subscribe(&paths, &json!({ "kinds": ["receipt"] }))?;
+        assert_eq!(ack["seq"], first);
+        assert_eq!(ack["gap"], false);
+
+        let mut resumed = EventSubscriptions::default();
+        resumed.subscribe(
+            &paths,
+            &json!({ "since": 0, "filters": { "sessionKey": "telegram:1" } }),
+        )?;
+
+        journal.append(KIND_PRESENCE, &json!({ "key": "mac", "reason": "connect" }))?;
+        let receipt = journal.append(
+            KIND_RECEIPT,
+            &json!({ "sessionKey": "telegram:1", "status": "sent" }),
+        )?;
+
+        let frames = live.poll(&journal)?;
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0]["type"], "event");
+        assert_eq!(frames[0]["event"], "receipt");
+        assert_eq!(frames[0]["seq"], receipt);
+
+        let frames = resumed.poll(&journal)?;
+        let seqs = frames.iter().map(|f| f["seq"].clone()).collect::<Vec<_>>();
+        assert_eq!(seqs, vec![json!(first), json!(receipt)]);
+        assert!(resumed.poll(&journal)?.is_empty());
+
+        assert!(live
+            .subscribe(&paths, &json!({ "kinds": ["bogus"] }))
+            .is_err());
+        let id = ack["subscriptionId"].as_str().unwrap_or_default();
+        assert_eq!(
+            live.unsubscribe(&json!({ "subscriptionId": id }))?["removed"],
+            true
+        );
+        assert!(live.is_empty());
+
+        journal.prune(1)?;
+        let ack = resumed.subscribe(&paths, &json!({ "since": 0 }))?;
+        assert_eq!(ack["gap"], true);
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[test]
+    fn publish_keeps_one_journal_per_state_dir() -> Result<()> {
+        let (base, paths) = test_paths("gateway-publish");
+        let (other_base, other) = test_paths("gateway-publish-other");
+        publish(&paths, KIND_INBOX, &json!({ "text": "one" }));
+        publish(&other, KIND_INBOX, &json!({ "text": "elsewhere" }));
+        publish(&paths, KIND_RECEIPT, &json!({ "status": "sent" }));
+
+        let events = EventJournal::open(&paths)?.after(0, 10)?;
+        let kinds = events.iter().map(|e| e.kind.as_str()).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![KIND_INBOX, KIND_RECEIPT]);
+        assert_eq!(EventJournal::open(&other)?.after(0, 10)?.len(), 1);
+
+        let _ = std::fs::remove_dir_all(base);
+        let _ = std::fs::remove_dir_all(other_base);
+        Ok(())
+    }
+
+    #[test]
+    fn imports_task_events_after_initial_position() -> Result<()> {
+        let (base, paths) = test_paths("gateway-task-events");
+        let tasks = TaskStore::open(&paths)?;
+        let task = tasks.create_task("events")?;
+        let run = tasks.create_run(&task.id, "running", None, None, None)?;
+        tasks.record_event(&run.id, "turn_started", &json!({}))?;
+
+        let journal = EventJournal::open(&paths)?;
+        assert_eq!(journal.import_task_events(&tasks)?, 0);
+        tasks.record_event(&run.id, "turn_completed", &json!({ "ok": true }))?;
+        assert_eq!(journal.import_task_events(&tasks)?, 1);
+        assert_eq!(journal.import_task_events(&tasks)?, 0);
+
+        let events = journal.after(0, 10)?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, KIND_TASK_RUN);
+        assert_eq!(events[0].run_id.as_deref(), Some(run.id.as_str()));
+        assert_eq!(events[0].payload["kind"], "turn_completed");
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+}
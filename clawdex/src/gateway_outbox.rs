@@ -0,0 +1,540 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row, TransactionBehavior};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::config::{ClawdPaths, GatewayConfig};
+use crate::gateway::gateway_dir;
+use crate::util::now_ms;
+
+const DB_FILE: &str = "outbox.sqlite";
+const SCHEMA_VERSION: i64 = 1;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+const DEFAULT_RETRY_BASE_MS: u64 = 2_000;
+const MAX_RETRY_DELAY_MS: u64 = 10 * 60 * 1000;
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_SENDING: &str = "sending";
+pub const STATUS_SENT: &str = "sent";
+pub const STATUS_DEAD: &str = "dead";
+const STATUSES: &[&str] = &[STATUS_PENDING, STATUS_SENDING, STATUS_SENT, STATUS_DEAD];
+
+pub fn db_path(paths: &ClawdPaths) -> PathBuf {
+    gateway_dir(paths).join(DB_FILE)
+}
+
+/// Exponential backoff for failed deliveries: attempt `n` waits
+/// `base * 2^(n-1)` (capped at ten minutes), with the upper half jittered so
+/// a flapping channel does not retry every session in lockstep.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_ms: DEFAULT_RETRY_BASE_MS,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(cfg: &GatewayConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            max_attempts: cfg
+                .outbox_max_attempts
+                .filter(|value| *value > 0)
+                .unwrap_or(defaults.max_attempts),
+            base_ms: cfg.outbox_retry_base_ms.unwrap_or(defaults.base_ms),
+        }
+    }
+
+    /// Delay before retry number `attempts` (1-based); `jitter` is in `[0, 1)`.
+    pub fn delay_ms(&self, attempts: u32, jitter: f64) -> u64 {
+        let exp = attempts.saturating_sub(1).min(32);
+        let delay = self
+            .base_ms
+            .saturating_mul(1u64 << exp)
+            .min(MAX_RETRY_DELAY_MS);
+        let half = delay / 2;
+        half + (half as f64 * jitter.clamp(0.0, 1.0)) as u64
+    }
+}
+
+fn jitter_fraction() -> f64 {
+    (Uuid::new_v4().as_u128() % 10_000) as f64 / 10_000.0
+}
+
+/// A stored outbound message and its delivery state.
+#[derive(Debug, Clone)]
+pub struct OutboxMessage {
+    pub id: String,
+    pub position: i64,
+    pub session_key: String,
+    pub status: String,
+    pub attempts: u32,
+    pub next_attempt_at_ms: i64,
+    pub last_error: Option<String>,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+    pub sent_at_ms: Option<i64>,
+    pub entry: Value,
+}
+
+impl OutboxMessage {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        let raw: String = row.get(10)?;
+        Ok(Self {
+            id: row.get(0)?,
+            position: row.get(1)?,
+            session_key: row.get(2)?,
+            status: row.get(3)?,
+            attempts: row.get(4)?,
+            next_attempt_at_ms: row.get(5)?,
+            last_error: row.get(6)?,
+            created_at_ms: row.get(7)?,
+            updated_at_ms: row.get(8)?,
+            sent_at_ms: row.get(9)?,
+            entry: serde_json::from_str(&raw).unwrap_or(Value::Null),
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "position": self.position,
+            "sessionKey": self.session_key,
+            "channel": self.entry.get("channel"),
+            "to": self.entry.get("to"),
+            "status": self.status,
+            "attempts": self.attempts,
+            "nextAttemptAtMs": self.next_attempt_at_ms,
+            "lastError": self.last_error,
+            "createdAtMs": self.created_at_ms,
+            "updatedAtMs": self.updated_at_ms,
+            "sentAtMs": self.sent_at_ms,
+            "message": self.entry,
+        })
+    }
+}
+
+const MESSAGE_COLUMNS: &str = "id, position, session_key, status, attempts, next_attempt_at_ms, \
+     last_error, created_at_ms, updated_at_ms, sent_at_ms, entry_json";
+
+/// Result of recording a failed delivery attempt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailureOutcome {
+    pub attempts: u32,
+    pub dead: bool,
+    pub next_attempt_at_ms: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct OutboxQuery {
+    pub status: Option<String>,
+    pub session_key: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Durable outbound queue behind `message.send`. Messages are delivered in
+/// `position` order per session: only the oldest undelivered message of a
+/// session is ever claimed, so a message waiting on a retry holds back the
+/// rest of its session. Dead-lettered messages no longer block their session.
+pub struct OutboxStore {
+    conn: Connection,
+}
+
+impl OutboxStore {
+    pub fn open(paths: &ClawdPaths) -> Result<Self> {
+        let dir = gateway_dir(paths);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("create gateway dir {}", dir.display()))?;
+        let conn = Connection::open(db_path(paths)).context("open outbox database")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL").ok();
+        conn.pragma_update(None, "synchronous", "NORMAL").ok();
+        let store = OutboxStore { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS outbox_messages (
+                id TEXT PRIMARY KEY,
+                position INTEGER NOT NULL,
+                session_key TEXT NOT NULL,
+                idempotency_key TEXT,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at_ms INTEGER NOT NULL,
+                last_error TEXT,
+                created_at_ms INTEGER NOT NULL,
+                updated_at_ms INTEGER NOT NULL,
+                sent_at_ms INTEGER,
+                entry_json TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS outbox_session ON outbox_messages(session_key, status, position);
+            CREATE INDEX IF NOT EXISTS outbox_status ON outbox_messages(status, position);
+            "#,
+        )?;
+        self.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    /// Stores a queued outbox entry (as written to `outbox.jsonl`). Returns
+    /// false when the message is already stored. Duplicate sends are caught
+    /// earlier by the gateway's idempotency keys.
+    pub fn enqueue(&self, entry: &Value) -> Result<bool> {
+        let id = entry
+            .get("id")
+            .and_then(|v| v.as_str())
+            .context("outbox entry is missing id")?;
+        let session_key = entry
+            .get("sessionKey")
+            .and_then(|v| v.as_str())
+            .unwrap_or("agent:main:main");
+        let created_at_ms = entry
+            .get("createdAtMs")
+            .and_then(|v| v.as_i64())
+            .unwrap_or_else(now_ms);
+        let inserted = self.conn.execute(
+            "INSERT INTO outbox_messages
+                (id, position, session_key, idempotency_key, status, next_attempt_at_ms,
+                 created_at_ms, updated_at_ms, entry_json)
+             VALUES (?1, (SELECT COALESCE(MAX(position), 0) + 1 FROM outbox_messages),
+                     ?2, ?3, ?4, ?5, ?5, ?5, ?6)
+             ON CONFLICT(id) DO NOTHING",
+            params![
+                id,
+                session_key,
+                entry.get("idempotencyKey").and_then(|v| v.as_str()),
+                STATUS_PENDING,
+                created_at_ms,
+                serde_json::to_string(entry)?,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Claims (marks `sending`) the head message of every session that is due
+    /// at `now`, oldest first.
+    pub fn claim_due(&mut self, now: i64, limit: usize) -> Result<Vec<OutboxMessage>> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("begin outbox transaction")?;
+        let claimed = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT {MESSAGE_COLUMNS} FROM outbox_messages m
+                 WHERE m.status = ?1
+                   AND m.next_attempt_at_ms <= ?2
+                   AND m.position = (
+                       SELECT MIN(position) FROM outbox_messages
+                       WHERE session_key = m.session_key AND status IN (?1, ?3)
+                   )
+                 ORDER BY m.position
+                 LIMIT ?4"
+            ))?;
+            let rows = stmt.query_map(
+                params![STATUS_PENDING, now, STATUS_SENDING, limit as i64],
+                OutboxMessage::from_row,
+            )?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        for message in &claimed {
+            tx.execute(
+                "UPDATE outbox_messages SET status = ?2, updated_at_ms = ?3 WHERE id = ?1",
+                params![message.id, STATUS_SENDING, now],
+            )?;
+        }
+        tx.commit().context("commit outbox transaction")?;
+        Ok(claimed
+            .into_iter()
+            .map(|mut message| {
+                message.status = STATUS_SENDING.to_string();
+                message
+            })
+            .collect())
+    }
+
+    pub fn mark_sent(&self, id: &str, now: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE outbox_messages
+             SET status = ?2, attempts = attempts + 1, last_error = NULL,
+                 sent_at_ms = ?3, updated_at_ms = ?3
+             WHERE id = ?1",
+            params![id, STATUS_SENT, now],
+        )?;
+        Ok(())
+    }
+
+    /// Records a failed attempt: schedules the next retry, or moves the
+    /// message to the dead-letter queue once `max_attempts` is reached.
+    pub fn mark_failed(
+        &self,
+        id: &str,
+        error: &str,
+        now: i64,
+        policy: &RetryPolicy,
+    ) -> Result<FailureOutcome> {
+        let attempts: u32 = self
+            .conn
+            .query_row(
+                "SELECT attempts FROM outbox_messages WHERE id = ?",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .with_context(|| format!("outbox message {id} not found"))?;
+        let attempts = attempts + 1;
+        let outcome = if attempts >= policy.max_attempts {
+            FailureOutcome {
+                attempts,
+                dead: true,
+                next_attempt_at_ms: None,
+            }
+        } else {
+            let delay = policy.delay_ms(attempts, jitter_fraction());
+            FailureOutcome {
+                attempts,
+                dead: false,
+                next_attempt_at_ms: Some(now.saturating_add(delay as i64)),
+            }
+        };
+        self.conn.execute(
+            "UPDATE outbox_messages
+             SET status = ?2, attempts = ?3, last_error = ?4,
+                 next_attempt_at_ms = COALESCE(?5, next_attempt_at_ms), updated_at_ms = ?6
+             WHERE id = ?1",
+            params![
+                id,
+                if outcome.dead {
+                    STATUS_DEAD
+                } else {
+                    STATUS_PENDING
+                },
+                attempts,
+                error,
+                outcome.next_attempt_at_ms,
+                now,
+            ],
+        )?;
+        Ok(outcome)
+    }
+
+    /// Returns messages left `sending` by a dispatcher that died mid-delivery
+    /// to `pending`. Delivery is at-least-once: they will be sent again.
+    pub fn release_claims(&self) -> Result<usize> {
+        Ok(self.conn.execute(
+            "UPDATE outbox_messages SET status = ?1, updated_at_ms = ?3 WHERE status = ?2",
+            params![STATUS_PENDING, STATUS_SENDING, now_ms()],
+        )?)
+    }
+
+    pub fn list(&self, query: &OutboxQuery) -> Result<Vec<OutboxMessage>> {
+        let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT) as i64;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {MESSAGE_COLUMNS} FROM outbox_messages
+             WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR session_key = ?2)
+             ORDER BY position DESC
+             LIMIT ?3"
+        ))?;
+        let rows = stmt.query_map(
+            params![query.status, query.session_key, limit],
+            OutboxMessage::from_row,
+        )?;
+        let mut messages = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        messages.reverse();
+        Ok(messages)
+    }
+
+    pub fn counts(&self) -> Result<Map<String, Value>> {
+        let mut counts = STATUSES
+            .iter()
+            .map(|status| (status.to_string(), json!(0)))
+            .collect::<Map<_, _>>();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT status, COUNT(*) FROM outbox_messages GROUP BY status")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let status: String = row.get(0)?;
+            let count: i64 = row.get(1)?;
+            counts.insert(status, json!(count));
+        }
+        Ok(counts)
+    }
+
+    /// Re-queues dead or sent messages for immediate delivery with a fresh
+    /// attempt budget. Replayed messages move to the back of their session's
+    /// queue. Selects by `ids` when given, otherwise every message in `status`.
+    pub fn replay(&mut self, ids: &[String], status: Option<&str>) -> Result<Vec<String>> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .context("begin outbox transaction")?;
+        let candidates = if ids.is_empty() {
+            let status = status.unwrap_or(STATUS_DEAD);
+            let mut stmt =
+                tx.prepare("SELECT id FROM outbox_messages WHERE status = ? ORDER BY position")?;
+            let rows = stmt.query_map(params![status], |row| row.get::<_, String>(0))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        } else {
+            ids.to_vec()
+        };
+        let now = now_ms();
+        let mut replayed = Vec::new();
+        for id in candidates {
+            let updated = tx.execute(
+                "UPDATE outbox_messages
+                 SET status = ?2, attempts = 0, last_error = NULL, sent_at_ms = NULL,
+                     next_attempt_at_ms = ?3, updated_at_ms = ?3,
+                     position = (SELECT MAX(position) + 1 FROM outbox_messages)
+                 WHERE id = ?1 AND status IN (?4, ?5)",
+                params![id, STATUS_PENDING, now, STATUS_DEAD, STATUS_SENT],
+            )?;
+            if updated > 0 {
+                replayed.push(id);
+            }
+        }
+        tx.commit().context("commit outbox transaction")?;
+        Ok(replayed)
+    }
+}
+
+pub fn parse_outbox_query(query: Option<&str>) -> Result<OutboxQuery> {
+    let mut parsed = OutboxQuery::default();
+    let Some(query) = query else {
+        return Ok(parsed);
+    };
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim() {
+            "status" => {
+                if !STATUSES.contains(&value) {
+                    anyhow::bail!("unknown outbox status {value:?}");
+                }
+                parsed.status = Some(value.to_string());
+            }
+            "sessionKey" | "session" => parsed.session_key = Some(value.to_string()),
+            "limit" => parsed.limit = value.parse::<usize>().ok(),
+            _ => {}
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::test_paths;
+
+    fn open_store(label: &str) -> Result<(PathBuf, OutboxStore)> {
+        let (base, paths) = test_paths(label);
+        Ok((base, OutboxStore::open(&paths)?))
+    }
+
+    fn entry(id: &str, session: &str) -> Value {
+        json!({
+            "id": id,
+            "sessionKey": session,
+            "channel": "telegram",
+            "to": "42",
+            "idempotencyKey": format!("key-{id}"),
+            "createdAtMs": 1_000,
+            "text": id,
+        })
+    }
+
+    fn ids(messages: &[OutboxMessage]) -> Vec<&str> {
+        messages.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn claims_one_message_per_session_in_order() -> Result<()> {
+        let (base, mut store) = open_store("outbox-fifo")?;
+        assert!(store.enqueue(&entry("a1", "s:a"))?);
+        assert!(store.enqueue(&entry("b1", "s:b"))?);
+        assert!(store.enqueue(&entry("a2", "s:a"))?);
+        assert!(!store.enqueue(&entry("a1", "s:a"))?);
+
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            base_ms: 1_000,
+        };
+        let batch = store.claim_due(2_000, 10)?;
+        assert_eq!(ids(&batch), vec!["a1", "b1"]);
+        assert!(store.claim_due(2_000, 10)?.is_empty());
+
+        let outcome = store.mark_failed("a1", "timeout", 2_000, &policy)?;
+        assert_eq!(outcome.attempts, 1);
+        assert!(!outcome.dead);
+        let retry_at = outcome.next_attempt_at_ms.expect("retry scheduled");
+        assert!((2_500..=3_000).contains(&retry_at));
+        store.mark_sent("b1", 2_000)?;
+
+        // a2 waits behind the failed a1 even though it is due.
+        assert!(store.claim_due(2_100, 10)?.is_empty());
+        assert_eq!(ids(&store.claim_due(retry_at, 10)?), vec!["a1"]);
+        let outcome = store.mark_failed("a1", "timeout", retry_at, &policy)?;
+        assert!(outcome.dead);
+
+        // Dead-lettered a1 no longer blocks the session.
+        assert_eq!(ids(&store.claim_due(retry_at, 10)?), vec!["a2"]);
+        store.mark_sent("a2", retry_at)?;
+
+        let dead = store.list(&OutboxQuery {
+            status: Some(STATUS_DEAD.to_string()),
+            ..Default::default()
+        })?;
+        assert_eq!(ids(&dead), vec!["a1"]);
+        assert_eq!(dead[0].last_error.as_deref(), Some("timeout"));
+        assert_eq!(store.counts()?.get("sent"), Some(&json!(2)));
+
+        assert_eq!(store.replay(&[], None)?, vec!["a1".to_string()]);
+        let replayed = store.claim_due(now_ms(), 10)?;
+        assert_eq!(ids(&replayed), vec!["a1"]);
+        assert_eq!(replayed[0].attempts, 0);
+        assert!(replayed[0].position > 3);
+
+        assert_eq!(store.release_claims()?, 1);
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[test]
+    fn retry_delay_grows_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_ms: 1_000,
+        };
+        assert_eq!(policy.delay_ms(1, 0.0), 500);
+        assert_eq!(policy.delay_ms(1, 0.999), 999);
+        assert_eq!(policy.delay_ms(3, 1.0), 4_000);
+        assert_eq!(policy.delay_ms(40, 1.0), MAX_RETRY_DELAY_MS);
+        assert!(parse_outbox_query(Some("status=bogus")).is_err());
+        let query = parse_outbox_query(Some("status=dead&sessionKey=telegram%3A42&limit=5"))
+            .expect("query");
+        assert_eq!(query.status.as_deref(), Some("dead"));
+        assert_eq!(query.session_key.as_deref(), Some("telegram:42"));
+        assert_eq!(query.limit, Some(5));
+        let query =
+            parse_outbox_query(Some("session=agent%3Amain%3Aslack%2FC%20%C3%A9+x")).expect("query");
+        assert_eq!(query.session_key.as_deref(), Some("agent:main:slack/C é x"));
+    }
+}
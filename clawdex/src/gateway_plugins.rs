@@ -0,0 +1,677 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::config::{ClawdPaths, WorkspacePolicy};
+
+const DEFAULT_METHOD_TIMEOUT_MS: u64 = 30_000;
+const MAX_METHOD_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+const STDERR_TAIL_CHARS: usize = 2_000;
+const KNOWN_REQUIREMENTS: &[&str] = &["network", "write"];
+/// Interpreters a handler may name instead of a plugin file. The first
+/// argument must then be a script inside the plugin directory.
+const HANDLER_INTERPRETERS: &[&str] = &["sh", "bash", "node", "python3", "python", "ruby", "perl"];
+
+/// Why a plugin gateway method call did not produce a result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginMethodError {
+    NotImplemented(String),
+    Forbidden(String),
+    Timeout(String),
+    /// An error object the handler returned itself (`code`, `message`).
+    Handler {
+        code: String,
+        message: String,
+    },
+    Internal(String),
+}
+
+/// How a plugin method is executed: a process that reads one JSON request
+/// on stdin and writes one JSON response line on stdout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerSpec {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+/// A gateway method declared in a plugin manifest.
+///
+/// `gatewayMethods` entries are either bare names (using the plugin-level
+/// `gatewayHandler`) or objects with `name`, `version`, `minVersion`,
+/// `timeoutMs`, `requires` and an optional per-method `handler`.
+#[derive(Debug, Clone)]
+pub struct PluginMethodSpec {
+    pub name: String,
+    pub plugin_id: String,
+    pub root: PathBuf,
+    pub version: u32,
+    pub min_version: u32,
+    pub timeout_ms: u64,
+    pub requires: Vec<String>,
+    pub handler: Option<HandlerSpec>,
+}
+
+fn parse_handler(value: Option<&Value>) -> Option<HandlerSpec> {
+    let value = value?;
+    if let Some(command) = value.as_str() {
+        return Some(HandlerSpec {
+            command: command.trim().to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+        })
+        .filter(|spec| !spec.command.is_empty());
+    }
+    let command = value
+        .get("command")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())?;
+    let args = value
+        .get("args")
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let env = value
+        .get("env")
+        .and_then(|v| v.as_object())
+        .map(|map| {
+            map.iter()
+                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(HandlerSpec {
+        command: command.to_string(),
+        args,
+        env,
+    })
+}
+
+fn value_u64(value: &Value, keys: &[&str]) -> Option<u64> {
+    keys.iter()
+        .find_map(|key| value.get(*key))
+        .and_then(|v| v.as_u64())
+}
+
+/// Gateway methods declared by one plugin manifest (`openclaw.plugin.json`,
+/// `.claude-plugin/plugin.json` or `plugin.json`).
+pub fn method_specs_from_manifest(
+    plugin_id: &str,
+    root: &Path,
+    manifest: &Value,
+) -> Vec<PluginMethodSpec> {
+    let Some(entries) = manifest
+        .get("gatewayMethods")
+        .or_else(|| manifest.get("gateway_methods"))
+        .and_then(|v| v.as_array())
+    else {
+        return Vec::new();
+    };
+    let default_handler = manifest
+        .get("gatewayHandler")
+        .or_else(|| manifest.get("gateway_handler"));
+    let default_timeout = default_handler.and_then(|v| value_u64(v, &["timeoutMs", "timeout_ms"]));
+    let default_handler = parse_handler(default_handler);
+
+    let mut out = Vec::new();
+    for entry in entries {
+        let spec = if let Some(name) = entry.as_str() {
+            PluginMethodSpec {
+                name: name.trim().to_string(),
+                plugin_id: plugin_id.to_string(),
+                root: root.to_path_buf(),
+                version: 1,
+                min_version: 1,
+                timeout_ms: default_timeout.unwrap_or(DEFAULT_METHOD_TIMEOUT_MS),
+                requires: Vec::new(),
+                handler: default_handler.clone(),
+            }
+        } else if entry.is_object() {
+            let version = value_u64(entry, &["version"]).unwrap_or(1).max(1) as u32;
+            let min_version = value_u64(entry, &["minVersion", "min_version"])
+                .map(|v| (v.max(1) as u32).min(version))
+                .unwrap_or(version);
+            PluginMethodSpec {
+                name: entry
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .trim()
+                    .to_string(),
+                plugin_id: plugin_id.to_string(),
+                root: root.to_path_buf(),
+                version,
+                min_version,
+                timeout_ms: value_u64(entry, &["timeoutMs", "timeout_ms"])
+                    .or(default_timeout)
+                    .unwrap_or(DEFAULT_METHOD_TIMEOUT_MS),
+                requires: entry
+                    .get("requires")
+                    .and_then(|v| v.as_array())
+                    .map(|list| {
+                        list.iter()
+                            .filter_map(|v| v.as_str().map(|s| s.trim().to_lowercase()))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                handler: parse_handler(entry.get("handler")).or_else(|| default_handler.clone()),
+            }
+        } else {
+            continue;
+        };
+        if !spec.name.is_empty() {
+            out.push(spec);
+        }
+    }
+    out
+}
+
+impl PluginMethodSpec {
+    /// Checks the method's `requires` list against the effective policy
+    /// (workspace policy narrowed by the plugin's declared permissions).
+    /// This only rejects calls early; the handler itself always runs under
+    /// that policy's sandbox.
+    pub fn check_permissions(&self, policy: &WorkspacePolicy) -> Result<(), PluginMethodError> {
+        for requirement in &self.requires {
+            let denied = match requirement.as_str() {
+                "network" => !policy.network_access,
+                "write" => policy.read_only,
+                other => {
+                    return Err(PluginMethodError::Forbidden(format!(
+                        "{} requires unknown permission {other:?} (known: {})",
+                        self.name,
+                        KNOWN_REQUIREMENTS.join(", ")
+                    )))
+                }
+            };
+            if denied {
+                return Err(PluginMethodError::Forbidden(format!(
+                    "{} requires {requirement} access, which plugin {} is not granted",
+                    self.name, self.plugin_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.clamp(1, MAX_METHOD_TIMEOUT_MS))
+    }
+}
+
+/// Resolves `raw` to an existing file inside the plugin root, following
+/// symlinks so they cannot point outside it.
+fn plugin_file(root: &Path, raw: &str) -> Result<PathBuf, PluginMethodError> {
+    let outside = || {
+        PluginMethodError::Forbidden(format!(
+            "gateway handler {raw:?} must be inside the plugin directory"
+        ))
+    };
+    let candidate = Path::new(raw);
+    if candidate.is_absolute()
+        || candidate
+            .components()
+            .any(|part| matches!(part, Component::ParentDir))
+    {
+        return Err(outside());
+    }
+    let root = root.canonicalize().map_err(|err| {
+        PluginMethodError::Internal(format!("resolve plugin root {}: {err}", root.display()))
+    })?;
+    let resolved = root.join(candidate).canonicalize().map_err(|err| {
+        PluginMethodError::Internal(format!("resolve gateway handler {raw:?}: {err}"))
+    })?;
+    if !resolved.starts_with(&root) || !resolved.is_file() {
+        return Err(outside());
+    }
+    Ok(resolved)
+}
+
+/// Resolves the handler to a program and arguments. The command is either a
+/// file inside the plugin root, or one of [`HANDLER_INTERPRETERS`] whose
+/// first argument is a script inside the plugin root. Anything else
+/// (including `sh -c ...`) is rejected.
+fn resolve_command(
+    root: &Path,
+    handler: &HandlerSpec,
+) -> Result<(PathBuf, Vec<String>), PluginMethodError> {
+    let command = handler.command.as_str();
+    if !HANDLER_INTERPRETERS.contains(&command) {
+        return Ok((plugin_file(root, command)?, handler.args.clone()));
+    }
+    let Some((script, rest)) = handler.args.split_first() else {
+        return Err(PluginMethodError::Forbidden(format!(
+            "gateway handler {command:?} must name a script inside the plugin directory"
+        )));
+    };
+    if script.starts_with('-') {
+        return Err(PluginMethodError::Forbidden(format!(
+            "gateway handler {command:?} must run a script, not {script:?}"
+        )));
+    }
+    let mut args = vec![plugin_file(root, script)?.to_string_lossy().to_string()];
+    args.extend(rest.iter().cloned());
+    Ok((PathBuf::from(command), args))
+}
+
+/// Wraps the handler in `codex sandbox <platform>` so it runs under the same
+/// sandbox policy as agent turns: read-only, or writable only within the
+/// allowed roots, with network access only when the policy grants it. On
+/// platforms without a sandbox the handler is refused.
+fn sandboxed_command(
+    paths: &ClawdPaths,
+    policy: &WorkspacePolicy,
+    program: &Path,
+    args: &[String],
+) -> Result<Command, PluginMethodError> {
+    let platform = match std::env::consts::OS {
+        "linux" => "linux",
+        "macos" => "macos",
+        other => {
+            return Err(PluginMethodError::Forbidden(format!(
+                "plugin gateway handlers need a sandbox, which is not available on {other}"
+            )))
+        }
+    };
+    let (cfg, _) = crate::config::load_config(
+        Some(paths.state_dir.clone()),
+        Some(paths.workspace_dir.clone()),
+    )
+    .map_err(|err| PluginMethodError::Internal(format!("load config: {err}")))?;
+    let codex = crate::daemon::resolve_codex_path(&cfg, None)
+        .map_err(|err| PluginMethodError::Internal(format!("resolve codex path: {err}")))?;
+    let mut command = Command::new(codex);
+    command.arg("sandbox").arg(platform);
+    if !policy.read_only {
+        let roots = policy
+            .allowed_roots
+            .iter()
+            .map(|root| root.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        command
+            .arg("--full-auto")
+            .arg("-c")
+            .arg(format!(
+                "sandbox_workspace_write.network_access={}",
+                policy.network_access
+            ))
+            .arg("-c")
+            .arg(format!(
+                "sandbox_workspace_write.writable_roots={}",
+                json!(roots)
+            ));
+    }
+    command.arg("--").arg(program).args(args);
+    Ok(command)
+}
+
+/// Executes a plugin gateway method by spawning its handler, sandboxed by
+/// `policy`, in the plugin root. The request line carries `id`, `method`,
+/// `version`, `params` and the effective `permissions`; the last stdout line
+/// must be a JSON object with either `result` or `error`.
+pub fn invoke(
+    spec: &PluginMethodSpec,
+    paths: &ClawdPaths,
+    params: &Value,
+    version: u32,
+    policy: &WorkspacePolicy,
+) -> Result<Value, PluginMethodError> {
+    let Some(handler) = spec.handler.as_ref() else {
+        return Err(PluginMethodError::NotImplemented(format!(
+            "plugin {} declares {} without a gatewayHandler",
+            spec.plugin_id, spec.name
+        )));
+    };
+    spec.check_permissions(policy)?;
+    let (program, args) = resolve_command(&spec.root, handler)?;
+    // The codex read-only sandbox has no network, whatever the policy says.
+    let network_access = policy.network_access && !policy.read_only;
+    let request = json!({
+        "id": Uuid::new_v4().to_string(),
+        "method": spec.name,
+        "version": version,
+        "params": params,
+        "plugin": { "id": spec.plugin_id, "root": spec.root },
+        "permissions": { "readOnly": policy.read_only, "networkAccess": network_access },
+    });
+
+    let mut child = sandboxed_command(paths, policy, &program, &args)?
+        .envs(&handler.env)
+        .env("CLAWDEX_PLUGIN_ID", &spec.plugin_id)
+        .env("CLAWDEX_PLUGIN_ROOT", &spec.root)
+        .env("CLAWDEX_STATE_DIR", &paths.state_dir)
+        .env("CLAWDEX_WORKSPACE", &paths.workspace_dir)
+        .env(
+            "CLAWDEX_READ_ONLY",
+            if policy.read_only { "1" } else { "0" },
+        )
+        .env(
+            "CLAWDEX_NETWORK_ACCESS",
+            if network_access { "1" } else { "0" },
+        )
+        .current_dir(&spec.root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            PluginMethodError::Internal(format!(
+                "sandbox unavailable, not running gateway handler {} for {}: {err}",
+                program.display(),
+                spec.name
+            ))
+        })?;
+
+    // Written on its own thread: a handler that never drains its stdin must not keep the
+    // deadline below from firing. One that exits without reading its input is reported below.
+    if let Some(mut stdin) = child.stdin.take() {
+        let line = format!("{request}\n");
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(line.as_bytes());
+        });
+    }
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut out = String::new();
+        if let Some(pipe) = stdout.as_mut() {
+            let _ = pipe.read_to_string(&mut out);
+        }
+        out
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut out = String::new();
+        if let Some(pipe) = stderr.as_mut() {
+            let _ = pipe.read_to_string(&mut out);
+        }
+        out
+    });
+
+    let deadline = Instant::now() + spec.timeout();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(PluginMethodError::Timeout(format!(
+                    "{} timed out after {}ms",
+                    spec.name, spec.timeout_ms
+                )));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(err) => {
+                return Err(PluginMethodError::Internal(format!(
+                    "wait for gateway handler: {err}"
+                )))
+            }
+        }
+    };
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    let response = stdout
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .and_then(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|value| value.is_object());
+    let Some(response) = response else {
+        let tail = stderr.trim();
+        let tail = &tail[tail
+            .char_indices()
+            .rev()
+            .nth(STDERR_TAIL_CHARS)
+            .map(|(i, _)| i)
+            .unwrap_or(0)..];
+        return Err(PluginMethodError::Internal(format!(
+            "gateway handler for {} exited with {status} without a JSON response{}",
+            spec.name,
+            if tail.is_empty() {
+                String::new()
+            } else {
+                format!(": {tail}")
+            }
+        )));
+    };
+    if let Some(error) = response.get("error").filter(|v| !v.is_null()) {
+        let message = error
+            .get("message")
+            .and_then(|v| v.as_str())
+            .or_else(|| error.as_str())
+            .unwrap_or("plugin handler failed")
+            .to_string();
+        let code = error
+            .get("code")
+            .and_then(|v| v.as_str())
+            .unwrap_or("plugin_error")
+            .to_string();
+        return Err(PluginMethodError::Handler { code, message });
+    }
+    Ok(response.get("result").cloned().unwrap_or(Value::Null))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+    use crate::config::test_support::test_paths;
+
+    fn write_script(root: &Path, name: &str, body: &str) {
+        std::fs::create_dir_all(root).expect("plugin root");
+        let path = root.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).expect("script");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    }
+
+    /// Points `codex.path` at a stand-in for `codex sandbox` that records its
+    /// sandbox arguments and then runs the wrapped command.
+    fn install_fake_sandbox(base: &Path, paths: &ClawdPaths) -> PathBuf {
+        let bin = base.join("bin");
+        let log = bin.join("sandbox-args.log");
+        write_script(
+            &bin,
+            "codex",
+            &format!(
+                r#"while [ "$#" -gt 0 ] && [ "$1" != "--" ]; do echo "$1" >> '{}'; shift; done
+shift
+exec "$@""#,
+                log.display()
+            ),
+        );
+        std::fs::write(
+            paths.state_dir.join("config.json"),
+            serde_json::to_vec(&json!({ "codex": { "path": bin.join("codex") } })).expect("config"),
+        )
+        .expect("write config");
+        log
+    }
+
+    fn handler(command: &str, args: &[&str]) -> HandlerSpec {
+        HandlerSpec {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            env: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn parses_method_entries_and_version_ranges() {
+        let manifest = json!({
+            "gatewayHandler": { "command": "./bin/handler", "args": ["--stdio"], "timeoutMs": 500 },
+            "gatewayMethods": [
+                "plugin.legacy",
+                { "name": "plugin.v3", "version": 3, "minVersion": 2, "requires": ["Network"] },
+                { "name": "plugin.own", "handler": "python3" },
+                { "version": 2 }
+            ]
+        });
+        let specs = method_specs_from_manifest("demo", Path::new("/plugins/demo"), &manifest);
+        assert_eq!(specs.len(), 3);
+        assert_eq!(specs[0].timeout_ms, 500);
+        assert_eq!(
+            specs[0].handler.as_ref().map(|h| h.args.clone()),
+            Some(vec!["--stdio".to_string()])
+        );
+        assert_eq!(specs[1].requires, vec!["network".to_string()]);
+        assert_eq!(
+            specs[2].handler.as_ref().map(|h| h.command.as_str()),
+            Some("python3")
+        );
+        assert_eq!((specs[0].min_version, specs[0].version), (1, 1));
+        assert_eq!((specs[1].min_version, specs[1].version), (2, 3));
+        assert_eq!((specs[2].min_version, specs[2].version), (1, 1));
+    }
+
+    #[test]
+    fn handler_commands_must_stay_inside_the_plugin() {
+        let base = std::env::temp_dir().join(format!("clawdex-plugin-cmd-{}", Uuid::new_v4()));
+        let root = base.join("plugin");
+        write_script(&root, "run.sh", "true");
+        write_script(&base, "outside.sh", "true");
+        std::os::unix::fs::symlink(base.join("outside.sh"), root.join("link.sh")).expect("link");
+
+        let (program, args) = resolve_command(&root, &handler("./run.sh", &["x"])).expect("file");
+        assert!(program.ends_with("run.sh"));
+        assert_eq!(args, vec!["x".to_string()]);
+        let (program, args) = resolve_command(&root, &handler("sh", &["run.sh"])).expect("sh");
+        assert_eq!(program, PathBuf::from("sh"));
+        assert!(args[0].ends_with("run.sh"));
+
+        for (command, args) in [
+            ("sh", vec!["-c", "curl example.com"]),
+            ("sh", vec![]),
+            ("curl", vec!["example.com"]),
+            ("../outside.sh", vec![]),
+            ("/bin/sh", vec!["run.sh"]),
+            ("link.sh", vec![]),
+            ("node", vec!["../outside.sh"]),
+        ] {
+            let result = resolve_command(&root, &handler(command, &args));
+            assert!(
+                matches!(
+                    result,
+                    Err(PluginMethodError::Forbidden(_)) | Err(PluginMethodError::Internal(_))
+                ),
+                "{command} {args:?} should be rejected"
+            );
+        }
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn permission_checks_use_the_effective_policy() {
+        let (base, paths) = test_paths("plugin-perms");
+        let manifest = json!({
+            "gatewayMethods": [
+                { "name": "plugin.fetch", "requires": ["network"] },
+                { "name": "plugin.odd", "requires": ["root"] }
+            ]
+        });
+        let specs = method_specs_from_manifest("demo", &base, &manifest);
+        let mut policy = paths.workspace_policy.clone();
+        policy.network_access = true;
+        assert!(specs[0].check_permissions(&policy).is_ok());
+        policy.network_access = false;
+        assert!(matches!(
+            specs[0].check_permissions(&policy),
+            Err(PluginMethodError::Forbidden(_))
+        ));
+        assert!(matches!(
+            specs[1].check_permissions(&policy),
+            Err(PluginMethodError::Forbidden(_))
+        ));
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn invokes_stdio_handler_under_sandbox_with_timeouts_and_errors() {
+        let (base, paths) = test_paths("plugin-invoke");
+        let sandbox_log = install_fake_sandbox(&base, &paths);
+        let root = base.join("plugin");
+        write_script(
+            &root,
+            "echo.sh",
+            r#"read -r line
+echo "log line" >&2
+printf '{"result":{"request":%s,"plugin":"%s"}}\n' "$line" "$CLAWDEX_PLUGIN_ID""#,
+        );
+        write_script(
+            &root,
+            "fail.sh",
+            r#"read -r line
+echo '{"error":{"code":"bad_input","message":"nope"}}'"#,
+        );
+        write_script(&root, "slow.sh", "sleep 5");
+        write_script(&root, "crash.sh", "echo boom >&2\nexit 3");
+        let manifest = json!({
+            "gatewayHandler": { "command": "sh", "args": ["echo.sh"] },
+            "gatewayMethods": [
+                { "name": "demo.echo", "version": 2 },
+                { "name": "demo.fail", "handler": { "command": "sh", "args": ["fail.sh"] } },
+                { "name": "demo.slow", "timeoutMs": 100, "handler": { "command": "sh", "args": ["slow.sh"] } },
+                { "name": "demo.crash", "handler": { "command": "sh", "args": ["crash.sh"] } }
+            ]
+        });
+        let specs = method_specs_from_manifest("demo", &root, &manifest);
+
+        // A method that declares no `requires` still runs under the plugin's
+        // read-only policy.
+        let mut read_only = paths.workspace_policy.clone();
+        read_only.read_only = true;
+        let result = invoke(&specs[0], &paths, &json!({ "x": 1 }), 2, &read_only).expect("echo");
+        assert_eq!(result["plugin"], "demo");
+        assert_eq!(result["request"]["method"], "demo.echo");
+        assert_eq!(result["request"]["version"], 2);
+        assert_eq!(result["request"]["params"], json!({ "x": 1 }));
+        assert_eq!(result["request"]["permissions"]["readOnly"], true);
+        let sandbox_args = std::fs::read_to_string(&sandbox_log).expect("sandbox log");
+        assert!(sandbox_args.starts_with("sandbox\n"), "{sandbox_args}");
+        assert!(!sandbox_args.contains("--full-auto"), "{sandbox_args}");
+
+        let policy = paths.workspace_policy.clone();
+        assert_eq!(
+            invoke(&specs[1], &paths, &json!({}), 1, &policy),
+            Err(PluginMethodError::Handler {
+                code: "bad_input".to_string(),
+                message: "nope".to_string()
+            })
+        );
+        let sandbox_args = std::fs::read_to_string(&sandbox_log).expect("sandbox log");
+        assert!(sandbox_args.contains("--full-auto"), "{sandbox_args}");
+        assert!(sandbox_args.contains("sandbox_workspace_write.writable_roots="));
+
+        let started = Instant::now();
+        assert!(matches!(
+            invoke(&specs[2], &paths, &json!({}), 1, &policy),
+            Err(PluginMethodError::Timeout(_))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(4));
+        // Params larger than the pipe buffer, sent to a handler that never reads them.
+        let started = Instant::now();
+        let big = json!({ "blob": "x".repeat(1 << 20) });
+        assert!(matches!(
+            invoke(&specs[2], &paths, &big, 1, &policy),
+            Err(PluginMethodError::Timeout(_))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(4));
+        match invoke(&specs[3], &paths, &json!({}), 1, &policy) {
+            Err(PluginMethodError::Internal(message)) => assert!(message.contains("boom")),
+            other => panic!("unexpected {other:?}"),
+        }
+        let _ = std::fs::remove_dir_all(base);
+    }
+}
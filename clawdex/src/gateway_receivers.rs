@@ -0,0 +1,684 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use reqwest::blocking::Client;
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+
+use crate::config::{ClawdPaths, GatewayChannelConfig, GatewayConfig};
+use crate::gateway::{gateway_dir, record_incoming};
+use crate::gateway_channels::{
+    build_client, channel_api_base, channel_kind, matrix_url, matrix_whoami, resolve_secret,
+    DEFAULT_TELEGRAM_API_BASE,
+};
+use crate::util::{read_json_value, write_json_value};
+
+const RECEIVERS_FILE: &str = "receivers.json";
+const LONG_POLL_TIMEOUT_SECS: u64 = 30;
+const RECEIVER_RETRY_DELAY: Duration = Duration::from_secs(5);
+const SLACK_SIGNATURE_MAX_SKEW_SECS: i64 = 5 * 60;
+
+/// One `gateway.channels` entry seen from the inbound side.
+#[derive(Debug, Clone)]
+pub struct InboundChannel {
+    pub key: String,
+    pub channel: String,
+    pub kind: String,
+    pub account_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub cfg: GatewayChannelConfig,
+}
+
+impl InboundChannel {
+    fn from_entry(key: &str, cfg: &GatewayChannelConfig) -> Option<Self> {
+        let key = key.trim().to_lowercase();
+        if key.is_empty() || cfg.enabled == Some(false) {
+            return None;
+        }
+        let (channel, suffix) = match key.split_once(':') {
+            Some((channel, suffix)) => (channel.to_string(), Some(suffix.to_string())),
+            None => (key.clone(), None),
+        };
+        let account_id = cfg
+            .account_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string())
+            .or(suffix);
+        let agent_id = cfg
+            .agent_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string());
+        Some(Self {
+            kind: channel_kind(&key, cfg),
+            key,
+            channel,
+            account_id,
+            agent_id,
+            cfg: cfg.clone(),
+        })
+    }
+
+    fn token(&self) -> Option<String> {
+        resolve_secret(self.cfg.token.as_deref(), self.cfg.token_env.as_deref())
+    }
+
+    pub fn slack_signing_secret(&self) -> Option<String> {
+        resolve_secret(
+            self.cfg.signing_secret.as_deref(),
+            self.cfg.signing_secret_env.as_deref(),
+        )
+    }
+
+    /// Base `record_incoming` payload for a message from `from` on this channel.
+    fn incoming(&self, from: &str, text: &str) -> Value {
+        json!({
+            "channel": self.channel,
+            "from": from,
+            "text": text,
+            "accountId": self.account_id,
+            "agentId": self.agent_id,
+        })
+    }
+}
+
+pub fn inbound_channel(cfg: &GatewayConfig, key: &str) -> Option<InboundChannel> {
+    let key = key.trim().to_lowercase();
+    cfg.channels
+        .as_ref()?
+        .iter()
+        .find(|(entry_key, _)| entry_key.trim().to_lowercase() == key)
+        .and_then(|(entry_key, entry)| InboundChannel::from_entry(entry_key, entry))
+}
+
+fn receivers_path(paths: &ClawdPaths) -> std::path::PathBuf {
+    gateway_dir(paths).join(RECEIVERS_FILE)
+}
+
+fn load_cursor(paths: &ClawdPaths, key: &str) -> Result<Option<Value>> {
+    Ok(read_json_value(&receivers_path(paths))?
+        .and_then(|value| value.get(key).cloned())
+        .filter(|value| !value.is_null()))
+}
+
+fn save_cursor(paths: &ClawdPaths, key: &str, cursor: Value) -> Result<()> {
+    let path = receivers_path(paths);
+    let mut map = read_json_value(&path)?
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_else(Map::new);
+    map.insert(key.to_string(), cursor);
+    write_json_value(&path, &Value::Object(map))
+}
+
+/// Start a background receiver for every `gateway.channels` entry with
+/// `inbound: true` whose kind polls (Telegram `getUpdates`, Matrix `/sync`).
+/// Slack is push-based and handled by the gateway's events endpoint instead.
+pub fn start_receivers(paths: &ClawdPaths, cfg: &GatewayConfig) -> Result<usize> {
+    let Some(channels) = cfg.channels.as_ref() else {
+        return Ok(0);
+    };
+    let mut started = 0;
+    for (key, entry) in channels {
+        let Some(inbound) = InboundChannel::from_entry(key, entry) else {
+            continue;
+        };
+        if inbound.cfg.inbound != Some(true) {
+            continue;
+        }
+        let paths = paths.clone();
+        match inbound.kind.as_str() {
+            "telegram" => {
+                inbound
+                    .token()
+                    .with_context(|| format!("gateway.channels.{key}: telegram requires token"))?;
+                std::thread::spawn(move || run_telegram_receiver(paths, inbound));
+            }
+            "matrix" => {
+                inbound
+                    .token()
+                    .with_context(|| format!("gateway.channels.{key}: matrix requires token"))?;
+                std::thread::spawn(move || run_matrix_receiver(paths, inbound));
+            }
+            "slack" => continue,
+            other => anyhow::bail!("gateway.channels.{key}: {other} has no inbound receiver"),
+        }
+        started += 1;
+    }
+    Ok(started)
+}
+
+fn long_poll_client() -> Result<Client> {
+    build_client(Duration::from_secs(LONG_POLL_TIMEOUT_SECS + 10))
+}
+
+fn run_telegram_receiver(paths: ClawdPaths, inbound: InboundChannel) {
+    let client = match long_poll_client() {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("[clawdex][gateway] {} receiver: {err}", inbound.key);
+            return;
+        }
+    };
+    loop {
+        if let Err(err) = poll_telegram_once(&paths, &inbound, &client, LONG_POLL_TIMEOUT_SECS) {
+            eprintln!("[clawdex][gateway] {} receiver: {err}", inbound.key);
+            std::thread::sleep(RECEIVER_RETRY_DELAY);
+        }
+    }
+}
+
+/// One `getUpdates` long-poll. Confirms the batch through the next `offset`
+/// (also kept in `receivers.json`) and returns how many messages were recorded.
+pub fn poll_telegram_once(
+    paths: &ClawdPaths,
+    inbound: &InboundChannel,
+    client: &Client,
+    timeout_secs: u64,
+) -> Result<usize> {
+    let token = inbound.token().context("telegram requires token")?;
+    let api_base = channel_api_base(&inbound.cfg, DEFAULT_TELEGRAM_API_BASE);
+    let mut body = json!({
+        "timeout": timeout_secs,
+        "allowed_updates": ["message", "channel_post"],
+    });
+    if let Some(offset) = load_cursor(paths, &inbound.key)? {
+        body["offset"] = offset;
+    }
+    let resp = client
+        .post(format!("{api_base}/bot{token}/getUpdates"))
+        .json(&body)
+        .send()
+        .map_err(|err| anyhow::anyhow!("telegram getUpdates failed: {}", err.without_url()))?;
+    let value = resp.json::<Value>().unwrap_or(Value::Null);
+    if value.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+        let description = value
+            .get("description")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unexpected response");
+        anyhow::bail!("telegram getUpdates failed: {description}");
+    }
+    let updates = value
+        .get("result")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let mut recorded = 0;
+    let mut next_offset = None;
+    for update in &updates {
+        if let Some(update_id) = update.get("update_id").and_then(|v| v.as_i64()) {
+            next_offset = Some(update_id + 1);
+        }
+        if let Some(payload) = telegram_update_to_incoming(update, inbound) {
+            record_incoming(paths, &payload)?;
+            recorded += 1;
+        }
+    }
+    if let Some(offset) = next_offset {
+        save_cursor(paths, &inbound.key, json!(offset))?;
+    }
+    Ok(recorded)
+}
+
+fn telegram_update_to_incoming(update: &Value, inbound: &InboundChannel) -> Option<Value> {
+    let message = update
+        .get("message")
+        .or_else(|| update.get("channel_post"))?;
+    if message
+        .get("from")
+        .and_then(|from| from.get("is_bot"))
+        .and_then(|v| v.as_bool())
+        == Some(true)
+    {
+        return None;
+    }
+    let chat_id = message.get("chat")?.get("id")?;
+    let chat_id = chat_id
+        .as_i64()
+        .map(|id| id.to_string())
+        .or_else(|| chat_id.as_str().map(|s| s.to_string()))?;
+    let text = message
+        .get("text")
+        .or_else(|| message.get("caption"))
+        .and_then(|v| v.as_str())
+        .filter(|text| !text.trim().is_empty())?;
+    let mut payload = inbound.incoming(&chat_id, text);
+    if let Some(message_id) = message.get("message_id").and_then(|v| v.as_i64()) {
+        payload["messageId"] = Value::String(format!("{chat_id}:{message_id}"));
+    }
+    if let Some(thread_id) = message.get("message_thread_id").and_then(|v| v.as_i64()) {
+        payload["threadId"] = Value::String(thread_id.to_string());
+    }
+    if let Some(sender) = message
+        .get("from")
+        .and_then(|from| from.get("username"))
+        .and_then(|v| v.as_str())
+    {
+        payload["sender"] = Value::String(sender.to_string());
+    }
+    Some(payload)
+}
+
+fn run_matrix_receiver(paths: ClawdPaths, inbound: InboundChannel) {
+    let client = match long_poll_client() {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("[clawdex][gateway] {} receiver: {err}", inbound.key);
+            return;
+        }
+    };
+    let mut own_user_id = inbound.cfg.user_id.clone();
+    loop {
+        if own_user_id.is_none() {
+            let homeserver = channel_api_base(&inbound.cfg, "");
+            match matrix_whoami(&client, homeserver, &inbound.token().unwrap_or_default()) {
+                Ok(user_id) => own_user_id = Some(user_id),
+                Err(err) => {
+                    eprintln!("[clawdex][gateway] {} receiver: {err}", inbound.key);
+                    std::thread::sleep(RECEIVER_RETRY_DELAY);
+                    continue;
+                }
+            }
+        }
+        let timeout_ms = LONG_POLL_TIMEOUT_SECS * 1000;
+        if let Err(err) = sync_matrix_once(
+            &paths,
+            &inbound,
+            &client,
+            own_user_id.as_deref(),
+            timeout_ms,
+        ) {
+            eprintln!("[clawdex][gateway] {} receiver: {err}", inbound.key);
+            std::thread::sleep(RECEIVER_RETRY_DELAY);
+        }
+    }
+}
+
+/// One `/sync` round. The first sync without a stored `since` token only
+/// records the position, so a new bridge does not replay room history.
+pub fn sync_matrix_once(
+    paths: &ClawdPaths,
+    inbound: &InboundChannel,
+    client: &Client,
+    own_user_id: Option<&str>,
+    timeout_ms: u64,
+) -> Result<usize> {
+    let token = inbound.token().context("matrix requires token")?;
+    let homeserver = channel_api_base(&inbound.cfg, "");
+    if homeserver.is_empty() {
+        anyhow::bail!("matrix requires apiBase (homeserver url)");
+    }
+    let since =
+        load_cursor(paths, &inbound.key)?.and_then(|value| value.as_str().map(|s| s.to_string()));
+    let timeout = if since.is_some() { timeout_ms } else { 0 };
+    let mut query = vec![("timeout", timeout.to_string())];
+    if let Some(since) = since.as_ref() {
+        query.push(("since", since.clone()));
+    }
+    let resp = client
+        .get(matrix_url(homeserver, &["sync"])?)
+        .bearer_auth(&token)
+        .query(&query)
+        .send()
+        .map_err(|err| anyhow::anyhow!("matrix sync failed: {}", err.without_url()))?;
+    let status = resp.status();
+    let value = resp.json::<Value>().unwrap_or(Value::Null);
+    let next_batch = value
+        .get("next_batch")
+        .and_then(|v| v.as_str())
+        .with_context(|| {
+            let err = value
+                .get("error")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+            format!("matrix sync failed: {err}")
+        })?
+        .to_string();
+    let mut recorded = 0;
+    if since.is_some() {
+        for payload in matrix_sync_to_incoming(&value, own_user_id, inbound) {
+            record_incoming(paths, &payload)?;
+            recorded += 1;
+        }
+    }
+    save_cursor(paths, &inbound.key, Value::String(next_batch))?;
+    Ok(recorded)
+}
+
+fn matrix_sync_to_incoming(
+    sync: &Value,
+    own_user_id: Option<&str>,
+    inbound: &InboundChannel,
+) -> Vec<Value> {
+    let mut out = Vec::new();
+    let Some(rooms) = sync
+        .get("rooms")
+        .and_then(|rooms| rooms.get("join"))
+        .and_then(|join| join.as_object())
+    else {
+        return out;
+    };
+    for (room_id, room) in rooms {
+        let events = room
+            .get("timeline")
+            .and_then(|timeline| timeline.get("events"))
+            .and_then(|events| events.as_array());
+        for event in events.into_iter().flatten() {
+            if event.get("type").and_then(|v| v.as_str()) != Some("m.room.message") {
+                continue;
+            }
+            let sender = event.get("sender").and_then(|v| v.as_str());
+            if sender.is_some() && sender == own_user_id {
+                continue;
+            }
+            let Some(text) = event
+                .get("content")
+                .and_then(|content| content.get("body"))
+                .and_then(|v| v.as_str())
+                .filter(|text| !text.trim().is_empty())
+            else {
+                continue;
+            };
+            let mut payload = inbound.incoming(room_id, text);
+            if let Some(event_id) = event.get("event_id").and_then(|v| v.as_str()) {
+                payload["messageId"] = Value::String(event_id.to_string());
+            }
+            if let Some(sender) = sender {
+                payload["sender"] = Value::String(sender.to_string());
+            }
+            out.push(payload);
+        }
+    }
+    out
+}
+
+/// Outcome of a Slack Events API delivery.
+#[derive(Debug, PartialEq)]
+pub enum SlackEvent {
+    /// `url_verification` handshake: echo the challenge back.
+    Challenge(String),
+    /// A user message to record, with Slack's `event_id` for retry dedup.
+    Incoming {
+        event_id: String,
+        payload: Value,
+    },
+    Ignored,
+}
+
+/// Check `X-Slack-Signature` (`v0=` + hex HMAC-SHA256 of `v0:<ts>:<body>`) and
+/// reject timestamps more than five minutes from `now_secs`.
+pub fn verify_slack_signature(
+    secret: &str,
+    timestamp: Option<&str>,
+    signature: Option<&str>,
+    body: &[u8],
+    now_secs: i64,
+) -> Result<()> {
+    let timestamp = timestamp
+        .map(str::trim)
+        .context("missing X-Slack-Request-Timestamp")?;
+    let ts = timestamp
+        .parse::<i64>()
+        .context("invalid X-Slack-Request-Timestamp")?;
+    if (now_secs - ts).abs() > SLACK_SIGNATURE_MAX_SKEW_SECS {
+        anyhow::bail!("stale slack request timestamp");
+    }
+    let signature = signature
+        .map(str::trim)
+        .context("missing X-Slack-Signature")?;
+    let signature = signature
+        .strip_prefix("v0=")
+        .and_then(|hex_digest| hex::decode(hex_digest).ok())
+        .context("malformed X-Slack-Signature")?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .context("invalid slack signing secret")?;
+    mac.update(format!("v0:{timestamp}:").as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| anyhow::anyhow!("invalid slack signature"))?;
+    Ok(())
+}
+
+pub fn parse_slack_event(body: &Value, inbound: &InboundChannel) -> SlackEvent {
+    match body.get("type").and_then(|v| v.as_str()) {
+        Some("url_verification") => body
+            .get("challenge")
+            .and_then(|v| v.as_str())
+            .map(|challenge| SlackEvent::Challenge(challenge.to_string()))
+            .unwrap_or(SlackEvent::Ignored),
+        Some("event_callback") => {
+            let Some(event) = body.get("event") else {
+                return SlackEvent::Ignored;
+            };
+            // Edits, joins and bot posts (including our own replies) carry a
+            // subtype or bot_id; only plain user messages become inbox entries.
+            if event.get("type").and_then(|v| v.as_str()) != Some("message")
+                || event.get("subtype").is_some()
+                || event.get("bot_id").is_some()
+            {
+                return SlackEvent::Ignored;
+            }
+            let (Some(channel), Some(text)) = (
+                event.get("channel").and_then(|v| v.as_str()),
+                event
+                    .get("text")
+                    .and_then(|v| v.as_str())
+                    .filter(|text| !text.trim().is_empty()),
+            ) else {
+                return SlackEvent::Ignored;
+            };
+            let mut payload = inbound.incoming(channel, text);
+            if let Some(ts) = event.get("ts").and_then(|v| v.as_str()) {
+                payload["messageId"] = Value::String(format!("{channel}:{ts}"));
+            }
+            if let Some(thread_ts) = event.get("thread_ts").and_then(|v| v.as_str()) {
+                payload["threadId"] = Value::String(thread_ts.to_string());
+            }
+            if let Some(user) = event.get("user").and_then(|v| v.as_str()) {
+                payload["sender"] = Value::String(user.to_string());
+            }
+            let event_id = body
+                .get("event_id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| {
+                    payload
+                        .get("messageId")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string()
+                });
+            SlackEvent::Incoming { event_id, payload }
+        }
+        _ => SlackEvent::Ignored,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::test_paths;
+    use crate::gateway_channels::test_support::MockHttpServer;
+
+    fn inbound(key: &str, cfg: Value) -> InboundChannel {
+        let cfg: GatewayChannelConfig = serde_json::from_value(cfg).expect("channel config");
+        InboundChannel::from_entry(key, &cfg).expect("inbound channel")
+    }
+
+    fn slack_signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac key");
+        mac.update(format!("v0:{timestamp}:").as_bytes());
+        mac.update(body);
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn slack_signature_matches_documented_example() {
+        // Signing secret, timestamp and body from Slack's "Verifying requests from Slack" guide.
+        let secret = "8f742231b10e8888abcd99yyyzzz85a5";
+        let body = b"token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&\
+            channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&\
+            command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2F\
+            T1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&\
+            trigger_id=398738663015.47445629121.803a0bc887a14d10d2c659f4f1e7ca35";
+        let signature = "v0=fdb27e6ab1c596d7d5c434a3f9410ba03708c5cf22c6236b82eebb831facbb64";
+        let now = 1_531_420_618;
+        assert!(
+            verify_slack_signature(secret, Some("1531420618"), Some(signature), body, now).is_ok()
+        );
+        let tampered = signature.replace("v0=fd", "v0=fe");
+        assert!(
+            verify_slack_signature(secret, Some("1531420618"), Some(&tampered), body, now).is_err()
+        );
+        assert!(
+            verify_slack_signature(secret, Some("1531420618"), Some("v0=zz"), body, now).is_err()
+        );
+    }
+
+    #[test]
+    fn slack_events_verify_and_parse() {
+        let body = br#"{"type":"event_callback"}"#;
+        let now = 1_700_000_000;
+        let signature = slack_signature("shh", now, body);
+        let ts = now.to_string();
+        assert!(verify_slack_signature("shh", Some(&ts), Some(&signature), body, now).is_ok());
+        assert!(verify_slack_signature("other", Some(&ts), Some(&signature), body, now).is_err());
+        assert!(
+            verify_slack_signature("shh", Some(&ts), Some(&signature), body, now + 600).is_err()
+        );
+        assert!(verify_slack_signature("shh", None, Some(&signature), body, now).is_err());
+
+        let slack = inbound(
+            "slack:acme",
+            json!({ "signingSecret": "shh", "agentId": "ops" }),
+        );
+        assert_eq!(slack.account_id.as_deref(), Some("acme"));
+        assert_eq!(
+            parse_slack_event(
+                &json!({ "type": "url_verification", "challenge": "abc" }),
+                &slack
+            ),
+            SlackEvent::Challenge("abc".to_string())
+        );
+        let event = json!({
+            "type": "event_callback",
+            "event_id": "Ev1",
+            "event": {
+                "type": "message",
+                "channel": "C1",
+                "user": "U1",
+                "text": "deploy?",
+                "ts": "1700.1",
+                "thread_ts": "1699.9"
+            }
+        });
+        let SlackEvent::Incoming { event_id, payload } = parse_slack_event(&event, &slack) else {
+            panic!("expected incoming event");
+        };
+        assert_eq!(event_id, "Ev1");
+        assert_eq!(payload["channel"], "slack");
+        assert_eq!(payload["from"], "C1");
+        assert_eq!(payload["accountId"], "acme");
+        assert_eq!(payload["agentId"], "ops");
+        assert_eq!(payload["threadId"], "1699.9");
+
+        let bot = json!({
+            "type": "event_callback",
+            "event": { "type": "message", "channel": "C1", "text": "reply", "bot_id": "B1" }
+        });
+        assert_eq!(parse_slack_event(&bot, &slack), SlackEvent::Ignored);
+    }
+
+    #[test]
+    fn telegram_poll_records_messages_and_advances_offset() -> Result<()> {
+        let mock = MockHttpServer::start(vec![
+            (
+                200,
+                json!({ "ok": true, "result": [
+                    { "update_id": 10, "message": {
+                        "message_id": 5, "chat": { "id": 42 }, "text": "hello",
+                        "from": { "username": "alice", "is_bot": false }
+                    } },
+                    { "update_id": 11, "message": { "message_id": 6, "chat": { "id": 42 } } }
+                ] }),
+            ),
+            (200, json!({ "ok": true, "result": [] })),
+        ]);
+        let (base, paths) = test_paths("telegram-receiver");
+        let telegram = inbound(
+            "telegram",
+            json!({ "token": "123:abc", "apiBase": mock.base_url, "accountId": "main" }),
+        );
+        let client = build_client(Duration::from_secs(5))?;
+
+        assert_eq!(poll_telegram_once(&paths, &telegram, &client, 0)?, 1);
+        assert_eq!(poll_telegram_once(&paths, &telegram, &client, 0)?, 0);
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].url, "/bot123:abc/getUpdates");
+        assert!(requests[0].body.get("offset").is_none());
+        assert_eq!(requests[1].body["offset"], 12);
+
+        let inbox = crate::gateway::drain_inbox(&paths)?;
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0]["from"], "42");
+        assert_eq!(inbox[0]["text"], "hello");
+        assert_eq!(inbox[0]["accountId"], "main");
+        assert_eq!(inbox[0]["sessionKey"], "telegram:42");
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+
+    #[test]
+    fn matrix_sync_skips_history_and_own_messages() -> Result<()> {
+        let mock = MockHttpServer::start(vec![
+            (200, json!({ "next_batch": "s1" })),
+            (
+                200,
+                json!({ "next_batch": "s2", "rooms": { "join": { "!r:hs": { "timeline": { "events": [
+                    { "type": "m.room.message", "event_id": "$1", "sender": "@bob:hs",
+                      "content": { "msgtype": "m.text", "body": "ping" } },
+                    { "type": "m.room.message", "event_id": "$2", "sender": "@clawdex:hs",
+                      "content": { "msgtype": "m.text", "body": "pong" } },
+                    { "type": "m.room.member", "event_id": "$3", "sender": "@carol:hs" }
+                ] } } } } }),
+            ),
+        ]);
+        let (base, paths) = test_paths("matrix-receiver");
+        let matrix = inbound(
+            "matrix",
+            json!({ "token": "mx", "apiBase": mock.base_url, "userId": "@clawdex:hs" }),
+        );
+        let client = build_client(Duration::from_secs(5))?;
+
+        assert_eq!(
+            sync_matrix_once(&paths, &matrix, &client, Some("@clawdex:hs"), 0)?,
+            0
+        );
+        assert_eq!(
+            sync_matrix_once(&paths, &matrix, &client, Some("@clawdex:hs"), 0)?,
+            1
+        );
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].url, "/_matrix/client/v3/sync?timeout=0");
+        assert_eq!(
+            requests[1].url,
+            "/_matrix/client/v3/sync?timeout=0&since=s1"
+        );
+        assert_eq!(load_cursor(&paths, "matrix")?, Some(json!("s2")));
+
+        let inbox = crate::gateway::drain_inbox(&paths)?;
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0]["from"], "!r:hs");
+        assert_eq!(inbox[0]["text"], "ping");
+
+        let _ = std::fs::remove_dir_all(base);
+        Ok(())
+    }
+}
@@ -5,16 +5,30 @@ pub mod approvals;
 pub mod claude_plugins;
 pub mod config;
 pub mod cron;
+pub mod cron_calendar;
+pub mod cron_cli;
+pub mod cron_lock;
+pub mod cron_pool;
+pub mod cron_store;
 pub mod daemon;
 pub mod daemon_client;
 pub mod gateway;
+pub mod gateway_channels;
+pub mod gateway_events;
+pub mod gateway_outbox;
+pub mod gateway_plugins;
+pub mod gateway_receivers;
 pub mod heartbeat;
+pub mod local_embeddings;
 pub mod mcp;
 pub mod memory;
+pub mod memory_ann;
+pub mod memory_watch;
 pub mod permissions;
 pub mod policies;
 pub mod plugins;
 pub mod runner;
+pub mod session_digest;
 pub mod sessions;
 pub mod skill_renderer;
 pub mod skills_sync;
@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+const TOKENIZER_FILE: &str = "tokenizer.json";
+const WEIGHTS_FILE: &str = "model.safetensors";
+const MAX_WORD_CHARS: usize = 100;
+
+/// CPU-only sentence embedding model for air-gapped hosts.
+///
+/// Loads a static-embedding model in the model2vec layout: a directory holding a WordPiece
+/// `tokenizer.json` and a `model.safetensors` with one `[vocab, dims]` embedding table. A text is
+/// embedded by tokenizing it and mean-pooling the token rows, so no inference runtime is needed.
+pub struct LocalEmbeddingModel {
+    path: PathBuf,
+    vocab: HashMap<String, usize>,
+    unk_id: Option<usize>,
+    continuation_prefix: String,
+    lowercase: bool,
+    dims: usize,
+    weights: Vec<f32>,
+}
+
+impl std::fmt::Debug for LocalEmbeddingModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalEmbeddingModel")
+            .field("path", &self.path)
+            .field("vocab", &self.vocab.len())
+            .field("dims", &self.dims)
+            .finish()
+    }
+}
+
+/// Loads the model at `path`, reusing an already loaded copy.
+pub fn load_local_model(path: &Path) -> Result<Arc<LocalEmbeddingModel>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Arc<LocalEmbeddingModel>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(model) = cache.lock().ok().and_then(|map| map.get(path).cloned()) {
+        return Ok(model);
+    }
+    let model = Arc::new(LocalEmbeddingModel::load(path)?);
+    if let Ok(mut map) = cache.lock() {
+        map.insert(path.to_path_buf(), model.clone());
+    }
+    Ok(model)
+}
+
+impl LocalEmbeddingModel {
+    pub fn load(path: &Path) -> Result<Self> {
+        let tokenizer_path = path.join(TOKENIZER_FILE);
+        let raw = std::fs::read_to_string(&tokenizer_path)
+            .with_context(|| format!("read {}", tokenizer_path.display()))?;
+        let tokenizer: Value = serde_json::from_str(&raw)
+            .with_context(|| format!("parse {}", tokenizer_path.display()))?;
+        let model = tokenizer
+            .get("model")
+            .context("tokenizer.json is missing model")?;
+        let kind = model.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if kind != "WordPiece" {
+            bail!("unsupported tokenizer type {kind:?} (expected WordPiece)");
+        }
+        let vocab = model
+            .get("vocab")
+            .and_then(|v| v.as_object())
+            .context("tokenizer.json is missing model.vocab")?
+            .iter()
+            .filter_map(|(token, id)| id.as_u64().map(|id| (token.clone(), id as usize)))
+            .collect::<HashMap<_, _>>();
+        let unk_id = model
+            .get("unk_token")
+            .and_then(|v| v.as_str())
+            .and_then(|token| vocab.get(token).copied());
+        let continuation_prefix = model
+            .get("continuing_subword_prefix")
+            .and_then(|v| v.as_str())
+            .unwrap_or("##")
+            .to_string();
+        let lowercase = tokenizer
+            .get("normalizer")
+            .and_then(|n| n.get("lowercase"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let weights_path = path.join(WEIGHTS_FILE);
+        let bytes = std::fs::read(&weights_path)
+            .with_context(|| format!("read {}", weights_path.display()))?;
+        let (rows, dims, weights) = read_embedding_table(&bytes)
+            .with_context(|| format!("parse {}", weights_path.display()))?;
+        if let Some(max_id) = vocab.values().max() {
+            if *max_id >= rows {
+                bail!("tokenizer vocab has id {max_id} but the embedding table has {rows} rows");
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            vocab,
+            unk_id,
+            continuation_prefix,
+            lowercase,
+            dims,
+            weights,
+        })
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Mean-pooled embedding of `text`. Unknown tokens are skipped; a text with no known tokens
+    /// embeds to the zero vector.
+    pub fn embed(&self, text: &str) -> Vec<f32> {
+        let mut pooled = vec![0f32; self.dims];
+        let mut count = 0usize;
+        for id in self.tokenize(text) {
+            if Some(id) == self.unk_id {
+                continue;
+            }
+            let row = &self.weights[id * self.dims..(id + 1) * self.dims];
+            for (acc, value) in pooled.iter_mut().zip(row) {
+                *acc += value;
+            }
+            count += 1;
+        }
+        if count > 0 {
+            for value in &mut pooled {
+                *value /= count as f32;
+            }
+        }
+        pooled
+    }
+
+    fn tokenize(&self, text: &str) -> Vec<usize> {
+        let normalized = if self.lowercase {
+            text.to_lowercase()
+        } else {
+            text.to_string()
+        };
+        let mut ids = Vec::new();
+        for word in split_words(&normalized) {
+            self.word_piece(&word, &mut ids);
+        }
+        ids
+    }
+
+    /// Greedy longest-match-first WordPiece, as in BERT.
+    fn word_piece(&self, word: &str, ids: &mut Vec<usize>) {
+        let chars = word.chars().collect::<Vec<_>>();
+        if chars.len() > MAX_WORD_CHARS {
+            ids.extend(self.unk_id);
+            return;
+        }
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let mut end = chars.len();
+            let mut found = None;
+            while start < end {
+                let mut candidate = chars[start..end].iter().collect::<String>();
+                if start > 0 {
+                    candidate.insert_str(0, &self.continuation_prefix);
+                }
+                if let Some(id) = self.vocab.get(&candidate) {
+                    found = Some(*id);
+                    break;
+                }
+                end -= 1;
+            }
+            let Some(id) = found else {
+                ids.extend(self.unk_id);
+                return;
+            };
+            pieces.push(id);
+            start = end;
+        }
+        ids.extend(pieces);
+    }
+}
+
+/// BERT-style pre-tokenization: split on whitespace, and emit punctuation and CJK characters as
+/// words of their own.
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for ch in text.chars() {
+        if ch.is_whitespace() || ch.is_control() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+        } else if ch.is_ascii_punctuation() || is_cjk(ch) || (!ch.is_alphanumeric() && !ch.is_ascii()) {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            words.push(ch.to_string());
+        } else {
+            current.push(ch);
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x4E00..=0x9FFF
+            | 0x3400..=0x4DBF
+            | 0x20000..=0x2A6DF
+            | 0xF900..=0xFAFF
+            | 0x2F800..=0x2FA1F)
+}
+
+/// Reads the embedding table out of a safetensors file. Prefers a tensor named `embeddings`
+/// (model2vec) and otherwise takes the only 2-D tensor present.
+fn read_embedding_table(bytes: &[u8]) -> Result<(usize, usize, Vec<f32>)> {
+    if bytes.len() < 8 {
+        bail!("file too short");
+    }
+    let header_len = u64::from_le_bytes(bytes[..8].try_into()?) as usize;
+    let data_start = 8usize
+        .checked_add(header_len)
+        .filter(|end| *end <= bytes.len())
+        .context("header length out of range")?;
+    let header: Value =
+        serde_json::from_slice(&bytes[8..data_start]).context("parse safetensors header")?;
+    let tensors = header.as_object().context("safetensors header is not an object")?;
+    let is_matrix = |info: &Value| {
+        info.get("shape")
+            .and_then(|v| v.as_array())
+            .map(|shape| shape.len() == 2)
+            .unwrap_or(false)
+    };
+    let info = match tensors.get("embeddings").filter(|info| is_matrix(info)) {
+        Some(info) => info,
+        None => {
+            let mut matrices = tensors
+                .iter()
+                .filter(|(name, info)| name.as_str() != "__metadata__" && is_matrix(info));
+            match (matrices.next(), matrices.next()) {
+                (Some((_, info)), None) => info,
+                _ => bail!("expected an `embeddings` tensor"),
+            }
+        }
+    };
+
+    let shape = info
+        .get("shape")
+        .and_then(|v| v.as_array())
+        .context("tensor shape")?;
+    let rows = shape[0].as_u64().context("tensor rows")? as usize;
+    let dims = shape[1].as_u64().context("tensor dims")? as usize;
+    let offsets = info
+        .get("data_offsets")
+        .and_then(|v| v.as_array())
+        .context("tensor data_offsets")?;
+    let begin = offsets.first().and_then(|v| v.as_u64()).context("tensor offset")? as usize;
+    let end = offsets.get(1).and_then(|v| v.as_u64()).context("tensor offset")? as usize;
+    let data = bytes
+        .get(data_start + begin..data_start + end)
+        .context("tensor data out of range")?;
+    let dtype = info.get("dtype").and_then(|v| v.as_str()).unwrap_or("");
+    let (width, decode): (usize, fn(&[u8]) -> f32) = match dtype {
+        "F32" => (4, |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        "F16" => (2, |b| f16_to_f32(u16::from_le_bytes([b[0], b[1]]))),
+        "BF16" => (2, |b| f32::from_bits((u16::from_le_bytes([b[0], b[1]]) as u32) << 16)),
+        other => bail!("unsupported tensor dtype {other:?}"),
+    };
+    if rows == 0 || dims == 0 || data.len() != rows * dims * width {
+        bail!("tensor shape [{rows}, {dims}] does not match its data");
+    }
+    let weights = data.chunks_exact(width).map(decode).collect();
+    Ok((rows, dims, weights))
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) as u32) << 31;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+    let value = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal: renormalize into an f32 exponent.
+            let mut e = 127 - 15 + 1;
+            let mut m = mantissa;
+            while m & 0x400 == 0 {
+                m <<= 1;
+                e -= 1;
+            }
+            sign | (e << 23) | ((m & 0x3ff) << 13)
+        }
+        (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_model(dir: &Path) {
+        let vocab = ["[UNK]", "pin", "##s", "needle", "tomato", "."];
+        let tokenizer = serde_json::json!({
+            "normalizer": { "type": "BertNormalizer", "lowercase": true },
+            "model": {
+                "type": "WordPiece",
+                "unk_token": "[UNK]",
+                "continuing_subword_prefix": "##",
+                "vocab": vocab
+                    .iter()
+                    .enumerate()
+                    .map(|(id, token)| (token.to_string(), serde_json::json!(id)))
+                    .collect::<serde_json::Map<_, _>>(),
+            }
+        });
+        std::fs::write(dir.join(TOKENIZER_FILE), tokenizer.to_string()).unwrap();
+        let rows: [[f32; 3]; 6] = [
+            [9.0, 9.0, 9.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ];
+        let data = rows
+            .iter()
+            .flatten()
+            .flat_map(|v| half_bits(*v).to_le_bytes())
+            .collect::<Vec<u8>>();
+        let header = serde_json::json!({
+            "embeddings": { "dtype": "F16", "shape": [6, 3], "data_offsets": [0, data.len()] }
+        })
+        .to_string();
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(&data);
+        std::fs::write(dir.join(WEIGHTS_FILE), bytes).unwrap();
+    }
+
+    fn half_bits(value: f32) -> u16 {
+        match value {
+            0.0 => 0,
+            1.0 => 0x3c00,
+            9.0 => 0x4880,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn embeds_with_word_piece_and_mean_pooling() {
+        let dir = std::env::temp_dir().join(format!("clawdex-local-embed-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_model(&dir);
+        let model = LocalEmbeddingModel::load(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(model.dims(), 3);
+        assert_eq!(model.tokenize("Pins."), vec![1, 2, 5]);
+        // Unknown words are dropped rather than pulling the mean towards the [UNK] row.
+        assert_eq!(model.embed("needle zebra"), vec![1.0, 0.0, 0.0]);
+        assert_eq!(model.embed("pins tomato"), vec![2.0 / 3.0, 1.0 / 3.0, 0.0]);
+        assert_eq!(model.embed("zebra"), vec![0.0, 0.0, 0.0]);
+    }
+}
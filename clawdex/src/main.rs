@@ -1,9 +1,12 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
-use clawdex::{config, daemon, gateway, mcp, permissions, plugins, skills_sync, tasks, ui_bridge};
+use clawdex::{
+    config, cron_cli, cron_lock, daemon, gateway, mcp, memory, permissions, plugins, skills_sync,
+    tasks, ui_bridge,
+};
 
 #[derive(Parser)]
 #[command(author, version, about = "Clawdex compatibility runtime", long_about = None)]
@@ -91,6 +94,16 @@ enum Commands {
         #[command(subcommand)]
         command: PermissionsCommand,
     },
+    /// Memory index inspection and maintenance
+    Memory {
+        #[command(subcommand)]
+        command: MemoryCommand,
+    },
+    /// Cron scheduler maintenance
+    Cron {
+        #[command(subcommand)]
+        command: CronCommand,
+    },
 }
 
 #[derive(Subcommand)]
@@ -385,6 +398,285 @@ enum PermissionsCommand {
     },
 }
 
+#[derive(Subcommand)]
+enum CronCommand {
+    /// List cron jobs with their next run and last status
+    List {
+        /// Include disabled jobs
+        #[arg(long)]
+        all: bool,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Add a cron job
+    Add {
+        #[arg(long)]
+        name: String,
+        /// Job id (generated when omitted)
+        #[arg(long)]
+        id: Option<String>,
+        #[command(flatten)]
+        job: CronJobArgs,
+        /// Create the job disabled
+        #[arg(long)]
+        disabled: bool,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Update a cron job; only the flags given are changed
+    Update {
+        #[arg(long = "job-id")]
+        job_id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[command(flatten)]
+        job: CronJobArgs,
+        #[arg(long, conflicts_with = "disable")]
+        enable: bool,
+        #[arg(long)]
+        disable: bool,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Remove a cron job
+    Remove {
+        #[arg(long = "job-id")]
+        job_id: String,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Run a cron job now (through the daemon when it is running)
+    Run {
+        #[arg(long = "job-id")]
+        job_id: String,
+        /// Run even if the job is not due
+        #[arg(long)]
+        force: bool,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Show recorded runs of a cron job
+    Runs {
+        #[arg(long = "job-id")]
+        job_id: String,
+        #[arg(long)]
+        limit: Option<usize>,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Show upcoming fire times
+    Next {
+        /// One job instead of every enabled job
+        #[arg(long = "job-id")]
+        job_id: Option<String>,
+        /// Number of fire times to show (default 5)
+        #[arg(long)]
+        count: Option<usize>,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Validate a schedule and show its next fire times in UTC and its time zone
+    Preview {
+        /// Preview this job's schedule instead of one given by flags
+        #[arg(long = "job-id", conflicts_with_all = ["cron", "tz", "every", "at"])]
+        job_id: Option<String>,
+        /// Cron expression (seconds first, e.g. "0 0 9 * * Mon-Fri")
+        #[arg(long, conflicts_with_all = ["every", "at"])]
+        cron: Option<String>,
+        /// IANA time zone for --cron (default UTC)
+        #[arg(long)]
+        tz: Option<String>,
+        /// Fixed interval, e.g. 15m or 1h30m
+        #[arg(long, conflicts_with = "at")]
+        every: Option<String>,
+        /// One-shot time, e.g. "2026-10-20 09:00 Europe/Oslo" or +20m
+        #[arg(long)]
+        at: Option<String>,
+        /// Number of fire times to show (default 5)
+        #[arg(long)]
+        count: Option<usize>,
+        #[arg(long)]
+        json: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Release stale job locks and close the runs they belonged to
+    Unlock {
+        #[arg(long = "job-id")]
+        job_id: Option<String>,
+        /// Every job instead of one
+        #[arg(long, conflicts_with = "job_id")]
+        all: bool,
+        /// Also release locks whose owner still looks alive
+        #[arg(long)]
+        force: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+}
+
+/// Schedule and payload flags shared by `cron add` and `cron update`.
+#[derive(Args)]
+struct CronJobArgs {
+    /// Cron expression (seconds first, e.g. "0 0 9 * * Mon-Fri")
+    #[arg(long, conflicts_with_all = ["every", "at"])]
+    cron: Option<String>,
+    /// IANA time zone for --cron (default UTC)
+    #[arg(long)]
+    tz: Option<String>,
+    /// Fixed interval, e.g. 15m or 1h30m
+    #[arg(long, conflicts_with = "at")]
+    every: Option<String>,
+    /// One-shot time, e.g. "2026-10-20 09:00 Europe/Oslo" or +20m
+    #[arg(long)]
+    at: Option<String>,
+    /// Agent turn prompt (isolated session)
+    #[arg(long, conflicts_with = "system_event")]
+    message: Option<String>,
+    /// System event text (main session)
+    #[arg(long = "system-event")]
+    system_event: Option<String>,
+    /// Session target: main or isolated
+    #[arg(long)]
+    session: Option<String>,
+    /// Wake mode: now or next-heartbeat
+    #[arg(long)]
+    wake: Option<String>,
+    /// Run after this job finishes (repeatable)
+    #[arg(long = "depends-on")]
+    depends_on: Vec<String>,
+}
+
+impl CronJobArgs {
+    fn into_flags(self, name: Option<String>, enabled: Option<bool>) -> cron_cli::JobFlags {
+        cron_cli::JobFlags {
+            name,
+            cron: self.cron,
+            tz: self.tz,
+            every: self.every,
+            at: self.at,
+            message: self.message,
+            system_event: self.system_event,
+            session: self.session,
+            wake: self.wake,
+            depends_on: self.depends_on,
+            enabled,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum MemoryCommand {
+    /// Show index statistics and health
+    Status {
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Sync the index now (optionally re-chunking or re-embedding everything)
+    Reindex {
+        /// Re-chunk every file even if unchanged
+        #[arg(long)]
+        force: bool,
+        /// Drop stored embeddings and failure backoffs before syncing
+        #[arg(long)]
+        reembed: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Search memory like the memory_search tool
+    Search {
+        #[arg(long)]
+        query: String,
+        #[arg(long = "max-results")]
+        max_results: Option<usize>,
+        #[arg(long = "min-score")]
+        min_score: Option<f64>,
+        #[arg(long = "session-key")]
+        session_key: Option<String>,
+        #[arg(long = "path-glob")]
+        path_glob: Vec<String>,
+        /// memory, sessions or extra (repeatable)
+        #[arg(long)]
+        source: Vec<String>,
+        #[arg(long = "modified-after")]
+        modified_after: Option<String>,
+        #[arg(long = "modified-before")]
+        modified_before: Option<String>,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Read lines from a memory file like the memory_get tool
+    Get {
+        #[arg(long)]
+        path: String,
+        #[arg(long)]
+        from: Option<usize>,
+        #[arg(long)]
+        lines: Option<usize>,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// List files whose embeddings are failing
+    Failures {
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+        /// Clear failure rows so the next sync retries immediately
+        #[arg(long)]
+        clear: bool,
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+    /// Drop orphaned vectors and stale vector indexes, then compact the database
+    Vacuum {
+        #[arg(long = "state-dir")]
+        state_dir: Option<PathBuf>,
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+    },
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -749,5 +1041,214 @@ fn main() -> Result<()> {
                 Ok(())
             }
         },
+        Commands::Memory { command } => {
+            let value = match command {
+                MemoryCommand::Status { state_dir, workspace } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    memory::memory_status(&paths)?
+                }
+                MemoryCommand::Reindex {
+                    force,
+                    reembed,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    memory::memory_reindex(&paths, force, reembed)?
+                }
+                MemoryCommand::Search {
+                    query,
+                    max_results,
+                    min_score,
+                    session_key,
+                    path_glob,
+                    source,
+                    modified_after,
+                    modified_before,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    let mut args = serde_json::json!({ "query": query });
+                    if let Some(max_results) = max_results {
+                        args["maxResults"] = max_results.into();
+                    }
+                    if let Some(min_score) = min_score {
+                        args["minScore"] = min_score.into();
+                    }
+                    if let Some(session_key) = session_key {
+                        args["sessionKey"] = session_key.into();
+                    }
+                    if !path_glob.is_empty() {
+                        args["pathGlobs"] = path_glob.into();
+                    }
+                    if !source.is_empty() {
+                        args["sources"] = source.into();
+                    }
+                    if let Some(modified_after) = modified_after {
+                        args["modifiedAfter"] = modified_after.into();
+                    }
+                    if let Some(modified_before) = modified_before {
+                        args["modifiedBefore"] = modified_before.into();
+                    }
+                    memory::memory_search(&paths, &args)?
+                }
+                MemoryCommand::Get {
+                    path,
+                    from,
+                    lines,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    let mut args = serde_json::json!({ "path": path });
+                    if let Some(from) = from {
+                        args["from"] = from.into();
+                    }
+                    if let Some(lines) = lines {
+                        args["lines"] = lines.into();
+                    }
+                    memory::memory_get(&paths, &args)?
+                }
+                MemoryCommand::Failures {
+                    limit,
+                    clear,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    memory::memory_failures(&paths, limit, clear)?
+                }
+                MemoryCommand::Vacuum { state_dir, workspace } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    memory::memory_vacuum(&paths)?
+                }
+            };
+            println!("{}", serde_json::to_string_pretty(&value)?);
+            Ok(())
+        }
+        Commands::Cron { command } => {
+            let output = match command {
+                CronCommand::List {
+                    all,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    cron_cli::list_command(&paths, all, json)?
+                }
+                CronCommand::Add {
+                    name,
+                    id,
+                    job,
+                    disabled,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    let flags = job.into_flags(Some(name), disabled.then_some(false));
+                    cron_cli::add_command(&paths, id.as_deref(), &flags, json)?
+                }
+                CronCommand::Update {
+                    job_id,
+                    name,
+                    job,
+                    enable,
+                    disable,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    let enabled = if enable {
+                        Some(true)
+                    } else if disable {
+                        Some(false)
+                    } else {
+                        None
+                    };
+                    let flags = job.into_flags(name, enabled);
+                    cron_cli::update_command(&paths, &job_id, &flags, json)?
+                }
+                CronCommand::Remove {
+                    job_id,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    cron_cli::remove_command(&paths, &job_id, json)?
+                }
+                CronCommand::Run {
+                    job_id,
+                    force,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    cron_cli::run_command(&paths, &job_id, force, json)?
+                }
+                CronCommand::Runs {
+                    job_id,
+                    limit,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    cron_cli::runs_command(&paths, &job_id, limit, json)?
+                }
+                CronCommand::Next {
+                    job_id,
+                    count,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    cron_cli::next_command(&paths, job_id.as_deref(), count, json)?
+                }
+                CronCommand::Preview {
+                    job_id,
+                    cron,
+                    tz,
+                    every,
+                    at,
+                    count,
+                    json,
+                    state_dir,
+                    workspace,
+                } => {
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    let flags = cron_cli::JobFlags {
+                        cron,
+                        tz,
+                        every,
+                        at,
+                        ..cron_cli::JobFlags::default()
+                    };
+                    cron_cli::preview_command(&paths, job_id.as_deref(), &flags, count, json)?
+                }
+                CronCommand::Unlock {
+                    job_id,
+                    all,
+                    force,
+                    state_dir,
+                    workspace,
+                } => {
+                    if job_id.is_none() && !all {
+                        anyhow::bail!("pass --job-id <id> or --all");
+                    }
+                    let (_cfg, paths) = config::load_config(state_dir, workspace)?;
+                    let value = cron_lock::unlock(&paths, job_id.as_deref(), force)?;
+                    serde_json::to_string_pretty(&value)?
+                }
+            };
+            println!("{output}");
+            Ok(())
+        }
     }
 }
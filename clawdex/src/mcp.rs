@@ -11,6 +11,7 @@ use crate::config::{
     resolve_cron_enabled, resolve_heartbeat_enabled, resolve_mcp_policy, ClawdConfig, ClawdPaths,
 };
 use crate::cron;
+use crate::cron_calendar::CALENDAR_KEYS;
 use crate::daemon_client;
 use crate::gateway;
 use crate::heartbeat;
@@ -41,6 +42,14 @@ const CRON_RUNS_REQUEST_SCHEMA: &str =
     include_str!("../../compat/tool-schemas/cron.runs.request.schema.json");
 const CRON_RUNS_RESPONSE_SCHEMA: &str =
     include_str!("../../compat/tool-schemas/cron.runs.response.schema.json");
+const CRON_CANCEL_REQUEST_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/cron.cancel.request.schema.json");
+const CRON_CANCEL_RESPONSE_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/cron.cancel.response.schema.json");
+const CRON_PREVIEW_REQUEST_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/cron.preview.request.schema.json");
+const CRON_PREVIEW_RESPONSE_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/cron.preview.response.schema.json");
 const CRON_STATUS_REQUEST_SCHEMA: &str =
     include_str!("../../compat/tool-schemas/cron.status.request.schema.json");
 const CRON_STATUS_RESPONSE_SCHEMA: &str =
@@ -57,6 +66,14 @@ const MEMORY_WRITE_REQUEST_SCHEMA: &str =
     include_str!("../../compat/tool-schemas/memory_write.request.schema.json");
 const MEMORY_WRITE_RESPONSE_SCHEMA: &str =
     include_str!("../../compat/tool-schemas/memory_write.response.schema.json");
+const MEMORY_FORGET_REQUEST_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/memory_forget.request.schema.json");
+const MEMORY_FORGET_RESPONSE_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/memory_forget.response.schema.json");
+const MEMORY_EDIT_REQUEST_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/memory_edit.request.schema.json");
+const MEMORY_EDIT_RESPONSE_SCHEMA: &str =
+    include_str!("../../compat/tool-schemas/memory_edit.response.schema.json");
 const MESSAGE_SEND_REQUEST_SCHEMA: &str =
     include_str!("../../compat/tool-schemas/message.send.request.schema.json");
 const MESSAGE_SEND_RESPONSE_SCHEMA: &str =
@@ -304,6 +321,16 @@ fn tool_definitions() -> Vec<Tool> {
             icons: None,
             meta: None,
         },
+        Tool {
+            name: "cron.cancel".to_string(),
+            title: None,
+            description: Some("Cancel a running or queued cron job".to_string()),
+            input_schema: schema_value(CRON_CANCEL_REQUEST_SCHEMA),
+            output_schema: Some(schema_value(CRON_CANCEL_RESPONSE_SCHEMA)),
+            annotations: None,
+            icons: None,
+            meta: None,
+        },
         Tool {
             name: "cron.runs".to_string(),
             title: None,
@@ -314,6 +341,16 @@ fn tool_definitions() -> Vec<Tool> {
             icons: None,
             meta: None,
         },
+        Tool {
+            name: "cron.preview".to_string(),
+            title: None,
+            description: Some("Validate a cron schedule and show its next fire times".to_string()),
+            input_schema: schema_value(CRON_PREVIEW_REQUEST_SCHEMA),
+            output_schema: Some(schema_value(CRON_PREVIEW_RESPONSE_SCHEMA)),
+            annotations: None,
+            icons: None,
+            meta: None,
+        },
         Tool {
             name: "memory_search".to_string(),
             title: None,
@@ -344,6 +381,30 @@ fn tool_definitions() -> Vec<Tool> {
             icons: None,
             meta: None,
         },
+        Tool {
+            name: "memory_forget".to_string(),
+            title: None,
+            description: Some(
+                "Remove a memory_write entry with explicit approval; keeps a tombstone".to_string(),
+            ),
+            input_schema: schema_value(MEMORY_FORGET_REQUEST_SCHEMA),
+            output_schema: Some(schema_value(MEMORY_FORGET_RESPONSE_SCHEMA)),
+            annotations: None,
+            icons: None,
+            meta: None,
+        },
+        Tool {
+            name: "memory_edit".to_string(),
+            title: None,
+            description: Some(
+                "Correct a memory_write entry with explicit approval; keeps edit history".to_string(),
+            ),
+            input_schema: schema_value(MEMORY_EDIT_REQUEST_SCHEMA),
+            output_schema: Some(schema_value(MEMORY_EDIT_RESPONSE_SCHEMA)),
+            annotations: None,
+            icons: None,
+            meta: None,
+        },
         Tool {
             name: "message.send".to_string(),
             title: None,
@@ -504,6 +565,18 @@ fn handle_tool_call(
                     .map_err(|err| JsonRpcError::internal(err.to_string()))?
             }
         }
+        "cron.cancel" => {
+            let job_id = arguments
+                .get("jobId")
+                .or_else(|| arguments.get("id"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            if job_id.is_empty() {
+                return Err(JsonRpcError::invalid_params("missing jobId"));
+            }
+            cron::cancel_job(paths, &arguments)
+                .map_err(|err| JsonRpcError::internal(err.to_string()))?
+        }
         "cron.runs" => {
             let job_id = arguments
                 .get("jobId")
@@ -515,6 +588,8 @@ fn handle_tool_call(
             }
             cron::runs(paths, &arguments).map_err(|err| JsonRpcError::internal(err.to_string()))?
         }
+        "cron.preview" => cron::preview_schedule(&arguments)
+            .map_err(|err| JsonRpcError::invalid_params(err.to_string()))?,
         "memory_search" => {
             let query = arguments
                 .get("query")
@@ -546,6 +621,33 @@ fn handle_tool_call(
             memory::memory_write(paths, &arguments)
                 .map_err(|err| JsonRpcError::internal(err.to_string()))?
         }
+        "memory_forget" | "memory_edit" => {
+            let entry_id = arguments
+                .get("entryId")
+                .map(|v| match v {
+                    Value::String(s) => s.trim().to_string(),
+                    other => other.to_string(),
+                })
+                .unwrap_or_default();
+            if entry_id.is_empty() {
+                return Err(JsonRpcError::invalid_params("missing entryId"));
+            }
+            if name == "memory_forget" {
+                memory::memory_forget(paths, &arguments)
+                    .map_err(|err| JsonRpcError::internal(err.to_string()))?
+            } else {
+                let content = arguments
+                    .get("content")
+                    .or_else(|| arguments.get("text"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                if content.trim().is_empty() {
+                    return Err(JsonRpcError::invalid_params("missing content"));
+                }
+                memory::memory_edit(paths, &arguments)
+                    .map_err(|err| JsonRpcError::internal(err.to_string()))?
+            }
+        }
         "message.send" => {
             if let Some(map) = arguments.as_object_mut() {
                 for field in ["text", "message"] {
@@ -675,7 +777,15 @@ fn sanitize_tool_response(name: &str, value: Value) -> Value {
         "cron.add" | "cron.update" => sanitize_cron_job(value),
         "cron.remove" => sanitize_object_fields(value, &["ok", "removed"]),
         "cron.run" => sanitize_object_fields(value, &["ok", "ran", "reason"]),
+        "cron.cancel" => sanitize_object_fields(
+            value,
+            &["ok", "cancelled", "running", "dequeued", "reason"],
+        ),
         "cron.runs" => sanitize_cron_runs_response(value),
+        "cron.preview" => sanitize_object_fields(
+            value,
+            &["ok", "kind", "expr", "fields", "timezone", "runs", "warnings"],
+        ),
         "memory_search" => sanitize_memory_search_response(value),
         "memory_get" => sanitize_object_fields(
             value,
@@ -692,7 +802,13 @@ fn sanitize_tool_response(name: &str, value: Value) -> Value {
         ),
         "memory_write" => sanitize_object_fields(
             value,
-            &["ok", "scope", "path", "indexed", "disabled", "error"],
+            &["ok", "scope", "path", "entryId", "indexed", "disabled", "error"],
+        ),
+        "memory_forget" | "memory_edit" => sanitize_object_fields(
+            value,
+            &[
+                "ok", "action", "scope", "path", "entryId", "indexed", "disabled", "error",
+            ],
         ),
         "message.send" => sanitize_object_fields(
             value,
@@ -747,6 +863,9 @@ fn sanitize_cron_list_response(value: Value) -> Value {
             let sanitized = jobs.iter().map(sanitize_cron_job_ref).collect::<Vec<_>>();
             out.insert("jobs".to_string(), Value::Array(sanitized));
         }
+        if let Some(Value::Array(warnings)) = map.get("warnings") {
+            out.insert("warnings".to_string(), Value::Array(warnings.clone()));
+        }
     }
     Value::Object(out)
 }
@@ -770,6 +889,13 @@ fn sanitize_cron_runs_response(value: Value) -> Value {
                             "runAtMs",
                             "durationMs",
                             "nextRunAtMs",
+                            "scheduledAtMs",
+                            "lastScheduledAtMs",
+                            "missedCount",
+                            "attempt",
+                            "retryAtMs",
+                            "triggered",
+                            "triggeredBy",
                         ],
                     )
                 })
@@ -805,6 +931,14 @@ fn sanitize_cron_job_map(map: &Map<String, Value>) -> Map<String, Value> {
     insert_field(&mut out, map, "description");
     insert_field(&mut out, map, "enabled");
     insert_field(&mut out, map, "deleteAfterRun");
+    insert_non_null_field(&mut out, map, "catchUp");
+    insert_non_null_field(&mut out, map, "maxLatenessMs");
+    insert_non_null_field(&mut out, map, "maxConcurrentRuns");
+    insert_non_null_field(&mut out, map, "timeoutMs");
+    insert_non_null_field(&mut out, map, "retry");
+    insert_non_null_field(&mut out, map, "dependsOn");
+    insert_non_null_field(&mut out, map, "onSuccess");
+    insert_non_null_field(&mut out, map, "onFailure");
     insert_field(&mut out, map, "createdAtMs");
     insert_field(&mut out, map, "updatedAtMs");
     if let Some(schedule) = map.get("schedule") {
@@ -842,6 +976,7 @@ fn sanitize_cron_schedule(value: &Value) -> Value {
                 "every" => {
                     insert_field(&mut out, map, "everyMs");
                     insert_field(&mut out, map, "anchorMs");
+                    insert_non_null_field(&mut out, map, "tz");
                 }
                 "cron" => {
                     insert_field(&mut out, map, "expr");
@@ -850,6 +985,11 @@ fn sanitize_cron_schedule(value: &Value) -> Value {
                 }
                 _ => {}
             }
+            if kind != "at" {
+                for key in CALENDAR_KEYS {
+                    insert_non_null_field(&mut out, map, key);
+                }
+            }
         }
         return Value::Object(out);
     }
@@ -902,6 +1042,13 @@ fn sanitize_cron_state(value: &Value) -> Value {
             "lastStatus",
             "lastError",
             "lastDurationMs",
+            "lastMissedAtMs",
+            "catchUpQueue",
+            "retryAttempt",
+            "retryAtMs",
+            "triggeredAtMs",
+            "upstream",
+            "upstreams",
         ],
     )
 }
@@ -928,6 +1075,7 @@ fn sanitize_memory_search_response(value: Value) -> Value {
                             "score",
                             "snippet",
                             "source",
+                            "section",
                             "citation",
                         ],
                     )
@@ -1020,11 +1168,15 @@ fn validate_tool_arguments(name: &str, arguments: &Value) -> std::result::Result
         "cron.list" => Some(CRON_LIST_REQUEST_SCHEMA),
         "cron.remove" => Some(CRON_REMOVE_REQUEST_SCHEMA),
         "cron.run" => Some(CRON_RUN_REQUEST_SCHEMA),
+        "cron.cancel" => Some(CRON_CANCEL_REQUEST_SCHEMA),
         "cron.runs" => Some(CRON_RUNS_REQUEST_SCHEMA),
+        "cron.preview" => Some(CRON_PREVIEW_REQUEST_SCHEMA),
         "cron.status" => Some(CRON_STATUS_REQUEST_SCHEMA),
         "memory_search" => Some(MEMORY_SEARCH_REQUEST_SCHEMA),
         "memory_get" => Some(MEMORY_GET_REQUEST_SCHEMA),
         "memory_write" => Some(MEMORY_WRITE_REQUEST_SCHEMA),
+        "memory_forget" => Some(MEMORY_FORGET_REQUEST_SCHEMA),
+        "memory_edit" => Some(MEMORY_EDIT_REQUEST_SCHEMA),
         "message.send" => Some(MESSAGE_SEND_REQUEST_SCHEMA),
         "channels.list" => Some(CHANNELS_LIST_REQUEST_SCHEMA),
         "channels.resolve_target" => Some(CHANNELS_RESOLVE_REQUEST_SCHEMA),
@@ -1061,11 +1213,15 @@ fn validate_tool_response(name: &str, result: &Value) -> std::result::Result<(),
         "cron.list" => Some(CRON_LIST_RESPONSE_SCHEMA),
         "cron.remove" => Some(CRON_REMOVE_RESPONSE_SCHEMA),
         "cron.run" => Some(CRON_RUN_RESPONSE_SCHEMA),
+        "cron.cancel" => Some(CRON_CANCEL_RESPONSE_SCHEMA),
         "cron.runs" => Some(CRON_RUNS_RESPONSE_SCHEMA),
+        "cron.preview" => Some(CRON_PREVIEW_RESPONSE_SCHEMA),
         "cron.status" => Some(CRON_STATUS_RESPONSE_SCHEMA),
         "memory_search" => Some(MEMORY_SEARCH_RESPONSE_SCHEMA),
         "memory_get" => Some(MEMORY_GET_RESPONSE_SCHEMA),
         "memory_write" => Some(MEMORY_WRITE_RESPONSE_SCHEMA),
+        "memory_forget" => Some(MEMORY_FORGET_RESPONSE_SCHEMA),
+        "memory_edit" => Some(MEMORY_EDIT_RESPONSE_SCHEMA),
         "message.send" => Some(MESSAGE_SEND_RESPONSE_SCHEMA),
         "channels.list" => Some(CHANNELS_LIST_RESPONSE_SCHEMA),
         "channels.resolve_target" => Some(CHANNELS_RESOLVE_RESPONSE_SCHEMA),
@@ -1116,11 +1272,35 @@ fn normalize_args_for_validation(name: &str, arguments: &Value) -> Value {
                     ("max_results", "maxResults"),
                     ("min_score", "minScore"),
                     ("session_key", "sessionKey"),
+                    ("agent_id", "agentId"),
+                    ("path_globs", "pathGlobs"),
+                    ("modified_after", "modifiedAfter"),
+                    ("modified_before", "modifiedBefore"),
                 ],
             );
         }
         "memory_write" => {
-            normalize_aliases(map, &[("memory_scope", "scope"), ("plugin_id", "pluginId")]);
+            normalize_aliases(
+                map,
+                &[
+                    ("memory_scope", "scope"),
+                    ("plugin_id", "pluginId"),
+                    ("agent_id", "agentId"),
+                    ("session_key", "sessionKey"),
+                ],
+            );
+        }
+        "memory_forget" | "memory_edit" => {
+            normalize_aliases(
+                map,
+                &[
+                    ("memory_scope", "scope"),
+                    ("plugin_id", "pluginId"),
+                    ("agent_id", "agentId"),
+                    ("session_key", "sessionKey"),
+                    ("entry_id", "entryId"),
+                ],
+            );
         }
         "message.send" => {
             normalize_aliases(
@@ -1179,8 +1359,19 @@ fn normalize_aliases(map: &mut Map<String, Value>, pairs: &[(&str, &str)]) {
     }
 }
 
+const CRON_JOB_ALIASES: &[(&str, &str)] = &[
+    ("session_key", "sessionKey"),
+    ("catch_up", "catchUp"),
+    ("max_lateness_ms", "maxLatenessMs"),
+    ("max_concurrent_runs", "maxConcurrentRuns"),
+    ("timeout_ms", "timeoutMs"),
+    ("depends_on", "dependsOn"),
+    ("on_success", "onSuccess"),
+    ("on_failure", "onFailure"),
+];
+
 fn normalize_cron_job_for_validation(map: &mut Map<String, Value>, apply_defaults: bool) {
-    normalize_aliases(map, &[("session_key", "sessionKey")]);
+    normalize_aliases(map, CRON_JOB_ALIASES);
     if let Some(Value::Object(schedule)) = map.get_mut("schedule") {
         normalize_cron_schedule_for_validation(schedule);
     }
@@ -1212,7 +1403,7 @@ fn normalize_cron_job_for_validation(map: &mut Map<String, Value>, apply_default
     }
 
     if let Some(Value::Object(patch)) = map.get_mut("patch") {
-        normalize_aliases(patch, &[("session_key", "sessionKey")]);
+        normalize_aliases(patch, CRON_JOB_ALIASES);
         if let Some(Value::Object(schedule)) = patch.get_mut("schedule") {
             normalize_cron_schedule_for_validation(schedule);
         }
@@ -1353,6 +1544,23 @@ mod tests {
         assert!(validate_tool_arguments("memory_search", &args).is_ok());
     }
 
+    #[test]
+    fn validates_memory_search_filters() {
+        let args = json!({
+            "query": "hi",
+            "path_globs": "memory/2026-*",
+            "sources": ["memory", "extra"],
+            "modifiedAfter": "2026-01-01",
+            "modified_before": 1767225600000i64,
+            "agent_id": "kline"
+        });
+        assert!(validate_tool_arguments("memory_search", &args).is_ok());
+        let args = json!({ "query": "hi", "sources": ["inbox"] });
+        assert!(validate_tool_arguments("memory_search", &args).is_err());
+        let args = json!({ "query": "hi", "pathGlobs": [1] });
+        assert!(validate_tool_arguments("memory_search", &args).is_err());
+    }
+
     #[test]
     fn validates_memory_write_arguments() {
         let args = json!({
@@ -1362,6 +1570,35 @@ mod tests {
             "confirmation": "WRITE_MEMORY",
         });
         assert!(validate_tool_arguments("memory_write", &args).is_ok());
+        let args = json!({
+            "content": "Remember this",
+            "scope": "agent",
+            "agent_id": "kline",
+            "session_key": "agent:kline:telegram:1",
+        });
+        assert!(validate_tool_arguments("memory_write", &args).is_ok());
+    }
+
+    #[test]
+    fn validates_memory_forget_and_edit_arguments() {
+        let args = json!({
+            "entry_id": "1767225600000",
+            "scope": "workspace",
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+            "reason": "outdated",
+        });
+        assert!(validate_tool_arguments("memory_forget", &args).is_ok());
+        assert!(validate_tool_arguments("memory_forget", &json!({ "approved": true })).is_err());
+        let args = json!({
+            "entryId": 1767225600000i64,
+            "content": "Corrected",
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+        });
+        assert!(validate_tool_arguments("memory_edit", &args).is_ok());
+        let args = json!({ "entryId": "1", "approved": true });
+        assert!(validate_tool_arguments("memory_edit", &args).is_err());
     }
 
     #[test]
@@ -1495,11 +1732,15 @@ mod tests {
             ("cron.list", CRON_LIST_RESPONSE_SCHEMA),
             ("cron.remove", CRON_REMOVE_RESPONSE_SCHEMA),
             ("cron.run", CRON_RUN_RESPONSE_SCHEMA),
+            ("cron.cancel", CRON_CANCEL_RESPONSE_SCHEMA),
             ("cron.runs", CRON_RUNS_RESPONSE_SCHEMA),
+            ("cron.preview", CRON_PREVIEW_RESPONSE_SCHEMA),
             ("cron.status", CRON_STATUS_RESPONSE_SCHEMA),
             ("memory_search", MEMORY_SEARCH_RESPONSE_SCHEMA),
             ("memory_get", MEMORY_GET_RESPONSE_SCHEMA),
             ("memory_write", MEMORY_WRITE_RESPONSE_SCHEMA),
+            ("memory_forget", MEMORY_FORGET_RESPONSE_SCHEMA),
+            ("memory_edit", MEMORY_EDIT_RESPONSE_SCHEMA),
             ("message.send", MESSAGE_SEND_RESPONSE_SCHEMA),
             ("channels.list", CHANNELS_LIST_RESPONSE_SCHEMA),
             ("channels.resolve_target", CHANNELS_RESOLVE_RESPONSE_SCHEMA),
@@ -1580,6 +1821,10 @@ mod tests {
         assert_response_ok("cron.update", sample_cron_job());
         assert_response_ok("cron.remove", json!({ "ok": true, "removed": true }));
         assert_response_ok("cron.run", json!({ "ok": true, "ran": true }));
+        assert_response_ok(
+            "cron.cancel",
+            json!({ "ok": true, "cancelled": true, "running": true, "dequeued": 0 }),
+        );
         assert_response_ok(
             "cron.runs",
             json!({
@@ -1588,6 +1833,25 @@ mod tests {
                 ]
             }),
         );
+        assert_response_ok(
+            "cron.preview",
+            json!({
+                "ok": true,
+                "kind": "cron",
+                "expr": "0 0 9 * * *",
+                "fields": 6,
+                "timezone": "Europe/Oslo",
+                "runs": [
+                    {
+                        "atMs": 1,
+                        "utc": "2026-10-19T07:00:00Z",
+                        "local": "2026-10-19T09:00:00+02:00",
+                        "dstAmbiguous": false
+                    }
+                ],
+                "warnings": []
+            }),
+        );
         assert_response_ok(
             "memory_search",
             json!({
@@ -1612,7 +1876,15 @@ mod tests {
         );
         assert_response_ok(
             "memory_write",
-            json!({ "ok": true, "scope": "workspace", "path": "memory/writes/workspace/a.md", "indexed": true }),
+            json!({ "ok": true, "scope": "workspace", "path": "memory/writes/workspace/a.md", "entryId": "1", "indexed": true }),
+        );
+        assert_response_ok(
+            "memory_forget",
+            json!({ "ok": true, "action": "forget", "scope": "workspace", "path": "memory/writes/workspace/a.md", "entryId": "1", "indexed": true }),
+        );
+        assert_response_ok(
+            "memory_edit",
+            json!({ "ok": true, "action": "edit", "scope": "workspace", "path": "memory/writes/workspace/a.md", "entryId": "1", "indexed": true }),
         );
         assert_response_ok(
             "message.send",
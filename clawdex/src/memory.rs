@@ -1,10 +1,11 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::collections::hash_map::DefaultHasher;
 use std::fs::OpenOptions;
 use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, UNIX_EPOCH};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use reqwest::blocking::Client;
@@ -12,21 +13,49 @@ use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
 use rusqlite::types::Value as SqlValue;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use walkdir::WalkDir;
 
 use crate::config::{
     resolve_embeddings_config, resolve_memory_enabled, ClawdConfig, ClawdPaths, EmbeddingsConfig,
 };
+use crate::local_embeddings::{load_local_model, LocalEmbeddingModel};
+use crate::memory_ann::{AnnEntry, AnnIndex, AnnKey};
 use crate::util::{now_ms, read_to_string};
 
 const DB_FILE: &str = "fts.sqlite";
 const DEFAULT_CHUNK_TOKENS: usize = 400;
 const DEFAULT_CHUNK_OVERLAP: usize = 80;
-const SCHEMA_VERSION: i64 = 3;
+const SCHEMA_VERSION: i64 = 6;
+const MEMORY_FTS_SQL: &str = "CREATE VIRTUAL TABLE IF NOT EXISTS memory_fts USING fts5(path, start_line UNINDEXED, end_line UNINDEXED, source UNINDEXED, section UNINDEXED, text, tokenize='unicode61')";
 const EMBEDDINGS_QUERY_FAILURE_PATH: &str = "__query__";
 const EMBEDDINGS_QUERY_FAILURE_SOURCE: &str = "__query__";
 const MEMORY_WRITES_DIR: &str = "writes";
 const MEMORY_WRITE_CONFIRMATION: &str = "WRITE_MEMORY";
+const MEMORY_WRITE_HISTORY_FILE: &str = "history.jsonl";
+const MEMORY_WRITE_LOCK_FILE: &str = ".lock";
+const MEMORY_WRITE_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+/// A lock file older than this was left by a writer that died mid-update.
+const MEMORY_WRITE_LOCK_STALE: Duration = Duration::from_secs(60);
+const MEMORY_ENTRY_HEADING: &str = "## Entry ";
+/// Workspace directory for agent-private notes (`memory/agents/<agentId>/...`).
+const MEMORY_AGENTS_DIR: &str = "memory/agents/";
+const DEFAULT_HYBRID_VECTOR_WEIGHT: f64 = 0.4;
+const DEFAULT_HYBRID_TEXT_WEIGHT: f64 = 0.6;
+const DEFAULT_HYBRID_CANDIDATE_MULTIPLIER: usize = 4;
+const DEFAULT_HYBRID_RRF_K: f64 = 60.0;
+const DEFAULT_VECTOR_INDEX_PROBES: usize = 16;
+const META_INDEX_EPOCH: &str = "index_epoch";
+const META_EMBEDDINGS_GENERATION: &str = "embeddings_generation";
+const META_WATCHER_HEARTBEAT: &str = "watcher_heartbeat_ms";
+const META_WATCHER_SESSIONS: &str = "watcher_sessions";
+const META_WATCHER_PENDING: &str = "watcher_pending";
+const META_LOCAL_BACKFILL: &str = "local_backfill_ms";
+/// Searches top up the local fallback's chunk vectors at most this often, and at most
+/// `LOCAL_BACKFILL_BATCH_LIMIT` chunks at a time.
+const LOCAL_BACKFILL_INTERVAL_MS: i64 = 60_000;
+const LOCAL_BACKFILL_BATCH_LIMIT: usize = 256;
+const WATCHER_HEARTBEAT_TTL_MS: i64 = 15_000;
 
 #[derive(Debug, Clone)]
 struct SearchRow {
@@ -35,11 +64,34 @@ struct SearchRow {
     end_line: i64,
     text: String,
     source: String,
+    section: Option<String>,
     fts_score: f64,
     embed_score: Option<f64>,
     final_score: f64,
 }
 
+#[derive(Debug, Clone, Copy)]
+struct HybridSettings {
+    enabled: bool,
+    vector_weight: f64,
+    text_weight: f64,
+    candidate_multiplier: usize,
+    rrf_k: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryReindexSummary {
+    pub indexed: usize,
+    pub removed: usize,
+    pub full_sync: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct VectorIndexSettings {
+    persist: bool,
+    probes: usize,
+}
+
 #[derive(Debug, Clone)]
 struct EmbeddingProvider {
     client: Client,
@@ -49,12 +101,16 @@ struct EmbeddingProvider {
     model: String,
     api_key: String,
     batch_size: usize,
+    local: Option<Arc<LocalEmbeddingModel>>,
+    /// Local model used while this (remote) provider is backing off after failures.
+    fallback: Option<Box<EmbeddingProvider>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum EmbeddingProviderKind {
     OpenAICompatible,
     Ollama,
+    Local,
 }
 
 #[derive(Debug, Deserialize)]
@@ -78,6 +134,15 @@ struct MemoryChunk {
     start_line: i64,
     end_line: i64,
     text: String,
+    /// Heading breadcrumb the chunk sits under, e.g. `Projects > Billing > Decisions`.
+    section: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ChunkSettings {
+    tokens: usize,
+    overlap: usize,
+    structured: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +152,8 @@ struct IndexedFile {
     size: i64,
     source: String,
     content: String,
+    /// Owning agent namespace (see `indexed_agent_namespace`); `None` for shared memory.
+    agent_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -94,6 +161,7 @@ enum MemoryWriteScope {
     Global,
     Workspace,
     Plugin(String),
+    Agent(String),
 }
 
 pub fn memory_get(paths: &ClawdPaths, args: &Value) -> Result<Value> {
@@ -190,35 +258,154 @@ pub fn memory_write(paths: &ClawdPaths, args: &Value) -> Result<Value> {
         );
     }
 
-    let default_scope = cfg
-        .memory
-        .as_ref()
-        .and_then(|m| m.write_scope.as_deref())
-        .unwrap_or("workspace");
-    let scope_raw = args
-        .get("scope")
-        .or_else(|| args.get("memoryScope"))
-        .and_then(|v| v.as_str())
-        .unwrap_or(default_scope);
-    let scope = parse_memory_write_scope(scope_raw, args)?;
+    let scope = resolve_memory_write_scope(&cfg, args)?;
     let target = memory_write_target_path(paths, &scope)?;
     if let Some(parent) = target.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("create {}", parent.display()))?;
     }
-    append_memory_write_entry(paths, &target, &scope, args, content)?;
+    let entry_id = {
+        let _lock = lock_memory_writes(paths)?;
+        append_memory_write_entry(paths, &target, &scope, args, content)?
+    };
     sync_memory_index(paths, None)?;
 
-    let path = target
-        .strip_prefix(&paths.state_dir)
-        .ok()
-        .map(|rel| normalize_rel_path(&rel.to_string_lossy()))
-        .unwrap_or_else(|| target.to_string_lossy().to_string());
+    Ok(json!({
+        "ok": true,
+        "scope": memory_scope_label(&scope),
+        "path": memory_write_display_path(paths, &target),
+        "entryId": entry_id.to_string(),
+        "indexed": true,
+    }))
+}
+
+/// Removes an entry previously appended by `memory_write`, leaving a tombstone in the write
+/// history and purging its chunks from the index.
+pub fn memory_forget(paths: &ClawdPaths, args: &Value) -> Result<Value> {
+    rewrite_memory_write_entry(paths, args, "memory_forget", false)
+}
+
+/// Replaces the content (and optionally the title) of an entry previously appended by
+/// `memory_write`. The previous version is recorded in the write history.
+pub fn memory_edit(paths: &ClawdPaths, args: &Value) -> Result<Value> {
+    rewrite_memory_write_entry(paths, args, "memory_edit", true)
+}
+
+fn rewrite_memory_write_entry(
+    paths: &ClawdPaths,
+    args: &Value,
+    tool: &str,
+    edit: bool,
+) -> Result<Value> {
+    let cfg = paths_config(paths)?;
+    if !resolve_memory_enabled(&cfg) {
+        return Ok(json!({
+            "ok": false,
+            "disabled": true,
+            "error": "memory disabled"
+        }));
+    }
+
+    let entry_id = args
+        .get("entryId")
+        .or_else(|| args.get("entry_id"))
+        .and_then(|v| match v {
+            Value::String(s) => s.trim().parse::<i64>().ok(),
+            other => other.as_i64(),
+        })
+        .with_context(|| format!("{tool} requires entryId"))?;
+    let content = if edit {
+        let content = args
+            .get("content")
+            .or_else(|| args.get("text"))
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .with_context(|| format!("{tool} requires content"))?;
+        Some(content)
+    } else {
+        None
+    };
+
+    let require_approval = cfg
+        .memory
+        .as_ref()
+        .and_then(|m| m.write_requires_approval)
+        .unwrap_or(true);
+    if require_approval && !memory_write_has_confirmation(args) {
+        anyhow::bail!(
+            "{tool} requires explicit approval (set approved=true and confirmation=\"{MEMORY_WRITE_CONFIRMATION}\")"
+        );
+    }
+
+    let scope = resolve_memory_write_scope(&cfg, args)?;
+    let target = memory_write_target_path(paths, &scope)?;
+    // Held until the history record is written, so a concurrent write or edit cannot land
+    // between reading the file and replacing it.
+    let lock = lock_memory_writes(paths)?;
+    let original = match std::fs::read_to_string(&target) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err).with_context(|| format!("read {}", target.display())),
+    };
+    let lines: Vec<&str> = original.lines().collect();
+    let Some((start, end)) = find_memory_write_entry(&lines, entry_id) else {
+        anyhow::bail!(
+            "memory entry {entry_id} not found in {} scope",
+            memory_scope_label(&scope)
+        );
+    };
+    let previous = lines[start..end].join("\n");
+
+    let mut updated: Vec<String> = lines[..start].iter().map(|l| l.to_string()).collect();
+    if let Some(content) = content {
+        updated.extend(edited_memory_write_entry(
+            &lines[start..end],
+            args,
+            content,
+            end < lines.len(),
+        ));
+    }
+    updated.extend(lines[end..].iter().map(|l| l.to_string()));
+    let mut text = updated.join("\n");
+    text.push('\n');
+    std::fs::write(&target, text).with_context(|| format!("write {}", target.display()))?;
+
+    let action = if content.is_some() { "edit" } else { "forget" };
+    let reason = args
+        .get("reason")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let mut record = json!({
+        "ts": now_ms(),
+        "action": action,
+        "entryId": entry_id.to_string(),
+        "scope": memory_scope_label(&scope),
+        "path": memory_write_display_path(paths, &target),
+        "previousSha256": sha256_hex(&previous),
+        "previousText": previous,
+    });
+    if let Some(content) = content {
+        record["contentSha256"] = Value::String(sha256_hex(content.trim_end()));
+    }
+    if let Some(reason) = reason {
+        record["reason"] = Value::String(reason.to_string());
+    }
+    append_memory_write_history(paths, &record)?;
+    drop(lock);
+
+    // Drop the chunks that covered the old entry right away, then re-chunk the file; untouched
+    // chunks keep their embeddings.
+    purge_indexed_lines(paths, &target, start as i64 + 1, end as i64)?;
+    reindex_memory_paths(paths, std::slice::from_ref(&target))?;
 
     Ok(json!({
         "ok": true,
+        "action": action,
         "scope": memory_scope_label(&scope),
-        "path": path,
+        "path": memory_write_display_path(paths, &target),
+        "entryId": entry_id.to_string(),
         "indexed": true,
     }))
 }
@@ -259,8 +446,9 @@ pub fn memory_search(paths: &ClawdPaths, args: &Value) -> Result<Value> {
 
     let citations_mode = resolve_citations_mode(&cfg);
     let include_citations = should_include_citations(citations_mode.as_str(), session_key.as_deref());
+    let filter = parse_search_filter(paths, &cfg, args)?;
 
-    let (chunk_tokens, chunk_overlap) = resolve_chunking(&cfg);
+    let chunking = resolve_chunking(&cfg);
     let extra_paths = memory_extra_paths(paths, &cfg);
     let include_sessions = cfg
         .memory
@@ -270,112 +458,118 @@ pub fn memory_search(paths: &ClawdPaths, args: &Value) -> Result<Value> {
         || session_key.is_some();
 
     let embeddings_cfg = resolve_embeddings_config(&cfg);
-    ensure_index(
-        paths,
-        &embeddings_cfg,
-        &extra_paths,
-        include_sessions,
-        session_key.as_deref(),
-        chunk_tokens,
-        chunk_overlap,
-    )?;
-    let conn = open_db(paths)?;
+    if !index_is_watched(paths, include_sessions) {
+        ensure_index(
+            paths,
+            &embeddings_cfg,
+            &extra_paths,
+            include_sessions,
+            session_key.as_deref(),
+            chunking,
+        )?;
+    }
+    let mut conn = open_db(paths)?;
 
-    let sources = resolve_sources(include_sessions);
-    let source_filter = if sources.is_empty() {
-        String::new()
+    let sources = filter.restrict_sources(resolve_sources(include_sessions));
+    let (filter_sql, filter_params) = filter.sql_clause("memory_fts", &sources);
+    let hybrid = resolve_hybrid(&cfg);
+    let vector_index = resolve_vector_index(&cfg);
+    let candidate_limit = if hybrid.enabled {
+        max_results.saturating_mul(hybrid.candidate_multiplier)
     } else {
-        format!(
-            " AND source IN ({})",
-            vec!["?"; sources.len()].join(", ")
-        )
+        max_results
     };
     let sql = format!(
-        "SELECT path, start_line, end_line, text, source, bm25(memory_fts) as score FROM memory_fts WHERE memory_fts MATCH ?{source_filter} ORDER BY score LIMIT ?"
+        "SELECT path, start_line, end_line, text, source, section, bm25(memory_fts) as score FROM memory_fts WHERE memory_fts MATCH ?{filter_sql} ORDER BY score LIMIT ?"
     );
     let mut params_vec: Vec<SqlValue> = Vec::new();
     params_vec.push(SqlValue::from(query.clone()));
-    for source in &sources {
-        params_vec.push(SqlValue::from(source.clone()));
-    }
-    params_vec.push(SqlValue::from(max_results as i64));
+    params_vec.extend(filter_params.iter().cloned());
+    params_vec.push(SqlValue::from(candidate_limit as i64));
 
     let mut rows = Vec::new();
-    let mut stmt = conn.prepare(&sql)?;
-    let mut results = stmt.query(params_from_iter(params_vec.iter()))?;
-    while let Some(row) = results.next()? {
-        let path: String = row.get(0)?;
-        let start_line: i64 = row.get(1)?;
-        let end_line: i64 = row.get(2)?;
-        let text: String = row.get(3)?;
-        let source: String = row.get(4)?;
-        let bm25: f64 = row.get(5)?;
-        let fts_score = 1.0 / (1.0 + bm25.abs());
-        rows.push(SearchRow {
-            path,
-            start_line,
-            end_line,
-            text,
-            source,
-            fts_score,
-            embed_score: None,
-            final_score: fts_score,
-        });
+    {
+        let mut stmt = conn.prepare(&sql)?;
+        let mut results = stmt.query(params_from_iter(params_vec.iter()))?;
+        while let Some(row) = results.next()? {
+            let path: String = row.get(0)?;
+            let start_line: i64 = row.get(1)?;
+            let end_line: i64 = row.get(2)?;
+            let text: String = row.get(3)?;
+            let source: String = row.get(4)?;
+            let section: Option<String> = row.get(5)?;
+            let bm25: f64 = row.get(6)?;
+            let fts_score = 1.0 / (1.0 + bm25.abs());
+            rows.push(SearchRow {
+                path,
+                start_line,
+                end_line,
+                text,
+                source,
+                section,
+                fts_score,
+                embed_score: None,
+                final_score: fts_score,
+            });
+        }
     }
 
-    if let Some(provider) = build_embedding_provider(&embeddings_cfg)? {
-        if !rows.is_empty()
-            && should_attempt_embeddings(
-                &conn,
-                EMBEDDINGS_QUERY_FAILURE_PATH,
-                EMBEDDINGS_QUERY_FAILURE_SOURCE,
-                provider.cache_provider(),
-                provider.model.as_str(),
-            )?
-        {
-            match provider.embed(&[query.clone()]) {
-                Ok(query_vec) => {
-                    let _ = clear_embedding_failure(
-                        &conn,
-                        EMBEDDINGS_QUERY_FAILURE_PATH,
-                        EMBEDDINGS_QUERY_FAILURE_SOURCE,
-                        provider.cache_provider(),
-                        provider.model.as_str(),
-                    );
-                    if let Some(query_embedding) = query_vec.first() {
-                        for row in &mut rows {
-                            if let Some(vector) = load_row_embedding(
-                                &conn,
-                                row,
-                                provider.cache_provider(),
-                                provider.model.as_str(),
-                            )? {
-                                let score = cosine_similarity(query_embedding, &vector);
-                                row.embed_score = Some(score);
-                                row.final_score = 0.6 * row.fts_score + 0.4 * score;
-                            }
-                        }
-                    }
+    let mut vector_rows: Option<Vec<SearchRow>> = None;
+    if let Some(provider) = build_embedding_provider(&embeddings_cfg, paths)? {
+        if let Some((provider, query_embedding)) = embed_search_query(
+            &mut conn,
+            &provider,
+            &query,
+            &sources,
+            !rows.is_empty(),
+            hybrid.enabled,
+        )? {
+            let query_embedding = query_embedding.as_slice();
+            if hybrid.enabled {
+                let index = load_vector_index(
+                    paths,
+                    &conn,
+                    provider.cache_provider(),
+                    provider.model.as_str(),
+                    &vector_index,
+                )?;
+                // Chunks outside the vector top-k still get their similarity reported.
+                for row in &mut rows {
+                    row.embed_score = index.score_key(&ann_key(row), query_embedding);
                 }
-                Err(err) => {
-                    // Best-effort: fall back to FTS-only scoring on embeddings failure.
-                    let _ = record_embedding_failure(
+                vector_rows = Some(search_vector_index(
+                    &conn,
+                    &index,
+                    query_embedding,
+                    (&filter_sql, &filter_params),
+                    candidate_limit,
+                    vector_index.probes,
+                )?);
+            } else {
+                for row in &mut rows {
+                    if let Some(vector) = load_row_embedding(
                         &conn,
-                        EMBEDDINGS_QUERY_FAILURE_PATH,
-                        EMBEDDINGS_QUERY_FAILURE_SOURCE,
+                        row,
                         provider.cache_provider(),
                         provider.model.as_str(),
-                        &err.to_string(),
-                    );
+                    )? {
+                        let score = cosine_similarity(query_embedding, &vector);
+                        row.embed_score = Some(score);
+                        row.final_score = hybrid.weighted_score(row.fts_score, score);
+                    }
                 }
             }
         }
     }
 
+    if let Some(vector_rows) = vector_rows {
+        rows = fuse_ranked_rows(rows, vector_rows, &hybrid);
+    }
+
     rows.sort_by(|a, b| b.final_score.partial_cmp(&a.final_score).unwrap());
 
     let mut output = Vec::new();
-    for row in rows.into_iter() {
+    for row in rows.into_iter().take(max_results) {
         if row.final_score < min_score {
             continue;
         }
@@ -391,6 +585,9 @@ pub fn memory_search(paths: &ClawdPaths, args: &Value) -> Result<Value> {
             "embeddingScore": row.embed_score,
             "source": row.source,
         });
+        if let Some(section) = row.section {
+            entry["section"] = Value::String(section);
+        }
         if include_citations {
             if let (Some(path), Some(start), Some(end)) = (
                 entry.get("path").and_then(|v| v.as_str()),
@@ -423,7 +620,7 @@ pub fn sync_memory_index(paths: &ClawdPaths, session_key: Option<&str>) -> Resul
         return Ok(());
     }
 
-    let (chunk_tokens, chunk_overlap) = resolve_chunking(&cfg);
+    let chunking = resolve_chunking(&cfg);
     let extra_paths = memory_extra_paths(paths, &cfg);
     let include_sessions = cfg
         .memory
@@ -439,8 +636,7 @@ pub fn sync_memory_index(paths: &ClawdPaths, session_key: Option<&str>) -> Resul
         &extra_paths,
         include_sessions,
         session_key,
-        chunk_tokens,
-        chunk_overlap,
+        chunking,
     )?;
 
     Ok(())
@@ -452,38 +648,37 @@ fn ensure_index(
     extra_paths: &[PathBuf],
     include_sessions: bool,
     session_key: Option<&str>,
-    chunk_tokens: usize,
-    chunk_overlap: usize,
+    chunking: ChunkSettings,
 ) -> Result<()> {
     let mut conn = open_db(paths)?;
     ensure_schema(&conn)?;
 
-    let provider = build_embedding_provider(embeddings_cfg)?;
+    let provider = build_embedding_provider(embeddings_cfg, paths)?;
     let mut active: HashSet<(String, String)> = HashSet::new();
 
     for file in list_memory_files(&paths.workspace_dir, extra_paths) {
-        if let Some(entry) = build_index_entry(&paths.workspace_dir, &file, "memory")? {
+        if let Some(entry) = build_index_entry(paths, &file, "memory")? {
             active.insert((entry.rel_path.clone(), entry.source.clone()));
             index_file(
                 &mut conn,
                 entry,
                 provider.as_ref(),
-                chunk_tokens,
-                chunk_overlap,
+                chunking,
             )?;
         }
     }
 
     if include_sessions {
+        let offsets = crate::session_digest::transcript_index_offsets(paths)?;
         for file in list_session_files(paths, session_key) {
-            if let Some(entry) = build_session_entry(&file)? {
+            let skip = session_digest_offset(&offsets, &file);
+            if let Some(entry) = build_session_entry(&file, skip)? {
                 active.insert((entry.rel_path.clone(), entry.source.clone()));
                 index_file(
                     &mut conn,
                     entry,
                     provider.as_ref(),
-                    chunk_tokens,
-                    chunk_overlap,
+                    chunking,
                 )?;
             }
         }
@@ -503,33 +698,202 @@ fn ensure_index(
     }
 
     for (path, source) in stale {
+        remove_indexed_file(&conn, &path, &source)?;
+    }
+
+    Ok(())
+}
+
+fn remove_indexed_file(conn: &Connection, path: &str, source: &str) -> Result<bool> {
+    conn.execute(
+        "DELETE FROM memory_fts WHERE path = ? AND source = ?",
+        params![path, source],
+    )?;
+    conn.execute(
+        "DELETE FROM memory_embeddings WHERE path = ? AND source = ?",
+        params![path, source],
+    )?;
+    conn.execute(
+        "DELETE FROM memory_embedding_failures WHERE path = ? AND source = ?",
+        params![path, source],
+    )?;
+    let removed = conn.execute(
+        "DELETE FROM memory_files WHERE path = ? AND source = ?",
+        params![path, source],
+    )?;
+    Ok(removed > 0)
+}
+
+/// Re-indexes only the given files, as reported by the filesystem watcher. Paths that no longer
+/// exist are dropped from the index; directories (or anything that cannot be attributed to a
+/// single file) fall back to a full `ensure_index` pass.
+pub fn reindex_memory_paths(paths: &ClawdPaths, changed: &[PathBuf]) -> Result<MemoryReindexSummary> {
+    let mut summary = MemoryReindexSummary::default();
+    let cfg = paths_config(paths)?;
+    if !resolve_memory_enabled(&cfg) {
+        return Ok(summary);
+    }
+
+    let chunking = resolve_chunking(&cfg);
+    let extra_paths = memory_extra_paths(paths, &cfg);
+    let include_sessions = cfg
+        .memory
+        .as_ref()
+        .and_then(|m| m.session_memory)
+        .unwrap_or(false);
+    let embeddings_cfg = resolve_embeddings_config(&cfg);
+    let provider = build_embedding_provider(&embeddings_cfg, paths)?;
+
+    let mut conn = open_db(paths)?;
+    ensure_schema(&conn)?;
+
+    for abs_path in changed {
+        if abs_path.is_dir() {
+            summary.full_sync = true;
+            continue;
+        }
+        let extension = abs_path.extension().and_then(|s| s.to_str());
+        if abs_path.parent() == Some(paths.sessions_dir.as_path()) && extension == Some("jsonl") {
+            if !include_sessions {
+                continue;
+            }
+            let offsets = crate::session_digest::transcript_index_offsets(paths)?;
+            match build_session_entry(abs_path, session_digest_offset(&offsets, abs_path))? {
+                Some(entry) => {
+                    index_file(&mut conn, entry, provider.as_ref(), chunking)?;
+                    summary.indexed += 1;
+                }
+                None => {
+                    if remove_indexed_file(&conn, &session_rel_path(abs_path), "sessions")? {
+                        summary.removed += 1;
+                    }
+                }
+            }
+            continue;
+        }
+
+        let rel_path = match abs_path.strip_prefix(&paths.workspace_dir) {
+            Ok(rel) => normalize_rel_path(&rel.to_string_lossy()),
+            Err(_) => abs_path.to_string_lossy().to_string(),
+        };
+        let in_scope = (abs_path.starts_with(&paths.workspace_dir) && is_memory_rel_path(&rel_path))
+            || extra_paths
+                .iter()
+                .any(|extra| abs_path == extra || abs_path.starts_with(extra));
+        if !in_scope {
+            continue;
+        }
+        if extension != Some("md") {
+            // A removed or renamed directory only shows up as its own path.
+            if !abs_path.exists() {
+                summary.full_sync = true;
+            }
+            continue;
+        }
+        let allowed = is_memory_rel_path(&rel_path) || is_allowed_extra_path(abs_path, &extra_paths)?;
+        let entry = if allowed {
+            build_index_entry(paths, abs_path, "memory")?
+        } else {
+            None
+        };
+        match entry {
+            Some(entry) => {
+                index_file(&mut conn, entry, provider.as_ref(), chunking)?;
+                summary.indexed += 1;
+            }
+            None => {
+                if remove_indexed_file(&conn, &rel_path, "memory")? {
+                    summary.removed += 1;
+                }
+            }
+        }
+    }
+    drop(conn);
+
+    if summary.full_sync {
+        sync_memory_index(paths, None)?;
+    }
+    Ok(summary)
+}
+
+/// Directories the watcher should observe, paired with whether to watch them recursively. Extra
+/// paths that name a single file are watched through their parent so atomic-rename saves are
+/// still seen.
+pub fn memory_watch_roots(paths: &ClawdPaths, cfg: &ClawdConfig) -> Vec<(PathBuf, bool)> {
+    let mut roots = vec![
+        (paths.workspace_dir.clone(), false),
+        (paths.workspace_dir.join("memory"), true),
+    ];
+    for extra in memory_extra_paths(paths, cfg) {
+        if extra.is_file() {
+            if let Some(parent) = extra.parent() {
+                roots.push((parent.to_path_buf(), false));
+            }
+        } else {
+            roots.push((extra, true));
+        }
+    }
+    if cfg
+        .memory
+        .as_ref()
+        .and_then(|m| m.session_memory)
+        .unwrap_or(false)
+    {
+        roots.push((paths.sessions_dir.clone(), false));
+    }
+    roots
+}
+
+/// Records that a live watcher is keeping the index current, so searches can skip the full
+/// rescan. `sessions` says whether transcripts are covered too. While `pending` (changes are
+/// queued or a full pass is due) searches do not trust the index and sync it themselves.
+pub fn record_watcher_heartbeat(paths: &ClawdPaths, sessions: bool, pending: bool) -> Result<()> {
+    let conn = open_db(paths)?;
+    ensure_schema(&conn)?;
+    for (key, value) in [
+        (META_WATCHER_HEARTBEAT, now_ms()),
+        (META_WATCHER_SESSIONS, sessions as i64),
+        (META_WATCHER_PENDING, pending as i64),
+    ] {
         conn.execute(
-            "DELETE FROM memory_fts WHERE path = ? AND source = ?",
-            params![path, source],
-        )?;
-        conn.execute(
-            "DELETE FROM memory_embeddings WHERE path = ? AND source = ?",
-            params![path, source],
-        )?;
-        conn.execute(
-            "DELETE FROM memory_embedding_failures WHERE path = ? AND source = ?",
-            params![path, source],
-        )?;
-        conn.execute(
-            "DELETE FROM memory_files WHERE path = ? AND source = ?",
-            params![path, source],
+            "INSERT OR REPLACE INTO memory_meta(key, value) VALUES (?1, ?2)",
+            params![key, value],
         )?;
     }
-
     Ok(())
 }
 
+fn index_is_watched(paths: &ClawdPaths, include_sessions: bool) -> bool {
+    let Ok(conn) = open_db(paths) else {
+        return false;
+    };
+    if ensure_schema(&conn).is_err() {
+        return false;
+    }
+    let read = |key: &str| -> Option<i64> {
+        conn.query_row(
+            "SELECT value FROM memory_meta WHERE key = ?",
+            params![key],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()
+    };
+    let Some(heartbeat) = read(META_WATCHER_HEARTBEAT) else {
+        return false;
+    };
+    if now_ms() - heartbeat > WATCHER_HEARTBEAT_TTL_MS || read(META_WATCHER_PENDING) != Some(0) {
+        return false;
+    }
+    !include_sessions || read(META_WATCHER_SESSIONS).unwrap_or(0) != 0
+}
+
 fn index_file(
     conn: &mut Connection,
     entry: IndexedFile,
     provider: Option<&EmbeddingProvider>,
-    chunk_tokens: usize,
-    chunk_overlap: usize,
+    chunking: ChunkSettings,
 ) -> Result<()> {
     let rel_path = entry.rel_path.clone();
     let source = entry.source.clone();
@@ -590,6 +954,10 @@ fn index_file(
         }
     }
 
+    let reusable = match provider {
+        Some(provider) => load_reusable_embeddings(conn, &rel_path, &source, provider)?,
+        None => HashMap::new(),
+    };
     conn.execute(
         "DELETE FROM memory_fts WHERE path = ? AND source = ?",
         params![&rel_path, &source],
@@ -599,7 +967,7 @@ fn index_file(
         params![&rel_path, &source],
     )?;
 
-    let chunks = chunk_markdown(&entry.content, chunk_tokens, chunk_overlap)
+    let chunks = chunk_memory_text(&entry.content, chunking)
         .into_iter()
         .filter(|chunk| !chunk.text.trim().is_empty())
         .collect::<Vec<_>>();
@@ -607,23 +975,52 @@ fn index_file(
     let tx = conn.transaction()?;
     for chunk in &chunks {
         tx.execute(
-            "INSERT INTO memory_fts(path, start_line, end_line, source, text) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO memory_fts(path, start_line, end_line, source, section, text) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
                 &rel_path,
                 chunk.start_line,
                 chunk.end_line,
                 &source,
+                &chunk.section,
                 &chunk.text
             ],
         )?;
     }
     tx.execute(
-        "INSERT OR REPLACE INTO memory_files(path, source, mtime, size) VALUES (?1, ?2, ?3, ?4)",
-        params![&rel_path, &source, entry.mtime, entry.size],
+        "INSERT OR REPLACE INTO memory_files(path, source, mtime, size, agent_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![&rel_path, &source, entry.mtime, entry.size, &entry.agent_id],
     )?;
     tx.commit()?;
 
     if let Some(provider) = provider {
+        // Chunks whose text survived the edit (or a re-chunk) keep their vectors; only new text
+        // goes to the provider.
+        let mut pending = Vec::new();
+        let tx = conn.transaction()?;
+        for chunk in chunks {
+            let Some((dims, vector)) = reusable.get(chunk.text.trim()) else {
+                pending.push(chunk);
+                continue;
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO memory_embeddings(path, start_line, end_line, source, provider, model, dims, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    &rel_path,
+                    chunk.start_line,
+                    chunk.end_line,
+                    &source,
+                    provider.cache_provider(),
+                    provider.model.as_str(),
+                    dims,
+                    vector
+                ],
+            )?;
+        }
+        tx.commit()?;
+        let chunks = pending;
+        if chunks.is_empty() {
+            return Ok(());
+        }
         if should_attempt_embeddings(
             conn,
             &rel_path,
@@ -658,32 +1055,529 @@ fn index_file(
     Ok(())
 }
 
-fn load_row_embedding(
-    conn: &Connection,
-    row: &SearchRow,
-    provider: &str,
-    model: &str,
-) -> Result<Option<Vec<f32>>> {
-    let vector_json: Option<String> = conn
-        .query_row(
-            "SELECT vector FROM memory_embeddings WHERE path = ? AND start_line = ? AND end_line = ? AND source = ? AND provider = ? AND model = ?",
-            params![
-                &row.path,
-                row.start_line,
-                row.end_line,
-                &row.source,
-                provider,
-                model
-            ],
-            |row| row.get(0),
-        )
-        .optional()?;
-    let Some(vector_json) = vector_json else {
-        return Ok(None);
-    };
-    let parsed = serde_json::from_str::<Vec<f32>>(&vector_json).ok();
-    Ok(parsed.map(sanitize_and_normalize_embedding))
-}
+/// Embeds the search query and returns it together with the provider whose chunk vectors it can
+/// be compared against. While the configured provider is inside its failure backoff window (or
+/// fails outright), the local fallback model takes over if one is configured.
+fn embed_search_query<'a>(
+    conn: &mut Connection,
+    provider: &'a EmbeddingProvider,
+    query: &str,
+    sources: &[String],
+    has_fts_rows: bool,
+    hybrid: bool,
+) -> Result<Option<(&'a EmbeddingProvider, Vec<f32>)>> {
+    let backing_off = provider.fallback.is_some()
+        && provider_backing_off(conn, provider.cache_provider(), provider.model.as_str())?;
+    if !backing_off
+        && should_attempt_embeddings(
+            conn,
+            EMBEDDINGS_QUERY_FAILURE_PATH,
+            EMBEDDINGS_QUERY_FAILURE_SOURCE,
+            provider.cache_provider(),
+            provider.model.as_str(),
+        )?
+    {
+        // Only embed the query when there is something to compare it against: FTS hits to
+        // rerank, or (in hybrid mode) chunk vectors to search directly.
+        let has_candidates = has_fts_rows
+            || (hybrid
+                && count_embeddings(
+                    conn,
+                    sources,
+                    provider.cache_provider(),
+                    provider.model.as_str(),
+                )? > 0);
+        if !has_candidates {
+            return Ok(None);
+        }
+        match provider.embed(&[query.to_string()]) {
+            Ok(mut query_vec) => {
+                let _ = clear_embedding_failure(
+                    conn,
+                    EMBEDDINGS_QUERY_FAILURE_PATH,
+                    EMBEDDINGS_QUERY_FAILURE_SOURCE,
+                    provider.cache_provider(),
+                    provider.model.as_str(),
+                );
+                return Ok(query_vec.pop().map(|vector| (provider, vector)));
+            }
+            Err(err) => {
+                // Best-effort: fall back to the local model, or FTS-only scoring.
+                let _ = record_embedding_failure(
+                    conn,
+                    EMBEDDINGS_QUERY_FAILURE_PATH,
+                    EMBEDDINGS_QUERY_FAILURE_SOURCE,
+                    provider.cache_provider(),
+                    provider.model.as_str(),
+                    &err.to_string(),
+                );
+            }
+        }
+    }
+
+    let Some(local) = provider.fallback.as_deref() else {
+        return Ok(None);
+    };
+    if !has_fts_rows && !hybrid {
+        return Ok(None);
+    }
+    // The local model is cheap, so fill in chunks it has not embedded yet rather than comparing
+    // the query against a partial set of vectors. This is throttled: it scans every chunk.
+    backfill_missing_embeddings(conn, local, sources)?;
+    let mut query_vec = local.embed(&[query.to_string()])?;
+    Ok(query_vec.pop().map(|vector| (local, vector)))
+}
+
+fn provider_backing_off(conn: &Connection, provider: &str, model: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(1) FROM memory_embedding_failures WHERE provider = ? AND model = ? AND next_retry_ms > ?",
+        params![provider, model, now_ms()],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Embeds up to `LOCAL_BACKFILL_BATCH_LIMIT` chunks that have no vector from `provider`. Runs at
+/// most once per `LOCAL_BACKFILL_INTERVAL_MS`; the attempt is recorded up front so a failing
+/// model is not retried on every search.
+fn backfill_missing_embeddings(
+    conn: &mut Connection,
+    provider: &EmbeddingProvider,
+    sources: &[String],
+) -> Result<()> {
+    let now = now_ms();
+    let last_attempt: Option<i64> = conn
+        .query_row(
+            "SELECT value FROM memory_meta WHERE key = ?",
+            params![META_LOCAL_BACKFILL],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if last_attempt.is_some_and(|at| now.saturating_sub(at) < LOCAL_BACKFILL_INTERVAL_MS) {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO memory_meta(key, value) VALUES (?1, ?2)",
+        params![META_LOCAL_BACKFILL, now],
+    )?;
+
+    let mut embedded: HashSet<AnnKey> = HashSet::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT path, start_line, end_line, source FROM memory_embeddings WHERE provider = ? AND model = ?",
+        )?;
+        let mut rows = stmt.query(params![provider.cache_provider(), provider.model.as_str()])?;
+        while let Some(row) = rows.next()? {
+            embedded.insert(AnnKey {
+                path: row.get(0)?,
+                start_line: row.get(1)?,
+                end_line: row.get(2)?,
+                source: row.get(3)?,
+            });
+        }
+    }
+    let mut missing: Vec<(AnnKey, String)> = Vec::new();
+    {
+        let mut stmt =
+            conn.prepare("SELECT path, start_line, end_line, source, text FROM memory_fts")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let key = AnnKey {
+                path: row.get(0)?,
+                start_line: row.get(1)?,
+                end_line: row.get(2)?,
+                source: row.get(3)?,
+            };
+            let text: String = row.get(4)?;
+            if text.trim().is_empty()
+                || embedded.contains(&key)
+                || (!sources.is_empty() && !sources.contains(&key.source))
+            {
+                continue;
+            }
+            missing.push((key, text));
+            if missing.len() >= LOCAL_BACKFILL_BATCH_LIMIT {
+                break;
+            }
+        }
+    }
+
+    for batch in missing.chunks(provider.batch_size.max(1)) {
+        let inputs = batch.iter().map(|(_, text)| text.clone()).collect::<Vec<_>>();
+        let vectors = provider.embed(&inputs)?;
+        let tx = conn.transaction()?;
+        for ((key, _), vector) in batch.iter().zip(vectors) {
+            let vec = sanitize_and_normalize_embedding(vector);
+            tx.execute(
+                "INSERT OR REPLACE INTO memory_embeddings(path, start_line, end_line, source, provider, model, dims, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    key.path,
+                    key.start_line,
+                    key.end_line,
+                    key.source,
+                    provider.cache_provider(),
+                    provider.model.as_str(),
+                    vec.len() as i64,
+                    encode_vector_blob(&vec)
+                ],
+            )?;
+        }
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Existing vectors for a file, keyed by trimmed chunk text, so re-indexing only embeds text
+/// that actually changed.
+fn load_reusable_embeddings(
+    conn: &Connection,
+    rel_path: &str,
+    source: &str,
+    provider: &EmbeddingProvider,
+) -> Result<HashMap<String, (i64, Vec<u8>)>> {
+    let mut vectors: HashMap<(i64, i64), (i64, Vec<u8>)> = HashMap::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT start_line, end_line, dims, vector FROM memory_embeddings WHERE path = ? AND source = ? AND provider = ? AND model = ?",
+        )?;
+        let mut rows = stmt.query(params![
+            rel_path,
+            source,
+            provider.cache_provider(),
+            provider.model.as_str()
+        ])?;
+        while let Some(row) = rows.next()? {
+            vectors.insert((row.get(0)?, row.get(1)?), (row.get(2)?, row.get(3)?));
+        }
+    }
+    if vectors.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut reusable = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT start_line, end_line, text FROM memory_fts WHERE path = ? AND source = ?",
+    )?;
+    let mut rows = stmt.query(params![rel_path, source])?;
+    while let Some(row) = rows.next()? {
+        let key: (i64, i64) = (row.get(0)?, row.get(1)?);
+        let text: String = row.get(2)?;
+        if let Some(vector) = vectors.remove(&key) {
+            reusable.insert(text.trim().to_string(), vector);
+        }
+    }
+    Ok(reusable)
+}
+
+fn load_row_embedding(
+    conn: &Connection,
+    row: &SearchRow,
+    provider: &str,
+    model: &str,
+) -> Result<Option<Vec<f32>>> {
+    let blob: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT vector FROM memory_embeddings WHERE path = ? AND start_line = ? AND end_line = ? AND source = ? AND provider = ? AND model = ?",
+            params![
+                &row.path,
+                row.start_line,
+                row.end_line,
+                &row.source,
+                provider,
+                model
+            ],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(blob.and_then(|bytes| decode_vector_blob(&bytes)))
+}
+
+fn count_embeddings(
+    conn: &Connection,
+    sources: &[String],
+    provider: &str,
+    model: &str,
+) -> Result<i64> {
+    let mut sql =
+        "SELECT COUNT(1) FROM memory_embeddings WHERE provider = ? AND model = ?".to_string();
+    if !sources.is_empty() {
+        sql.push_str(&format!(
+            " AND source IN ({})",
+            vec!["?"; sources.len()].join(", ")
+        ));
+    }
+    let mut params_vec: Vec<SqlValue> = vec![
+        SqlValue::from(provider.to_string()),
+        SqlValue::from(model.to_string()),
+    ];
+    for source in sources {
+        params_vec.push(SqlValue::from(source.clone()));
+    }
+    let count: i64 = conn.query_row(&sql, params_from_iter(params_vec.iter()), |row| row.get(0))?;
+    Ok(count)
+}
+
+/// Nearest-neighbour search over the chunk embeddings, independent of the FTS match. Returns at
+/// most `limit` rows that pass the search filter clause, best first.
+fn search_vector_index(
+    conn: &Connection,
+    index: &AnnIndex,
+    query_embedding: &[f32],
+    (filter_sql, filter_params): (&str, &[SqlValue]),
+    limit: usize,
+    probes: usize,
+) -> Result<Vec<SearchRow>> {
+    let mut rows = Vec::new();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT path, start_line, end_line, source, text, section FROM memory_fts WHERE rowid = ?{filter_sql}"
+    ))?;
+    let mut params_vec: Vec<SqlValue> = vec![SqlValue::Null];
+    params_vec.extend(filter_params.iter().cloned());
+    for (idx, score) in index.search(query_embedding, probes) {
+        if rows.len() >= limit {
+            break;
+        }
+        let entry = index.entry(idx);
+        params_vec[0] = SqlValue::from(entry.fts_rowid);
+        let chunk = stmt
+            .query_row(params_from_iter(params_vec.iter()), |row| {
+                Ok((
+                    AnnKey {
+                        path: row.get(0)?,
+                        start_line: row.get(1)?,
+                        end_line: row.get(2)?,
+                        source: row.get(3)?,
+                    },
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            })
+            .optional()?;
+        // The index may trail the FTS table by one rebuild; never return a mismatched chunk.
+        let Some((key, text, section)) = chunk.filter(|(key, _, _)| *key == entry.key) else {
+            continue;
+        };
+        rows.push(SearchRow {
+            path: key.path,
+            start_line: key.start_line,
+            end_line: key.end_line,
+            text,
+            source: key.source,
+            section,
+            fts_score: 0.0,
+            embed_score: Some(score),
+            final_score: score,
+        });
+    }
+    Ok(rows)
+}
+
+fn load_vector_index(
+    paths: &ClawdPaths,
+    conn: &Connection,
+    provider: &str,
+    model: &str,
+    settings: &VectorIndexSettings,
+) -> Result<Arc<AnnIndex>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Arc<AnnIndex>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+
+    let (epoch, generation) = embeddings_generation(conn)?;
+    let index_path = vector_index_path(paths, provider, model);
+    let is_current = |index: &AnnIndex| index.epoch == epoch && index.generation == generation;
+
+    if let Some(index) = cache
+        .lock()
+        .ok()
+        .and_then(|map| map.get(&index_path).cloned())
+    {
+        if is_current(&index) {
+            return Ok(index);
+        }
+    }
+
+    let mut loaded = None;
+    if settings.persist && index_path.exists() {
+        match AnnIndex::load(&index_path) {
+            Ok(index) if is_current(&index) => loaded = Some(index),
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!(
+                    "[clawdex][memory] ignoring unreadable vector index {}: {err}",
+                    index_path.display()
+                );
+            }
+        }
+    }
+    let index = match loaded {
+        Some(index) => index,
+        None => {
+            let index = build_vector_index(conn, provider, model, epoch, generation)?;
+            if settings.persist {
+                if let Err(err) = index.save(&index_path) {
+                    eprintln!(
+                        "[clawdex][memory] failed to persist vector index {}: {err}",
+                        index_path.display()
+                    );
+                }
+            }
+            index
+        }
+    };
+
+    let index = Arc::new(index);
+    if let Ok(mut map) = cache.lock() {
+        map.insert(index_path, index.clone());
+    }
+    Ok(index)
+}
+
+fn build_vector_index(
+    conn: &Connection,
+    provider: &str,
+    model: &str,
+    epoch: i64,
+    generation: i64,
+) -> Result<AnnIndex> {
+    // Resolve FTS rowids in one pass; joining against the FTS5 table on UNINDEXED columns would
+    // rescan it once per embedding.
+    let mut rowids: HashMap<AnnKey, i64> = HashMap::new();
+    {
+        let mut stmt =
+            conn.prepare("SELECT rowid, path, start_line, end_line, source FROM memory_fts")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let key = AnnKey {
+                path: row.get(1)?,
+                start_line: row.get(2)?,
+                end_line: row.get(3)?,
+                source: row.get(4)?,
+            };
+            rowids.insert(key, row.get(0)?);
+        }
+    }
+
+    let mut items = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT path, start_line, end_line, source, vector FROM memory_embeddings WHERE provider = ? AND model = ?",
+    )?;
+    let mut rows = stmt.query(params![provider, model])?;
+    while let Some(row) = rows.next()? {
+        let key = AnnKey {
+            path: row.get(0)?,
+            start_line: row.get(1)?,
+            end_line: row.get(2)?,
+            source: row.get(3)?,
+        };
+        let blob: Vec<u8> = row.get(4)?;
+        let (Some(fts_rowid), Some(vector)) = (rowids.get(&key).copied(), decode_vector_blob(&blob))
+        else {
+            continue;
+        };
+        items.push((AnnEntry { key, fts_rowid }, vector));
+    }
+    Ok(AnnIndex::build(epoch, generation, items))
+}
+
+fn vector_index_path(paths: &ClawdPaths, provider: &str, model: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(provider.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(model.as_bytes());
+    let digest = hex::encode(hasher.finalize());
+    paths
+        .memory_dir
+        .join(format!("fts.vectors-{}.ann", &digest[..16]))
+}
+
+fn embeddings_generation(conn: &Connection) -> Result<(i64, i64)> {
+    let mut stmt = conn.prepare("SELECT key, value FROM memory_meta")?;
+    let mut rows = stmt.query([])?;
+    let mut epoch = 0;
+    let mut generation = 0;
+    while let Some(row) = rows.next()? {
+        let key: String = row.get(0)?;
+        match key.as_str() {
+            META_INDEX_EPOCH => epoch = row.get(1)?,
+            META_EMBEDDINGS_GENERATION => generation = row.get(1)?,
+            _ => {}
+        }
+    }
+    Ok((epoch, generation))
+}
+
+fn ann_key(row: &SearchRow) -> AnnKey {
+    AnnKey {
+        path: row.path.clone(),
+        start_line: row.start_line,
+        end_line: row.end_line,
+        source: row.source.clone(),
+    }
+}
+
+fn encode_vector_blob(vector: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(vector.len() * 4);
+    for value in vector {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+fn decode_vector_blob(bytes: &[u8]) -> Option<Vec<f32>> {
+    if bytes.is_empty() || !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect(),
+    )
+}
+
+/// Merges the FTS and vector candidate lists with weighted reciprocal-rank fusion. Scores are
+/// normalized so a chunk ranked first by both retrievers scores 1.0.
+fn fuse_ranked_rows(
+    fts_rows: Vec<SearchRow>,
+    vector_rows: Vec<SearchRow>,
+    hybrid: &HybridSettings,
+) -> Vec<SearchRow> {
+    let mut merged: Vec<SearchRow> = Vec::new();
+    let mut ranks: Vec<(Option<usize>, Option<usize>)> = Vec::new();
+    let mut by_key: HashMap<(String, i64, i64, String), usize> = HashMap::new();
+
+    for (rank, row) in fts_rows.into_iter().enumerate() {
+        let key = (row.path.clone(), row.start_line, row.end_line, row.source.clone());
+        by_key.insert(key, merged.len());
+        merged.push(row);
+        ranks.push((Some(rank), None));
+    }
+
+    for (vector_rank, row) in vector_rows.into_iter().enumerate() {
+        let key = (row.path.clone(), row.start_line, row.end_line, row.source.clone());
+        if let Some(idx) = by_key.get(&key) {
+            merged[*idx].embed_score = row.embed_score;
+            ranks[*idx].1 = Some(vector_rank);
+        } else {
+            by_key.insert(key, merged.len());
+            merged.push(row);
+            ranks.push((None, Some(vector_rank)));
+        }
+    }
+
+    let k = hybrid.rrf_k;
+    let best = (hybrid.text_weight + hybrid.vector_weight) / (k + 1.0);
+    for (row, (fts_rank, vector_rank)) in merged.iter_mut().zip(ranks) {
+        let mut fused = 0.0;
+        if let Some(rank) = fts_rank {
+            fused += hybrid.text_weight / (k + rank as f64 + 1.0);
+        }
+        if let Some(rank) = vector_rank {
+            fused += hybrid.vector_weight / (k + rank as f64 + 1.0);
+        }
+        row.final_score = if best > 0.0 { fused / best } else { 0.0 };
+    }
+    merged
+}
 
 fn backfill_embeddings(
     conn: &mut Connection,
@@ -719,9 +1613,8 @@ fn backfill_embeddings(
         for (idx, vector) in vectors.into_iter().enumerate() {
             let (start_line, end_line, _) = &batch[idx];
             let vec = sanitize_and_normalize_embedding(vector);
-            let vec_json = serde_json::to_string(&vec)?;
             tx.execute(
-                "INSERT OR REPLACE INTO memory_embeddings(path, start_line, end_line, source, provider, model, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT OR REPLACE INTO memory_embeddings(path, start_line, end_line, source, provider, model, dims, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 params![
                     rel_path,
                     start_line,
@@ -729,7 +1622,8 @@ fn backfill_embeddings(
                     source,
                     provider.cache_provider(),
                     provider.model.as_str(),
-                    vec_json
+                    vec.len() as i64,
+                    encode_vector_blob(&vec)
                 ],
             )?;
         }
@@ -768,9 +1662,8 @@ fn index_embeddings_for_chunks(
         let tx = conn.transaction()?;
         for (chunk, vector) in filtered.into_iter().zip(vectors.into_iter()) {
             let vec = sanitize_and_normalize_embedding(vector);
-            let vec_json = serde_json::to_string(&vec)?;
             tx.execute(
-                "INSERT INTO memory_embeddings(path, start_line, end_line, source, provider, model, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT INTO memory_embeddings(path, start_line, end_line, source, provider, model, dims, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 params![
                     rel_path,
                     chunk.start_line,
@@ -778,7 +1671,8 @@ fn index_embeddings_for_chunks(
                     source,
                     provider.cache_provider(),
                     provider.model.as_str(),
-                    vec_json
+                    vec.len() as i64,
+                    encode_vector_blob(&vec)
                 ],
             )?;
         }
@@ -796,29 +1690,167 @@ fn open_db(paths: &ClawdPaths) -> Result<Connection> {
 
 fn ensure_schema(conn: &Connection) -> Result<()> {
     let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
-    if version < SCHEMA_VERSION {
+    if version == 3 {
+        migrate_embeddings_to_blobs(conn)?;
+    } else if version < 3 {
         conn.execute("DROP TABLE IF EXISTS memory_fts", [])?;
         conn.execute("DROP TABLE IF EXISTS memory_embeddings", [])?;
         conn.execute("DROP TABLE IF EXISTS memory_files", [])?;
         conn.execute("DROP TABLE IF EXISTS memory_embedding_failures", [])?;
-        conn.execute(&format!("PRAGMA user_version = {}", SCHEMA_VERSION), [])?;
     }
+    if (3..5).contains(&version) {
+        migrate_fts_sections(conn)?;
+    }
+    conn.execute(MEMORY_FTS_SQL, [])?;
     conn.execute(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS memory_fts USING fts5(path, start_line UNINDEXED, end_line UNINDEXED, source UNINDEXED, text, tokenize='unicode61')",
+        "CREATE TABLE IF NOT EXISTS memory_files (path TEXT, source TEXT, mtime INTEGER, size INTEGER, agent_id TEXT, PRIMARY KEY(path, source))",
         [],
     )?;
+    if (3..6).contains(&version) {
+        migrate_file_agent_ids(conn)?;
+    }
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS memory_files (path TEXT, source TEXT, mtime INTEGER, size INTEGER, PRIMARY KEY(path, source))",
+        "CREATE TABLE IF NOT EXISTS memory_embeddings (path TEXT, start_line INTEGER, end_line INTEGER, source TEXT, provider TEXT, model TEXT, dims INTEGER, vector BLOB, PRIMARY KEY(path, start_line, end_line, source, provider, model))",
         [],
     )?;
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS memory_embeddings (path TEXT, start_line INTEGER, end_line INTEGER, source TEXT, provider TEXT, model TEXT, vector TEXT, PRIMARY KEY(path, start_line, end_line, source, provider, model))",
+        "CREATE TABLE IF NOT EXISTS memory_meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL)",
         [],
     )?;
+    conn.execute(
+        "INSERT OR IGNORE INTO memory_meta(key, value) VALUES (?1, ?2)",
+        params![META_INDEX_EPOCH, now_ms()],
+    )?;
+    conn.execute(
+        "INSERT OR IGNORE INTO memory_meta(key, value) VALUES (?1, 0)",
+        params![META_EMBEDDINGS_GENERATION],
+    )?;
+    // Any change to the embeddings table invalidates persisted vector indexes.
+    for (name, event) in [
+        ("memory_embeddings_ai", "INSERT"),
+        ("memory_embeddings_ad", "DELETE"),
+        ("memory_embeddings_au", "UPDATE"),
+    ] {
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS {name} AFTER {event} ON memory_embeddings BEGIN UPDATE memory_meta SET value = value + 1 WHERE key = '{META_EMBEDDINGS_GENERATION}'; END"
+            ),
+            [],
+        )?;
+    }
     conn.execute(
         "CREATE TABLE IF NOT EXISTS memory_embedding_failures (path TEXT, source TEXT, provider TEXT, model TEXT, attempts INTEGER, last_attempt_ms INTEGER, next_retry_ms INTEGER, last_error TEXT, PRIMARY KEY(path, source, provider, model))",
         [],
     )?;
+    if version < SCHEMA_VERSION {
+        conn.execute(&format!("PRAGMA user_version = {}", SCHEMA_VERSION), [])?;
+    }
+    Ok(())
+}
+
+/// Schema 5 had no `agent_id` on `memory_files`. Every file is marked stale so the next sync
+/// records its owning agent; chunk embeddings are reused.
+fn migrate_file_agent_ids(conn: &Connection) -> Result<()> {
+    let has_column: i64 = conn.query_row(
+        "SELECT COUNT(1) FROM pragma_table_info('memory_files') WHERE name = 'agent_id'",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_column > 0 {
+        return Ok(());
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("ALTER TABLE memory_files ADD COLUMN agent_id TEXT", [])?;
+    tx.execute("UPDATE memory_files SET mtime = -1", [])?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Schema 4 had no `section` column on the FTS table. FTS5 tables cannot be altered, so the
+/// rows are copied into a fresh table and every file is marked stale; the next sync re-chunks
+/// with heading breadcrumbs and reuses embeddings for chunks whose text did not change.
+fn migrate_fts_sections(conn: &Connection) -> Result<()> {
+    let sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE name = 'memory_fts'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(sql) = sql else {
+        return Ok(());
+    };
+    if sql.contains("section") {
+        return Ok(());
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("ALTER TABLE memory_fts RENAME TO memory_fts_v4", [])?;
+    tx.execute(MEMORY_FTS_SQL, [])?;
+    tx.execute(
+        "INSERT INTO memory_fts(path, start_line, end_line, source, section, text) SELECT path, start_line, end_line, source, NULL, text FROM memory_fts_v4",
+        [],
+    )?;
+    tx.execute("DROP TABLE memory_fts_v4", [])?;
+    let has_files: i64 = tx.query_row(
+        "SELECT COUNT(1) FROM sqlite_master WHERE type = 'table' AND name = 'memory_files'",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_files > 0 {
+        tx.execute("UPDATE memory_files SET mtime = -1", [])?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Schema 3 stored vectors as JSON text; rewrite them in place as packed little-endian f32 blobs
+/// so existing embeddings survive the upgrade without another round of provider calls.
+fn migrate_embeddings_to_blobs(conn: &Connection) -> Result<()> {
+    let exists: i64 = conn.query_row(
+        "SELECT COUNT(1) FROM sqlite_master WHERE type = 'table' AND name = 'memory_embeddings'",
+        [],
+        |row| row.get(0),
+    )?;
+    if exists == 0 {
+        return Ok(());
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "ALTER TABLE memory_embeddings RENAME TO memory_embeddings_v3",
+        [],
+    )?;
+    tx.execute(
+        "CREATE TABLE memory_embeddings (path TEXT, start_line INTEGER, end_line INTEGER, source TEXT, provider TEXT, model TEXT, dims INTEGER, vector BLOB, PRIMARY KEY(path, start_line, end_line, source, provider, model))",
+        [],
+    )?;
+    {
+        let mut select = tx.prepare(
+            "SELECT path, start_line, end_line, source, provider, model, vector FROM memory_embeddings_v3",
+        )?;
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO memory_embeddings(path, start_line, end_line, source, provider, model, dims, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        let mut rows = select.query([])?;
+        while let Some(row) = rows.next()? {
+            let vector_json: String = row.get(6)?;
+            let Ok(vector) = serde_json::from_str::<Vec<f32>>(&vector_json) else {
+                continue;
+            };
+            let vector = sanitize_and_normalize_embedding(vector);
+            insert.execute(params![
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                vector.len() as i64,
+                encode_vector_blob(&vector)
+            ])?;
+        }
+    }
+    tx.execute("DROP TABLE memory_embeddings_v3", [])?;
+    tx.commit()?;
     Ok(())
 }
 
@@ -961,6 +1993,32 @@ fn memory_writes_root(paths: &ClawdPaths) -> PathBuf {
     paths.memory_dir.join(MEMORY_WRITES_DIR)
 }
 
+/// The memory_write notes root as it appears in indexed paths, with a trailing `/`.
+fn memory_writes_prefix(paths: &ClawdPaths) -> String {
+    let writes_root = memory_writes_root(paths);
+    let prefix = match writes_root.strip_prefix(&paths.workspace_dir) {
+        Ok(rel) => normalize_rel_path(&rel.to_string_lossy()),
+        Err(_) => writes_root.to_string_lossy().to_string(),
+    };
+    format!("{}/", prefix.trim_end_matches('/'))
+}
+
+/// The agent namespace owning a memory file: `agent/<id>.md` notes under the memory_write root
+/// and anything in `memory/agents/<id>/`. Files directly in `memory/agents/` belong to no agent
+/// (the empty key), so only unrestricted searches see them.
+fn indexed_agent_namespace(paths: &ClawdPaths, rel_path: &str) -> Option<String> {
+    let notes = format!("{}agent/", memory_writes_prefix(paths));
+    if let Some(file) = rel_path.strip_prefix(&notes) {
+        return Some(agent_namespace_key(file.strip_suffix(".md").unwrap_or(file)));
+    }
+    let rest = rel_path.strip_prefix(MEMORY_AGENTS_DIR)?;
+    Some(
+        rest.split_once('/')
+            .map(|(agent_id, _)| agent_namespace_key(agent_id))
+            .unwrap_or_default(),
+    )
+}
+
 fn memory_write_has_confirmation(args: &Value) -> bool {
     let approved = args
         .get("approved")
@@ -977,6 +2035,35 @@ fn memory_write_has_confirmation(args: &Value) -> bool {
         .is_some_and(|value| value.eq_ignore_ascii_case(MEMORY_WRITE_CONFIRMATION))
 }
 
+/// Picks the write target for `memory_write`/`memory_forget`/`memory_edit`. With namespaces
+/// enabled, calls from an agent session default to that agent's namespace and may not touch
+/// another agent's private namespace.
+fn resolve_memory_write_scope(cfg: &ClawdConfig, args: &Value) -> Result<MemoryWriteScope> {
+    let namespaces = resolve_memory_namespaces(cfg);
+    let caller = resolve_memory_agent_id(args, namespaces.enabled)?;
+    let explicit = args
+        .get("scope")
+        .or_else(|| args.get("memoryScope"))
+        .and_then(|v| v.as_str());
+    let scope = match (explicit, caller.as_deref()) {
+        (Some(scope_raw), _) => parse_memory_write_scope(scope_raw, args)?,
+        (None, Some(agent_id)) if namespaces.enabled => MemoryWriteScope::Agent(agent_id.to_string()),
+        (None, _) => parse_memory_write_scope(
+            cfg.memory
+                .as_ref()
+                .and_then(|m| m.write_scope.as_deref())
+                .unwrap_or("workspace"),
+            args,
+        )?,
+    };
+    if let (MemoryWriteScope::Agent(target), Some(caller)) = (&scope, caller.as_deref()) {
+        if namespaces.enabled && target != caller && !namespaces.shared.contains(target) {
+            anyhow::bail!("agent `{caller}` cannot write to the `{target}` agent memory namespace");
+        }
+    }
+    Ok(scope)
+}
+
 fn parse_memory_write_scope(scope_raw: &str, args: &Value) -> Result<MemoryWriteScope> {
     let normalized = scope_raw.trim().to_lowercase();
     if normalized.is_empty() || normalized == "workspace" {
@@ -996,7 +2083,94 @@ fn parse_memory_write_scope(scope_raw: &str, args: &Value) -> Result<MemoryWrite
             .context("memory_write scope=plugin requires pluginId")?;
         return Ok(MemoryWriteScope::Plugin(normalize_plugin_scope_id(plugin_id)?));
     }
-    anyhow::bail!("invalid memory scope `{scope_raw}` (expected global, workspace, or plugin)");
+    if let Some(agent_id) = normalized.strip_prefix("agent:") {
+        return Ok(MemoryWriteScope::Agent(normalize_agent_namespace_id(agent_id)?));
+    }
+    if normalized == "agent" {
+        let agent_id = resolve_memory_agent_id(args, true)?
+            .context("memory_write scope=agent requires agentId or an agent session key")?;
+        return Ok(MemoryWriteScope::Agent(agent_id));
+    }
+    anyhow::bail!(
+        "invalid memory scope `{scope_raw}` (expected global, workspace, plugin, or agent)"
+    );
+}
+
+/// The agent a memory call acts for: `agentId`, or the `<id>` of an `agent:<id>:...`
+/// `sessionKey` when `from_session` is set.
+fn resolve_memory_agent_id(args: &Value, from_session: bool) -> Result<Option<String>> {
+    if let Some(agent_id) = args
+        .get("agentId")
+        .or_else(|| args.get("agent_id"))
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.trim().is_empty())
+    {
+        return normalize_agent_namespace_id(agent_id).map(Some);
+    }
+    if !from_session {
+        return Ok(None);
+    }
+    let Some(session_key) = args
+        .get("sessionKey")
+        .or_else(|| args.get("session_key"))
+        .and_then(|v| v.as_str())
+    else {
+        return Ok(None);
+    };
+    match session_agent_id(session_key) {
+        Some(agent_id) => normalize_agent_namespace_id(&agent_id).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn session_agent_id(session_key: &str) -> Option<String> {
+    let parts: Vec<&str> = session_key.trim().split(':').collect();
+    if parts.len() < 3 || parts[0] != "agent" || parts[1].trim().is_empty() {
+        return None;
+    }
+    Some(parts[1].trim().to_string())
+}
+
+/// Agent ids are compared case-insensitively: the search filter and the indexer both key
+/// namespaces through this.
+fn agent_namespace_key(raw: &str) -> String {
+    raw.trim().to_lowercase()
+}
+
+fn normalize_agent_namespace_id(raw: &str) -> Result<String> {
+    let trimmed = agent_namespace_key(raw);
+    if trimmed.is_empty() {
+        anyhow::bail!("agentId is required for agent memory scope");
+    }
+    if trimmed
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+    {
+        return Ok(trimmed);
+    }
+    anyhow::bail!("agentId contains invalid characters");
+}
+
+struct MemoryNamespaces {
+    enabled: bool,
+    shared: Vec<String>,
+    cross_agent_reads: bool,
+}
+
+fn resolve_memory_namespaces(cfg: &ClawdConfig) -> MemoryNamespaces {
+    let raw = cfg.memory.as_ref().and_then(|m| m.namespaces.as_ref());
+    MemoryNamespaces {
+        enabled: raw.and_then(|n| n.enabled).unwrap_or(false),
+        shared: raw
+            .and_then(|n| n.shared.as_ref())
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| normalize_agent_namespace_id(id).ok())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        cross_agent_reads: raw.and_then(|n| n.cross_agent_reads).unwrap_or(false),
+    }
 }
 
 fn normalize_plugin_scope_id(raw: &str) -> Result<String> {
@@ -1022,6 +2196,7 @@ fn memory_write_target_path(paths: &ClawdPaths, scope: &MemoryWriteScope) -> Res
             root.join("workspace").join(format!("{key}.md"))
         }
         MemoryWriteScope::Plugin(plugin_id) => root.join("plugin").join(format!("{plugin_id}.md")),
+        MemoryWriteScope::Agent(agent_id) => root.join("agent").join(format!("{agent_id}.md")),
     };
     Ok(path)
 }
@@ -1037,16 +2212,183 @@ fn memory_scope_label(scope: &MemoryWriteScope) -> String {
         MemoryWriteScope::Global => "global".to_string(),
         MemoryWriteScope::Workspace => "workspace".to_string(),
         MemoryWriteScope::Plugin(plugin_id) => format!("plugin:{plugin_id}"),
+        MemoryWriteScope::Agent(agent_id) => format!("agent:{agent_id}"),
     }
 }
 
+fn memory_write_display_path(paths: &ClawdPaths, target: &Path) -> String {
+    target
+        .strip_prefix(&paths.state_dir)
+        .ok()
+        .map(|rel| normalize_rel_path(&rel.to_string_lossy()))
+        .unwrap_or_else(|| target.to_string_lossy().to_string())
+}
+
+/// Entry ids are creation timestamps; keep them unique when several writes land in the same
+/// millisecond.
+fn next_memory_entry_id() -> i64 {
+    static LAST: OnceLock<Mutex<i64>> = OnceLock::new();
+    let mut last = LAST
+        .get_or_init(|| Mutex::new(0))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let id = now_ms().max(*last + 1);
+    *last = id;
+    id
+}
+
+/// Line range `[start, end)` of `## Entry <id>` up to the next entry heading.
+fn find_memory_write_entry(lines: &[&str], entry_id: i64) -> Option<(usize, usize)> {
+    let heading = format!("{MEMORY_ENTRY_HEADING}{entry_id}");
+    let start = lines.iter().position(|line| line.trim_end() == heading)?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.starts_with(MEMORY_ENTRY_HEADING))
+        .map(|offset| start + 1 + offset)
+        .unwrap_or(lines.len());
+    Some((start, end))
+}
+
+/// Rebuilds an entry with new content, keeping its heading and metadata list and stamping it
+/// with `- edited: <ms>`.
+fn edited_memory_write_entry(
+    entry: &[&str],
+    args: &Value,
+    content: &str,
+    followed_by_entry: bool,
+) -> Vec<String> {
+    let title = args
+        .get("title")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let mut out = vec![entry[0].to_string()];
+    let mut saw_title = false;
+    for line in entry[1..].iter().take_while(|line| line.starts_with("- ")) {
+        if line.starts_with("- edited:") {
+            continue;
+        }
+        if line.starts_with("- title:") {
+            saw_title = true;
+            if let Some(title) = title {
+                out.push(format!("- title: {title}"));
+                continue;
+            }
+        }
+        out.push(line.to_string());
+    }
+    if let Some(title) = title.filter(|_| !saw_title) {
+        out.insert(1, format!("- title: {title}"));
+    }
+    out.push(format!("- edited: {}", now_ms()));
+    out.push(String::new());
+    out.extend(content.trim_end().lines().map(str::to_string));
+    out.push(String::new());
+    if followed_by_entry {
+        out.push(String::new());
+    }
+    out
+}
+
+/// Serializes `memory_write` appends and `memory_edit`/`memory_forget` rewrites: a mutex within
+/// the process, plus a `create_new` lock file in the notes root for other processes.
+struct MemoryWritesLock {
+    path: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Drop for MemoryWritesLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn lock_memory_writes(paths: &ClawdPaths) -> Result<MemoryWritesLock> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    let guard = LOCK
+        .get_or_init(|| Mutex::new(()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let root = memory_writes_root(paths);
+    std::fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+    let path = root.join(MEMORY_WRITE_LOCK_FILE);
+    let deadline = Instant::now() + MEMORY_WRITE_LOCK_TIMEOUT;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok(MemoryWritesLock { path, _guard: guard }),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                let stale = std::fs::metadata(&path)
+                    .and_then(|meta| meta.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age > MEMORY_WRITE_LOCK_STALE);
+                if stale {
+                    let _ = std::fs::remove_file(&path);
+                    continue;
+                }
+                if Instant::now() >= deadline {
+                    anyhow::bail!("memory notes are locked by another writer ({})", path.display());
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            Err(err) => return Err(err).with_context(|| format!("create {}", path.display())),
+        }
+    }
+}
+
+fn append_memory_write_history(paths: &ClawdPaths, record: &Value) -> Result<()> {
+    let root = memory_writes_root(paths);
+    std::fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+    let path = root.join(MEMORY_WRITE_HISTORY_FILE);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(record)?)
+        .with_context(|| format!("write {}", path.display()))?;
+    Ok(())
+}
+
+fn sha256_hex(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Deletes the indexed chunks (and their vectors) overlapping `start..=end` of a memory file and
+/// marks the file stale so the next index pass re-chunks it.
+fn purge_indexed_lines(paths: &ClawdPaths, abs_path: &Path, start: i64, end: i64) -> Result<()> {
+    let rel_path = match abs_path.strip_prefix(&paths.workspace_dir) {
+        Ok(rel) => normalize_rel_path(&rel.to_string_lossy()),
+        Err(_) => abs_path.to_string_lossy().to_string(),
+    };
+    let conn = open_db(paths)?;
+    ensure_schema(&conn)?;
+    let tx = conn.unchecked_transaction()?;
+    for table in ["memory_fts", "memory_embeddings"] {
+        tx.execute(
+            &format!(
+                "DELETE FROM {table} WHERE path = ? AND source = 'memory' AND start_line <= ? AND end_line >= ?"
+            ),
+            params![&rel_path, end, start],
+        )?;
+    }
+    tx.execute(
+        "DELETE FROM memory_files WHERE path = ? AND source = 'memory'",
+        params![&rel_path],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
 fn append_memory_write_entry(
     paths: &ClawdPaths,
     target: &Path,
     scope: &MemoryWriteScope,
     args: &Value,
     content: &str,
-) -> Result<()> {
+) -> Result<i64> {
     let title = args
         .get("title")
         .and_then(|v| v.as_str())
@@ -1075,8 +2417,8 @@ fn append_memory_write_entry(
             .with_context(|| format!("write {}", target.display()))?;
     }
 
-    let entry_id = now_ms();
-    writeln!(file, "\n## Entry {entry_id}")
+    let entry_id = next_memory_entry_id();
+    writeln!(file, "\n{MEMORY_ENTRY_HEADING}{entry_id}")
         .with_context(|| format!("write {}", target.display()))?;
     if let Some(title) = title {
         writeln!(file, "- title: {title}")
@@ -1096,7 +2438,7 @@ fn append_memory_write_entry(
     }
     writeln!(file, "\n{}\n", content.trim_end())
         .with_context(|| format!("write {}", target.display()))?;
-    Ok(())
+    Ok(entry_id)
 }
 
 fn is_memory_rel_path(rel_path: &str) -> bool {
@@ -1218,7 +2560,7 @@ fn list_memory_files(workspace: &Path, extra_paths: &[PathBuf]) -> Vec<PathBuf>
     deduped
 }
 
-fn build_index_entry(workspace: &Path, abs_path: &Path, source: &str) -> Result<Option<IndexedFile>> {
+fn build_index_entry(paths: &ClawdPaths, abs_path: &Path, source: &str) -> Result<Option<IndexedFile>> {
     let meta = match std::fs::symlink_metadata(abs_path) {
         Ok(meta) => meta,
         Err(_) => return Ok(None),
@@ -1234,16 +2576,18 @@ fn build_index_entry(workspace: &Path, abs_path: &Path, source: &str) -> Result<
         .unwrap_or(0);
     let size = meta.len() as i64;
     let content = read_to_string(abs_path)?;
-    let rel_path = match abs_path.strip_prefix(workspace) {
+    let rel_path = match abs_path.strip_prefix(&paths.workspace_dir) {
         Ok(rel) => normalize_rel_path(&rel.to_string_lossy()),
         Err(_) => abs_path.to_string_lossy().to_string(),
     };
+    let agent_id = indexed_agent_namespace(paths, &rel_path);
     Ok(Some(IndexedFile {
         rel_path,
         mtime,
         size,
         source: source.to_string(),
         content,
+        agent_id,
     }))
 }
 
@@ -1268,7 +2612,18 @@ fn list_session_files(paths: &ClawdPaths, session_key: Option<&str>) -> Vec<Path
     files
 }
 
-fn build_session_entry(abs_path: &Path) -> Result<Option<IndexedFile>> {
+fn session_digest_offset(offsets: &HashMap<String, usize>, abs_path: &Path) -> usize {
+    abs_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .and_then(|name| offsets.get(name))
+        .copied()
+        .unwrap_or(0)
+}
+
+/// Builds the indexable view of a transcript. `skip_lines` leading transcript lines are left out
+/// when they are already covered by a session digest.
+fn build_session_entry(abs_path: &Path, skip_lines: usize) -> Result<Option<IndexedFile>> {
     let meta = match std::fs::symlink_metadata(abs_path) {
         Ok(meta) => meta,
         Err(_) => return Ok(None),
@@ -1277,8 +2632,58 @@ fn build_session_entry(abs_path: &Path) -> Result<Option<IndexedFile>> {
         return Ok(None);
     }
     let raw = read_to_string(abs_path)?;
+    let collected = parse_session_messages(&raw, skip_lines)
+        .into_iter()
+        .map(|message| message.text)
+        .collect::<Vec<_>>();
+    let content = collected.join("\n");
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let size = meta.len() as i64;
+    let rel_path = session_rel_path(abs_path);
+    // Transcript file names are lossy, so the owner comes from the recorded session key.
+    let agent_id = raw
+        .lines()
+        .find_map(|line| {
+            let value = serde_json::from_str::<Value>(line).ok()?;
+            value.get("sessionKey")?.as_str().map(str::to_string)
+        })
+        .and_then(|key| session_agent_id(&key))
+        .map(|id| agent_namespace_key(&id));
+    Ok(Some(IndexedFile {
+        rel_path,
+        mtime,
+        size,
+        source: "sessions".to_string(),
+        content,
+        agent_id,
+    }))
+}
+
+/// A user or assistant message from a session transcript, rendered as `Role: text`.
+#[derive(Debug, Clone)]
+pub struct SessionMessage {
+    /// 1-based line number in the transcript file.
+    pub line: usize,
+    pub text: String,
+}
+
+/// Reads the user/assistant messages of a transcript that appear after line `after_line`.
+pub fn read_session_messages(abs_path: &Path, after_line: usize) -> Result<Vec<SessionMessage>> {
+    let raw = read_to_string(abs_path)?;
+    Ok(parse_session_messages(&raw, after_line))
+}
+
+fn parse_session_messages(raw: &str, after_line: usize) -> Vec<SessionMessage> {
     let mut collected = Vec::new();
-    for line in raw.lines() {
+    for (idx, line) in raw.lines().enumerate().skip(after_line) {
         if line.trim().is_empty() {
             continue;
         }
@@ -1299,27 +2704,24 @@ fn build_session_entry(abs_path: &Path) -> Result<Option<IndexedFile>> {
         let content = message.and_then(|m| m.get("content"));
         let text = content.and_then(extract_session_text);
         let Some(text) = text else { continue };
-        collected.push(format!("{role}: {text}"));
-    }
-    let content = collected.join("\n");
-    if content.trim().is_empty() {
-        return Ok(None);
+        collected.push(SessionMessage {
+            line: idx + 1,
+            text: format!("{role}: {text}"),
+        });
     }
-    let mtime = meta
-        .modified()
-        .ok()
-        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
-        .map(|d| d.as_millis() as i64)
-        .unwrap_or(0);
-    let size = meta.len() as i64;
-    let rel_path = session_rel_path(abs_path);
-    Ok(Some(IndexedFile {
-        rel_path,
-        mtime,
-        size,
-        source: "sessions".to_string(),
-        content,
-    }))
+    collected
+}
+
+/// Forces the next index pass to rebuild a transcript's chunks even though the file itself did
+/// not change (its digest coverage did). Existing embeddings are reused by chunk text.
+pub fn mark_session_transcript_stale(paths: &ClawdPaths, abs_path: &Path) -> Result<()> {
+    let conn = open_db(paths)?;
+    ensure_schema(&conn)?;
+    conn.execute(
+        "DELETE FROM memory_files WHERE path = ? AND source = 'sessions'",
+        params![session_rel_path(abs_path)],
+    )?;
+    Ok(())
 }
 
 fn session_rel_path(abs_path: &Path) -> String {
@@ -1367,7 +2769,7 @@ fn extract_session_text(value: &Value) -> Option<String> {
     }
 }
 
-fn resolve_chunking(cfg: &ClawdConfig) -> (usize, usize) {
+fn resolve_chunking(cfg: &ClawdConfig) -> ChunkSettings {
     let chunk_tokens = cfg
         .memory
         .as_ref()
@@ -1379,7 +2781,67 @@ fn resolve_chunking(cfg: &ClawdConfig) -> (usize, usize) {
         .as_ref()
         .and_then(|m| m.chunk_overlap)
         .unwrap_or(DEFAULT_CHUNK_OVERLAP);
-    (chunk_tokens, overlap.min(chunk_tokens))
+    let strategy = cfg
+        .memory
+        .as_ref()
+        .and_then(|m| m.chunk_strategy.as_deref())
+        .map(|s| s.trim().to_lowercase());
+    ChunkSettings {
+        tokens: chunk_tokens,
+        overlap: overlap.min(chunk_tokens),
+        // "fixed" keeps the plain size-based splitter; anything else is structure-aware.
+        structured: strategy.as_deref() != Some("fixed"),
+    }
+}
+
+fn resolve_hybrid(cfg: &ClawdConfig) -> HybridSettings {
+    let hybrid = cfg.memory.as_ref().and_then(|m| m.hybrid.as_ref());
+    let weight = |value: Option<f64>, default: f64| {
+        value
+            .filter(|w| w.is_finite() && *w >= 0.0)
+            .unwrap_or(default)
+    };
+    let mut vector_weight = weight(
+        hybrid.and_then(|h| h.vector_weight),
+        DEFAULT_HYBRID_VECTOR_WEIGHT,
+    );
+    let mut text_weight = weight(hybrid.and_then(|h| h.text_weight), DEFAULT_HYBRID_TEXT_WEIGHT);
+    if vector_weight + text_weight <= 0.0 {
+        vector_weight = DEFAULT_HYBRID_VECTOR_WEIGHT;
+        text_weight = DEFAULT_HYBRID_TEXT_WEIGHT;
+    }
+    HybridSettings {
+        // Off unless asked for: fused scores are on a different scale than the rerank scores
+        // existing `minScore` thresholds were tuned against.
+        enabled: hybrid.and_then(|h| h.enabled).unwrap_or(false),
+        vector_weight,
+        text_weight,
+        candidate_multiplier: hybrid
+            .and_then(|h| h.candidate_multiplier)
+            .unwrap_or(DEFAULT_HYBRID_CANDIDATE_MULTIPLIER)
+            .clamp(1, 20),
+        rrf_k: hybrid
+            .and_then(|h| h.rrf_k)
+            .filter(|k| k.is_finite() && *k >= 0.0)
+            .unwrap_or(DEFAULT_HYBRID_RRF_K),
+    }
+}
+
+fn resolve_vector_index(cfg: &ClawdConfig) -> VectorIndexSettings {
+    let index = cfg.memory.as_ref().and_then(|m| m.vector_index.as_ref());
+    let enabled = index.and_then(|i| i.enabled).unwrap_or(true);
+    VectorIndexSettings {
+        persist: enabled,
+        // A disabled index still searches in-process, but exhaustively.
+        probes: if enabled {
+            index
+                .and_then(|i| i.probes)
+                .unwrap_or(DEFAULT_VECTOR_INDEX_PROBES)
+                .max(1)
+        } else {
+            usize::MAX
+        },
+    }
 }
 
 fn resolve_citations_mode(cfg: &ClawdConfig) -> String {
@@ -1421,11 +2883,233 @@ fn parse_agent_session_key(value: &str) -> Option<String> {
     if parts[0] != "agent" {
         return None;
     }
-    let rest = parts[2..].join(":");
-    if rest.is_empty() {
-        None
-    } else {
-        Some(rest)
+    let rest = parts[2..].join(":");
+    if rest.is_empty() {
+        None
+    } else {
+        Some(rest)
+    }
+}
+
+/// Caller-supplied restrictions for `memory_search`. All of them are compiled into the SQL
+/// `WHERE` clause so ranking and limits apply to the filtered set, not to a post-filtered page.
+#[derive(Debug, Clone, Default)]
+struct SearchFilter {
+    /// Requested scopes: `memory` (MEMORY.md, memory/ and memory_write notes), `sessions`, or
+    /// `extra` (configured extra paths).
+    scopes: Vec<String>,
+    path_globs: Vec<String>,
+    modified_after: Option<i64>,
+    modified_before: Option<i64>,
+    writes_prefix: String,
+    /// Agent namespaces the caller may read; `None` searches every namespace.
+    agent_namespaces: Option<Vec<String>>,
+}
+
+const SEARCH_SOURCE_SCOPES: [&str; 3] = ["memory", "sessions", "extra"];
+
+fn parse_search_filter(paths: &ClawdPaths, cfg: &ClawdConfig, args: &Value) -> Result<SearchFilter> {
+    let string_list = |value: Option<&Value>| -> Vec<String> {
+        match value {
+            Some(Value::String(s)) => vec![s.trim().to_string()],
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.trim().to_string())
+                .collect(),
+            _ => Vec::new(),
+        }
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect()
+    };
+
+    let mut scopes = Vec::new();
+    for scope in string_list(args.get("sources")) {
+        let scope = scope.to_lowercase();
+        if !SEARCH_SOURCE_SCOPES.contains(&scope.as_str()) {
+            anyhow::bail!("unknown memory source: {scope} (expected memory, sessions or extra)");
+        }
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    let path_globs = string_list(args.get("pathGlobs").or_else(|| args.get("path_globs")))
+        .into_iter()
+        .map(|glob| normalize_rel_path(&glob))
+        .collect();
+    let modified_after = parse_search_time(
+        "modifiedAfter",
+        args.get("modifiedAfter").or_else(|| args.get("modified_after")),
+    )?;
+    let modified_before = parse_search_time(
+        "modifiedBefore",
+        args.get("modifiedBefore").or_else(|| args.get("modified_before")),
+    )?;
+
+    let agent_namespaces = resolve_search_namespaces(cfg, args)?;
+    Ok(SearchFilter {
+        scopes,
+        path_globs,
+        modified_after,
+        modified_before,
+        writes_prefix: memory_writes_prefix(paths),
+        agent_namespaces,
+    })
+}
+
+/// Agent namespaces a search may read. A call from an agent session reads its own namespace plus
+/// the shared ones; `agentId` or `allAgents: true` reaching into another agent's namespace needs
+/// `memory.namespaces.crossAgentReads`. Calls without an agent (CLI, operators) see everything
+/// unless they pass `agentId`.
+fn resolve_search_namespaces(cfg: &ClawdConfig, args: &Value) -> Result<Option<Vec<String>>> {
+    let namespaces = resolve_memory_namespaces(cfg);
+    let caller = args
+        .get("sessionKey")
+        .or_else(|| args.get("session_key"))
+        .and_then(|v| v.as_str())
+        .and_then(session_agent_id)
+        .map(|id| normalize_agent_namespace_id(&id))
+        .transpose()?;
+    let requested = resolve_memory_agent_id(args, false)?;
+    let all_agents = args
+        .get("allAgents")
+        .or_else(|| args.get("all_agents"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if let Some(caller) = caller.as_deref() {
+        let foreign = requested
+            .as_deref()
+            .filter(|id| *id != caller && !namespaces.shared.iter().any(|s| s == id));
+        if (all_agents || foreign.is_some()) && !namespaces.cross_agent_reads {
+            anyhow::bail!(
+                "agent `{caller}` cannot read {} (memory.namespaces.crossAgentReads is off)",
+                foreign.map_or("other agents' memory".to_string(), |id| format!(
+                    "the `{id}` agent memory namespace"
+                ))
+            );
+        }
+    }
+    if all_agents {
+        return Ok(None);
+    }
+    Ok(requested.or(caller).map(|agent_id| {
+        let mut visible = namespaces.shared;
+        if !visible.contains(&agent_id) {
+            visible.push(agent_id);
+        }
+        visible
+    }))
+}
+
+/// Accepts epoch milliseconds, an RFC 3339 timestamp, or a `YYYY-MM-DD` date (UTC midnight).
+fn parse_search_time(name: &str, value: Option<&Value>) -> Result<Option<i64>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    if let Some(ms) = value.as_i64() {
+        return Ok(Some(ms));
+    }
+    if let Some(ms) = value.as_f64() {
+        return Ok(Some(ms as i64));
+    }
+    let Some(raw) = value.as_str().map(str::trim) else {
+        anyhow::bail!("{name} must be a timestamp");
+    };
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(raw) {
+        return Ok(Some(dt.timestamp_millis()));
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        if let Some(dt) = date.and_hms_opt(0, 0, 0) {
+            return Ok(Some(dt.and_utc().timestamp_millis()));
+        }
+    }
+    anyhow::bail!("{name} must be epoch milliseconds, RFC 3339 or YYYY-MM-DD: {raw}")
+}
+
+impl SearchFilter {
+    fn wants_source(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    /// Narrows the configured `source` values to the requested scopes (`memory` and `extra` both
+    /// live under the `memory` source). Asking for a source that is not configured, such as
+    /// `sessions` with `sessionMemory: false`, matches nothing.
+    fn restrict_sources(&self, sources: Vec<String>) -> Vec<String> {
+        if self.scopes.is_empty() {
+            return sources;
+        }
+        sources
+            .into_iter()
+            .filter(|source| match source.as_str() {
+                "memory" => self.wants_source("memory") || self.wants_source("extra"),
+                other => self.wants_source(other),
+            })
+            .collect()
+    }
+
+    /// Returns a `" AND ..."` fragment over `table` (which must expose `path` and `source`)
+    /// along with its positional parameters.
+    fn sql_clause(&self, table: &str, sources: &[String]) -> (String, Vec<SqlValue>) {
+        let mut sql = String::new();
+        let mut params_vec: Vec<SqlValue> = Vec::new();
+        if sources.is_empty() {
+            // Every requested scope was excluded by configuration.
+            sql.push_str(" AND 0");
+            return (sql, params_vec);
+        }
+        sql.push_str(&format!(
+            " AND {table}.source IN ({})",
+            vec!["?"; sources.len()].join(", ")
+        ));
+        params_vec.extend(sources.iter().map(|s| SqlValue::from(s.clone())));
+
+        if self.wants_source("memory") != self.wants_source("extra") {
+            let core = format!(
+                "({table}.path IN ('MEMORY.md', 'memory.md') OR substr({table}.path, 1, 7) = 'memory/' OR substr({table}.path, 1, ?) = ?)"
+            );
+            params_vec.push(SqlValue::from(self.writes_prefix.chars().count() as i64));
+            params_vec.push(SqlValue::from(self.writes_prefix.clone()));
+            let negate = if self.wants_source("extra") { "NOT " } else { "" };
+            sql.push_str(&format!(
+                " AND ({table}.source <> 'memory' OR {negate}{core})"
+            ));
+        }
+        if !self.path_globs.is_empty() {
+            let globs = vec![format!("{table}.path GLOB ?"); self.path_globs.len()];
+            sql.push_str(&format!(" AND ({})", globs.join(" OR ")));
+            params_vec.extend(self.path_globs.iter().map(|g| SqlValue::from(g.clone())));
+        }
+        if self.modified_after.is_some() || self.modified_before.is_some() {
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM memory_files f WHERE f.path = {table}.path AND f.source = {table}.source"
+            ));
+            if let Some(after) = self.modified_after {
+                sql.push_str(" AND f.mtime >= ?");
+                params_vec.push(SqlValue::from(after));
+            }
+            if let Some(before) = self.modified_before {
+                sql.push_str(" AND f.mtime < ?");
+                params_vec.push(SqlValue::from(before));
+            }
+            sql.push(')');
+        }
+        if let Some(visible) = self.agent_namespaces.as_ref() {
+            // Agent-private files carry their owner in `memory_files.agent_id` (set at index
+            // time); shared memory has none.
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM memory_files a WHERE a.path = {table}.path AND a.source = {table}.source AND (a.agent_id IS NULL"
+            ));
+            if !visible.is_empty() {
+                sql.push_str(&format!(
+                    " OR a.agent_id IN ({})",
+                    vec!["?"; visible.len()].join(", ")
+                ));
+                params_vec.extend(visible.iter().map(|id| SqlValue::from(id.clone())));
+            }
+            sql.push_str("))");
+        }
+        (sql, params_vec)
     }
 }
 
@@ -1448,6 +3132,270 @@ fn format_citation(path: &str, start_line: i64, end_line: i64) -> String {
     }
 }
 
+fn chunk_memory_text(content: &str, chunking: ChunkSettings) -> Vec<MemoryChunk> {
+    if chunking.structured {
+        chunk_markdown_structured(content, chunking.tokens, chunking.overlap)
+    } else {
+        chunk_markdown(content, chunking.tokens, chunking.overlap)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkdownBlockKind {
+    Heading,
+    Fence,
+    List,
+    Paragraph,
+}
+
+#[derive(Debug, Clone)]
+struct MarkdownBlock {
+    kind: MarkdownBlockKind,
+    start_line: i64,
+    lines: Vec<String>,
+    section: Option<String>,
+}
+
+/// Splits markdown into headings, fenced code blocks, lists and paragraphs, tagging each block
+/// with the heading path in effect where it starts. Blank lines only separate blocks.
+fn parse_markdown_blocks(content: &str) -> Vec<MarkdownBlock> {
+    let lines: Vec<&str> = content.split('\n').collect();
+    let mut blocks: Vec<MarkdownBlock> = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let breadcrumb = |headings: &Vec<(usize, String)>| {
+        if headings.is_empty() {
+            None
+        } else {
+            Some(
+                headings
+                    .iter()
+                    .map(|(_, title)| title.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" > "),
+            )
+        }
+    };
+
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line = lines[idx];
+        let line_no = idx as i64 + 1;
+        if line.trim().is_empty() {
+            idx += 1;
+            continue;
+        }
+        if let Some((level, title)) = parse_markdown_heading(line) {
+            while headings.last().map(|(l, _)| *l >= level).unwrap_or(false) {
+                headings.pop();
+            }
+            if !title.is_empty() {
+                headings.push((level, title));
+            }
+            blocks.push(MarkdownBlock {
+                kind: MarkdownBlockKind::Heading,
+                start_line: line_no,
+                lines: vec![line.to_string()],
+                section: breadcrumb(&headings),
+            });
+            idx += 1;
+            continue;
+        }
+        if let Some(fence) = parse_markdown_fence(line) {
+            let fence_char = fence.chars().next().unwrap_or('`');
+            let mut block_lines = vec![line.to_string()];
+            idx += 1;
+            while idx < lines.len() {
+                block_lines.push(lines[idx].to_string());
+                idx += 1;
+                let closing = lines[idx - 1].trim_start();
+                if closing.starts_with(&fence)
+                    && closing.trim_start_matches(fence_char).trim().is_empty()
+                {
+                    break;
+                }
+            }
+            blocks.push(MarkdownBlock {
+                kind: MarkdownBlockKind::Fence,
+                start_line: line_no,
+                lines: block_lines,
+                section: breadcrumb(&headings),
+            });
+            continue;
+        }
+        let kind = if is_markdown_list_item(line) {
+            MarkdownBlockKind::List
+        } else {
+            MarkdownBlockKind::Paragraph
+        };
+        let mut block_lines = vec![line.to_string()];
+        idx += 1;
+        while idx < lines.len() {
+            let next = lines[idx];
+            if next.trim().is_empty()
+                || parse_markdown_heading(next).is_some()
+                || parse_markdown_fence(next).is_some()
+            {
+                break;
+            }
+            // A list directly under a paragraph starts its own block.
+            if kind == MarkdownBlockKind::Paragraph && is_markdown_list_item(next) {
+                break;
+            }
+            block_lines.push(next.to_string());
+            idx += 1;
+        }
+        blocks.push(MarkdownBlock {
+            kind,
+            start_line: line_no,
+            lines: block_lines,
+            section: breadcrumb(&headings),
+        });
+    }
+    blocks
+}
+
+fn parse_markdown_heading(line: &str) -> Option<(usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim().to_string();
+    Some((level, title))
+}
+
+fn parse_markdown_fence(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    for marker in ['`', '~'] {
+        let count = trimmed.chars().take_while(|c| *c == marker).count();
+        if count >= 3 {
+            return Some(marker.to_string().repeat(count));
+        }
+    }
+    None
+}
+
+fn is_markdown_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if let Some(rest) = trimmed.strip_prefix(['-', '*', '+']) {
+        return rest.is_empty() || rest.starts_with([' ', '\t']);
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 || digits > 9 {
+        return false;
+    }
+    let rest = &trimmed[digits..];
+    rest.starts_with(". ") || rest.starts_with(") ") || rest == "." || rest == ")"
+}
+
+/// Packs markdown blocks into chunks of roughly `chunk_tokens`. Every heading starts a new
+/// chunk, blocks are never split unless a single block exceeds the budget on its own, and the
+/// overlap only carries over between chunks of the same section.
+fn chunk_markdown_structured(
+    content: &str,
+    chunk_tokens: usize,
+    chunk_overlap: usize,
+) -> Vec<MemoryChunk> {
+    let max_chars = std::cmp::max(32, chunk_tokens.saturating_mul(4));
+    let overlap_chars = chunk_overlap.saturating_mul(4);
+    let mut chunks = Vec::new();
+    let mut current: Vec<(String, i64)> = Vec::new();
+    let mut current_chars = 0usize;
+    let mut current_section: Option<String> = None;
+
+    let flush = |current: &Vec<(String, i64)>,
+                 section: &Option<String>,
+                 chunks: &mut Vec<MemoryChunk>| {
+        if current.iter().all(|(line, _)| line.trim().is_empty()) {
+            return;
+        }
+        let start_line = current.first().map(|c| c.1).unwrap_or(1);
+        let end_line = current.last().map(|c| c.1).unwrap_or(start_line);
+        let text = current
+            .iter()
+            .map(|c| c.0.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        chunks.push(MemoryChunk {
+            start_line,
+            end_line,
+            text,
+            section: section.clone(),
+        });
+    };
+
+    let carry_overlap = |current: &mut Vec<(String, i64)>, current_chars: &mut usize| {
+        if overlap_chars == 0 || current.is_empty() {
+            current.clear();
+            *current_chars = 0;
+            return;
+        }
+        let mut acc = 0usize;
+        let mut kept: Vec<(String, i64)> = Vec::new();
+        for entry in current.iter().rev() {
+            acc += entry.0.chars().count() + 1;
+            kept.push(entry.clone());
+            if acc >= overlap_chars {
+                break;
+            }
+        }
+        kept.reverse();
+        *current_chars = kept.iter().map(|e| e.0.chars().count() + 1).sum();
+        *current = kept;
+    };
+
+    for block in parse_markdown_blocks(content) {
+        if !current.is_empty()
+            && (block.kind == MarkdownBlockKind::Heading || block.section != current_section)
+        {
+            flush(&current, &current_section, &mut chunks);
+            current.clear();
+            current_chars = 0;
+        }
+        if current.is_empty() {
+            current_section = block.section.clone();
+        }
+
+        let block_chars: usize = block.lines.iter().map(|l| l.chars().count() + 1).sum();
+        if !current.is_empty() && current_chars + block_chars > max_chars {
+            flush(&current, &current_section, &mut chunks);
+            carry_overlap(&mut current, &mut current_chars);
+        }
+        if let Some((_, last_line)) = current.last() {
+            // Keep the blank separator lines so line numbers stay contiguous.
+            for blank in (*last_line + 1)..block.start_line {
+                current.push((String::new(), blank));
+                current_chars += 1;
+            }
+        }
+        for (offset, line) in block.lines.iter().enumerate() {
+            let line_no = block.start_line + offset as i64;
+            for segment in split_line_segments(line, max_chars) {
+                let line_size = segment.chars().count() + 1;
+                if current_chars + line_size > max_chars && !current.is_empty() {
+                    flush(&current, &current_section, &mut chunks);
+                    carry_overlap(&mut current, &mut current_chars);
+                }
+                current.push((segment, line_no));
+                current_chars += line_size;
+            }
+        }
+    }
+    flush(&current, &current_section, &mut chunks);
+    chunks
+}
+
 fn chunk_markdown(content: &str, chunk_tokens: usize, chunk_overlap: usize) -> Vec<MemoryChunk> {
     let lines: Vec<&str> = content.split('\n').collect();
     if lines.is_empty() {
@@ -1470,6 +3418,7 @@ fn chunk_markdown(content: &str, chunk_tokens: usize, chunk_overlap: usize) -> V
             start_line,
             end_line,
             text,
+            section: None,
         });
     };
 
@@ -1535,15 +3484,14 @@ fn split_line_segments(line: &str, max_chars: usize) -> Vec<String> {
     segments
 }
 
-fn build_embedding_provider(cfg: &EmbeddingsConfig) -> Result<Option<EmbeddingProvider>> {
+fn build_embedding_provider(
+    cfg: &EmbeddingsConfig,
+    paths: &ClawdPaths,
+) -> Result<Option<EmbeddingProvider>> {
     let enabled = cfg.enabled.unwrap_or(false);
     if !enabled {
         return Ok(None);
     }
-    let model = match cfg.model.as_ref() {
-        Some(model) => model.clone(),
-        None => return Ok(None),
-    };
 
     let raw_provider = cfg
         .provider
@@ -1552,6 +3500,34 @@ fn build_embedding_provider(cfg: &EmbeddingsConfig) -> Result<Option<EmbeddingPr
         .trim()
         .to_string();
     let provider_lc = raw_provider.to_lowercase();
+    let model_path = cfg
+        .model_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| resolve_local_model_path(paths, p));
+    if provider_lc == "local" {
+        if let Some(model_path) = model_path.as_ref() {
+            return build_local_provider(cfg, model_path).map(Some);
+        }
+    }
+    let local_fallback = match model_path.as_ref() {
+        Some(model_path) if cfg.local_fallback.unwrap_or(true) => {
+            match build_local_provider(cfg, model_path) {
+                Ok(provider) => Some(provider),
+                Err(err) => {
+                    eprintln!("[clawdex][memory] local embedding fallback unavailable: {err:#}");
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let model = match cfg.model.as_ref() {
+        Some(model) => model.clone(),
+        None => return Ok(local_fallback),
+    };
     let (provider_id, provider_base_override) =
         if raw_provider.starts_with("http://") || raw_provider.starts_with("https://") {
             ("openai-compatible".to_string(), Some(raw_provider.clone()))
@@ -1559,8 +3535,8 @@ fn build_embedding_provider(cfg: &EmbeddingsConfig) -> Result<Option<EmbeddingPr
             (provider_lc, None)
         };
     let provider_id = if provider_id == "local" {
-        // OpenClaw config calls this "local" (node-llama-cpp); in Clawdex we currently
-        // implement local embeddings via a local Ollama HTTP endpoint.
+        // OpenClaw config calls this "local" (node-llama-cpp). Without a `modelPath` for the
+        // built-in model, Clawdex treats it as a local Ollama HTTP endpoint.
         "ollama".to_string()
     } else {
         provider_id
@@ -1578,7 +3554,7 @@ fn build_embedding_provider(cfg: &EmbeddingsConfig) -> Result<Option<EmbeddingPr
         .or_else(|| default_api_base(&provider_id))
         .unwrap_or_default();
     if api_base.is_empty() {
-        return Ok(None);
+        return Ok(local_fallback);
     }
 
     let api_key_env = cfg
@@ -1593,7 +3569,7 @@ fn build_embedding_provider(cfg: &EmbeddingsConfig) -> Result<Option<EmbeddingPr
     if (provider_id == "openai" || provider_id == "codex") && api_key.trim().is_empty() {
         // Keep the previous behavior: OpenAI/Codex embeddings are only enabled when a key is
         // present, rather than attempting a request that will 401.
-        return Ok(None);
+        return Ok(local_fallback);
     }
 
     let batch_size = cfg.batch_size.unwrap_or(32);
@@ -1606,9 +3582,47 @@ fn build_embedding_provider(cfg: &EmbeddingsConfig) -> Result<Option<EmbeddingPr
         model,
         api_key,
         batch_size,
+        local: None,
+        fallback: local_fallback.map(Box::new),
     }))
 }
 
+fn build_local_provider(cfg: &EmbeddingsConfig, model_path: &Path) -> Result<EmbeddingProvider> {
+    let model = load_local_model(model_path)
+        .with_context(|| format!("load local embedding model {}", model_path.display()))?;
+    // Vectors are cached per provider/model, so key them by the model directory.
+    let model_name = model_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "local".to_string());
+    Ok(EmbeddingProvider {
+        client: Client::new(),
+        kind: EmbeddingProviderKind::Local,
+        provider_key: provider_cache_key("local", &model_path.to_string_lossy()),
+        api_base: String::new(),
+        model: model_name,
+        api_key: String::new(),
+        batch_size: cfg.batch_size.unwrap_or(32),
+        local: Some(model),
+        fallback: None,
+    })
+}
+
+fn resolve_local_model_path(paths: &ClawdPaths, raw: &str) -> PathBuf {
+    if let Some(rest) = raw.strip_prefix("~/") {
+        if let Ok(home) = crate::util::home_dir() {
+            return home.join(rest);
+        }
+    }
+    let path = PathBuf::from(raw);
+    if path.is_absolute() {
+        path
+    } else {
+        // Relative model paths live alongside the rest of Clawdex state.
+        paths.state_dir.join(path)
+    }
+}
+
 fn default_api_base(provider: &str) -> Option<String> {
     if provider == "openai" || provider == "codex" || provider == "openai-compatible" {
         if let Ok(env) = std::env::var("OPENAI_API_BASE") {
@@ -1647,6 +3661,13 @@ fn provider_cache_key(provider: &str, api_base: &str) -> String {
     format!("{provider}@{base}")
 }
 
+impl HybridSettings {
+    fn weighted_score(&self, fts_score: f64, embed_score: f64) -> f64 {
+        let total = self.text_weight + self.vector_weight;
+        (self.text_weight * fts_score + self.vector_weight * embed_score) / total
+    }
+}
+
 impl EmbeddingProvider {
     fn cache_provider(&self) -> &str {
         self.provider_key.as_str()
@@ -1656,6 +3677,13 @@ impl EmbeddingProvider {
         match self.kind {
             EmbeddingProviderKind::OpenAICompatible => self.embed_openai_compatible(inputs),
             EmbeddingProviderKind::Ollama => self.embed_ollama(inputs),
+            EmbeddingProviderKind::Local => {
+                let model = self.local.as_ref().context("local embedding model not loaded")?;
+                Ok(inputs
+                    .iter()
+                    .map(|input| sanitize_and_normalize_embedding(model.embed(input)))
+                    .collect())
+            }
         }
     }
 
@@ -1831,6 +3859,278 @@ fn sanitize_and_normalize_embedding(mut vec: Vec<f32>) -> Vec<f32> {
     vec
 }
 
+/// Index statistics and health for `clawdex memory status`.
+pub fn memory_status(paths: &ClawdPaths) -> Result<Value> {
+    let cfg = paths_config(paths)?;
+    let conn = open_db(paths)?;
+    ensure_schema(&conn)?;
+    let count = |sql: &str| -> Result<i64> { Ok(conn.query_row(sql, [], |row| row.get(0))?) };
+
+    let mut by_source = serde_json::Map::new();
+    {
+        let mut stmt =
+            conn.prepare("SELECT source, COUNT(1) FROM memory_files GROUP BY source ORDER BY source")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let source: String = row.get(0)?;
+            let files: i64 = row.get(1)?;
+            by_source.insert(source, json!(files));
+        }
+    }
+    let mut models = Vec::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT provider, model, MAX(dims), COUNT(1) FROM memory_embeddings GROUP BY provider, model ORDER BY provider, model",
+        )?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            models.push(json!({
+                "provider": row.get::<_, String>(0)?,
+                "model": row.get::<_, String>(1)?,
+                "dims": row.get::<_, Option<i64>>(2)?,
+                "count": row.get::<_, i64>(3)?,
+            }));
+        }
+    }
+    let chunks = count("SELECT COUNT(1) FROM memory_fts")?;
+
+    let embeddings_cfg = resolve_embeddings_config(&cfg);
+    let mut provider_value = Value::Null;
+    let mut missing = Value::Null;
+    let mut vector_index = Value::Null;
+    match build_embedding_provider(&embeddings_cfg, paths) {
+        Ok(Some(provider)) => {
+            provider_value = json!({
+                "provider": provider.cache_provider(),
+                "model": provider.model,
+                "fallback": provider.fallback.as_ref().map(|f| f.cache_provider().to_string()),
+            });
+            missing = json!(count_missing_embeddings(&conn, &provider)?);
+            let index_path = vector_index_path(paths, provider.cache_provider(), &provider.model);
+            vector_index = json!({
+                "path": index_path.to_string_lossy(),
+                "persisted": index_path.exists(),
+                "bytes": std::fs::metadata(&index_path).map(|m| m.len()).unwrap_or(0),
+            });
+        }
+        Ok(None) => {}
+        Err(err) => provider_value = json!({ "error": err.to_string() }),
+    }
+
+    let (epoch, generation) = embeddings_generation(&conn)?;
+    let watcher_meta = |key: &str| -> Result<Option<i64>> {
+        Ok(conn
+            .query_row(
+                "SELECT value FROM memory_meta WHERE key = ?",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    };
+    let watcher_heartbeat = watcher_meta(META_WATCHER_HEARTBEAT)?;
+    let watcher_pending = watcher_meta(META_WATCHER_PENDING)?.unwrap_or(0) != 0;
+    let db_path = paths.memory_dir.join(DB_FILE);
+    Ok(json!({
+        "enabled": resolve_memory_enabled(&cfg),
+        "dbPath": db_path.to_string_lossy(),
+        "dbBytes": std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0),
+        "schemaVersion": count("PRAGMA user_version")?,
+        "indexEpoch": epoch,
+        "embeddingsGeneration": generation,
+        "files": {
+            "total": count("SELECT COUNT(1) FROM memory_files")?,
+            "bySource": by_source,
+        },
+        "chunks": chunks,
+        "embeddings": {
+            "total": count("SELECT COUNT(1) FROM memory_embeddings")?,
+            "models": models,
+        },
+        "provider": provider_value,
+        "missingEmbeddings": missing,
+        "failures": {
+            "total": count("SELECT COUNT(1) FROM memory_embedding_failures")?,
+            "backingOff": conn.query_row(
+                "SELECT COUNT(1) FROM memory_embedding_failures WHERE next_retry_ms > ?",
+                params![now_ms()],
+                |row| row.get::<_, i64>(0),
+            )?,
+        },
+        "vectorIndex": vector_index,
+        "watcher": {
+            "heartbeatMs": watcher_heartbeat,
+            "active": watcher_heartbeat
+                .map(|ms| now_ms() - ms <= WATCHER_HEARTBEAT_TTL_MS)
+                .unwrap_or(false),
+            "pending": watcher_pending,
+        },
+    }))
+}
+
+fn count_missing_embeddings(conn: &Connection, provider: &EmbeddingProvider) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COUNT(1) FROM memory_fts f WHERE NOT EXISTS (SELECT 1 FROM memory_embeddings e WHERE e.path = f.path AND e.start_line = f.start_line AND e.end_line = f.end_line AND e.source = f.source AND e.provider = ? AND e.model = ?)",
+        params![provider.cache_provider(), provider.model.as_str()],
+        |row| row.get(0),
+    )?)
+}
+
+/// Brings the index up to date. `force` re-chunks every file (reusing embeddings whose text is
+/// unchanged); `reembed` also drops all stored vectors and failure backoffs first.
+pub fn memory_reindex(paths: &ClawdPaths, force: bool, reembed: bool) -> Result<Value> {
+    let started = now_ms();
+    if force || reembed {
+        let conn = open_db(paths)?;
+        ensure_schema(&conn)?;
+        if reembed {
+            conn.execute("DELETE FROM memory_embeddings", [])?;
+            conn.execute("DELETE FROM memory_embedding_failures", [])?;
+        }
+        conn.execute("UPDATE memory_files SET mtime = -1", [])?;
+    }
+    sync_memory_index(paths, None)?;
+    Ok(json!({
+        "ok": true,
+        "force": force,
+        "reembed": reembed,
+        "durationMs": now_ms() - started,
+        "status": memory_status(paths)?,
+    }))
+}
+
+/// Files whose embeddings are failing, most recent first. `clear` drops the rows so the next
+/// sync retries immediately.
+pub fn memory_failures(paths: &ClawdPaths, limit: usize, clear: bool) -> Result<Value> {
+    let conn = open_db(paths)?;
+    ensure_schema(&conn)?;
+    let mut failures = Vec::new();
+    {
+        let mut stmt = conn.prepare(
+            "SELECT path, source, provider, model, attempts, last_attempt_ms, next_retry_ms, last_error FROM memory_embedding_failures ORDER BY last_attempt_ms DESC LIMIT ?",
+        )?;
+        let mut rows = stmt.query(params![limit.max(1) as i64])?;
+        let now = now_ms();
+        while let Some(row) = rows.next()? {
+            let next_retry_ms: i64 = row.get(6)?;
+            failures.push(json!({
+                "path": row.get::<_, String>(0)?,
+                "source": row.get::<_, String>(1)?,
+                "provider": row.get::<_, String>(2)?,
+                "model": row.get::<_, String>(3)?,
+                "attempts": row.get::<_, i64>(4)?,
+                "lastAttemptMs": row.get::<_, i64>(5)?,
+                "nextRetryMs": next_retry_ms,
+                "backingOff": next_retry_ms > now,
+                "lastError": row.get::<_, Option<String>>(7)?,
+            }));
+        }
+    }
+    let total: i64 =
+        conn.query_row("SELECT COUNT(1) FROM memory_embedding_failures", [], |row| row.get(0))?;
+    let cleared = if clear {
+        conn.execute("DELETE FROM memory_embedding_failures", [])?
+    } else {
+        0
+    };
+    Ok(json!({
+        "total": total,
+        "failures": failures,
+        "cleared": cleared,
+    }))
+}
+
+/// Drops vectors and failure rows that no longer belong to an indexed chunk or file, removes
+/// persisted vector indexes for inactive models, and compacts the database.
+pub fn memory_vacuum(paths: &ClawdPaths) -> Result<Value> {
+    let cfg = paths_config(paths)?;
+    let db_path = paths.memory_dir.join(DB_FILE);
+    let bytes_before = std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0);
+    let conn = open_db(paths)?;
+    ensure_schema(&conn)?;
+    let orphan_embeddings = delete_orphan_embeddings(&conn)?;
+    let orphan_failures = conn.execute(
+        "DELETE FROM memory_embedding_failures WHERE path <> ?1 AND NOT EXISTS (SELECT 1 FROM memory_files m WHERE m.path = memory_embedding_failures.path AND m.source = memory_embedding_failures.source)",
+        params![EMBEDDINGS_QUERY_FAILURE_PATH],
+    )?;
+
+    let mut keep = HashSet::new();
+    if let Ok(Some(provider)) = build_embedding_provider(&resolve_embeddings_config(&cfg), paths) {
+        keep.insert(vector_index_path(paths, provider.cache_provider(), &provider.model));
+        if let Some(fallback) = provider.fallback.as_deref() {
+            keep.insert(vector_index_path(paths, fallback.cache_provider(), &fallback.model));
+        }
+    }
+    let mut removed_indexes = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&paths.memory_dir) {
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("fts.vectors-") && name.ends_with(".ann") && !keep.contains(&path) {
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("remove {}", path.display()))?;
+                removed_indexes.push(name);
+            }
+        }
+    }
+
+    conn.execute("INSERT INTO memory_fts(memory_fts) VALUES ('optimize')", [])?;
+    conn.execute("VACUUM", [])?;
+    drop(conn);
+    Ok(json!({
+        "ok": true,
+        "orphanEmbeddings": orphan_embeddings,
+        "orphanFailures": orphan_failures,
+        "removedVectorIndexes": removed_indexes,
+        "bytesBefore": bytes_before,
+        "bytesAfter": std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0),
+    }))
+}
+
+/// Deletes embeddings whose chunk is gone. Live chunk keys are collected in one pass, as in
+/// `build_vector_index`: a correlated lookup on the FTS5 table's UNINDEXED columns would rescan
+/// it once per embedding.
+fn delete_orphan_embeddings(conn: &Connection) -> Result<usize> {
+    let mut live: HashSet<AnnKey> = HashSet::new();
+    {
+        let mut stmt = conn.prepare("SELECT path, start_line, end_line, source FROM memory_fts")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            live.insert(AnnKey {
+                path: row.get(0)?,
+                start_line: row.get(1)?,
+                end_line: row.get(2)?,
+                source: row.get(3)?,
+            });
+        }
+    }
+    let mut orphans = Vec::new();
+    {
+        let mut stmt = conn
+            .prepare("SELECT rowid, path, start_line, end_line, source FROM memory_embeddings")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let key = AnnKey {
+                path: row.get(1)?,
+                start_line: row.get(2)?,
+                end_line: row.get(3)?,
+                source: row.get(4)?,
+            };
+            if !live.contains(&key) {
+                orphans.push(row.get::<_, i64>(0)?);
+            }
+        }
+    }
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut delete = tx.prepare("DELETE FROM memory_embeddings WHERE rowid = ?")?;
+        for rowid in &orphans {
+            delete.execute(params![rowid])?;
+        }
+    }
+    tx.commit()?;
+    Ok(orphans.len())
+}
+
 fn paths_config(paths: &ClawdPaths) -> Result<crate::config::ClawdConfig> {
     // Reload config to access memory settings. This avoids threading config through all calls.
     let (cfg, _) = crate::config::load_config(Some(paths.state_dir.clone()), Some(paths.workspace_dir.clone()))?;
@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+const MAGIC: &[u8; 8] = b"CLXANN\0\0";
+const FORMAT_VERSION: u32 = 1;
+/// Below this many vectors a single list is used, i.e. search is an exact scan.
+const MIN_VECTORS_FOR_CLUSTERING: usize = 2048;
+const KMEANS_ITERATIONS: usize = 8;
+const KMEANS_SAMPLE_LIMIT: usize = 16_384;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnnKey {
+    pub path: String,
+    pub start_line: i64,
+    pub end_line: i64,
+    pub source: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnnEntry {
+    pub key: AnnKey,
+    /// `memory_fts` rowid of the chunk at build time, used to fetch the chunk text.
+    pub fts_rowid: i64,
+}
+
+/// Inverted-file (IVF) index over normalized chunk embeddings.
+///
+/// Vectors are bucketed under k-means centroids; a query only scans the lists of its closest
+/// centroids. The index is tagged with the `memory_meta` epoch/generation it was built from so a
+/// stale file is detected and rebuilt rather than served.
+#[derive(Debug)]
+pub struct AnnIndex {
+    pub epoch: i64,
+    pub generation: i64,
+    dims: usize,
+    entries: Vec<AnnEntry>,
+    vectors: Vec<f32>,
+    centroids: Vec<f32>,
+    lists: Vec<Vec<u32>>,
+    by_key: HashMap<AnnKey, u32>,
+}
+
+impl AnnIndex {
+    pub fn build(epoch: i64, generation: i64, items: Vec<(AnnEntry, Vec<f32>)>) -> Self {
+        let dims = items.first().map(|(_, v)| v.len()).unwrap_or(0);
+        let mut entries = Vec::with_capacity(items.len());
+        let mut vectors = Vec::with_capacity(items.len() * dims);
+        for (entry, vector) in items {
+            // Mixed dimensions mean a model change mid-index; skip rows that cannot be compared.
+            if vector.len() != dims {
+                continue;
+            }
+            entries.push(entry);
+            vectors.extend_from_slice(&vector);
+        }
+
+        let count = entries.len();
+        let list_count = if count < MIN_VECTORS_FOR_CLUSTERING || dims == 0 {
+            1
+        } else {
+            ((count as f64).sqrt().round() as usize).max(1)
+        };
+        let centroids = if list_count == 1 {
+            vec![0.0; dims]
+        } else {
+            train_centroids(&vectors, dims, list_count)
+        };
+        let mut lists = vec![Vec::new(); list_count];
+        for idx in 0..count {
+            let vector = &vectors[idx * dims..(idx + 1) * dims];
+            let list = if list_count == 1 {
+                0
+            } else {
+                nearest_centroid(&centroids, dims, vector)
+            };
+            lists[list].push(idx as u32);
+        }
+
+        let mut index = Self {
+            epoch,
+            generation,
+            dims,
+            entries,
+            vectors,
+            centroids,
+            lists,
+            by_key: HashMap::new(),
+        };
+        index.rebuild_key_map();
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entry(&self, idx: usize) -> &AnnEntry {
+        &self.entries[idx]
+    }
+
+    /// Returns `(entry index, dot product)` pairs from the `probes` closest lists, best first.
+    /// Vectors are stored normalized, so the dot product is the cosine similarity.
+    pub fn search(&self, query: &[f32], probes: usize) -> Vec<(usize, f64)> {
+        if query.len() != self.dims || self.entries.is_empty() || self.lists.is_empty() {
+            return Vec::new();
+        }
+        let probes = probes.clamp(1, self.lists.len());
+        let selected: Vec<usize> = if probes >= self.lists.len() {
+            (0..self.lists.len()).collect()
+        } else {
+            let mut ranked = (0..self.lists.len())
+                .map(|list| (list, dot(query, self.centroid(list))))
+                .collect::<Vec<_>>();
+            ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            ranked.into_iter().take(probes).map(|(list, _)| list).collect()
+        };
+
+        let mut hits = Vec::new();
+        for list in selected {
+            for idx in &self.lists[list] {
+                let idx = *idx as usize;
+                hits.push((idx, dot(query, self.vector(idx))));
+            }
+        }
+        hits.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        hits
+    }
+
+    /// Exact similarity for a specific chunk, regardless of which lists were probed.
+    pub fn score_key(&self, key: &AnnKey, query: &[f32]) -> Option<f64> {
+        if query.len() != self.dims {
+            return None;
+        }
+        let idx = *self.by_key.get(key)? as usize;
+        Some(dot(query, self.vector(idx)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("ann.tmp");
+        {
+            let file = File::create(&tmp).with_context(|| format!("create {}", tmp.display()))?;
+            let mut out = BufWriter::new(file);
+            out.write_all(MAGIC)?;
+            out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+            out.write_all(&self.epoch.to_le_bytes())?;
+            out.write_all(&self.generation.to_le_bytes())?;
+            out.write_all(&(self.dims as u32).to_le_bytes())?;
+            out.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+            out.write_all(&(self.lists.len() as u32).to_le_bytes())?;
+            for entry in &self.entries {
+                write_str(&mut out, &entry.key.path)?;
+                out.write_all(&entry.key.start_line.to_le_bytes())?;
+                out.write_all(&entry.key.end_line.to_le_bytes())?;
+                write_str(&mut out, &entry.key.source)?;
+                out.write_all(&entry.fts_rowid.to_le_bytes())?;
+            }
+            write_f32s(&mut out, &self.vectors)?;
+            write_f32s(&mut out, &self.centroids)?;
+            for list in &self.lists {
+                out.write_all(&(list.len() as u32).to_le_bytes())?;
+                for idx in list {
+                    out.write_all(&idx.to_le_bytes())?;
+                }
+            }
+            out.flush()?;
+        }
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("rename {} -> {}", tmp.display(), path.display()))?;
+        Ok(())
+    }
+
+    /// Reads an index written by [`AnnIndex::save`]. Every length in the file is checked against
+    /// the bytes that remain before anything is allocated, so a truncated or corrupt file is an
+    /// error (and the caller rebuilds) rather than a huge allocation.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+        let len = file.metadata()?.len();
+        let mut input = IndexReader {
+            input: BufReader::new(file),
+            remaining: len,
+        };
+        let magic = input.bytes(MAGIC.len())?;
+        if magic != MAGIC {
+            anyhow::bail!("{} is not a memory vector index", path.display());
+        }
+        let version = input.u32()?;
+        if version != FORMAT_VERSION {
+            anyhow::bail!("unsupported vector index format {version}");
+        }
+        let epoch = input.i64()?;
+        let generation = input.i64()?;
+        let dims = input.u32()? as usize;
+        let count = input.u32()? as usize;
+        let list_count = input.u32()? as usize;
+        if count > 0 && list_count == 0 {
+            anyhow::bail!("corrupt vector index: {count} vectors in no lists");
+        }
+
+        // Each entry takes at least its fixed-width fields.
+        input.ensure(count.checked_mul(MIN_ENTRY_BYTES))?;
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let path = input.str()?;
+            let start_line = input.i64()?;
+            let end_line = input.i64()?;
+            let source = input.str()?;
+            let fts_rowid = input.i64()?;
+            entries.push(AnnEntry {
+                key: AnnKey {
+                    path,
+                    start_line,
+                    end_line,
+                    source,
+                },
+                fts_rowid,
+            });
+        }
+        let vectors = input.f32s(count.checked_mul(dims))?;
+        let centroids = input.f32s(list_count.checked_mul(dims))?;
+        input.ensure(list_count.checked_mul(4))?;
+        let mut lists = Vec::with_capacity(list_count);
+        for _ in 0..list_count {
+            let len = input.u32()? as usize;
+            input.ensure(len.checked_mul(4))?;
+            let mut list = Vec::with_capacity(len);
+            for _ in 0..len {
+                let idx = input.u32()?;
+                if idx as usize >= count {
+                    anyhow::bail!("corrupt vector index list entry");
+                }
+                list.push(idx);
+            }
+            lists.push(list);
+        }
+
+        let mut index = Self {
+            epoch,
+            generation,
+            dims,
+            entries,
+            vectors,
+            centroids,
+            lists,
+            by_key: HashMap::new(),
+        };
+        index.rebuild_key_map();
+        Ok(index)
+    }
+
+    fn rebuild_key_map(&mut self) {
+        self.by_key = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry.key.clone(), idx as u32))
+            .collect();
+    }
+
+    fn vector(&self, idx: usize) -> &[f32] {
+        &self.vectors[idx * self.dims..(idx + 1) * self.dims]
+    }
+
+    fn centroid(&self, list: usize) -> &[f32] {
+        &self.centroids[list * self.dims..(list + 1) * self.dims]
+    }
+}
+
+fn train_centroids(vectors: &[f32], dims: usize, list_count: usize) -> Vec<f32> {
+    let count = vectors.len() / dims;
+    // Deterministic init/training sample: evenly strided rows keep rebuilds reproducible.
+    let sample_stride = (count / KMEANS_SAMPLE_LIMIT).max(1);
+    let sample = (0..count).step_by(sample_stride).collect::<Vec<_>>();
+    let init_stride = (sample.len() / list_count).max(1);
+    let mut centroids = Vec::with_capacity(list_count * dims);
+    for list in 0..list_count {
+        let idx = sample[(list * init_stride).min(sample.len() - 1)];
+        centroids.extend_from_slice(&vectors[idx * dims..(idx + 1) * dims]);
+    }
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![0.0f64; list_count * dims];
+        let mut counts = vec![0usize; list_count];
+        for idx in &sample {
+            let vector = &vectors[idx * dims..(idx + 1) * dims];
+            let list = nearest_centroid(&centroids, dims, vector);
+            counts[list] += 1;
+            for (sum, value) in sums[list * dims..(list + 1) * dims].iter_mut().zip(vector) {
+                *sum += *value as f64;
+            }
+        }
+        for list in 0..list_count {
+            if counts[list] == 0 {
+                // Keep the previous centroid for empty clusters.
+                continue;
+            }
+            let slot = &mut centroids[list * dims..(list + 1) * dims];
+            let mut norm = 0.0f64;
+            for (value, sum) in slot.iter_mut().zip(&sums[list * dims..(list + 1) * dims]) {
+                let mean = sum / counts[list] as f64;
+                *value = mean as f32;
+                norm += mean * mean;
+            }
+            let norm = norm.sqrt();
+            if norm > 1e-10 {
+                for value in slot.iter_mut() {
+                    *value = (*value as f64 / norm) as f32;
+                }
+            }
+        }
+    }
+    centroids
+}
+
+fn nearest_centroid(centroids: &[f32], dims: usize, vector: &[f32]) -> usize {
+    let mut best = 0usize;
+    let mut best_score = f64::MIN;
+    for (list, centroid) in centroids.chunks_exact(dims).enumerate() {
+        let score = dot(vector, centroid);
+        if score > best_score {
+            best_score = score;
+            best = list;
+        }
+    }
+    best
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f64 {
+    let mut acc = 0.0f32;
+    for (x, y) in a.iter().zip(b) {
+        acc += x * y;
+    }
+    acc as f64
+}
+
+fn write_str(out: &mut impl Write, value: &str) -> Result<()> {
+    out.write_all(&(value.len() as u32).to_le_bytes())?;
+    out.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+fn write_f32s(out: &mut impl Write, values: &[f32]) -> Result<()> {
+    for value in values {
+        out.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Path and source lengths plus start line, end line and rowid.
+const MIN_ENTRY_BYTES: usize = 4 + 8 + 8 + 4 + 8;
+
+/// Reads the index file while tracking how many bytes are left in it.
+struct IndexReader<R> {
+    input: R,
+    remaining: u64,
+}
+
+impl<R: Read> IndexReader<R> {
+    /// Fails unless `bytes` (`None` on overflow) more bytes remain in the file.
+    fn ensure(&self, bytes: Option<usize>) -> Result<usize> {
+        bytes
+            .filter(|bytes| *bytes as u64 <= self.remaining)
+            .context("vector index is truncated or corrupt")
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        let len = self.ensure(Some(len))?;
+        let mut buf = vec![0u8; len];
+        self.input.read_exact(&mut buf)?;
+        self.remaining -= len as u64;
+        Ok(buf)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let buf = self.bytes(4)?;
+        Ok(u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        let mut raw = [0u8; 8];
+        raw.copy_from_slice(&self.bytes(8)?);
+        Ok(i64::from_le_bytes(raw))
+    }
+
+    fn str(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?).context("invalid utf-8 in vector index")
+    }
+
+    fn f32s(&mut self, count: Option<usize>) -> Result<Vec<f32>> {
+        let len = self.ensure(count.and_then(|count| count.checked_mul(4)))?;
+        Ok(self
+            .bytes(len)?
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(idx: usize) -> AnnKey {
+        AnnKey {
+            path: format!("memory/{idx}.md"),
+            start_line: 1,
+            end_line: 2,
+            source: "memory".to_string(),
+        }
+    }
+
+    fn unit(dims: usize, seed: usize) -> Vec<f32> {
+        let mut state = (seed as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let mut v = (0..dims)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                ((state >> 40) as f32 / (1u64 << 24) as f32) - 0.5
+            })
+            .collect::<Vec<_>>();
+        let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        for x in &mut v {
+            *x /= norm;
+        }
+        v
+    }
+
+    #[test]
+    fn clustered_index_finds_exact_match_and_round_trips() {
+        let dims = 16;
+        let items = (0..MIN_VECTORS_FOR_CLUSTERING + 500)
+            .map(|idx| {
+                (
+                    AnnEntry {
+                        key: key(idx),
+                        fts_rowid: idx as i64,
+                    },
+                    unit(dims, idx),
+                )
+            })
+            .collect::<Vec<_>>();
+        let target = unit(dims, 1234);
+        let index = AnnIndex::build(7, 3, items);
+        assert!(index.lists.len() > 1);
+
+        let hits = index.search(&target, 8);
+        assert_eq!(index.entry(hits[0].0).key, key(1234));
+        assert!(hits[0].1 > 0.999);
+
+        let path = std::env::temp_dir().join(format!("clawdex-ann-{}.ann", uuid::Uuid::new_v4()));
+        index.save(&path).unwrap();
+        let loaded = AnnIndex::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!((loaded.epoch, loaded.generation), (7, 3));
+        assert_eq!(loaded.len(), index.len());
+        let hits = loaded.search(&target, 8);
+        assert_eq!(loaded.entry(hits[0].0).fts_rowid, 1234);
+        assert!(loaded.score_key(&key(1234), &target).unwrap() > 0.999);
+    }
+
+    #[test]
+    fn corrupt_counts_are_rejected_before_allocating() {
+        let items = (0..4)
+            .map(|idx| {
+                (
+                    AnnEntry {
+                        key: key(idx),
+                        fts_rowid: idx as i64,
+                    },
+                    unit(8, idx),
+                )
+            })
+            .collect::<Vec<_>>();
+        let path = std::env::temp_dir().join(format!("clawdex-ann-{}.ann", uuid::Uuid::new_v4()));
+        let mut index = AnnIndex::build(1, 1, items);
+        index.save(&path).unwrap();
+        let good = std::fs::read(&path).unwrap();
+
+        // dims, entry count and list count follow the magic, version, epoch and generation.
+        let header = MAGIC.len() + 4 + 8 + 8;
+        for (offset, value) in [
+            (header, u32::MAX),
+            (header + 4, u32::MAX),
+            (header + 8, 1 << 30),
+        ] {
+            let mut bad = good.clone();
+            bad[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            std::fs::write(&path, &bad).unwrap();
+            assert!(AnnIndex::load(&path).is_err(), "offset {offset}");
+        }
+        std::fs::write(&path, &good[..good.len() - 3]).unwrap();
+        assert!(AnnIndex::load(&path).is_err());
+
+        // Vectors without any list would leave nothing to probe.
+        let mut listless = good.clone();
+        listless[header + 8..header + 12].copy_from_slice(&0u32.to_le_bytes());
+        std::fs::write(&path, &listless).unwrap();
+        assert!(AnnIndex::load(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+
+        index.lists.clear();
+        assert!(index.search(&unit(8, 0), 4).is_empty());
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::{json, Value};
+
+use crate::config::{ClawdConfig, ClawdPaths};
+use crate::memory;
+use crate::util::now_ms;
+
+const DEFAULT_DEBOUNCE_MS: u64 = 750;
+const HEARTBEAT_INTERVAL_MS: i64 = 5_000;
+const RETRY_DELAY_MS: i64 = 30_000;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default)]
+struct WatcherStatus {
+    running: bool,
+    watched: Vec<String>,
+    queue_depth: usize,
+    full_sync_pending: bool,
+    last_event_ms: Option<i64>,
+    last_sync_ms: Option<i64>,
+    indexed: u64,
+    removed: u64,
+    full_syncs: u64,
+    errors: u64,
+    last_error: Option<String>,
+}
+
+fn status_cell() -> &'static Mutex<WatcherStatus> {
+    static STATUS: OnceLock<Mutex<WatcherStatus>> = OnceLock::new();
+    STATUS.get_or_init(|| Mutex::new(WatcherStatus::default()))
+}
+
+fn update_status(f: impl FnOnce(&mut WatcherStatus)) {
+    if let Ok(mut status) = status_cell().lock() {
+        f(&mut status);
+    }
+}
+
+/// Snapshot of the memory watcher for `/v1/admin/overview`.
+pub fn status() -> Value {
+    let Ok(status) = status_cell().lock() else {
+        return json!({ "running": false });
+    };
+    json!({
+        "running": status.running,
+        "watched": status.watched,
+        "queueDepth": status.queue_depth,
+        "fullSyncPending": status.full_sync_pending,
+        "lastEventMs": status.last_event_ms,
+        "lastSyncMs": status.last_sync_ms,
+        "indexed": status.indexed,
+        "removed": status.removed,
+        "fullSyncs": status.full_syncs,
+        "errors": status.errors,
+        "lastError": status.last_error,
+    })
+}
+
+pub fn queue_depth() -> usize {
+    status_cell()
+        .lock()
+        .map(|status| status.queue_depth + usize::from(status.full_sync_pending))
+        .unwrap_or(0)
+}
+
+fn resolve_watch_enabled(cfg: &ClawdConfig) -> bool {
+    let Some(memory) = cfg.memory.as_ref() else {
+        return true;
+    };
+    if memory.enabled == Some(false) {
+        return false;
+    }
+    memory
+        .sync
+        .as_ref()
+        .and_then(|s| s.watch)
+        .unwrap_or(true)
+}
+
+fn resolve_debounce_ms(cfg: &ClawdConfig) -> i64 {
+    cfg.memory
+        .as_ref()
+        .and_then(|m| m.sync.as_ref())
+        .and_then(|s| s.debounce_ms)
+        .unwrap_or(DEFAULT_DEBOUNCE_MS) as i64
+}
+
+/// Starts the watcher thread. Returns `None` when watching is disabled in config.
+pub fn spawn_memory_watcher(
+    cfg: &ClawdConfig,
+    paths: &ClawdPaths,
+    shutdown: Arc<AtomicBool>,
+) -> Result<Option<thread::JoinHandle<()>>> {
+    if !resolve_watch_enabled(cfg) {
+        return Ok(None);
+    }
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let watcher = notify::recommended_watcher(tx).context("create memory watcher")?;
+    let mut state = WatchState {
+        cfg: cfg.clone(),
+        paths: paths.clone(),
+        watcher,
+        attached: HashSet::new(),
+        pending: HashMap::new(),
+        // Anything that changed while the daemon was down is only caught by a full pass.
+        full_sync: true,
+        debounce_ms: resolve_debounce_ms(cfg),
+        next_heartbeat: 0,
+        retry_at: 0,
+        reported_pending: None,
+    };
+    state.attach_roots();
+
+    let handle = thread::Builder::new()
+        .name("clawdex-memory-watch".to_string())
+        .spawn(move || {
+            update_status(|status| status.running = true);
+            while !shutdown.load(Ordering::SeqCst) {
+                match rx.recv_timeout(POLL_INTERVAL) {
+                    Ok(event) => {
+                        state.record(event);
+                        while let Ok(event) = rx.try_recv() {
+                            state.record(event);
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+                state.tick();
+            }
+            update_status(|status| status.running = false);
+        })
+        .context("spawn memory watcher thread")?;
+    Ok(Some(handle))
+}
+
+struct WatchState {
+    cfg: ClawdConfig,
+    paths: ClawdPaths,
+    watcher: RecommendedWatcher,
+    attached: HashSet<PathBuf>,
+    pending: HashMap<PathBuf, i64>,
+    full_sync: bool,
+    debounce_ms: i64,
+    next_heartbeat: i64,
+    retry_at: i64,
+    /// Last `pending` state written with the heartbeat.
+    reported_pending: Option<bool>,
+}
+
+impl WatchState {
+    /// Watches every root that exists and is not yet attached. Roots that appear later (e.g. a
+    /// freshly created `memory/`) are picked up on the next heartbeat and trigger a full pass.
+    fn attach_roots(&mut self) {
+        let mut added = false;
+        for (root, recursive) in memory::memory_watch_roots(&self.paths, &self.cfg) {
+            if self.attached.contains(&root) || !root.is_dir() {
+                continue;
+            }
+            let mode = if recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            match self.watcher.watch(&root, mode) {
+                Ok(()) => {
+                    self.attached.insert(root);
+                    added = true;
+                }
+                Err(err) => {
+                    eprintln!(
+                        "[clawdex][memory] failed to watch {}: {err}",
+                        root.display()
+                    );
+                }
+            }
+        }
+        if added {
+            self.full_sync = true;
+        }
+        let mut watched = self
+            .attached
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        watched.sort();
+        update_status(|status| status.watched = watched);
+    }
+
+    fn record(&mut self, event: notify::Result<Event>) {
+        let now = now_ms();
+        match event {
+            Ok(event) => {
+                if event.need_rescan() {
+                    self.full_sync = true;
+                }
+                if matches!(event.kind, EventKind::Access(_)) {
+                    return;
+                }
+                for path in event.paths {
+                    self.pending.insert(path, now);
+                }
+            }
+            Err(err) => {
+                eprintln!("[clawdex][memory] watcher error: {err}");
+                self.full_sync = true;
+            }
+        }
+        self.publish_queue();
+        update_status(|status| status.last_event_ms = Some(now));
+    }
+
+    fn tick(&mut self) {
+        let now = now_ms();
+        let heartbeat_due = now >= self.next_heartbeat;
+        if heartbeat_due {
+            self.attach_roots();
+            self.next_heartbeat = now + HEARTBEAT_INTERVAL_MS;
+        }
+
+        if self.full_sync {
+            if now >= self.retry_at {
+                self.full_sync = false;
+                // A full pass covers whatever was queued.
+                self.pending.clear();
+                self.publish_queue();
+                let result = memory::sync_memory_index(&self.paths, None);
+                self.finish(result.map(|()| memory::MemoryReindexSummary {
+                    full_sync: true,
+                    ..Default::default()
+                }));
+            }
+        } else {
+            let ready = self
+                .pending
+                .iter()
+                .filter(|(_, seen)| now - **seen >= self.debounce_ms)
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>();
+            if !ready.is_empty() {
+                for path in &ready {
+                    self.pending.remove(path);
+                }
+                self.publish_queue();
+                let result = memory::reindex_memory_paths(&self.paths, &ready);
+                self.finish(result);
+            }
+        }
+
+        // Searches only trust the index once the queue has drained, so the heartbeat also goes
+        // out as soon as the watcher falls behind or catches up.
+        let pending = self.full_sync || !self.pending.is_empty();
+        if heartbeat_due || self.reported_pending != Some(pending) {
+            let sessions = self
+                .cfg
+                .memory
+                .as_ref()
+                .and_then(|m| m.session_memory)
+                .unwrap_or(false);
+            match memory::record_watcher_heartbeat(&self.paths, sessions, pending) {
+                Ok(()) => self.reported_pending = Some(pending),
+                Err(err) => eprintln!("[clawdex][memory] watcher heartbeat failed: {err}"),
+            }
+        }
+    }
+
+    fn finish(&mut self, result: Result<memory::MemoryReindexSummary>) {
+        let now = now_ms();
+        match result {
+            Ok(summary) => update_status(|status| {
+                status.last_sync_ms = Some(now);
+                status.indexed += summary.indexed as u64;
+                status.removed += summary.removed as u64;
+                if summary.full_sync {
+                    status.full_syncs += 1;
+                }
+            }),
+            Err(err) => {
+                eprintln!("[clawdex][memory] incremental sync failed: {err}");
+                // Retry with a full pass so nothing is silently left stale.
+                self.full_sync = true;
+                self.retry_at = now + RETRY_DELAY_MS;
+                update_status(|status| {
+                    status.errors += 1;
+                    status.last_error = Some(err.to_string());
+                });
+            }
+        }
+        self.publish_queue();
+    }
+
+    fn publish_queue(&self) {
+        let depth = self.pending.len();
+        let full_sync = self.full_sync;
+        update_status(|status| {
+            status.queue_depth = depth;
+            status.full_sync_pending = full_sync;
+        });
+    }
+}
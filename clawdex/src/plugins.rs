@@ -209,7 +209,7 @@ enum PluginManifestKind {
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct PluginPermissions {
+pub(crate) struct PluginPermissions {
     #[serde(default, alias = "mcp_allow", alias = "mcpAllow")]
     mcp_allow: Option<Vec<String>>,
     #[serde(default, alias = "mcp_deny", alias = "mcpDeny")]
@@ -555,7 +555,7 @@ fn load_plugin_manifest(root: &Path) -> Result<PluginManifestInfo> {
     })
 }
 
-fn plugin_permissions_for_root(root: &Path) -> Option<PluginPermissions> {
+pub(crate) fn plugin_permissions_for_root(root: &Path) -> Option<PluginPermissions> {
     load_plugin_manifest(root).ok().map(|manifest| manifest.permissions)
 }
 
@@ -749,7 +749,7 @@ fn is_root_allowed(candidate: &Path, allowed_roots: &[PathBuf]) -> bool {
     allowed_roots.iter().any(|root| candidate.starts_with(root))
 }
 
-fn apply_plugin_permissions_to_policy(
+pub(crate) fn apply_plugin_permissions_to_policy(
     policy: &WorkspacePolicy,
     permissions: Option<&PluginPermissions>,
     workspace_dir: &Path,
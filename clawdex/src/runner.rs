@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use codex_app_server_protocol::{AskForApproval, ReadOnlyAccess, SandboxPolicy};
+use codex_app_server_protocol::{AskForApproval, ReadOnlyAccess, SandboxPolicy, UserInput};
 use codex_utils_absolute_path::AbsolutePathBuf;
 
 use crate::app_server::{ApprovalMode, CodexClient, TurnOutcome};
@@ -88,16 +88,55 @@ impl CodexRunner {
         workspace_policy: &WorkspacePolicy,
         cwd: PathBuf,
     ) -> Result<TurnOutcome> {
-        let thread_id = if let Some(thread) = self.isolated_threads.get(key) {
-            thread.clone()
-        } else {
-            let thread = self.client.thread_start()?;
-            self.isolated_threads.insert(key.to_string(), thread.clone());
-            thread
-        };
+        let thread_id = self.isolated_thread(key)?;
         self.run_with_policy(&thread_id, message, approval_policy, workspace_policy, cwd)
     }
 
+    /// Runs a turn on the main thread, or on the isolated thread for `isolated_key`, and asks the
+    /// app-server to interrupt it once `should_interrupt` returns true. The check runs whenever
+    /// the turn emits an event and at least every 250ms while it is silent; an app-server that
+    /// does not finish the interrupted turn within 10s is stopped.
+    pub fn run_interruptible(
+        &mut self,
+        isolated_key: Option<&str>,
+        message: &str,
+        approval_policy: AskForApproval,
+        workspace_policy: &WorkspacePolicy,
+        cwd: PathBuf,
+        should_interrupt: &mut dyn FnMut() -> bool,
+    ) -> Result<TurnOutcome> {
+        let thread_id = match isolated_key {
+            Some(key) => self.isolated_thread(key)?,
+            None => self.main_thread.clone(),
+        };
+        let sandbox_policy = workspace_sandbox_policy(workspace_policy)?;
+        self.client.run_turn_with_inputs_interruptible(
+            &thread_id,
+            vec![UserInput::Text {
+                text: message.to_string(),
+                text_elements: Vec::new(),
+            }],
+            Some(approval_policy),
+            sandbox_policy,
+            Some(cwd),
+            |_, _| should_interrupt(),
+        )
+    }
+
+    /// False once the app-server has exited; the runner must then be replaced.
+    pub fn is_running(&mut self) -> bool {
+        self.client.is_running()
+    }
+
+    fn isolated_thread(&mut self, key: &str) -> Result<String> {
+        if let Some(thread) = self.isolated_threads.get(key) {
+            return Ok(thread.clone());
+        }
+        let thread = self.client.thread_start()?;
+        self.isolated_threads.insert(key.to_string(), thread.clone());
+        Ok(thread)
+    }
+
     fn run_with_policy(
         &mut self,
         thread_id: &str,
@@ -0,0 +1,323 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{load_config, resolve_memory_enabled, ClawdConfig, ClawdPaths};
+use crate::memory;
+use crate::util::{now_ms, read_json_value, write_json_value};
+
+const STATE_FILE: &str = "session_digests.json";
+const DEFAULT_IDLE_MINUTES: u64 = 30;
+const DEFAULT_INTERVAL_MINUTES: u64 = 10;
+const DEFAULT_MIN_MESSAGES: usize = 2;
+const DEFAULT_MAX_INPUT_CHARS: usize = 24_000;
+
+const DIGEST_PROMPT: &str = "Summarize this conversation excerpt for long-term memory. Write compact markdown bullet points covering decisions, facts learned, user preferences, and open follow-ups. Skip greetings and anything only relevant to the moment. Reply with the bullet points only.";
+
+#[derive(Debug, Clone)]
+pub struct SessionDigestSettings {
+    pub enabled: bool,
+    pub idle_ms: i64,
+    pub interval_ms: i64,
+    pub min_messages: usize,
+    pub max_input_chars: usize,
+    /// Keep indexing the raw transcript lines a digest already covers.
+    pub index_transcripts: bool,
+}
+
+pub fn resolve_settings(cfg: &ClawdConfig) -> SessionDigestSettings {
+    let raw = cfg.memory.as_ref().and_then(|m| m.session_summaries.as_ref());
+    let minutes = |value: Option<u64>, default: u64| value.unwrap_or(default).max(1) as i64 * 60_000;
+    SessionDigestSettings {
+        enabled: resolve_memory_enabled(cfg) && raw.and_then(|r| r.enabled).unwrap_or(false),
+        idle_ms: minutes(raw.and_then(|r| r.idle_minutes), DEFAULT_IDLE_MINUTES),
+        interval_ms: minutes(raw.and_then(|r| r.interval_minutes), DEFAULT_INTERVAL_MINUTES),
+        min_messages: raw
+            .and_then(|r| r.min_messages)
+            .unwrap_or(DEFAULT_MIN_MESSAGES)
+            .max(1),
+        max_input_chars: raw
+            .and_then(|r| r.max_input_chars)
+            .unwrap_or(DEFAULT_MAX_INPUT_CHARS)
+            .max(1_000),
+        index_transcripts: raw.and_then(|r| r.index_transcripts).unwrap_or(true),
+    }
+}
+
+/// Transcript lines summarized by one digest section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestRange {
+    pub from_line: usize,
+    pub to_line: usize,
+    pub messages: usize,
+    pub summarized_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDigestRecord {
+    #[serde(default)]
+    pub session_key: Option<String>,
+    /// Digest path relative to the workspace.
+    pub digest_path: String,
+    /// Transcript lines `1..=covered_lines` are summarized.
+    pub covered_lines: usize,
+    pub updated_at_ms: i64,
+    #[serde(default)]
+    pub ranges: Vec<DigestRange>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DigestState {
+    /// Keyed by transcript file name under the sessions dir.
+    #[serde(default)]
+    sessions: BTreeMap<String, SessionDigestRecord>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SessionDigestRun {
+    pub summarized: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+fn state_path(paths: &ClawdPaths) -> PathBuf {
+    paths.memory_dir.join(STATE_FILE)
+}
+
+fn load_state(paths: &ClawdPaths) -> Result<DigestState> {
+    match read_json_value(&state_path(paths))? {
+        Some(value) => Ok(serde_json::from_value(value).unwrap_or_default()),
+        None => Ok(DigestState::default()),
+    }
+}
+
+fn save_state(paths: &ClawdPaths, state: &DigestState) -> Result<()> {
+    write_json_value(&state_path(paths), &serde_json::to_value(state)?)
+}
+
+/// Digest records per transcript file name.
+pub fn list_digests(paths: &ClawdPaths) -> Result<BTreeMap<String, SessionDigestRecord>> {
+    Ok(load_state(paths)?.sessions)
+}
+
+/// Digest markdown for a transcript: `memory/sessions/<transcript stem>.md` in the workspace, so
+/// it is indexed like any other memory file.
+pub fn digest_path(paths: &ClawdPaths, transcript: &Path) -> PathBuf {
+    let stem = transcript
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("session");
+    paths
+        .workspace_dir
+        .join("memory")
+        .join("sessions")
+        .join(format!("{stem}.md"))
+}
+
+/// Number of leading transcript lines the memory index should skip per transcript file name.
+/// Empty unless digests replace the raw transcript (`indexTranscripts: false`).
+pub fn transcript_index_offsets(paths: &ClawdPaths) -> Result<HashMap<String, usize>> {
+    let (cfg, _) = load_config(
+        Some(paths.state_dir.clone()),
+        Some(paths.workspace_dir.clone()),
+    )?;
+    let settings = resolve_settings(&cfg);
+    if !settings.enabled || settings.index_transcripts {
+        return Ok(HashMap::new());
+    }
+    Ok(load_state(paths)?
+        .sessions
+        .into_iter()
+        .map(|(name, record)| (name, record.covered_lines))
+        .collect())
+}
+
+/// Summarizes every transcript that has been idle for `idleMinutes` and has enough messages past
+/// its last digest. `summarize(key, prompt)` runs the model turn; `key` is stable per transcript.
+pub fn run_session_digests(
+    cfg: &ClawdConfig,
+    paths: &ClawdPaths,
+    now: i64,
+    summarize: &mut dyn FnMut(&str, &str) -> Result<String>,
+) -> Result<SessionDigestRun> {
+    let settings = resolve_settings(cfg);
+    let mut run = SessionDigestRun::default();
+    if !settings.enabled {
+        return Ok(run);
+    }
+    let mut state = load_state(paths)?;
+    let Ok(entries) = std::fs::read_dir(&paths.sessions_dir) else {
+        return Ok(run);
+    };
+    let mut transcripts = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("jsonl"))
+        .collect::<Vec<_>>();
+    transcripts.sort();
+
+    for transcript in transcripts {
+        let Some(name) = transcript.file_name().and_then(|s| s.to_str()).map(str::to_string) else {
+            continue;
+        };
+        let modified_ms = std::fs::metadata(&transcript)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(now);
+        if now - modified_ms < settings.idle_ms {
+            run.skipped += 1;
+            continue;
+        }
+        let covered = state.sessions.get(&name).map(|r| r.covered_lines).unwrap_or(0);
+        match digest_transcript(paths, &settings, &transcript, &name, covered, &mut state, summarize)
+        {
+            Ok(true) => {
+                run.summarized += 1;
+                save_state(paths, &state)?;
+                if let Err(err) = reindex_after_digest(paths, &settings, &transcript) {
+                    eprintln!("[clawdex][memory] digest index update failed for {name}: {err}");
+                }
+            }
+            Ok(false) => run.skipped += 1,
+            Err(err) => {
+                run.failed += 1;
+                eprintln!("[clawdex][memory] session digest failed for {name}: {err}");
+            }
+        }
+    }
+    Ok(run)
+}
+
+fn digest_transcript(
+    paths: &ClawdPaths,
+    settings: &SessionDigestSettings,
+    transcript: &Path,
+    name: &str,
+    covered: usize,
+    state: &mut DigestState,
+    summarize: &mut dyn FnMut(&str, &str) -> Result<String>,
+) -> Result<bool> {
+    let messages = memory::read_session_messages(transcript, covered)?;
+    if messages.len() < settings.min_messages {
+        return Ok(false);
+    }
+    // Take as many whole messages as fit the budget; the rest is picked up on the next tick.
+    let mut batch = Vec::new();
+    let mut chars = 0usize;
+    for message in &messages {
+        let len = message.text.chars().count() + 1;
+        if !batch.is_empty() && chars + len > settings.max_input_chars {
+            break;
+        }
+        chars += len;
+        batch.push(message);
+    }
+    let from_line = covered + 1;
+    let to_line = batch.last().map(|m| m.line).unwrap_or(covered);
+    let session_key = read_session_key(transcript);
+    let label = session_key.clone().unwrap_or_else(|| name.to_string());
+
+    let excerpt = batch
+        .iter()
+        .map(|m| m.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let prompt = format!(
+        "{DIGEST_PROMPT}\n\nSession: {label}\nTranscript lines {from_line}-{to_line}:\n\n{excerpt}"
+    );
+    let thread_key = format!("session-digest:{name}");
+    let summary = summarize(&thread_key, &prompt)?;
+    let summary = summary.trim();
+    if summary.is_empty() {
+        anyhow::bail!("summarizer returned an empty digest");
+    }
+
+    let digest = digest_path(paths, transcript);
+    if let Some(parent) = digest.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&digest)
+        .with_context(|| format!("open {}", digest.display()))?;
+    let empty = file.metadata().map(|meta| meta.len() == 0).unwrap_or(true);
+    if empty {
+        writeln!(file, "# Session digest: {label}\n\n- transcript: sessions/{name}")
+            .with_context(|| format!("write {}", digest.display()))?;
+    }
+    let now = now_ms();
+    let stamp = Utc
+        .timestamp_millis_opt(now)
+        .single()
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default();
+    writeln!(
+        file,
+        "\n## Lines {from_line}-{to_line} ({stamp})\n\n{summary}"
+    )
+    .with_context(|| format!("write {}", digest.display()))?;
+
+    let digest_rel = digest
+        .strip_prefix(&paths.workspace_dir)
+        .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| digest.to_string_lossy().to_string());
+    let record = state
+        .sessions
+        .entry(name.to_string())
+        .or_insert_with(|| SessionDigestRecord {
+            session_key: session_key.clone(),
+            digest_path: digest_rel.clone(),
+            covered_lines: 0,
+            updated_at_ms: now,
+            ranges: Vec::new(),
+        });
+    record.session_key = session_key.or(record.session_key.take());
+    record.digest_path = digest_rel;
+    record.covered_lines = to_line;
+    record.updated_at_ms = now;
+    record.ranges.push(DigestRange {
+        from_line,
+        to_line,
+        messages: batch.len(),
+        summarized_at_ms: now,
+    });
+    Ok(true)
+}
+
+fn reindex_after_digest(
+    paths: &ClawdPaths,
+    settings: &SessionDigestSettings,
+    transcript: &Path,
+) -> Result<()> {
+    let mut changed = vec![digest_path(paths, transcript)];
+    if !settings.index_transcripts {
+        // The covered lines now live in the digest; rebuild the transcript's chunks without them.
+        memory::mark_session_transcript_stale(paths, transcript)?;
+        changed.push(transcript.to_path_buf());
+    }
+    memory::reindex_memory_paths(paths, &changed)?;
+    Ok(())
+}
+
+fn read_session_key(transcript: &Path) -> Option<String> {
+    let raw = std::fs::read_to_string(transcript).ok()?;
+    raw.lines().find_map(|line| {
+        serde_json::from_str::<Value>(line)
+            .ok()?
+            .get("sessionKey")?
+            .as_str()
+            .map(str::to_string)
+            .filter(|key| !key.is_empty())
+    })
+}
@@ -44,6 +44,7 @@ pub fn append_session_message(
     let entry = json!({
         "type": "message",
         "timestampMs": now_ms(),
+        "sessionKey": session_key.trim(),
         "message": {
             "role": role,
             "content": [
@@ -737,6 +737,44 @@ impl TaskStore {
         Ok(events)
     }
 
+    /// Events recorded after `after_rowid`, in insertion order, paired with
+    /// their rowid so callers can tail the table across runs.
+    pub fn list_events_after_rowid(
+        &self,
+        after_rowid: i64,
+        limit: usize,
+    ) -> Result<Vec<(i64, TaskEvent)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rowid, id, task_run_id, ts_ms, kind, payload_json FROM events WHERE rowid > ? ORDER BY rowid ASC LIMIT ?",
+        )?;
+        let rows = stmt.query_map(params![after_rowid, limit as i64], |row| {
+            let payload_json: String = row.get(5)?;
+            let payload: Value = serde_json::from_str(&payload_json).unwrap_or(Value::Null);
+            Ok((
+                row.get(0)?,
+                TaskEvent {
+                    id: row.get(1)?,
+                    task_run_id: row.get(2)?,
+                    ts_ms: row.get(3)?,
+                    kind: row.get(4)?,
+                    payload,
+                },
+            ))
+        })?;
+        let mut events = Vec::new();
+        for row in rows {
+            events.push(row?);
+        }
+        Ok(events)
+    }
+
+    pub fn max_event_rowid(&self) -> Result<i64> {
+        let max: Option<i64> = self
+            .conn
+            .query_row("SELECT MAX(rowid) FROM events", [], |row| row.get(0))?;
+        Ok(max.unwrap_or(0))
+    }
+
     pub fn upsert_plugin(&self, plugin: &PluginRecord) -> Result<PluginRecord> {
         self.conn.execute(
             r#"
@@ -0,0 +1,39 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use clawdex::config::{load_config, ClawdConfig, ClawdPaths};
+
+/// A throwaway state dir and workspace under the temp dir, removed again on drop.
+pub struct TestState {
+    pub base: PathBuf,
+    pub cfg: ClawdConfig,
+    pub paths: ClawdPaths,
+}
+
+impl TestState {
+    /// Creates `clawdex-<label>-<uuid>` with `memory_cfg` as the `memory` section of its
+    /// `config.json5`.
+    pub fn with_memory(label: &str, memory_cfg: Value) -> Result<Self> {
+        let base = std::env::temp_dir().join(format!("clawdex-{label}-{}", Uuid::new_v4()));
+        let state_dir = base.join("state");
+        let workspace_dir = base.join("workspace");
+        fs::create_dir_all(&state_dir)?;
+        fs::create_dir_all(&workspace_dir)?;
+        fs::write(
+            state_dir.join("config.json5"),
+            serde_json::to_string_pretty(&json!({ "memory": memory_cfg }))?,
+        )?;
+        let (cfg, paths) = load_config(Some(state_dir), Some(workspace_dir))?;
+        Ok(Self { base, cfg, paths })
+    }
+}
+
+impl Drop for TestState {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.base);
+    }
+}
@@ -0,0 +1,72 @@
+use std::fs;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use clawdex::config::ClawdPaths;
+use clawdex::memory;
+
+mod common;
+use common::TestState;
+
+fn setup(memory_cfg: Value, note: &str) -> Result<TestState> {
+    let state = TestState::with_memory("memory-chunking", memory_cfg)?;
+    let memory_dir = state.paths.workspace_dir.join("memory");
+    fs::create_dir_all(&memory_dir)?;
+    fs::write(memory_dir.join("projects.md"), note)?;
+    Ok(state)
+}
+
+fn search(paths: &ClawdPaths, query: &str) -> Result<Vec<Value>> {
+    let res = memory::memory_search(paths, &json!({ "query": query }))?;
+    Ok(res
+        .get("results")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+const NOTE: &str = "# Projects\n\nOverview of active work.\n\n## Billing\n\nInvoices go out monthly.\n\n### Decisions\n\n- switch to usage pricing\n- keep annual plans\n\n```sql\nSELECT plan FROM invoices;\n\nUPDATE invoices SET paid = 1;\n```\n\n## Hiring\n\nOpen role for a platform engineer.\n";
+
+#[test]
+fn memory_search_returns_heading_breadcrumbs() -> Result<()> {
+    let state = setup(json!({ "enabled": true, "session_memory": false }), NOTE)?;
+    let paths = &state.paths;
+
+    let results = search(paths, "usage pricing")?;
+    assert!(!results.is_empty());
+    assert_eq!(
+        results[0].get("section").and_then(Value::as_str),
+        Some("Projects > Billing > Decisions")
+    );
+    let snippet = results[0].get("snippet").and_then(Value::as_str).unwrap_or("");
+    assert!(snippet.starts_with("### Decisions"));
+    // The fenced block stays whole even though it contains a blank line.
+    assert!(snippet.contains("SELECT plan FROM invoices;"));
+    assert!(snippet.contains("UPDATE invoices SET paid = 1;"));
+    assert!(!snippet.contains("Hiring"));
+    assert_eq!(results[0].get("startLine").and_then(Value::as_i64), Some(9));
+    assert_eq!(results[0].get("endLine").and_then(Value::as_i64), Some(18));
+
+    let results = search(paths, "platform engineer")?;
+    assert_eq!(
+        results[0].get("section").and_then(Value::as_str),
+        Some("Projects > Hiring")
+    );
+    Ok(())
+}
+
+#[test]
+fn fixed_chunk_strategy_keeps_size_based_chunks() -> Result<()> {
+    let state = setup(
+        json!({ "enabled": true, "session_memory": false, "chunkStrategy": "fixed" }),
+        NOTE,
+    )?;
+    let paths = &state.paths;
+
+    let results = search(paths, "usage pricing")?;
+    assert!(!results.is_empty());
+    assert!(results[0].get("section").is_none());
+    assert_eq!(results[0].get("startLine").and_then(Value::as_i64), Some(1));
+    Ok(())
+}
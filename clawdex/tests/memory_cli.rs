@@ -0,0 +1,59 @@
+use std::fs;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use clawdex::memory;
+
+mod common;
+use common::TestState;
+
+fn setup() -> Result<TestState> {
+    let state = TestState::with_memory(
+        "memory-cli",
+        json!({ "enabled": true, "session_memory": false }),
+    )?;
+    let memory_dir = state.paths.workspace_dir.join("memory");
+    fs::create_dir_all(&memory_dir)?;
+    fs::write(memory_dir.join("alpha.md"), "# Alpha\n\nalpha notes\n")?;
+    fs::write(memory_dir.join("beta.md"), "# Beta\n\nbeta notes\n")?;
+    Ok(state)
+}
+
+#[test]
+fn memory_status_reindex_and_vacuum_report_index_state() -> Result<()> {
+    let state = setup()?;
+    let paths = &state.paths;
+
+    let res = memory::memory_reindex(paths, false, false)?;
+    assert_eq!(res["ok"], json!(true));
+    let status = &res["status"];
+    assert_eq!(status["enabled"], json!(true));
+    assert_eq!(status["schemaVersion"], json!(6));
+    assert_eq!(status["files"]["total"], json!(2));
+    assert_eq!(status["files"]["bySource"]["memory"], json!(2));
+    assert_eq!(status["chunks"], json!(2));
+    assert_eq!(status["embeddings"]["total"], json!(0));
+    assert_eq!(status["provider"], Value::Null);
+    assert_eq!(status["failures"]["total"], json!(0));
+
+    // Deleted files drop out of the index; vacuum compacts what remains.
+    fs::remove_file(paths.workspace_dir.join("memory").join("beta.md"))?;
+    let res = memory::memory_reindex(paths, true, false)?;
+    assert_eq!(res["status"]["files"]["total"], json!(1));
+    assert_eq!(res["status"]["chunks"], json!(1));
+
+    fs::write(paths.memory_dir.join("fts.vectors-0000000000000000.ann"), b"stale")?;
+    let res = memory::memory_vacuum(paths)?;
+    assert_eq!(res["ok"], json!(true));
+    assert_eq!(
+        res["removedVectorIndexes"],
+        json!(["fts.vectors-0000000000000000.ann"])
+    );
+    assert!(!paths.memory_dir.join("fts.vectors-0000000000000000.ann").exists());
+
+    let failures = memory::memory_failures(paths, 10, true)?;
+    assert_eq!(failures["total"], json!(0));
+    assert_eq!(failures["failures"], json!([]));
+    Ok(())
+}
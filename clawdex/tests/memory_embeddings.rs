@@ -40,6 +40,10 @@ impl EmbeddingsServer {
         Self::start_with_handler(handle_embeddings_request_reject_empty)
     }
 
+    fn start_synonyms() -> Result<Self> {
+        Self::start_with_handler(handle_embeddings_request_synonyms)
+    }
+
     fn start_ollama() -> Result<Self> {
         Self::start_with_handler(handle_ollama_embeddings_request)
     }
@@ -143,6 +147,52 @@ fn handle_embeddings_request(mut request: tiny_http::Request) -> Result<()> {
     Ok(())
 }
 
+fn handle_embeddings_request_synonyms(mut request: tiny_http::Request) -> Result<()> {
+    if request.method() != &Method::Post {
+        let _ = request.respond(Response::empty(StatusCode(405)));
+        return Ok(());
+    }
+    let url = request.url().to_string();
+    if url != "/v1/embeddings" && url != "/embeddings" {
+        let _ = request.respond(Response::empty(StatusCode(404)));
+        return Ok(());
+    }
+
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)?;
+    let parsed: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+    let inputs = parsed
+        .get("input")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    // "needle" and "pin" are semantic neighbours even though they share no tokens.
+    let data = inputs
+        .iter()
+        .enumerate()
+        .map(|(idx, text)| {
+            let lower = text.as_str().unwrap_or("").to_lowercase();
+            let embedding = if lower.contains("needle") || lower.contains("pin") {
+                vec![1.0_f32, 0.0_f32, 0.0_f32]
+            } else {
+                vec![0.0_f32, 1.0_f32, 0.0_f32]
+            };
+            json!({ "index": idx, "embedding": embedding })
+        })
+        .collect::<Vec<_>>();
+
+    let response = json!({ "data": data });
+    let bytes = serde_json::to_vec(&response)?;
+    let header = tiny_http::Header::from_bytes(
+        &b"Content-Type"[..],
+        &b"application/json"[..],
+    )
+    .expect("content-type header");
+    let _ = request.respond(Response::from_data(bytes).with_header(header));
+    Ok(())
+}
+
 fn handle_ollama_embeddings_request(mut request: tiny_http::Request) -> Result<()> {
     if request.method() != &Method::Post {
         let _ = request.respond(Response::empty(StatusCode(405)));
@@ -260,6 +310,50 @@ fn temp_paths() -> Result<(PathBuf, clawdex::config::ClawdPaths)> {
     Ok((base, paths))
 }
 
+/// Writes a tiny static-embedding model (WordPiece tokenizer + f32 safetensors table) where
+/// "needle" and "pin" share a direction, like the synonyms server above.
+fn write_local_model(dir: &std::path::Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let rows: [(&str, [f32; 3]); 7] = [
+        ("[UNK]", [0.0, 0.0, 0.0]),
+        ("needle", [1.0, 0.0, 0.0]),
+        ("pin", [1.0, 0.0, 0.0]),
+        ("##s", [1.0, 0.0, 0.0]),
+        ("tomato", [0.0, 1.0, 0.0]),
+        ("##es", [0.0, 1.0, 0.0]),
+        ("watered", [0.0, 0.0, 1.0]),
+    ];
+    let vocab = rows
+        .iter()
+        .enumerate()
+        .map(|(id, (token, _))| (token.to_string(), json!(id)))
+        .collect::<serde_json::Map<_, _>>();
+    let tokenizer = json!({
+        "normalizer": { "type": "BertNormalizer", "lowercase": true },
+        "model": {
+            "type": "WordPiece",
+            "unk_token": "[UNK]",
+            "continuing_subword_prefix": "##",
+            "vocab": vocab,
+        }
+    });
+    fs::write(dir.join("tokenizer.json"), tokenizer.to_string())?;
+
+    let data = rows
+        .iter()
+        .flat_map(|(_, row)| row.iter().flat_map(|v| v.to_le_bytes()))
+        .collect::<Vec<u8>>();
+    let header = json!({
+        "embeddings": { "dtype": "F32", "shape": [rows.len(), 3], "data_offsets": [0, data.len()] }
+    })
+    .to_string();
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(&data);
+    fs::write(dir.join("model.safetensors"), bytes)?;
+    Ok(())
+}
+
 fn write_config(paths: &clawdex::config::ClawdPaths, value: &Value) -> Result<()> {
     fs::write(
         paths.state_dir.join("config.json5"),
@@ -512,3 +606,268 @@ fn memory_search_supports_ollama_without_api_key() -> Result<()> {
     let _ = fs::remove_dir_all(base);
     Ok(())
 }
+
+#[test]
+fn memory_search_hybrid_recalls_chunks_without_token_overlap() -> Result<()> {
+    let _guard = env_test_lock()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let server = EmbeddingsServer::start_synonyms()?;
+    std::env::set_var("CLAWDEX_TEST_API_KEY_5", "ok");
+
+    let (base, paths) = temp_paths()?;
+    let memory_dir = paths.workspace_dir.join("memory");
+    fs::create_dir_all(&memory_dir)?;
+    fs::write(memory_dir.join("sewing.md"), "bought a box of pins for the quilt\n")?;
+    fs::write(memory_dir.join("garden.md"), "watered the tomatoes\n")?;
+
+    let config = |hybrid_enabled: bool| {
+        json!({
+            "memory": {
+                "enabled": true,
+                "session_memory": false,
+                "embeddings": {
+                    "enabled": true,
+                    "provider": "openai",
+                    "model": "test",
+                    "api_base": server.api_base(),
+                    "api_key_env": "CLAWDEX_TEST_API_KEY_5",
+                    "batch_size": 32
+                },
+                "hybrid": {
+                    "enabled": hybrid_enabled,
+                    "vectorWeight": 0.5,
+                    "textWeight": 0.5
+                }
+            }
+        })
+    };
+
+    // Rerank-only mode cannot surface a chunk that BM25 never matched.
+    write_config(&paths, &config(false))?;
+    let res = memory::memory_search(&paths, &json!({ "query": "needle" }))?;
+    let results = res.get("results").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    assert!(results.is_empty());
+
+    write_config(&paths, &config(true))?;
+    let res = memory::memory_search(&paths, &json!({ "query": "needle" }))?;
+    let results = res.get("results").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    assert!(!results.is_empty());
+    assert_eq!(
+        results[0].get("path").and_then(|v| v.as_str()),
+        Some("memory/sewing.md")
+    );
+    assert!(results[0].get("embeddingScore").and_then(|v| v.as_f64()).unwrap_or(0.0) > 0.9);
+    assert!(results[0].get("score").and_then(|v| v.as_f64()).unwrap_or(0.0) > 0.0);
+
+    std::env::remove_var("CLAWDEX_TEST_API_KEY_5");
+    let _ = fs::remove_dir_all(base);
+    Ok(())
+}
+
+#[test]
+fn memory_search_migrates_json_vectors_without_reembedding() -> Result<()> {
+    let _guard = env_test_lock()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let server = EmbeddingsServer::start()?;
+    std::env::set_var("CLAWDEX_TEST_API_KEY_6", "ok");
+
+    let (base, paths) = temp_paths()?;
+    let memory_dir = paths.workspace_dir.join("memory");
+    fs::create_dir_all(&memory_dir)?;
+    fs::write(memory_dir.join("2026-02-01.md"), "alpha\nneedle here\nbeta\n")?;
+
+    write_config(
+        &paths,
+        &json!({
+            "memory": {
+                "enabled": true,
+                "session_memory": false,
+                "embeddings": {
+                    "enabled": true,
+                    "provider": "openai",
+                    "model": "test",
+                    "api_base": server.api_base(),
+                    "api_key_env": "CLAWDEX_TEST_API_KEY_6",
+                    "batch_size": 32
+                }
+            }
+        }),
+    )?;
+
+    memory::memory_search(&paths, &json!({ "query": "needle" }))?;
+    assert_eq!(server.count(), 2);
+
+    // Rewrite the index the way schema 3 stored it: JSON text vectors, no metadata table.
+    {
+        let conn = rusqlite::Connection::open(paths.memory_dir.join("fts.sqlite"))?;
+        let rows = {
+            let mut stmt = conn.prepare(
+                "SELECT path, start_line, end_line, source, provider, model, vector FROM memory_embeddings",
+            )?;
+            let rows = stmt
+                .query_map([], |row| {
+                    let blob: Vec<u8> = row.get(6)?;
+                    let vector = blob
+                        .chunks_exact(4)
+                        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                        .collect::<Vec<_>>();
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, i64>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, String>(5)?,
+                        serde_json::to_string(&vector).unwrap_or_default(),
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows
+        };
+        assert!(!rows.is_empty());
+        conn.execute_batch(
+            "DROP TABLE memory_embeddings; DROP TABLE memory_meta; \
+             CREATE TABLE memory_embeddings (path TEXT, start_line INTEGER, end_line INTEGER, source TEXT, provider TEXT, model TEXT, vector TEXT, PRIMARY KEY(path, start_line, end_line, source, provider, model)); \
+             PRAGMA user_version = 3;",
+        )?;
+        for row in rows {
+            conn.execute(
+                "INSERT INTO memory_embeddings(path, start_line, end_line, source, provider, model, vector) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![row.0, row.1, row.2, row.3, row.4, row.5, row.6],
+            )?;
+        }
+    }
+
+    let res = memory::memory_search(&paths, &json!({ "query": "needle" }))?;
+    let results = res.get("results").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    assert!(!results.is_empty());
+    assert!(results[0].get("embeddingScore").and_then(|v| v.as_f64()).unwrap_or(0.0) > 0.9);
+    // Only the query is embedded; migrated chunk vectors are reused.
+    assert_eq!(server.count(), 3);
+
+    let conn = rusqlite::Connection::open(paths.memory_dir.join("fts.sqlite"))?;
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    assert_eq!(version, 6);
+    let vector_type: String = conn.query_row(
+        "SELECT typeof(vector) FROM memory_embeddings LIMIT 1",
+        [],
+        |row| row.get(0),
+    )?;
+    assert_eq!(vector_type, "blob");
+
+    std::env::remove_var("CLAWDEX_TEST_API_KEY_6");
+    let _ = fs::remove_dir_all(base);
+    Ok(())
+}
+
+#[test]
+fn memory_search_uses_local_model_while_remote_backs_off() -> Result<()> {
+    let _guard = env_test_lock()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    let server = EmbeddingsServer::start_failing()?;
+    std::env::set_var("CLAWDEX_TEST_API_KEY_7", "ok");
+
+    let (base, paths) = temp_paths()?;
+    let memory_dir = paths.workspace_dir.join("memory");
+    fs::create_dir_all(&memory_dir)?;
+    fs::write(memory_dir.join("sewing.md"), "bought a box of pins for the quilt\n")?;
+    fs::write(memory_dir.join("garden.md"), "watered the tomatoes\n")?;
+    let model_dir = base.join("static-model");
+    write_local_model(&model_dir)?;
+
+    write_config(
+        &paths,
+        &json!({
+            "memory": {
+                "enabled": true,
+                "session_memory": false,
+                "embeddings": {
+                    "enabled": true,
+                    "provider": "openai",
+                    "model": "test",
+                    "api_base": server.api_base(),
+                    "api_key_env": "CLAWDEX_TEST_API_KEY_7",
+                    "batch_size": 32,
+                    "modelPath": model_dir.to_string_lossy()
+                }
+            }
+        }),
+    )?;
+
+    // Indexing fails remotely, which puts the provider in backoff; the query is then embedded
+    // locally and matched against locally backfilled chunk vectors.
+    let res = memory::memory_search(&paths, &json!({ "query": "needle" }))?;
+    let results = res.get("results").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    assert!(!results.is_empty());
+    assert_eq!(
+        results[0].get("path").and_then(|v| v.as_str()),
+        Some("memory/sewing.md")
+    );
+    assert!(results[0].get("embeddingScore").and_then(|v| v.as_f64()).unwrap_or(0.0) > 0.9);
+    let count_after_first = server.count();
+    assert!(count_after_first > 0);
+
+    let res = memory::memory_search(&paths, &json!({ "query": "needle" }))?;
+    let results = res.get("results").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    assert_eq!(
+        results[0].get("path").and_then(|v| v.as_str()),
+        Some("memory/sewing.md")
+    );
+    // No remote traffic while the backoff window is open.
+    assert_eq!(server.count(), count_after_first);
+
+    // The local backfill is throttled, so a new file waits for the next backfill window.
+    fs::write(memory_dir.join("kitchen.md"), "sharpened the bread knife\n")?;
+    memory::memory_search(&paths, &json!({ "query": "knife" }))?;
+    let conn = rusqlite::Connection::open(paths.memory_dir.join("fts.sqlite"))?;
+    let kitchen_vectors: i64 = conn.query_row(
+        "SELECT COUNT(1) FROM memory_embeddings WHERE path = 'memory/kitchen.md'",
+        [],
+        |row| row.get(0),
+    )?;
+    assert_eq!(kitchen_vectors, 0);
+
+    std::env::remove_var("CLAWDEX_TEST_API_KEY_7");
+    let _ = fs::remove_dir_all(base);
+    Ok(())
+}
+
+#[test]
+fn memory_search_supports_local_provider_offline() -> Result<()> {
+    let (base, paths) = temp_paths()?;
+    let memory_dir = paths.workspace_dir.join("memory");
+    fs::create_dir_all(&memory_dir)?;
+    fs::write(memory_dir.join("sewing.md"), "bought a box of pins for the quilt\n")?;
+    fs::write(memory_dir.join("garden.md"), "watered the tomatoes\n")?;
+    write_local_model(&paths.state_dir.join("models").join("static"))?;
+
+    write_config(
+        &paths,
+        &json!({
+            "memory": {
+                "enabled": true,
+                "session_memory": false,
+                "embeddings": {
+                    "enabled": true,
+                    "provider": "local",
+                    "modelPath": "models/static"
+                }
+            }
+        }),
+    )?;
+
+    let res = memory::memory_search(&paths, &json!({ "query": "needle" }))?;
+    let results = res.get("results").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    assert!(!results.is_empty());
+    assert_eq!(
+        results[0].get("path").and_then(|v| v.as_str()),
+        Some("memory/sewing.md")
+    );
+    assert!(results[0].get("embeddingScore").and_then(|v| v.as_f64()).unwrap_or(0.0) > 0.9);
+
+    let _ = fs::remove_dir_all(base);
+    Ok(())
+}
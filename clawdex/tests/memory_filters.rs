@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use clawdex::config::ClawdPaths;
+use clawdex::memory;
+
+mod common;
+use common::TestState;
+
+fn write_note(path: &Path, text: &str, mtime_ms: u64) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, text)?;
+    let file = fs::OpenOptions::new().write(true).open(path)?;
+    file.set_modified(UNIX_EPOCH + Duration::from_millis(mtime_ms))?;
+    Ok(())
+}
+
+fn setup() -> Result<TestState> {
+    let state = TestState::with_memory(
+        "memory-filters",
+        json!({
+            "enabled": true,
+            "session_memory": false,
+            "extraPaths": ["notes"]
+        }),
+    )?;
+    let workspace_dir = &state.paths.workspace_dir;
+    // 2025-12-15, 2026-01-10 and 2026-02-03 (UTC).
+    write_note(&workspace_dir.join("memory/2025-12-15.md"), "needle in december\n", 1_765_756_800_000)?;
+    write_note(&workspace_dir.join("memory/2026-01-10.md"), "needle in january\n", 1_768_003_200_000)?;
+    write_note(&workspace_dir.join("notes/ideas.md"), "needle in the notes\n", 1_770_076_800_000)?;
+    Ok(state)
+}
+
+fn search(paths: &ClawdPaths, args: Value) -> Result<Vec<String>> {
+    let res = memory::memory_search(paths, &args)?;
+    let mut found = res
+        .get("results")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|r| r.get("path").and_then(Value::as_str).map(str::to_string))
+        .collect::<Vec<_>>();
+    found.sort();
+    Ok(found)
+}
+
+#[test]
+fn memory_search_filters_by_path_source_and_mtime() -> Result<()> {
+    let state = setup()?;
+    let paths = &state.paths;
+
+    assert_eq!(
+        search(paths, json!({ "query": "needle" }))?,
+        vec!["memory/2025-12-15.md", "memory/2026-01-10.md", "notes/ideas.md"]
+    );
+    assert_eq!(
+        search(paths, json!({ "query": "needle", "pathGlobs": "memory/2026-*" }))?,
+        vec!["memory/2026-01-10.md"]
+    );
+    assert_eq!(
+        search(paths, json!({ "query": "needle", "sources": ["extra"] }))?,
+        vec!["notes/ideas.md"]
+    );
+    assert_eq!(
+        search(paths, json!({ "query": "needle", "sources": ["memory"] }))?,
+        vec!["memory/2025-12-15.md", "memory/2026-01-10.md"]
+    );
+    assert_eq!(
+        search(
+            paths,
+            json!({ "query": "needle", "modifiedAfter": "2026-01-01", "modifiedBefore": "2026-02-01" })
+        )?,
+        vec!["memory/2026-01-10.md"]
+    );
+    // Filters apply before the result limit, so the only match is still found.
+    assert_eq!(
+        search(
+            paths,
+            json!({ "query": "needle", "maxResults": 1, "modified_after": 1_770_000_000_000i64 })
+        )?,
+        vec!["notes/ideas.md"]
+    );
+    // Session transcripts stay out of reach while sessionMemory is off, even when asked for.
+    clawdex::sessions::append_session_message(paths, "agent:main:main", "user", "needle in a chat")?;
+    assert!(search(paths, json!({ "query": "needle", "sources": ["sessions"] }))?.is_empty());
+    assert!(!search(paths, json!({ "query": "needle" }))?
+        .iter()
+        .any(|path| path.starts_with("sessions/")));
+    assert!(memory::memory_search(paths, &json!({ "query": "needle", "sources": ["inbox"] })).is_err());
+    assert!(
+        memory::memory_search(paths, &json!({ "query": "needle", "modifiedAfter": "soon" })).is_err()
+    );
+    Ok(())
+}
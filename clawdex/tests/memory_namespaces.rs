@@ -0,0 +1,168 @@
+use std::fs;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use clawdex::config::ClawdPaths;
+use clawdex::memory;
+
+mod common;
+use common::TestState;
+
+fn setup(namespaces: Value) -> Result<TestState> {
+    setup_memory(json!({ "enabled": true, "session_memory": false, "namespaces": namespaces }))
+}
+
+fn setup_memory(memory_cfg: Value) -> Result<TestState> {
+    let state = TestState::with_memory("memory-namespaces", memory_cfg)?;
+    let workspace_dir = &state.paths.workspace_dir;
+    fs::create_dir_all(workspace_dir.join("memory").join("agents").join("kline"))?;
+    fs::create_dir_all(workspace_dir.join("memory").join("agents").join("main"))?;
+    fs::write(workspace_dir.join("MEMORY.md"), "# Shared\n\nlaunch codename is heron\n")?;
+    fs::write(
+        workspace_dir.join("memory/agents/main/private.md"),
+        "main launch checklist lives in notion\n",
+    )?;
+    fs::write(
+        workspace_dir.join("memory/agents/kline/private.md"),
+        "kline launch chart uses hourly candles\n",
+    )?;
+    Ok(state)
+}
+
+fn write(paths: &ClawdPaths, session_key: &str, content: &str) -> Result<Value> {
+    memory::memory_write(
+        paths,
+        &json!({
+            "content": content,
+            "sessionKey": session_key,
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+        }),
+    )
+}
+
+fn search(paths: &ClawdPaths, args: Value) -> Result<Vec<String>> {
+    let res = memory::memory_search(paths, &args)?;
+    let mut found = res
+        .get("results")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|r| r.get("snippet").and_then(Value::as_str).map(str::to_string))
+        .collect::<Vec<_>>();
+    found.sort();
+    Ok(found)
+}
+
+#[test]
+fn agents_write_and_search_their_own_namespace() -> Result<()> {
+    let state = setup(json!({ "enabled": true }))?;
+    let paths = &state.paths;
+
+    let res = write(paths, "agent:kline:telegram:1", "kline launch alert threshold is 4%")?;
+    assert_eq!(res["scope"], json!("agent:kline"));
+    assert_eq!(res["path"], json!("memory/writes/agent/kline.md"));
+    let res = write(paths, "agent:main:main", "main launch owner is dana")?;
+    assert_eq!(res["scope"], json!("agent:main"));
+
+    let kline = search(paths, json!({ "query": "launch", "sessionKey": "agent:kline:telegram:1" }))?;
+    assert!(kline.iter().any(|s| s.contains("heron")));
+    assert!(kline.iter().any(|s| s.contains("hourly candles")));
+    assert!(kline.iter().any(|s| s.contains("threshold")));
+    assert!(!kline.iter().any(|s| s.contains("notion") || s.contains("dana")));
+
+    // Unscoped searches (operators, CLI) still see every namespace.
+    let all = search(paths, json!({ "query": "launch", "maxResults": 20 }))?;
+    assert!(all.iter().any(|s| s.contains("notion")));
+    assert!(all.iter().any(|s| s.contains("hourly candles")));
+
+    // Agents cannot write into another agent's private namespace.
+    let denied = memory::memory_write(
+        paths,
+        &json!({
+            "content": "sneaky",
+            "scope": "agent:main",
+            "sessionKey": "agent:kline:telegram:1",
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+        }),
+    );
+    assert!(denied.is_err());
+    Ok(())
+}
+
+#[test]
+fn shared_namespaces_are_visible_to_every_agent() -> Result<()> {
+    let state = setup(json!({ "enabled": true, "shared": ["main"] }))?;
+    let paths = &state.paths;
+
+    let kline = search(paths, json!({ "query": "launch", "sessionKey": "agent:kline:telegram:1" }))?;
+    assert!(kline.iter().any(|s| s.contains("notion")));
+    let main = search(paths, json!({ "query": "launch", "agentId": "main" }))?;
+    assert!(!main.iter().any(|s| s.contains("hourly candles")));
+    Ok(())
+}
+
+#[test]
+fn namespaces_are_opt_in() -> Result<()> {
+    let state = setup(json!({}))?;
+    let paths = &state.paths;
+
+    let res = write(paths, "agent:kline:telegram:1", "kline launch alert threshold is 4%")?;
+    assert_eq!(res["scope"], json!("workspace"));
+    // Reads stay private either way: an agent session searches its own namespace.
+    let kline = search(paths, json!({ "query": "launch", "sessionKey": "agent:kline:telegram:1" }))?;
+    assert!(kline.iter().any(|s| s.contains("threshold")));
+    assert!(!kline.iter().any(|s| s.contains("notion")));
+    let kline = search(paths, json!({ "query": "launch", "agentId": "kline" }))?;
+    assert!(!kline.iter().any(|s| s.contains("notion")));
+    Ok(())
+}
+
+#[test]
+fn cross_agent_reads_need_the_config_opt_in() -> Result<()> {
+    let state = setup(json!({ "enabled": true }))?;
+    let paths = &state.paths;
+    let session = "agent:kline:telegram:1";
+
+    assert!(search(paths, json!({ "query": "launch", "sessionKey": session, "agentId": "main" })).is_err());
+    assert!(search(paths, json!({ "query": "launch", "sessionKey": session, "allAgents": true })).is_err());
+
+    let state = setup(json!({ "enabled": true, "crossAgentReads": true }))?;
+    let paths = &state.paths;
+    let main = search(paths, json!({ "query": "launch", "sessionKey": session, "agentId": "main" }))?;
+    assert!(main.iter().any(|s| s.contains("notion")));
+    assert!(!main.iter().any(|s| s.contains("hourly candles")));
+    let all = search(paths, json!({ "query": "launch", "sessionKey": session, "allAgents": true }))?;
+    assert!(all.iter().any(|s| s.contains("notion")));
+    assert!(all.iter().any(|s| s.contains("hourly candles")));
+    Ok(())
+}
+
+#[test]
+fn similar_agent_ids_and_cases_do_not_leak() -> Result<()> {
+    let state = setup_memory(json!({
+        "enabled": true,
+        "session_memory": true,
+        "namespaces": { "enabled": true }
+    }))?;
+    let paths = &state.paths;
+    // `agent:a:...` and `agent:a_b:...` transcripts share the `agent_a_` file name prefix.
+    clawdex::sessions::append_session_message(paths, "agent:a:main", "user", "orbit plan for a")?;
+    clawdex::sessions::append_session_message(paths, "agent:a_b:main", "user", "orbit plan for a_b")?;
+    fs::create_dir_all(paths.workspace_dir.join("memory/agents/Ops"))?;
+    fs::write(paths.workspace_dir.join("memory/agents/Ops/notes.md"), "orbit plan for ops\n")?;
+
+    let a = search(paths, json!({ "query": "orbit", "agentId": "a" }))?;
+    assert!(a.iter().any(|s| s.contains("orbit plan for a")));
+    assert!(!a.iter().any(|s| s.contains("a_b") || s.contains("ops")));
+    let a_b = search(paths, json!({ "query": "orbit", "agentId": "a_b" }))?;
+    assert!(a_b.iter().any(|s| s.contains("orbit plan for a_b")));
+    assert_eq!(a_b.len(), 1);
+    let ops = search(paths, json!({ "query": "orbit", "agentId": "OPS" }))?;
+    assert_eq!(ops.len(), 1);
+    assert!(ops[0].contains("orbit plan for ops"));
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use clawdex::config::load_config;
+use clawdex::{memory, memory_watch};
+
+fn wait_for(mut check: impl FnMut() -> Result<bool>) -> Result<bool> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if check()? {
+            return Ok(true);
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(false)
+}
+
+fn search_paths(paths: &clawdex::config::ClawdPaths, query: &str) -> Result<Vec<String>> {
+    let res = memory::memory_search(paths, &json!({ "query": query }))?;
+    Ok(res
+        .get("results")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|r| r.get("path").and_then(Value::as_str).map(str::to_string))
+        .collect())
+}
+
+#[test]
+fn watcher_indexes_and_removes_memory_files_incrementally() -> Result<()> {
+    let base = std::env::temp_dir().join(format!("clawdex-memory-watch-{}", Uuid::new_v4()));
+    let workspace_dir = base.join("workspace");
+    fs::create_dir_all(workspace_dir.join("memory"))?;
+    let (_cfg, paths) = load_config(Some(base.join("state")), Some(workspace_dir.clone()))?;
+    fs::write(
+        paths.state_dir.join("config.json5"),
+        serde_json::to_string_pretty(&json!({
+            "memory": {
+                "enabled": true,
+                "sync": { "watch": true, "debounceMs": 50 }
+            }
+        }))?,
+    )?;
+    let (cfg, paths) = load_config(Some(base.join("state")), Some(workspace_dir.clone()))?;
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let handle = memory_watch::spawn_memory_watcher(&cfg, &paths, shutdown.clone())?
+        .expect("watcher enabled");
+    assert!(wait_for(|| Ok(memory_watch::status()["fullSyncs"].as_u64() >= Some(1)))?);
+
+    let note = workspace_dir.join("memory").join("zebra.md");
+    fs::write(&note, "saw a zebra at the crossing\n")?;
+    assert!(wait_for(|| Ok(search_paths(&paths, "zebra")? == vec!["memory/zebra.md"]))?);
+    assert!(memory_watch::status()["indexed"].as_u64() >= Some(1));
+
+    fs::remove_file(&note)?;
+    assert!(wait_for(|| Ok(search_paths(&paths, "zebra")?.is_empty()))?);
+    assert!(memory_watch::status()["removed"].as_u64() >= Some(1));
+    assert_eq!(memory_watch::queue_depth(), 0);
+
+    shutdown.store(true, Ordering::SeqCst);
+    let _ = handle.join();
+    assert_eq!(memory_watch::status()["running"], json!(false));
+    let _ = fs::remove_dir_all(base);
+    Ok(())
+}
+
+#[test]
+fn searches_do_not_trust_the_watcher_while_changes_are_queued() -> Result<()> {
+    let base = std::env::temp_dir().join(format!("clawdex-memory-watch-queue-{}", Uuid::new_v4()));
+    let workspace_dir = base.join("workspace");
+    fs::create_dir_all(workspace_dir.join("memory"))?;
+    let (_cfg, paths) = load_config(Some(base.join("state")), Some(workspace_dir.clone()))?;
+    fs::write(
+        paths.state_dir.join("config.json5"),
+        serde_json::to_string_pretty(&json!({
+            "memory": {
+                "enabled": true,
+                "sync": { "watch": true, "debounceMs": 60000 }
+            }
+        }))?,
+    )?;
+    let (cfg, paths) = load_config(Some(base.join("state")), Some(workspace_dir.clone()))?;
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let handle = memory_watch::spawn_memory_watcher(&cfg, &paths, shutdown.clone())?
+        .expect("watcher enabled");
+    let watcher =
+        |key: &str| -> Result<Value> { Ok(memory::memory_status(&paths)?["watcher"][key].clone()) };
+    assert!(wait_for(|| Ok(watcher("active")? == json!(true) && watcher("pending")? == json!(false)))?);
+
+    // The long debounce keeps the change queued; the search indexes it itself.
+    fs::write(workspace_dir.join("memory").join("okapi.md"), "an okapi by the river\n")?;
+    assert!(wait_for(|| Ok(watcher("pending")? == json!(true)))?);
+    assert_eq!(search_paths(&paths, "okapi")?, vec!["memory/okapi.md"]);
+
+    shutdown.store(true, Ordering::SeqCst);
+    let _ = handle.join();
+    let _ = fs::remove_dir_all(base);
+    Ok(())
+}
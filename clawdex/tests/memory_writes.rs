@@ -0,0 +1,194 @@
+use std::fs;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use clawdex::config::ClawdPaths;
+use clawdex::memory;
+
+mod common;
+use common::TestState;
+
+fn setup() -> Result<TestState> {
+    TestState::with_memory(
+        "memory-writes",
+        json!({ "enabled": true, "session_memory": false }),
+    )
+}
+
+fn write(paths: &ClawdPaths, content: &str) -> Result<String> {
+    let res = memory::memory_write(
+        paths,
+        &json!({ "content": content, "approved": true, "confirmation": "WRITE_MEMORY" }),
+    )?;
+    Ok(res["entryId"].as_str().unwrap_or_default().to_string())
+}
+
+fn snippets(paths: &ClawdPaths, query: &str) -> Result<Vec<String>> {
+    let res = memory::memory_search(paths, &json!({ "query": query }))?;
+    Ok(res
+        .get("results")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|r| r.get("snippet").and_then(Value::as_str).map(str::to_string))
+        .collect())
+}
+
+fn history(paths: &ClawdPaths) -> Result<Vec<Value>> {
+    let raw = fs::read_to_string(paths.memory_dir.join("writes").join("history.jsonl"))?;
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[test]
+fn memory_forget_removes_entry_and_records_tombstone() -> Result<()> {
+    let state = setup()?;
+    let paths = &state.paths;
+    let first = write(paths, "The staging database password rotates on Fridays.")?;
+    let second = write(paths, "Deploys freeze during the holiday window.")?;
+    assert_ne!(first, second);
+    assert!(!snippets(paths, "staging password")?.is_empty());
+
+    let denied = memory::memory_forget(paths, &json!({ "entryId": first }));
+    assert!(denied.is_err());
+
+    let res = memory::memory_forget(
+        paths,
+        &json!({
+            "entryId": first,
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+            "reason": "wrong",
+        }),
+    )?;
+    assert_eq!(res["ok"], json!(true));
+    assert_eq!(res["action"], json!("forget"));
+    assert!(snippets(paths, "staging password")?.is_empty());
+    assert!(!snippets(paths, "holiday window")?.is_empty());
+
+    let target = paths.state_dir.join(res["path"].as_str().unwrap_or_default());
+    let text = fs::read_to_string(target)?;
+    assert!(!text.contains("staging database"));
+    assert!(text.contains(&format!("## Entry {second}")));
+
+    let records = history(paths)?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["action"], json!("forget"));
+    assert_eq!(records[0]["entryId"], json!(first));
+    assert_eq!(records[0]["reason"], json!("wrong"));
+    assert!(records[0]["previousSha256"].as_str().is_some());
+    // The removed entry can be restored from the history.
+    let previous = records[0]["previousText"].as_str().unwrap_or_default();
+    assert!(previous.starts_with(&format!("## Entry {first}")));
+    assert!(previous.contains("The staging database password rotates on Fridays."));
+
+    // Forgetting twice (or in another scope) reports the missing entry.
+    let again = memory::memory_forget(
+        paths,
+        &json!({ "entryId": first, "approved": true, "confirmation": "WRITE_MEMORY" }),
+    );
+    assert!(again.is_err());
+    let other_scope = memory::memory_forget(
+        paths,
+        &json!({
+            "entryId": second,
+            "scope": "global",
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+        }),
+    );
+    assert!(other_scope.is_err());
+    Ok(())
+}
+
+#[test]
+fn concurrent_writes_and_edits_do_not_lose_entries() -> Result<()> {
+    let state = setup()?;
+    let paths = &state.paths;
+    let res = memory::memory_write(
+        paths,
+        &json!({
+            "content": "Backups run nightly at 02:00.",
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+        }),
+    )?;
+    let id = res["entryId"].as_str().unwrap_or_default().to_string();
+    std::thread::scope(|scope| -> Result<()> {
+        let writer = scope.spawn(|| -> Result<Vec<String>> {
+            (0..10)
+                .map(|n| write(paths, &format!("Rotation note number {n}.")))
+                .collect()
+        });
+        for n in 0..10 {
+            memory::memory_edit(
+                paths,
+                &json!({
+                    "entryId": id,
+                    "content": format!("Backups run nightly at 0{n}:00."),
+                    "approved": true,
+                    "confirmation": "WRITE_MEMORY",
+                }),
+            )?;
+        }
+        let written = writer.join().expect("writer thread")?;
+        assert_eq!(written.len(), 10);
+        Ok(())
+    })?;
+
+    let target = paths.state_dir.join(res["path"].as_str().unwrap_or_default());
+    let text = fs::read_to_string(target)?;
+    for n in 0..10 {
+        assert!(text.contains(&format!("Rotation note number {n}.")));
+    }
+    assert!(text.contains("Backups run nightly at 09:00."));
+    Ok(())
+}
+
+#[test]
+fn memory_edit_replaces_entry_content() -> Result<()> {
+    let state = setup()?;
+    let paths = &state.paths;
+    let id = write(paths, "Standup moves to 9:30 on Tuesdays.")?;
+
+    let res = memory::memory_edit(
+        paths,
+        &json!({
+            "entryId": id,
+            "content": "Standup moves to 10:15 on Thursdays.",
+            "title": "Standup",
+            "approved": true,
+            "confirmation": "WRITE_MEMORY",
+        }),
+    )?;
+    assert_eq!(res["action"], json!("edit"));
+
+    assert!(snippets(paths, "Tuesdays")?.is_empty());
+    let found = snippets(paths, "Thursdays")?;
+    assert_eq!(found.len(), 1);
+    assert!(found[0].contains(&format!("## Entry {id}")));
+    assert!(found[0].contains("- title: Standup"));
+    assert!(found[0].contains("- edited: "));
+
+    let records = history(paths)?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["action"], json!("edit"));
+    assert!(records[0]["contentSha256"].as_str().is_some());
+    Ok(())
+}
+
+#[test]
+fn memory_edit_reports_disabled_memory_before_validating() -> Result<()> {
+    let state = TestState::with_memory("memory-writes-disabled", json!({ "enabled": false }))?;
+
+    let res = memory::memory_edit(&state.paths, &json!({ "entryId": "1" }))?;
+    assert_eq!(res["ok"], json!(false));
+    assert_eq!(res["disabled"], json!(true));
+    let res = memory::memory_forget(&state.paths, &json!({ "entryId": "1" }))?;
+    assert_eq!(res["disabled"], json!(true));
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use clawdex::config::ClawdPaths;
+use clawdex::{memory, session_digest, sessions};
+
+mod common;
+use common::TestState;
+
+fn setup(summaries: Value) -> Result<TestState> {
+    TestState::with_memory(
+        "session-digest",
+        json!({
+            "enabled": true,
+            "session_memory": true,
+            "sessionSummaries": summaries
+        }),
+    )
+}
+
+fn age_transcript(paths: &ClawdPaths, key: &str, minutes: u64) -> Result<()> {
+    let path = sessions::session_transcript_path(paths, key);
+    let file = fs::OpenOptions::new().write(true).open(path)?;
+    file.set_modified(SystemTime::now() - Duration::from_secs(minutes * 60))?;
+    Ok(())
+}
+
+fn snippets(paths: &ClawdPaths, query: &str) -> Result<Vec<(String, String)>> {
+    let res = memory::memory_search(paths, &json!({ "query": query }))?;
+    Ok(res
+        .get("results")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|r| {
+            (
+                r["path"].as_str().unwrap_or_default().to_string(),
+                r["snippet"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect())
+}
+
+#[test]
+fn idle_sessions_are_summarized_into_memory_digests() -> Result<()> {
+    let state = setup(json!({ "enabled": true, "idleMinutes": 30, "indexTranscripts": false }))?;
+    let (cfg, paths) = (&state.cfg, &state.paths);
+    let key = "agent:main:telegram:42";
+    sessions::append_session_message(paths, key, "user", "Our release train leaves every Wednesday.")?;
+    sessions::append_session_message(paths, key, "assistant", "Noted, Wednesday releases.")?;
+
+    let mut prompts = Vec::new();
+    let mut summarize = |thread: &str, prompt: &str| -> Result<String> {
+        prompts.push((thread.to_string(), prompt.to_string()));
+        Ok("- Releases ship on Wednesdays.".to_string())
+    };
+
+    // Still active: nothing happens.
+    let run = session_digest::run_session_digests(cfg, paths, clawdex::util::now_ms(), &mut summarize)?;
+    assert_eq!(run.summarized, 0);
+
+    age_transcript(paths, key, 45)?;
+    let run = session_digest::run_session_digests(cfg, paths, clawdex::util::now_ms(), &mut summarize)?;
+    assert_eq!(run.summarized, 1);
+
+    let transcript = sessions::session_transcript_path(paths, key);
+    let digest = session_digest::digest_path(paths, &transcript);
+    assert!(digest.starts_with(paths.workspace_dir.join("memory").join("sessions")));
+    let text = fs::read_to_string(&digest)?;
+    assert!(text.contains(&format!("# Session digest: {key}")));
+    assert!(text.contains("## Lines 1-2 ("));
+    assert!(text.contains("- Releases ship on Wednesdays."));
+
+    let records = session_digest::list_digests(paths)?;
+    let record = records.values().next().expect("digest record");
+    assert_eq!(record.session_key.as_deref(), Some(key));
+    assert_eq!(record.covered_lines, 2);
+    assert_eq!(record.ranges.len(), 1);
+    assert_eq!((record.ranges[0].from_line, record.ranges[0].to_line), (1, 2));
+
+    // The digest is searchable and the covered transcript lines are no longer indexed.
+    let found = snippets(paths, "releases")?;
+    assert!(found.iter().any(|(path, _)| path.starts_with("memory/sessions/")));
+    assert!(!found.iter().any(|(path, _)| path.starts_with("sessions/")));
+
+    // Nothing new: no second turn. New messages are summarized from where the last digest ended.
+    let run = session_digest::run_session_digests(cfg, paths, clawdex::util::now_ms(), &mut summarize)?;
+    assert_eq!(run.summarized, 0);
+    sessions::append_session_message(paths, key, "user", "Hotfixes can go out on Fridays.")?;
+    sessions::append_session_message(paths, key, "assistant", "Got it.")?;
+    age_transcript(paths, key, 45)?;
+    let run = session_digest::run_session_digests(cfg, paths, clawdex::util::now_ms(), &mut summarize)?;
+    assert_eq!(run.summarized, 1);
+
+    assert_eq!(prompts.len(), 2);
+    assert_eq!(prompts[0].0, prompts[1].0);
+    assert!(prompts[1].1.contains("Transcript lines 3-4"));
+    assert!(prompts[1].1.contains("Hotfixes can go out on Fridays."));
+    assert!(!prompts[1].1.contains("release train"));
+    let records = session_digest::list_digests(paths)?;
+    let record = records.values().next().expect("digest record");
+    assert_eq!(record.covered_lines, 4);
+    assert_eq!(record.ranges.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn session_digests_are_off_by_default() -> Result<()> {
+    let state = setup(json!({}))?;
+    let (cfg, paths) = (&state.cfg, &state.paths);
+    sessions::append_session_message(paths, "s1", "user", "hello there")?;
+    sessions::append_session_message(paths, "s1", "assistant", "hi")?;
+    age_transcript(paths, "s1", 120)?;
+    let mut summarize = |_: &str, _: &str| -> Result<String> { anyhow::bail!("should not run") };
+    let run = session_digest::run_session_digests(cfg, paths, clawdex::util::now_ms(), &mut summarize)?;
+    assert_eq!(run, session_digest::SessionDigestRun::default());
+    Ok(())
+}